@@ -0,0 +1,3 @@
+ALTER TABLE blog ADD COLUMN IF NOT EXISTS image_variants JSONB NOT NULL DEFAULT '[]'::jsonb;
+
+ALTER TABLE project ADD COLUMN IF NOT EXISTS image_variants JSONB NOT NULL DEFAULT '[]'::jsonb;
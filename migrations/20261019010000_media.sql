@@ -0,0 +1,54 @@
+CREATE TABLE IF NOT EXISTS media (
+    id UUID PRIMARY KEY,
+    public_id TEXT NOT NULL,
+    url TEXT NOT NULL,
+    variants JSONB NOT NULL DEFAULT '[]'::jsonb,
+    content_type TEXT,
+    byte_size BIGINT,
+    width INTEGER,
+    height INTEGER,
+    content_hash TEXT UNIQUE,
+    alt TEXT,
+    caption TEXT,
+    retain BOOLEAN NOT NULL DEFAULT FALSE,
+    uploaded_by UUID,
+    uploaded_by_name VARCHAR(255),
+    uploaded_by_email VARCHAR(255),
+    created_at TIMESTAMP NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMP NOT NULL DEFAULT NOW()
+);
+
+-- Each blog or project shows one image, so an entity holds at most one usage.
+CREATE TABLE IF NOT EXISTS media_usage (
+    entity TEXT NOT NULL,
+    entity_id UUID NOT NULL,
+    media_id UUID NOT NULL REFERENCES media (id) ON DELETE CASCADE,
+    PRIMARY KEY (entity, entity_id)
+);
+
+CREATE INDEX IF NOT EXISTS media_usage_media_id_idx ON media_usage (media_id);
+
+ALTER TABLE blog ADD COLUMN IF NOT EXISTS media_id UUID;
+
+ALTER TABLE project ADD COLUMN IF NOT EXISTS media_id UUID;
+
+-- Existing images become media items so that replacing them keeps cleaning up the asset.
+UPDATE blog SET media_id = gen_random_uuid() WHERE media_id IS NULL;
+
+UPDATE project SET media_id = gen_random_uuid() WHERE media_id IS NULL;
+
+INSERT INTO media (id, public_id, url, variants, created_at, updated_at)
+SELECT media_id, image_id, image, image_variants, created_at, updated_at FROM blog
+UNION ALL
+SELECT media_id, image_id, image, image_variants, created_at, updated_at FROM project
+ON CONFLICT (id) DO NOTHING;
+
+INSERT INTO media_usage (entity, entity_id, media_id)
+SELECT 'blog', id, media_id FROM blog
+UNION ALL
+SELECT 'project', id, media_id FROM project
+ON CONFLICT DO NOTHING;
+
+ALTER TABLE blog ADD CONSTRAINT blog_media_id_fkey FOREIGN KEY (media_id) REFERENCES media (id);
+
+ALTER TABLE project ADD CONSTRAINT project_media_id_fkey FOREIGN KEY (media_id) REFERENCES media (id);
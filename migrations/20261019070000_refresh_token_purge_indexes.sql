@@ -0,0 +1,5 @@
+-- The periodic purge looks tokens up by expiry and by how long ago revoked ones were issued.
+CREATE INDEX IF NOT EXISTS refresh_tokens_expires_at_idx ON refresh_tokens (expires_at);
+
+CREATE INDEX IF NOT EXISTS refresh_tokens_revoked_created_at_idx
+    ON refresh_tokens (created_at) WHERE revoked;
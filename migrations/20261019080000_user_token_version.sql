@@ -0,0 +1,3 @@
+-- Access tokens carry the version they were issued under; bumping it makes every outstanding
+-- token for the user fail verification.
+ALTER TABLE users ADD COLUMN IF NOT EXISTS token_version INTEGER NOT NULL DEFAULT 0;
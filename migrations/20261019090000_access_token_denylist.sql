@@ -0,0 +1,12 @@
+-- Revoked access tokens by `jti`, kept until they would have expired anyway.
+CREATE TABLE IF NOT EXISTS access_token_denylist (
+    jti UUID PRIMARY KEY,
+    expires_at TIMESTAMP NOT NULL
+);
+
+CREATE INDEX IF NOT EXISTS access_token_denylist_expires_at_idx ON access_token_denylist (expires_at);
+
+-- The access token last issued on each session, so ending a session can deny it too.
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS access_jti UUID;
+
+ALTER TABLE sessions ADD COLUMN IF NOT EXISTS access_expires_at TIMESTAMP;
@@ -0,0 +1,11 @@
+-- Revoked tokens are kept for a while after their revocation, not after their issue, so a
+-- long-lived token replayed soon after being revoked is still recognised.
+ALTER TABLE refresh_tokens ADD COLUMN IF NOT EXISTS revoked_at TIMESTAMP;
+
+-- When tokens already revoked were revoked is unknown; their retention starts now.
+UPDATE refresh_tokens SET revoked_at = NOW() WHERE revoked AND revoked_at IS NULL;
+
+DROP INDEX IF EXISTS refresh_tokens_revoked_created_at_idx;
+
+CREATE INDEX IF NOT EXISTS refresh_tokens_revoked_at_idx
+    ON refresh_tokens (revoked_at) WHERE revoked;
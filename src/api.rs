@@ -1,26 +1,67 @@
+pub mod api_key_routers;
 pub mod blog_api_routers;
+pub mod health_api_routers;
 pub mod image_api_routers;
+pub mod media_api_routers;
 pub mod project_api_routers;
 pub mod refresh_token_routers;
 pub mod stack_api_routers;
 pub mod user_api_routers;
+pub mod well_known_routers;
 
+use std::{sync::Arc, time::Duration};
+
+use tokio::sync::mpsc;
 use tower::ServiceBuilder;
+use tower_http::services::ServeDir;
 
 use axum::{
     Router,
-    http::StatusCode,
+    http::{HeaderValue, StatusCode, header},
+    middleware::from_fn_with_state,
     response::{IntoResponse, Response},
 };
 
 use crate::{
     api::{
-        blog_api_routers::blog_api_router, image_api_routers::image_api_router,
-        project_api_routers::project_api_router, refresh_token_routers::refresh_token_routers,
-        stack_api_routers::stack_api_router,
+        api_key_routers::api_key_router, blog_api_routers::blog_api_router,
+        health_api_routers::health_api_router, image_api_routers::image_api_router,
+        media_api_routers::media_api_router, project_api_routers::project_api_router,
+        refresh_token_routers::refresh_token_routers, stack_api_routers::stack_api_router,
+        well_known_routers::well_known_router,
+    },
+    api_key::{actor::ApiKeyActor, messages::ApiKeyMessage, repo::ApiKeyRepository},
+    auth::{actor::AuthActor, messages::AuthMessage, repo::UserRepository},
+    blog::{actor::BlogActor, messages::BlogMessage, repo::BlogRepository},
+    config::{
+        ImageLimits, LoginThrottleConfig, OidcConfig, PasswordResetConfig, RateLimitConfig,
+        TokenCleanupConfig, TokenConfig, TwoFactorConfig,
     },
+    core::jwt_keys::JwtKeys,
     errors::{api_errors::ApiErrors, error_handler::handle_404_with_path},
+    image::{
+        actor::ImageActor, messages::ImageMessage, reconcile::spawn_image_reconciler,
+        store::ImageStore,
+    },
+    mail::{mailer::Mailer, mailer_file::FileMailer},
+    media::{actor::MediaActor, messages::MediaMessage, repo::MediaRepository},
+    oidc::{actor::OidcActor, client::OidcClient, messages::OidcMessage},
+    project::{actor::ProjectActor, messages::ProjectMessage, repo::ProjectRepository},
+    rate_limit::{
+        middleware::{RateLimits, RouteGroup, rate_limit},
+        store::{RateLimitStore, spawn_rate_limit_pruner},
+        store_memory::MemoryRateLimitStore,
+    },
+    refresh_token::{
+        actor::RefreshTokenActor, cleanup::spawn_token_cleanup, messages::RefreshTokenMessage,
+        repo::RefreshTokenRepository,
+    },
+    stack::{actor::StackActor, messages::StackMessage, repo::StackRepository},
     state::AppState,
+    token_denylist::{
+        denylist::{TokenDenylist, spawn_denylist_sync},
+        store::DenylistStore,
+    },
 };
 
 use user_api_routers::user_api_router;
@@ -30,13 +71,21 @@ pub fn app_apis(state: AppState) -> Router {
         .nest(
             "/api/v1",
             Router::new()
+                .nest("/health", health_api_router(state.clone()))
                 .nest("/auth", user_api_router(state.clone()))
                 .nest("/stack", stack_api_router(state.clone()))
                 .nest("/image", image_api_router(state.clone()))
+                .nest("/media", media_api_router(state.clone()))
                 .nest("/blog", blog_api_router(state.clone()))
                 .nest("/project", project_api_router(state.clone()))
-                .nest("/token", refresh_token_routers(state.clone())),
+                .nest("/token", refresh_token_routers(state.clone()))
+                .nest("/api-keys", api_key_router(state.clone()))
+                .layer(from_fn_with_state(
+                    (state.clone(), RouteGroup::Global),
+                    rate_limit,
+                )),
         )
+        .nest("/.well-known", well_known_router(state.clone()))
         .fallback(handle_404_with_path)
         .layer(
             ServiceBuilder::new()
@@ -51,3 +100,302 @@ pub fn app_apis(state: AppState) -> Router {
                 .into_inner(),
         )
 }
+
+pub struct AppRepositories<U, S, B, P, T, M, K> {
+    pub users: U,
+    pub stacks: S,
+    pub blogs: B,
+    pub projects: P,
+    pub refresh_tokens: T,
+    pub media: M,
+    pub api_keys: K,
+}
+
+pub struct AppApisBuilder<U, S, B, P, T, M, K> {
+    repos: AppRepositories<U, S, B, P, T, M, K>,
+    image_store: Box<dyn ImageStore>,
+    jwt_keys: Arc<JwtKeys>,
+    tokens: TokenConfig,
+    image_reconcile_interval: Option<Duration>,
+    image_limits: ImageLimits,
+    mailer: Arc<dyn Mailer>,
+    password_reset: PasswordResetConfig,
+    login_throttle: LoginThrottleConfig,
+    two_factor: TwoFactorConfig,
+    token_cleanup: TokenCleanupConfig,
+    trust_forwarded_for: bool,
+    rate_limits: RateLimitConfig,
+    rate_limit_store: Arc<dyn RateLimitStore>,
+    denylist_store: Option<Arc<dyn DenylistStore>>,
+    oidc: Option<OidcConfig>,
+}
+
+impl<U, S, B, P, T, M, K> AppApisBuilder<U, S, B, P, T, M, K>
+where
+    U: UserRepository + Send + Sync + 'static,
+    S: StackRepository + Send + Sync + 'static,
+    B: BlogRepository + Send + Sync + 'static,
+    P: ProjectRepository + Send + Sync + 'static,
+    T: RefreshTokenRepository + Send + Sync + 'static,
+    M: MediaRepository + Send + Sync + 'static,
+    K: ApiKeyRepository + Send + Sync + 'static,
+{
+    pub fn new(
+        repos: AppRepositories<U, S, B, P, T, M, K>,
+        image_store: Box<dyn ImageStore>,
+        jwt_keys: JwtKeys,
+    ) -> Self {
+        Self {
+            repos,
+            image_store,
+            jwt_keys: Arc::new(jwt_keys),
+            tokens: TokenConfig::default(),
+            image_reconcile_interval: None,
+            image_limits: ImageLimits::default(),
+            mailer: Arc::new(FileMailer::new(None)),
+            password_reset: PasswordResetConfig::default(),
+            login_throttle: LoginThrottleConfig::default(),
+            two_factor: TwoFactorConfig::default(),
+            token_cleanup: TokenCleanupConfig::default(),
+            trust_forwarded_for: false,
+            rate_limits: RateLimitConfig::default(),
+            rate_limit_store: Arc::new(MemoryRateLimitStore::new()),
+            denylist_store: None,
+            oidc: None,
+        }
+    }
+
+    /// Defaults to logging outgoing email instead of sending it.
+    pub fn mailer(mut self, mailer: Arc<dyn Mailer>) -> Self {
+        self.mailer = mailer;
+        self
+    }
+
+    pub fn password_reset(mut self, config: PasswordResetConfig) -> Self {
+        self.password_reset = config;
+        self
+    }
+
+    pub fn login_throttle(mut self, config: LoginThrottleConfig) -> Self {
+        self.login_throttle = config;
+        self
+    }
+
+    pub fn two_factor(mut self, config: TwoFactorConfig) -> Self {
+        self.two_factor = config;
+        self
+    }
+
+    pub fn tokens(mut self, config: TokenConfig) -> Self {
+        self.tokens = config;
+        self
+    }
+
+    pub fn token_cleanup(mut self, config: TokenCleanupConfig) -> Self {
+        self.token_cleanup = config;
+        self
+    }
+
+    pub fn rate_limits(mut self, config: RateLimitConfig) -> Self {
+        self.rate_limits = config;
+        self
+    }
+
+    /// Defaults to keeping buckets in memory, which limits each instance separately.
+    pub fn rate_limit_store(mut self, store: Arc<dyn RateLimitStore>) -> Self {
+        self.rate_limit_store = store;
+        self
+    }
+
+    /// Where revoked access tokens are persisted; `None` keeps them in memory only.
+    pub fn denylist_store(mut self, store: Option<Arc<dyn DenylistStore>>) -> Self {
+        self.denylist_store = store;
+        self
+    }
+
+    /// Enables single sign-on through an OpenID provider.
+    pub fn oidc(mut self, config: OidcConfig) -> Self {
+        self.oidc = Some(config);
+        self
+    }
+
+    /// Use the last `X-Forwarded-For` entry as the client address; only behind a single proxy
+    /// that appends to it.
+    pub fn trust_forwarded_for(mut self, trust: bool) -> Self {
+        self.trust_forwarded_for = trust;
+        self
+    }
+
+    pub fn image_limits(mut self, limits: ImageLimits) -> Self {
+        self.image_limits = limits;
+        self
+    }
+
+    /// Enables the background job that reports image assets no row references.
+    pub fn image_reconcile_interval(mut self, every: Duration) -> Self {
+        self.image_reconcile_interval = Some(every);
+        self
+    }
+
+    /// Spawns every actor on its repository and returns the state wired to their channels.
+    pub fn build_state(self) -> AppState {
+        let image_circuit = self.image_store.circuit_breaker();
+
+        let (auth_tx, auth_rx) = mpsc::channel::<AuthMessage>(32);
+
+        let (stack_tx, stack_rx) = mpsc::channel::<StackMessage>(32);
+
+        let (image_tx, image_rx) = mpsc::channel::<ImageMessage>(32);
+
+        let (media_tx, media_rx) = mpsc::channel::<MediaMessage>(32);
+
+        let (blog_tx, blog_rx) = mpsc::channel::<BlogMessage>(32);
+
+        let (project_tx, project_rx) = mpsc::channel::<ProjectMessage>(32);
+
+        let (refresh_token_tx, refresh_token_rx) = mpsc::channel::<RefreshTokenMessage>(32);
+
+        let (api_key_tx, api_key_rx) = mpsc::channel::<ApiKeyMessage>(32);
+
+        let denylist = Arc::new(TokenDenylist::new(self.denylist_store));
+
+        tokio::spawn(
+            AuthActor::new(
+                self.repos.users,
+                self.mailer,
+                self.password_reset,
+                self.login_throttle,
+                self.two_factor,
+            )
+            .run(auth_rx),
+        );
+
+        tokio::spawn(StackActor::new(self.repos.stacks).run(stack_rx));
+
+        tokio::spawn(ImageActor::new(self.image_store).run(image_rx));
+
+        tokio::spawn(
+            MediaActor::new(
+                self.repos.media,
+                image_tx.clone(),
+                self.image_limits.clone(),
+            )
+            .run(media_rx),
+        );
+
+        tokio::spawn(BlogActor::new(self.repos.blogs, media_tx.clone()).run(blog_rx));
+
+        tokio::spawn(ProjectActor::new(self.repos.projects, media_tx.clone()).run(project_rx));
+
+        tokio::spawn(
+            RefreshTokenActor::new(
+                self.repos.refresh_tokens,
+                self.jwt_keys.clone(),
+                self.tokens.clone(),
+                self.token_cleanup,
+                auth_tx.clone(),
+                denylist.clone(),
+            )
+            .run(refresh_token_rx),
+        );
+
+        tokio::spawn(ApiKeyActor::new(self.repos.api_keys).run(api_key_rx));
+
+        let oidc_tx = self.oidc.map(|config| {
+            let (oidc_tx, oidc_rx) = mpsc::channel::<OidcMessage>(32);
+
+            tokio::spawn(OidcActor::new(OidcClient::new(config), auth_tx.clone()).run(oidc_rx));
+
+            oidc_tx
+        });
+
+        AppState {
+            auth_tx,
+            stack_tx,
+            image_tx,
+            media_tx,
+            blog_tx,
+            project_tx,
+            refresh_token_tx,
+            api_key_tx,
+            oidc_tx,
+            jwt_keys: self.jwt_keys,
+            tokens: self.tokens,
+            denylist,
+            image_circuit,
+            image_limits: self.image_limits,
+            trust_forwarded_for: self.trust_forwarded_for,
+            rate_limits: RateLimits {
+                config: self.rate_limits,
+                store: self.rate_limit_store,
+            },
+        }
+    }
+
+    pub fn build(self) -> Router {
+        let media_root = self.image_store.media_root();
+        let reconcile_interval = self.image_reconcile_interval;
+        let cleanup_interval = self.token_cleanup.interval;
+
+        let state = self.build_state();
+
+        if let Some(every) = reconcile_interval {
+            spawn_image_reconciler(state.clone(), every);
+        }
+
+        if let Some(every) = cleanup_interval {
+            spawn_token_cleanup(state.refresh_token_tx.clone(), every);
+        }
+
+        spawn_denylist_sync(state.denylist.clone(), Duration::from_secs(30));
+
+        if state.rate_limits.config.enabled {
+            spawn_rate_limit_pruner(
+                state.rate_limits.store.clone(),
+                state.rate_limits.config.longest_period(),
+                Duration::from_secs(10 * 60),
+            );
+        }
+
+        let router = app_apis(state);
+
+        match media_root {
+            Some(root) => router.nest_service(
+                "/media",
+                ServiceBuilder::new()
+                    .map_response(confine_svg)
+                    .service(ServeDir::new(root)),
+            ),
+            None => router,
+        }
+    }
+}
+
+/// An SVG opened directly is a document in our origin, so served ones may not run script and
+/// are downloaded rather than shown; `<img>` tags are unaffected by either header.
+fn confine_svg<B>(mut response: axum::http::Response<B>) -> axum::http::Response<B> {
+    let is_svg = response
+        .headers()
+        .get(header::CONTENT_TYPE)
+        .is_some_and(|value| value.as_bytes().starts_with(b"image/svg+xml"));
+
+    if is_svg {
+        let headers = response.headers_mut();
+        headers.insert(
+            header::CONTENT_SECURITY_POLICY,
+            HeaderValue::from_static(
+                "default-src 'none'; script-src 'none'; style-src 'unsafe-inline'; sandbox",
+            ),
+        );
+        headers.insert(
+            header::CONTENT_DISPOSITION,
+            HeaderValue::from_static("attachment"),
+        );
+        headers.insert(
+            header::X_CONTENT_TYPE_OPTIONS,
+            HeaderValue::from_static("nosniff"),
+        );
+    }
+
+    response
+}
@@ -0,0 +1,17 @@
+use axum::{
+    Router,
+    routing::{delete, get},
+};
+
+use crate::{
+    api_key::handlers::{create_api_key, list_api_keys, revoke_api_key},
+    state::AppState,
+};
+
+/// Key management takes an interactive login: no scope lets an API key in here.
+pub fn api_key_router(state: AppState) -> Router {
+    Router::new()
+        .route("/", get(list_api_keys).post(create_api_key))
+        .route("/{id}", delete(revoke_api_key))
+        .with_state(state)
+}
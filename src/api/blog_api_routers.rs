@@ -1,12 +1,16 @@
 use axum::{
-    Router,
+    Extension, Router,
+    middleware::from_fn_with_state,
     routing::{get, post},
 };
 
 use crate::{
+    api_key::dto::ApiScope,
     blog::handlers::{
         create_blog, delete_blog, get_all_blog, get_single_blog, get_total_blog_count, update_blog,
     },
+    extractor::api_key_extractor::ApiKeyScopes,
+    rate_limit::middleware::{RouteGroup, rate_limit},
     state::AppState,
 };
 
@@ -19,5 +23,13 @@ pub fn blog_api_router(state: AppState) -> Router {
             "/detail/{id}",
             get(get_single_blog).patch(update_blog).delete(delete_blog),
         )
+        .route_layer(from_fn_with_state(
+            (state.clone(), RouteGroup::PublicRead),
+            rate_limit,
+        ))
+        .layer(Extension(ApiKeyScopes::new(
+            ApiScope::BlogRead,
+            ApiScope::BlogWrite,
+        )))
         .with_state(state)
 }
@@ -0,0 +1,7 @@
+use axum::{Router, routing::get};
+
+use crate::{health::handlers::health, state::AppState};
+
+pub fn health_api_router(state: AppState) -> Router {
+    Router::new().route("/", get(health)).with_state(state)
+}
@@ -1,13 +1,35 @@
-use axum::{Router, routing::post};
+use axum::{
+    Extension, Router,
+    extract::DefaultBodyLimit,
+    middleware::from_fn_with_state,
+    routing::{post, put},
+};
 
 use crate::{
-    image::handlers::{upload_base64, upload_form},
+    api_key::dto::ApiScope,
+    extractor::api_key_extractor::ApiKeyScopes,
+    image::handlers::{
+        confirm_upload, issue_ticket, receive_direct, upload_base64, upload_files, upload_form,
+    },
+    rate_limit::middleware::{RouteGroup, rate_limit},
     state::AppState,
 };
 
 pub fn image_api_router(state: AppState) -> Router {
+    let body_limit = state.image_limits.max_request_bytes;
+
     Router::new()
         .route("/base64", post(upload_base64))
         .route("/file", post(upload_form))
+        .route("/files", post(upload_files))
+        .route("/ticket", post(issue_ticket))
+        .route("/confirm", post(confirm_upload))
+        .route("/direct/{public_id}", put(receive_direct))
+        .route_layer(from_fn_with_state(
+            (state.clone(), RouteGroup::Upload),
+            rate_limit,
+        ))
+        .layer(Extension(ApiKeyScopes::only(ApiScope::ImageUpload)))
+        .layer(DefaultBodyLimit::max(body_limit))
         .with_state(state)
 }
@@ -0,0 +1,43 @@
+use axum::{
+    Extension, Router,
+    extract::DefaultBodyLimit,
+    routing::{get, post},
+};
+
+use crate::{
+    api_key::dto::ApiScope,
+    extractor::api_key_extractor::ApiKeyScopes,
+    media::handlers::{
+        delete_media, get_all_media, get_single_media, update_media, upload_media,
+        upload_media_form,
+    },
+    state::AppState,
+};
+
+pub fn media_api_router(state: AppState) -> Router {
+    let body_limit = state.image_limits.max_request_bytes;
+
+    // Upload keys may add to the library but not edit or remove what is already in it.
+    let uploads = Router::new()
+        .route("/base64", post(upload_media))
+        .route("/file", post(upload_media_form))
+        .layer(Extension(ApiKeyScopes::only(ApiScope::ImageUpload)));
+
+    let library = Router::new()
+        .route("/all", get(get_all_media))
+        .route(
+            "/detail/{id}",
+            get(get_single_media)
+                .patch(update_media)
+                .delete(delete_media),
+        )
+        .layer(Extension(ApiKeyScopes::new(
+            ApiScope::MediaRead,
+            ApiScope::MediaWrite,
+        )));
+
+    uploads
+        .merge(library)
+        .layer(DefaultBodyLimit::max(body_limit))
+        .with_state(state)
+}
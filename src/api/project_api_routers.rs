@@ -1,13 +1,17 @@
 use axum::{
-    Router,
+    Extension, Router,
+    middleware::from_fn_with_state,
     routing::{get, post},
 };
 
 use crate::{
+    api_key::dto::ApiScope,
+    extractor::api_key_extractor::ApiKeyScopes,
     project::handlers::{
         create_project, delete_project, get_all_project, get_single_project,
         get_total_project_count, update_project,
     },
+    rate_limit::middleware::{RouteGroup, rate_limit},
     state::AppState,
 };
 
@@ -22,5 +26,13 @@ pub fn project_api_router(state: AppState) -> Router {
                 .patch(update_project)
                 .delete(delete_project),
         )
+        .route_layer(from_fn_with_state(
+            (state.clone(), RouteGroup::PublicRead),
+            rate_limit,
+        ))
+        .layer(Extension(ApiKeyScopes::new(
+            ApiScope::ProjectRead,
+            ApiScope::ProjectWrite,
+        )))
         .with_state(state)
 }
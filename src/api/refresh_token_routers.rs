@@ -1,13 +1,20 @@
 use crate::{
+    rate_limit::middleware::{RouteGroup, rate_limit},
     refresh_token::handlers::{logout, refresh},
     state::AppState,
 };
-use axum::{Router, routing::post};
+use axum::{Router, middleware::from_fn_with_state, routing::post};
 use tower_cookies::CookieManagerLayer;
 
 pub fn refresh_token_routers(state: AppState) -> Router {
     Router::new()
-        .route("/refresh", post(refresh))
+        .route(
+            "/refresh",
+            post(refresh).route_layer(from_fn_with_state(
+                (state.clone(), RouteGroup::Refresh),
+                rate_limit,
+            )),
+        )
         .route("/logout", post(logout))
         .layer(CookieManagerLayer::new())
         .with_state(state)
@@ -1,9 +1,13 @@
 use axum::{
-    Router,
+    Extension, Router,
+    middleware::from_fn_with_state,
     routing::{get, post},
 };
 
 use crate::{
+    api_key::dto::ApiScope,
+    extractor::api_key_extractor::ApiKeyScopes,
+    rate_limit::middleware::{RouteGroup, rate_limit},
     stack::handlers::{
         create_stack, delete_stack, get_all_stack, get_single_stack, get_single_stack_by_title,
         update_stack,
@@ -22,5 +26,13 @@ pub fn stack_api_router(state: AppState) -> Router {
                 .patch(update_stack)
                 .delete(delete_stack),
         )
+        .route_layer(from_fn_with_state(
+            (state.clone(), RouteGroup::PublicRead),
+            rate_limit,
+        ))
+        .layer(Extension(ApiKeyScopes::new(
+            ApiScope::StackRead,
+            ApiScope::StackWrite,
+        )))
         .with_state(state)
 }
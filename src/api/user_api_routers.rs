@@ -1,27 +1,61 @@
 use axum::{
     Router,
-    routing::{get, post},
+    middleware::from_fn_with_state,
+    routing::{delete, get, post},
 };
 
 use tower_cookies::CookieManagerLayer;
 
 use crate::{
     auth::handlers::{
-        delete_user, get_all_users, get_current_user, get_user, login, register, update_user,
+        change_current_password, confirm_two_factor, delete_user, disable_two_factor,
+        forgot_password, get_all_users, get_current_user, get_user, login, login_two_factor,
+        register, reset_password, setup_login_two_factor, setup_two_factor, unlock_user,
+        update_current_user, update_user,
+    },
+    oidc::handlers::{oidc_callback, oidc_login},
+    rate_limit::middleware::{RouteGroup, rate_limit},
+    refresh_token::handlers::{
+        force_logout_user, list_sessions, revoke_other_sessions, revoke_session,
     },
     state::AppState,
 };
 
 pub fn user_api_router(state: AppState) -> Router {
+    let credentials = Router::new()
+        .route("/login", post(login))
+        .route("/login/2fa", post(login_two_factor))
+        .route("/login/2fa/setup", post(setup_login_two_factor))
+        .route("/password/forgot", post(forgot_password))
+        .route("/password/reset", post(reset_password))
+        .route("/oidc/login", get(oidc_login))
+        .route("/oidc/callback", post(oidc_callback))
+        .route_layer(from_fn_with_state(
+            (state.clone(), RouteGroup::Login),
+            rate_limit,
+        ));
+
     Router::new()
+        .merge(credentials)
         .route("/register", post(register))
-        .route("/login", post(login))
         .route("/users", get(get_all_users))
         .route("/current_users", get(get_current_user))
+        .route("/me", get(get_current_user).patch(update_current_user))
+        .route("/me/password", post(change_current_password))
+        .route("/2fa/setup", post(setup_two_factor))
+        .route("/2fa/confirm", post(confirm_two_factor))
+        .route("/2fa/disable", post(disable_two_factor))
+        .route(
+            "/sessions",
+            get(list_sessions).delete(revoke_other_sessions),
+        )
+        .route("/sessions/{id}", delete(revoke_session))
         .route(
             "/users/{id}",
             get(get_user).patch(update_user).delete(delete_user),
         )
+        .route("/users/{id}/unlock", post(unlock_user))
+        .route("/users/{id}/logout", post(force_logout_user))
         .layer(CookieManagerLayer::new())
         .with_state(state)
 }
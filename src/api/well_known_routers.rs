@@ -0,0 +1,9 @@
+use axum::{Router, routing::get};
+
+use crate::{core::well_known_handlers::jwks, state::AppState};
+
+pub fn well_known_router(state: AppState) -> Router {
+    Router::new()
+        .route("/jwks.json", get(jwks))
+        .with_state(state)
+}
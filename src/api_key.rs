@@ -0,0 +1,9 @@
+pub mod actor;
+pub mod dispatcher;
+pub mod dto;
+pub mod handlers;
+pub mod messages;
+pub mod repo;
+#[cfg(test)]
+pub mod repo_memory;
+pub mod repo_sqlx;
@@ -0,0 +1,210 @@
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::{
+    api_key::{
+        dispatcher::api_key_dispatcher,
+        dto::{ApiKeyRecord, ApiScope, ValidatedCreateApiKey},
+        messages::{ApiKeyMessage, ApiKeyResponse, AuthenticatedApiKey, CreatedApiKey},
+        repo::ApiKeyRepository,
+    },
+    errors::api_errors::ApiErrors,
+    utils::tokens::{generate_opaque_token, hash_opaque_token, tokens_match},
+};
+
+/// Keys read `pk_<prefix>_<secret>`.
+const KEY_PREFIX: &str = "pk_";
+
+/// `last_used_at` is only rewritten once it is this stale, so a busy key does not cost a
+/// write per request.
+const LAST_USED_RESOLUTION: chrono::Duration = chrono::Duration::minutes(1);
+
+pub struct ApiKeyActor<R>
+where
+    R: ApiKeyRepository + Send + Sync + 'static,
+{
+    pub repo: R,
+}
+
+impl<R> ApiKeyActor<R>
+where
+    R: ApiKeyRepository + Send + Sync + 'static,
+{
+    pub fn new(repo: R) -> Self {
+        Self { repo }
+    }
+
+    pub async fn run(self, rx: mpsc::Receiver<ApiKeyMessage>) {
+        api_key_dispatcher(&self, rx).await;
+    }
+
+    pub async fn create_api_key(
+        &self,
+        user_id: Uuid,
+        data: ValidatedCreateApiKey,
+    ) -> Result<CreatedApiKey, ApiErrors> {
+        // Wide enough that two keys never realistically share one.
+        let prefix = hex::encode(rand::random::<[u8; 12]>());
+        let secret = generate_opaque_token();
+
+        let record = ApiKeyRecord {
+            id: Uuid::new_v4(),
+            name: data.name.as_str().to_string(),
+            prefix: prefix.clone(),
+            secret_hash: hash_opaque_token(&secret),
+            scopes: data
+                .scopes
+                .iter()
+                .map(|scope| scope.as_str().to_string())
+                .collect(),
+            user_id,
+            expires_at: data.expires_at,
+            last_used_at: None,
+            revoked_at: None,
+            created_at: chrono::Utc::now().naive_utc(),
+        };
+
+        self.repo.insert_api_key(&record).await?;
+
+        Ok(CreatedApiKey {
+            key: format!("{KEY_PREFIX}{prefix}_{secret}"),
+            api_key: record.into(),
+        })
+    }
+
+    pub async fn list_api_keys(&self) -> Result<Vec<ApiKeyResponse>, ApiErrors> {
+        Ok(self
+            .repo
+            .list_api_keys()
+            .await?
+            .into_iter()
+            .map(ApiKeyResponse::from)
+            .collect())
+    }
+
+    pub async fn revoke_api_key(&self, id: Uuid) -> Result<(), ApiErrors> {
+        if !self
+            .repo
+            .revoke_api_key(id, chrono::Utc::now().naive_utc())
+            .await?
+        {
+            return Err(ApiErrors::NotFound("API key not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    /// Every way a key can fail gets the same answer, so probing reveals nothing.
+    pub async fn authenticate(&self, key: String) -> Result<AuthenticatedApiKey, ApiErrors> {
+        let invalid = || ApiErrors::Unauthorized("Invalid API key".to_string());
+
+        let (prefix, secret) = key
+            .strip_prefix(KEY_PREFIX)
+            .and_then(|rest| rest.split_once('_'))
+            .ok_or_else(invalid)?;
+
+        let record = self
+            .repo
+            .find_api_key_by_prefix(prefix)
+            .await?
+            .ok_or_else(invalid)?;
+
+        let now = chrono::Utc::now().naive_utc();
+
+        if !tokens_match(&record.secret_hash, &hash_opaque_token(secret))
+            || record.revoked_at.is_some()
+            || record
+                .expires_at
+                .is_some_and(|expires_at| expires_at <= now)
+        {
+            return Err(invalid());
+        }
+
+        if record
+            .last_used_at
+            .is_none_or(|last_used_at| now - last_used_at >= LAST_USED_RESOLUTION)
+        {
+            self.repo.touch_api_key(record.id, now).await?;
+        }
+
+        Ok(AuthenticatedApiKey {
+            user_id: record.user_id,
+            // Scopes were checked when the key was made; one dropped since just stops counting.
+            scopes: record
+                .scopes
+                .iter()
+                .filter_map(|scope| ApiScope::new(scope).ok())
+                .collect(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{api_key::repo_memory::ApiKeyRepoMemory, fields::text::Text};
+
+    fn create_data(expires_at: Option<chrono::NaiveDateTime>) -> ValidatedCreateApiKey {
+        ValidatedCreateApiKey {
+            name: Text("CI deploy".to_string()),
+            scopes: vec![ApiScope::BlogWrite],
+            expires_at,
+        }
+    }
+
+    #[tokio::test]
+    async fn keys_are_stored_hashed_and_checked_in_full() {
+        let actor = ApiKeyActor::new(ApiKeyRepoMemory::new());
+        let user_id = Uuid::new_v4();
+
+        let created = actor
+            .create_api_key(user_id, create_data(None))
+            .await
+            .unwrap();
+
+        let stored = actor
+            .repo
+            .find_api_key_by_prefix(&created.api_key.prefix)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(!created.key.contains(&stored.secret_hash));
+        assert!(stored.last_used_at.is_none());
+
+        let authenticated = actor.authenticate(created.key.clone()).await.unwrap();
+        assert_eq!(authenticated.user_id, user_id);
+        assert_eq!(authenticated.scopes, [ApiScope::BlogWrite]);
+
+        let touched = actor.repo.list_api_keys().await.unwrap();
+        assert!(touched[0].last_used_at.is_some());
+
+        let last = if created.key.ends_with('0') { '1' } else { '0' };
+        let tampered = format!("{}{last}", &created.key[..created.key.len() - 1]);
+        assert!(actor.authenticate(tampered).await.is_err());
+        assert!(actor.authenticate("not-a-key".to_string()).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn expired_and_revoked_keys_are_refused() {
+        let actor = ApiKeyActor::new(ApiKeyRepoMemory::new());
+        let past = chrono::Utc::now().naive_utc() - chrono::Duration::minutes(1);
+
+        let expired = actor
+            .create_api_key(Uuid::new_v4(), create_data(Some(past)))
+            .await
+            .unwrap();
+        assert!(actor.authenticate(expired.key).await.is_err());
+
+        let revoked = actor
+            .create_api_key(Uuid::new_v4(), create_data(None))
+            .await
+            .unwrap();
+        actor.revoke_api_key(revoked.api_key.id).await.unwrap();
+        assert!(actor.authenticate(revoked.key).await.is_err());
+
+        assert!(matches!(
+            actor.revoke_api_key(revoked.api_key.id).await,
+            Err(ApiErrors::NotFound(_))
+        ));
+    }
+}
@@ -0,0 +1,36 @@
+use tokio::sync::mpsc;
+
+use crate::api_key::{actor::ApiKeyActor, messages::ApiKeyMessage, repo::ApiKeyRepository};
+
+pub async fn api_key_dispatcher<R>(actor: &ApiKeyActor<R>, mut rx: mpsc::Receiver<ApiKeyMessage>)
+where
+    R: ApiKeyRepository + Send + Sync + 'static,
+{
+    while let Some(msg) = rx.recv().await {
+        match msg {
+            ApiKeyMessage::Create {
+                user_id,
+                data,
+                respond_to,
+            } => {
+                let res = actor.create_api_key(user_id, data).await;
+                let _ = respond_to.send(res);
+            }
+
+            ApiKeyMessage::List { respond_to } => {
+                let res = actor.list_api_keys().await;
+                let _ = respond_to.send(res);
+            }
+
+            ApiKeyMessage::Revoke { id, respond_to } => {
+                let res = actor.revoke_api_key(id).await;
+                let _ = respond_to.send(res);
+            }
+
+            ApiKeyMessage::Authenticate { key, respond_to } => {
+                let res = actor.authenticate(key).await;
+                let _ = respond_to.send(res);
+            }
+        }
+    }
+}
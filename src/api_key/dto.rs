@@ -0,0 +1,82 @@
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{errors::api_errors::ApiErrors, fields::text::Text};
+
+/// What a key may do. Routers name the scope their reads and writes need; routes without one
+/// refuse API keys altogether.
+#[derive(Debug, Serialize, Clone, Copy, PartialEq)]
+pub enum ApiScope {
+    #[serde(rename = "blog:read")]
+    BlogRead,
+    #[serde(rename = "blog:write")]
+    BlogWrite,
+    #[serde(rename = "project:read")]
+    ProjectRead,
+    #[serde(rename = "project:write")]
+    ProjectWrite,
+    #[serde(rename = "stack:read")]
+    StackRead,
+    #[serde(rename = "stack:write")]
+    StackWrite,
+    #[serde(rename = "image:upload")]
+    ImageUpload,
+    #[serde(rename = "media:read")]
+    MediaRead,
+    #[serde(rename = "media:write")]
+    MediaWrite,
+}
+
+impl ApiScope {
+    pub fn new(value: &str) -> Result<Self, ApiErrors> {
+        match value {
+            "blog:read" => Ok(Self::BlogRead),
+            "blog:write" => Ok(Self::BlogWrite),
+            "project:read" => Ok(Self::ProjectRead),
+            "project:write" => Ok(Self::ProjectWrite),
+            "stack:read" => Ok(Self::StackRead),
+            "stack:write" => Ok(Self::StackWrite),
+            "image:upload" => Ok(Self::ImageUpload),
+            "media:read" => Ok(Self::MediaRead),
+            "media:write" => Ok(Self::MediaWrite),
+            other => Err(ApiErrors::BadRequest(format!(
+                "Unknown scope {other}; accepted are blog:read, blog:write, project:read, project:write, stack:read, stack:write, image:upload, media:read, media:write."
+            ))),
+        }
+    }
+
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::BlogRead => "blog:read",
+            Self::BlogWrite => "blog:write",
+            Self::ProjectRead => "project:read",
+            Self::ProjectWrite => "project:write",
+            Self::StackRead => "stack:read",
+            Self::StackWrite => "stack:write",
+            Self::ImageUpload => "image:upload",
+            Self::MediaRead => "media:read",
+            Self::MediaWrite => "media:write",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct ApiKeyRecord {
+    pub id: Uuid,
+    pub name: String,
+    pub prefix: String,
+    pub secret_hash: String,
+    pub scopes: Vec<String>,
+    pub user_id: Uuid,
+    pub expires_at: Option<NaiveDateTime>,
+    pub last_used_at: Option<NaiveDateTime>,
+    pub revoked_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+pub struct ValidatedCreateApiKey {
+    pub name: Text,
+    pub scopes: Vec<ApiScope>,
+    pub expires_at: Option<NaiveDateTime>,
+}
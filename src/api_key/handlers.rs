@@ -0,0 +1,99 @@
+use axum::{Json, extract::State};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::{
+    api_key::messages::ApiKeyMessage,
+    errors::api_errors::ApiErrors,
+    extractor::{auth_extractor::AuthUser, json_body::RequiredJson, path_id_extractor::PathParam},
+    fields::roles::Roles,
+    payload_handler::api_key_payload_handler::CreateApiKeyRequest,
+    state::AppState,
+};
+
+fn require_admin(roles: &Roles) -> Result<(), ApiErrors> {
+    if roles.as_str() == "normal" {
+        return Err(ApiErrors::BadRequest(
+            "Because of your ADMIN Level you can not manage API keys.".to_string(),
+        ));
+    }
+
+    Ok(())
+}
+
+/// Issues a key acting as the caller. The full key is in the response and nowhere else.
+pub async fn create_api_key(
+    AuthUser { id, roles, .. }: AuthUser,
+    State(state): State<AppState>,
+    RequiredJson(payload): RequiredJson<CreateApiKeyRequest>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    require_admin(&roles)?;
+
+    let data = payload.validate()?;
+
+    let (tx, rx) = oneshot::channel();
+
+    state
+        .api_key_tx
+        .send(ApiKeyMessage::Create {
+            user_id: id,
+            data,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
+
+    let created = rx
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))??;
+
+    Ok(Json(serde_json::json!({
+        "message": "success".to_string(),
+        "data": created,
+    })))
+}
+
+pub async fn list_api_keys(
+    AuthUser { roles, .. }: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    require_admin(&roles)?;
+
+    let (tx, rx) = oneshot::channel();
+
+    state
+        .api_key_tx
+        .send(ApiKeyMessage::List { respond_to: tx })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
+
+    let keys = rx
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))??;
+
+    Ok(Json(serde_json::json!({
+        "message": "success".to_string(),
+        "data": keys,
+    })))
+}
+
+pub async fn revoke_api_key(
+    AuthUser { roles, .. }: AuthUser,
+    State(state): State<AppState>,
+    PathParam(id): PathParam<Uuid>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    require_admin(&roles)?;
+
+    let (tx, rx) = oneshot::channel();
+
+    state
+        .api_key_tx
+        .send(ApiKeyMessage::Revoke { id, respond_to: tx })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
+
+    rx.await
+        .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))??;
+
+    Ok(Json(serde_json::json!({"message": "success".to_string(),})))
+}
@@ -0,0 +1,77 @@
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::{
+    api_key::dto::{ApiKeyRecord, ApiScope, ValidatedCreateApiKey},
+    errors::api_errors::ApiErrors,
+};
+
+#[derive(Serialize)]
+pub struct ApiKeyResponse {
+    pub id: Uuid,
+    pub name: String,
+    /// The public part of the key, for telling keys apart; the secret is never shown again.
+    pub prefix: String,
+    pub scopes: Vec<String>,
+    pub user_id: Uuid,
+    pub expires_at: Option<NaiveDateTime>,
+    pub last_used_at: Option<NaiveDateTime>,
+    pub revoked_at: Option<NaiveDateTime>,
+    pub created_at: NaiveDateTime,
+}
+
+impl From<ApiKeyRecord> for ApiKeyResponse {
+    fn from(record: ApiKeyRecord) -> Self {
+        Self {
+            id: record.id,
+            name: record.name,
+            prefix: record.prefix,
+            scopes: record.scopes,
+            user_id: record.user_id,
+            expires_at: record.expires_at,
+            last_used_at: record.last_used_at,
+            revoked_at: record.revoked_at,
+            created_at: record.created_at,
+        }
+    }
+}
+
+#[derive(Serialize)]
+pub struct CreatedApiKey {
+    /// The whole key, `pk_<prefix>_<secret>`; only its hash is kept, so this is the one chance
+    /// to copy it.
+    pub key: String,
+    pub api_key: ApiKeyResponse,
+}
+
+/// A key that checked out: whose it is and what it may do.
+pub struct AuthenticatedApiKey {
+    pub user_id: Uuid,
+    pub scopes: Vec<ApiScope>,
+}
+
+pub enum ApiKeyMessage {
+    /// Issues a key acting as `user_id`.
+    Create {
+        user_id: Uuid,
+        data: ValidatedCreateApiKey,
+        respond_to: oneshot::Sender<Result<CreatedApiKey, ApiErrors>>,
+    },
+
+    List {
+        respond_to: oneshot::Sender<Result<Vec<ApiKeyResponse>, ApiErrors>>,
+    },
+
+    Revoke {
+        id: Uuid,
+        respond_to: oneshot::Sender<Result<(), ApiErrors>>,
+    },
+
+    /// Checks a key presented in `X-Api-Key` and records that it was used.
+    Authenticate {
+        key: String,
+        respond_to: oneshot::Sender<Result<AuthenticatedApiKey, ApiErrors>>,
+    },
+}
@@ -0,0 +1,22 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use crate::{api_key::dto::ApiKeyRecord, errors::api_errors::ApiErrors};
+
+#[async_trait]
+pub trait ApiKeyRepository: Send + Sync {
+    /// `record.secret_hash` is the SHA-256 of the secret; the secret itself is never stored.
+    async fn insert_api_key(&self, record: &ApiKeyRecord) -> Result<(), ApiErrors>;
+
+    /// Every key, revoked and expired ones included, newest first.
+    async fn list_api_keys(&self) -> Result<Vec<ApiKeyRecord>, ApiErrors>;
+
+    async fn find_api_key_by_prefix(&self, prefix: &str)
+    -> Result<Option<ApiKeyRecord>, ApiErrors>;
+
+    /// False when there is no such key or it was already revoked.
+    async fn revoke_api_key(&self, id: Uuid, now: NaiveDateTime) -> Result<bool, ApiErrors>;
+
+    async fn touch_api_key(&self, id: Uuid, now: NaiveDateTime) -> Result<(), ApiErrors>;
+}
@@ -0,0 +1,79 @@
+use std::{cmp::Reverse, sync::Mutex};
+
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use crate::{
+    api_key::{dto::ApiKeyRecord, repo::ApiKeyRepository},
+    errors::api_errors::ApiErrors,
+};
+
+#[derive(Default)]
+pub struct ApiKeyRepoMemory {
+    keys: Mutex<Vec<ApiKeyRecord>>,
+}
+
+impl ApiKeyRepoMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ApiKeyRepository for ApiKeyRepoMemory {
+    async fn insert_api_key(&self, record: &ApiKeyRecord) -> Result<(), ApiErrors> {
+        let mut keys = self.keys.lock().unwrap();
+
+        if keys.iter().any(|key| key.prefix == record.prefix) {
+            return Err(ApiErrors::Conflict(
+                "API key prefix already in use".to_string(),
+            ));
+        }
+
+        keys.push(record.clone());
+
+        Ok(())
+    }
+
+    async fn list_api_keys(&self) -> Result<Vec<ApiKeyRecord>, ApiErrors> {
+        let mut keys = self.keys.lock().unwrap().clone();
+        keys.sort_by_key(|key| Reverse(key.created_at));
+
+        Ok(keys)
+    }
+
+    async fn find_api_key_by_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Option<ApiKeyRecord>, ApiErrors> {
+        let keys = self.keys.lock().unwrap();
+
+        Ok(keys.iter().find(|key| key.prefix == prefix).cloned())
+    }
+
+    async fn revoke_api_key(&self, id: Uuid, now: NaiveDateTime) -> Result<bool, ApiErrors> {
+        let mut keys = self.keys.lock().unwrap();
+
+        let Some(key) = keys
+            .iter_mut()
+            .find(|key| key.id == id && key.revoked_at.is_none())
+        else {
+            return Ok(false);
+        };
+
+        key.revoked_at = Some(now);
+
+        Ok(true)
+    }
+
+    async fn touch_api_key(&self, id: Uuid, now: NaiveDateTime) -> Result<(), ApiErrors> {
+        let mut keys = self.keys.lock().unwrap();
+
+        if let Some(key) = keys.iter_mut().find(|key| key.id == id) {
+            key.last_used_at = Some(now);
+        }
+
+        Ok(())
+    }
+}
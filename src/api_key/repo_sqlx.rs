@@ -0,0 +1,98 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    api_key::{dto::ApiKeyRecord, repo::ApiKeyRepository},
+    errors::api_errors::ApiErrors,
+};
+
+pub struct ApiKeyRepoSqlx {
+    pub pool: PgPool,
+}
+
+#[async_trait]
+impl ApiKeyRepository for ApiKeyRepoSqlx {
+    async fn insert_api_key(&self, record: &ApiKeyRecord) -> Result<(), ApiErrors> {
+        sqlx::query!(
+            r#"
+            INSERT INTO api_keys (id, name, prefix, secret_hash, scopes, user_id, expires_at, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            record.id,
+            record.name,
+            record.prefix,
+            record.secret_hash,
+            &record.scopes,
+            record.user_id,
+            record.expires_at,
+            record.created_at,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed to store API key".into()))?;
+
+        Ok(())
+    }
+
+    async fn list_api_keys(&self) -> Result<Vec<ApiKeyRecord>, ApiErrors> {
+        sqlx::query_as!(
+            ApiKeyRecord,
+            r#"
+            SELECT id, name, prefix, secret_hash, scopes, user_id, expires_at, last_used_at,
+                   revoked_at, created_at
+            FROM api_keys
+            ORDER BY created_at DESC
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("API key lookup failed".into()))
+    }
+
+    async fn find_api_key_by_prefix(
+        &self,
+        prefix: &str,
+    ) -> Result<Option<ApiKeyRecord>, ApiErrors> {
+        sqlx::query_as!(
+            ApiKeyRecord,
+            r#"
+            SELECT id, name, prefix, secret_hash, scopes, user_id, expires_at, last_used_at,
+                   revoked_at, created_at
+            FROM api_keys
+            WHERE prefix = $1
+            "#,
+            prefix
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("API key lookup failed".into()))
+    }
+
+    async fn revoke_api_key(&self, id: Uuid, now: NaiveDateTime) -> Result<bool, ApiErrors> {
+        let revoked = sqlx::query!(
+            "UPDATE api_keys SET revoked_at = $2 WHERE id = $1 AND revoked_at IS NULL",
+            id,
+            now,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("API key revoke failed".into()))?;
+
+        Ok(revoked.rows_affected() > 0)
+    }
+
+    async fn touch_api_key(&self, id: Uuid, now: NaiveDateTime) -> Result<(), ApiErrors> {
+        sqlx::query!(
+            "UPDATE api_keys SET last_used_at = $2 WHERE id = $1",
+            id,
+            now,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed to update API key".into()))?;
+
+        Ok(())
+    }
+}
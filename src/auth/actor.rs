@@ -1,27 +1,68 @@
+use std::{
+    net::IpAddr,
+    sync::{Arc, Mutex},
+};
+
+use chrono::Utc;
+use url::Url;
+
 use crate::{
     auth::{
         dispatcher::auth_dispatcher,
-        dto::{LoginResponse, RegisteredData, UpdatedData},
+        dto::{
+            LoginResponse, RegisteredData, TotpRecord, TwoFactorLoginResponse, TwoFactorSetup,
+            UpdatedData,
+        },
         messages::{AuthMessage, UserResponse},
+        repo::UserRepository,
+        throttle::LoginThrottle,
     },
-    core::password_core::{hash_password, verify_password},
-    errors::api_errors::ApiErrors,
-    fields::{
-        email::Email, password::Password, phone_number::PhoneNumber, roles::Roles, text::Text,
+    config::{LoginThrottleConfig, PasswordResetConfig, TwoFactorConfig},
+    core::{
+        password_core::{hash_password, verify_dummy_password, verify_password},
+        totp_core::{
+            generate_recovery_codes, generate_totp_secret, hash_recovery_code, otpauth_uri,
+            verify_totp,
+        },
     },
+    errors::api_errors::ApiErrors,
+    fields::{email::Email, password::Password, roles::Roles},
+    mail::mailer::{Mail, Mailer},
+    utils::tokens::{generate_opaque_token, hash_opaque_token},
 };
 
-use sqlx::PgPool;
 use tokio::sync::mpsc;
 use uuid::Uuid;
 
-pub struct AuthActor {
-    pool: PgPool,
+pub struct AuthActor<R>
+where
+    R: UserRepository + Send + Sync + 'static,
+{
+    pub repo: R,
+    pub mailer: Arc<dyn Mailer>,
+    pub password_reset: PasswordResetConfig,
+    pub throttle: Mutex<LoginThrottle>,
+    pub two_factor: TwoFactorConfig,
 }
 
-impl AuthActor {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+impl<R> AuthActor<R>
+where
+    R: UserRepository + Send + Sync + 'static,
+{
+    pub fn new(
+        repo: R,
+        mailer: Arc<dyn Mailer>,
+        password_reset: PasswordResetConfig,
+        login_throttle: LoginThrottleConfig,
+        two_factor: TwoFactorConfig,
+    ) -> Self {
+        Self {
+            repo,
+            mailer,
+            password_reset,
+            throttle: Mutex::new(LoginThrottle::new(login_throttle)),
+            two_factor,
+        }
     }
 
     pub async fn run(self, rx: mpsc::Receiver<AuthMessage>) {
@@ -30,29 +71,13 @@ impl AuthActor {
 
     pub async fn register(&self, user: RegisteredData) -> Result<Uuid, ApiErrors> {
         let hash = hash_password(user.password.as_str())
-            .map_err(|e| ApiErrors::PasswordFail(e.to_string()))?;
+            .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
 
         let id = Uuid::new_v4();
 
         let created_at = chrono::Utc::now().naive_utc();
 
-        sqlx::query!(
-            "INSERT INTO users (id, email, password, name, phone_number, roles, created_by, created_by_name, created_by_email, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
-            id,
-            user.email.as_str(),
-            hash,
-            user.name.as_str(),
-            user.phone_number.as_ref().map(|p| p.as_str()),
-            user.roles.as_str(),
-            user.created_by,
-            user.created_by_name.as_str(),
-            user.created_by_email.as_str(),
-            created_at,
-            created_at,
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|_| ApiErrors::Conflict("Email already exists".to_string()))?;
+        self.repo.insert_user(id, &user, &hash, created_at).await?;
 
         Ok(id)
     }
@@ -60,102 +85,445 @@ impl AuthActor {
     pub async fn login(
         &self,
         email: Email,
-        password: Password,
+        password: String,
+        ip: Option<IpAddr>,
     ) -> Result<LoginResponse, ApiErrors> {
-        let record = sqlx::query!(
-            "SELECT id, password FROM users WHERE email = $1",
-            email.as_str()
-        )
-        .fetch_one(&self.pool)
-        .await
-        .map_err(|_| ApiErrors::Unauthorized("Invalid credentials".to_string()))?;
+        self.throttle.lock().unwrap().check(email.as_str(), ip)?;
+
+        let record = self.repo.find_credentials_by_email(email.as_str()).await?;
 
-        verify_password(password.as_str(), &record.password)
-            .map_err(|e| ApiErrors::PasswordFail(e.to_string()))?;
+        // Unknown emails still pay for a hash verification, so timing does not tell them apart.
+        let user_id = match record {
+            Some(record) => verify_password(&password, &record.password)
+                .ok()
+                .map(|_| record.id),
+            None => {
+                verify_dummy_password(&password);
+                None
+            }
+        };
 
-        // let token = generate_token(record.id, &self.jwt_secret, self.jwt_expiry_hour)?;
+        let Some(id) = user_id else {
+            self.throttle
+                .lock()
+                .unwrap()
+                .record_failure(email.as_str(), ip);
+            return Err(ApiErrors::Unauthorized("Invalid credentials".to_string()));
+        };
 
-        Ok(LoginResponse { id: record.id })
+        // Failures stay on record until the second factor is passed as well.
+        if let Some(challenge) = self.two_factor_challenge(id).await? {
+            return Ok(challenge);
+        }
+
+        self.throttle.lock().unwrap().record_success(email.as_str());
+
+        Ok(LoginResponse::Authenticated { id })
     }
 
-    pub async fn get_user(&self, user_id: Uuid) -> Result<UserResponse, ApiErrors> {
-        let user = sqlx::query!("SELECT id, email, name, phone_number, roles, created_at, updated_at FROM users WHERE id = $1", user_id)
-            .fetch_one(&self.pool)
-            .await
-            .map_err(|_| ApiErrors::NotFound("User not found".to_string()))?;
+    async fn two_factor_challenge(
+        &self,
+        user_id: Uuid,
+    ) -> Result<Option<LoginResponse>, ApiErrors> {
+        let enrolled = self
+            .repo
+            .find_totp(user_id)
+            .await?
+            .is_some_and(|totp| totp.confirmed_at.is_some());
+
+        let setup_required = !enrolled && self.requires_two_factor(user_id).await?;
+
+        if !enrolled && !setup_required {
+            return Ok(None);
+        }
+
+        let challenge_token = generate_opaque_token();
+
+        let expires_at = Utc::now().naive_utc()
+            + chrono::Duration::from_std(self.two_factor.challenge_ttl)
+                .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+        self.repo
+            .insert_two_factor_challenge(user_id, &hash_opaque_token(&challenge_token), expires_at)
+            .await?;
+
+        Ok(Some(LoginResponse::TwoFactorRequired {
+            challenge_token,
+            setup_required,
+        }))
+    }
+
+    async fn requires_two_factor(&self, user_id: Uuid) -> Result<bool, ApiErrors> {
+        if !self.two_factor.required_for_admins {
+            return Ok(false);
+        }
+
+        let user = self.get_user(user_id).await?;
+
+        Ok(matches!(user.roles, Roles::Root | Roles::Mid))
+    }
+
+    /// Answers a login challenge with a TOTP or recovery code, or, for an account that had to
+    /// enrol, with the first code from the newly set up app.
+    pub async fn login_two_factor(
+        &self,
+        challenge_token: String,
+        code: String,
+        ip: Option<IpAddr>,
+    ) -> Result<TwoFactorLoginResponse, ApiErrors> {
+        let token_hash = hash_opaque_token(&challenge_token);
+
+        let challenge = self
+            .repo
+            .find_two_factor_challenge(&token_hash, Utc::now().naive_utc())
+            .await?
+            .ok_or_else(|| ApiErrors::Unauthorized("Invalid or expired challenge".to_string()))?;
+
+        let user = self.get_user(challenge.user_id).await?;
+
+        self.throttle
+            .lock()
+            .unwrap()
+            .check(user.email.as_str(), ip)?;
 
-        Ok(UserResponse {
+        let totp = self.repo.find_totp(user.id).await?.ok_or_else(|| {
+            ApiErrors::BadRequest("Set up two-factor authentication first".to_string())
+        })?;
+
+        let outcome = if totp.confirmed_at.is_some() {
+            self.verify_second_factor(user.id, &totp, &code)
+                .await?
+                .then_some(None)
+        } else {
+            self.confirm_enrolment(user.id, &totp, &code)
+                .await?
+                .map(Some)
+        };
+
+        let Some(recovery_codes) = outcome else {
+            self.throttle
+                .lock()
+                .unwrap()
+                .record_failure(user.email.as_str(), ip);
+
+            if challenge.attempts + 1 >= self.two_factor.max_attempts {
+                self.repo.delete_two_factor_challenge(&token_hash).await?;
+            } else {
+                self.repo.fail_two_factor_challenge(&token_hash).await?;
+            }
+
+            return Err(ApiErrors::Unauthorized("Invalid code".to_string()));
+        };
+
+        if !self.repo.delete_two_factor_challenge(&token_hash).await? {
+            return Err(ApiErrors::Unauthorized(
+                "Invalid or expired challenge".to_string(),
+            ));
+        }
+
+        self.throttle
+            .lock()
+            .unwrap()
+            .record_success(user.email.as_str());
+
+        Ok(TwoFactorLoginResponse {
             id: user.id,
-            email: Email(user.email),
-            name: Text(user.name),
-            phone_number: user.phone_number.map(PhoneNumber),
-            roles: Roles::new(&user.roles)?,
-            created_at: user.created_at,
-            updated_at: user.updated_at,
+            recovery_codes,
         })
     }
 
+    /// Starts the enrolment a login challenge asked for.
+    pub async fn setup_two_factor_for_challenge(
+        &self,
+        challenge_token: String,
+    ) -> Result<TwoFactorSetup, ApiErrors> {
+        let challenge = self
+            .repo
+            .find_two_factor_challenge(&hash_opaque_token(&challenge_token), Utc::now().naive_utc())
+            .await?
+            .ok_or_else(|| ApiErrors::Unauthorized("Invalid or expired challenge".to_string()))?;
+
+        self.setup_two_factor(challenge.user_id).await
+    }
+
+    pub async fn setup_two_factor(&self, user_id: Uuid) -> Result<TwoFactorSetup, ApiErrors> {
+        let user = self.get_user(user_id).await?;
+
+        if self
+            .repo
+            .find_totp(user_id)
+            .await?
+            .is_some_and(|totp| totp.confirmed_at.is_some())
+        {
+            return Err(ApiErrors::BadRequest(
+                "Two-factor authentication is already enabled".to_string(),
+            ));
+        }
+
+        let secret = generate_totp_secret();
+
+        self.repo.save_pending_totp(user_id, &secret).await?;
+
+        Ok(TwoFactorSetup {
+            otpauth_uri: otpauth_uri(&self.two_factor.issuer, user.email.as_str(), &secret),
+            secret,
+        })
+    }
+
+    /// Turns on two-factor authentication and answers with the recovery codes, which are
+    /// only ever shown here.
+    pub async fn confirm_two_factor(
+        &self,
+        user_id: Uuid,
+        code: String,
+    ) -> Result<Vec<String>, ApiErrors> {
+        let totp = self
+            .repo
+            .find_totp(user_id)
+            .await?
+            .ok_or_else(|| ApiErrors::BadRequest("Start two-factor setup first".to_string()))?;
+
+        if totp.confirmed_at.is_some() {
+            return Err(ApiErrors::BadRequest(
+                "Two-factor authentication is already enabled".to_string(),
+            ));
+        }
+
+        self.confirm_enrolment(user_id, &totp, &code)
+            .await?
+            .ok_or_else(|| ApiErrors::BadRequest("Invalid code".to_string()))
+    }
+
+    pub async fn disable_two_factor(
+        &self,
+        user_id: Uuid,
+        password: String,
+        code: String,
+    ) -> Result<(), ApiErrors> {
+        if self.requires_two_factor(user_id).await? {
+            return Err(ApiErrors::BadRequest(
+                "Two-factor authentication is required for your admin level".to_string(),
+            ));
+        }
+
+        let record = self
+            .repo
+            .find_credentials(user_id)
+            .await?
+            .ok_or_else(|| ApiErrors::NotFound("User not found".to_string()))?;
+
+        verify_password(&password, &record.password)
+            .map_err(|_| ApiErrors::BadRequest("Current password is incorrect".to_string()))?;
+
+        let totp = self
+            .repo
+            .find_totp(user_id)
+            .await?
+            .filter(|totp| totp.confirmed_at.is_some())
+            .ok_or_else(|| {
+                ApiErrors::BadRequest("Two-factor authentication is not enabled".to_string())
+            })?;
+
+        if !self.verify_second_factor(user_id, &totp, &code).await? {
+            return Err(ApiErrors::BadRequest("Invalid code".to_string()));
+        }
+
+        self.repo.delete_totp(user_id).await
+    }
+
+    /// A TOTP code not used before, or else an unused recovery code, which is then spent.
+    async fn verify_second_factor(
+        &self,
+        user_id: Uuid,
+        totp: &TotpRecord,
+        code: &str,
+    ) -> Result<bool, ApiErrors> {
+        let now = Utc::now();
+
+        match verify_totp(&totp.secret, code, now.timestamp(), totp.last_used_step) {
+            Some(step) => self.repo.use_totp_step(user_id, step).await,
+            None => {
+                self.repo
+                    .use_recovery_code(user_id, &hash_recovery_code(code), now.naive_utc())
+                    .await
+            }
+        }
+    }
+
+    async fn confirm_enrolment(
+        &self,
+        user_id: Uuid,
+        totp: &TotpRecord,
+        code: &str,
+    ) -> Result<Option<Vec<String>>, ApiErrors> {
+        let now = Utc::now();
+
+        let Some(step) = verify_totp(&totp.secret, code, now.timestamp(), None) else {
+            return Ok(None);
+        };
+
+        let recovery_codes = generate_recovery_codes();
+
+        let hashes: Vec<String> = recovery_codes
+            .iter()
+            .map(|code| hash_recovery_code(code))
+            .collect();
+
+        if !self
+            .repo
+            .confirm_totp(user_id, step, &hashes, now.naive_utc())
+            .await?
+        {
+            return Ok(None);
+        }
+
+        Ok(Some(recovery_codes))
+    }
+
+    pub async fn unlock_user(&self, user_id: Uuid) -> Result<bool, ApiErrors> {
+        let user = self.get_user(user_id).await?;
+
+        Ok(self.throttle.lock().unwrap().unlock(user.email.as_str()))
+    }
+
+    pub async fn get_user(&self, user_id: Uuid) -> Result<UserResponse, ApiErrors> {
+        self.repo
+            .find_user(user_id)
+            .await?
+            .ok_or_else(|| ApiErrors::NotFound("User not found".to_string()))
+    }
+
+    /// Signs in by an email the caller has already proven, e.g. through an identity provider.
+    /// Enrolled accounts and admins that must enrol get the same challenge as a password login.
+    pub async fn login_verified_email(
+        &self,
+        email: &Email,
+    ) -> Result<Option<LoginResponse>, ApiErrors> {
+        let Some(record) = self.repo.find_credentials_by_email(email.as_str()).await? else {
+            return Ok(None);
+        };
+
+        if let Some(challenge) = self.two_factor_challenge(record.id).await? {
+            return Ok(Some(challenge));
+        }
+
+        Ok(Some(LoginResponse::Authenticated { id: record.id }))
+    }
+
     pub async fn get_all_users(&self) -> Result<Vec<UserResponse>, ApiErrors> {
-        let users = sqlx::query!("SELECT id, email, name, phone_number, roles, created_at, updated_at FROM users ORDER BY created_at DESC")
-            .fetch_all(&self.pool)
-            .await
-            .map_err(|_| ApiErrors::InternalServerError("Failed to fetch users".to_string()))?;
-
-        // Ok(users
-        //     .into_iter()
-        //     .map(|u| UserResponse {
-        //         id: u.id,
-        //         email: Email(u.email),
-        //         name: Text(u.name),
-        //         phone_number: u.phone_number.map(PhoneNumber),
-        //         roles: Roles::new(&u.roles)?,
-        //         created_at: u.created_at,
-        //         updated_at: u.updated_at,
-        //     })
-        //     .collect())
-
-        let users = users
-            .into_iter()
-            .map(|u| {
-                Ok(UserResponse {
-                    id: u.id,
-                    email: Email(u.email),
-                    name: Text(u.name),
-                    phone_number: u.phone_number.map(PhoneNumber),
-                    roles: Roles::new(&u.roles)
-                        .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?,
-                    created_at: u.created_at,
-                    updated_at: u.updated_at,
-                })
-            })
-            .collect::<Result<Vec<_>, ApiErrors>>()?;
-
-        Ok(users)
+        self.repo.list_users().await
     }
 
     pub async fn update_user(&self, user: UpdatedData) -> Result<bool, ApiErrors> {
-        let result = sqlx::query!(r#"UPDATE users SET name = $1, phone_number = $2, roles = $3, edited_by = $4, edited_by_name = $5, edited_by_email = $6 WHERE id = $7"#, 
-                user.name.as_ref().map(|n| n.as_str()),
-                user.phone_number.as_ref().map(|p| p.as_str()),
-                user.roles.as_ref().map(|p| p.as_str()),
-                user.edited_by,
-                user.edited_by_name.as_str(),
-                user.edited_by_email.as_str(),
-                user.user_id,
-            )
-            .execute(&self.pool)
-            .await
-            .map_err(|_| ApiErrors::InternalServerError("Update failed".to_string()))?;
-
-        Ok(result.rows_affected() > 0)
+        self.repo.update_user(&user).await
+    }
+
+    pub async fn revoke_access_tokens(&self, user_id: Uuid) -> Result<bool, ApiErrors> {
+        self.repo.bump_token_version(user_id).await
     }
 
     pub async fn delete_user(&self, user_id: Uuid) -> Result<bool, ApiErrors> {
-        let result = sqlx::query!("DELETE FROM users WHERE id = $1", user_id)
-            .execute(&self.pool)
-            .await
-            .map_err(|_| ApiErrors::InternalServerError("Delete failed".to_string()))?;
+        self.repo.delete_user(user_id).await
+    }
+
+    pub async fn change_password(
+        &self,
+        user_id: Uuid,
+        current_password: String,
+        new_password: Password,
+    ) -> Result<(), ApiErrors> {
+        let record = self
+            .repo
+            .find_credentials(user_id)
+            .await?
+            .ok_or_else(|| ApiErrors::NotFound("User not found".to_string()))?;
+
+        verify_password(&current_password, &record.password)
+            .map_err(|_| ApiErrors::BadRequest("Current password is incorrect".to_string()))?;
+
+        if current_password == new_password.as_str() {
+            return Err(ApiErrors::BadRequest(
+                "New password must differ from the current one".to_string(),
+            ));
+        }
+
+        let hash = hash_password(new_password.as_str())
+            .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+        if !self.repo.update_password(user_id, &hash).await? {
+            return Err(ApiErrors::NotFound("User not found".to_string()));
+        }
+
+        Ok(())
+    }
+
+    pub async fn forgot_password(&self, email: Email) -> Result<(), ApiErrors> {
+        // Unknown emails look exactly like known ones to the caller.
+        let Some(record) = self.repo.find_credentials_by_email(email.as_str()).await? else {
+            return Ok(());
+        };
+
+        let token = generate_opaque_token();
+
+        let expires_at = Utc::now().naive_utc()
+            + chrono::Duration::from_std(self.password_reset.ttl)
+                .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+        self.repo
+            .insert_reset_token(record.id, &hash_opaque_token(&token), expires_at)
+            .await?;
+
+        let mut link = Url::parse(&self.password_reset.url)
+            .map_err(|_| ApiErrors::InternalServerError("Invalid reset url".to_string()))?;
+        link.query_pairs_mut().append_pair("token", &token);
+
+        let mail = Mail {
+            to: email.as_str().to_string(),
+            subject: "Reset your password".to_string(),
+            body: format!(
+                "Someone asked to reset the password for this account.\n\n\
+                 Open this link within {} minutes to choose a new one:\n{link}\n\n\
+                 If it wasn't you, ignore this email and your password stays the same.",
+                self.password_reset.ttl.as_secs() / 60
+            ),
+        };
+
+        // Sent in the background: a slow relay must neither hold up the actor nor make known
+        // emails answer later than unknown ones.
+        let mailer = self.mailer.clone();
+        tokio::spawn(async move {
+            if let Err(e) = mailer.send(mail).await {
+                println!("failed to send password reset email: {e}");
+            }
+        });
+
+        Ok(())
+    }
+
+    pub async fn reset_password(
+        &self,
+        token: String,
+        password: Password,
+    ) -> Result<Uuid, ApiErrors> {
+        let hash = hash_password(password.as_str())
+            .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+        let user_id = self
+            .repo
+            .consume_reset_token(&hash_opaque_token(&token), Utc::now().naive_utc())
+            .await?
+            .ok_or_else(|| ApiErrors::BadRequest("Invalid or expired reset token".to_string()))?;
+
+        if !self.repo.update_password(user_id, &hash).await? {
+            return Err(ApiErrors::NotFound("User not found".to_string()));
+        }
+
+        // Access tokens handed out under the old password have to go with it.
+        self.repo.bump_token_version(user_id).await?;
+
+        // Owning the inbox proves ownership of the account, so a lockout no longer applies.
+        self.unlock_user(user_id).await?;
 
-        Ok(result.rows_affected() > 0)
+        Ok(user_id)
     }
 }
@@ -1,8 +1,11 @@
 use tokio::sync::mpsc;
 
-use crate::auth::{actor::AuthActor, messages::AuthMessage};
+use crate::auth::{actor::AuthActor, messages::AuthMessage, repo::UserRepository};
 
-pub async fn auth_dispatcher(actor: &AuthActor, mut rx: mpsc::Receiver<AuthMessage>) {
+pub async fn auth_dispatcher<R>(actor: &AuthActor<R>, mut rx: mpsc::Receiver<AuthMessage>)
+where
+    R: UserRepository + Send + Sync + 'static,
+{
     while let Some(msg) = rx.recv().await {
         match msg {
             AuthMessage::Register { user, respond_to } => {
@@ -11,9 +14,10 @@ pub async fn auth_dispatcher(actor: &AuthActor, mut rx: mpsc::Receiver<AuthMessa
             AuthMessage::Login {
                 email,
                 password,
+                ip,
                 respond_to,
             } => {
-                let _ = respond_to.send(actor.login(email, password).await);
+                let _ = respond_to.send(actor.login(email, password, ip).await);
             }
             AuthMessage::GetUser {
                 user_id,
@@ -21,6 +25,15 @@ pub async fn auth_dispatcher(actor: &AuthActor, mut rx: mpsc::Receiver<AuthMessa
             } => {
                 let _ = respond_to.send(actor.get_user(user_id).await);
             }
+            AuthMessage::LoginVerifiedEmail { email, respond_to } => {
+                let _ = respond_to.send(actor.login_verified_email(&email).await);
+            }
+            AuthMessage::RevokeAccessTokens {
+                user_id,
+                respond_to,
+            } => {
+                let _ = respond_to.send(actor.revoke_access_tokens(user_id).await);
+            }
             AuthMessage::GetAllUsers { respond_to } => {
                 let _ = respond_to.send(actor.get_all_users().await);
             }
@@ -33,6 +46,70 @@ pub async fn auth_dispatcher(actor: &AuthActor, mut rx: mpsc::Receiver<AuthMessa
             } => {
                 let _ = respond_to.send(actor.delete_user(user_id).await);
             }
+            AuthMessage::UnlockUser {
+                user_id,
+                respond_to,
+            } => {
+                let _ = respond_to.send(actor.unlock_user(user_id).await);
+            }
+            AuthMessage::ChangePassword {
+                user_id,
+                current_password,
+                new_password,
+                respond_to,
+            } => {
+                let _ = respond_to.send(
+                    actor
+                        .change_password(user_id, current_password, new_password)
+                        .await,
+                );
+            }
+            AuthMessage::ForgotPassword { email, respond_to } => {
+                let _ = respond_to.send(actor.forgot_password(email).await);
+            }
+            AuthMessage::ResetPassword {
+                token,
+                password,
+                respond_to,
+            } => {
+                let _ = respond_to.send(actor.reset_password(token, password).await);
+            }
+            AuthMessage::LoginTwoFactor {
+                challenge_token,
+                code,
+                ip,
+                respond_to,
+            } => {
+                let _ = respond_to.send(actor.login_two_factor(challenge_token, code, ip).await);
+            }
+            AuthMessage::SetupTwoFactorForChallenge {
+                challenge_token,
+                respond_to,
+            } => {
+                let _ =
+                    respond_to.send(actor.setup_two_factor_for_challenge(challenge_token).await);
+            }
+            AuthMessage::SetupTwoFactor {
+                user_id,
+                respond_to,
+            } => {
+                let _ = respond_to.send(actor.setup_two_factor(user_id).await);
+            }
+            AuthMessage::ConfirmTwoFactor {
+                user_id,
+                code,
+                respond_to,
+            } => {
+                let _ = respond_to.send(actor.confirm_two_factor(user_id, code).await);
+            }
+            AuthMessage::DisableTwoFactor {
+                user_id,
+                password,
+                code,
+                respond_to,
+            } => {
+                let _ = respond_to.send(actor.disable_two_factor(user_id, password, code).await);
+            }
         }
     }
 }
@@ -1,4 +1,5 @@
-use serde::Deserialize;
+use chrono::NaiveDateTime;
+use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::fields::{
@@ -46,6 +47,77 @@ pub struct UpdateUserRequest {
     pub roles: Option<String>,
 }
 
-pub struct LoginResponse {
+pub struct ValidatedChangePassword {
+    pub current_password: String,
+    pub new_password: String,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateProfileRequest {
+    pub name: Option<String>,
+    pub phone_number: Option<String>,
+}
+
+pub struct ValidatedForgotPassword {
+    pub email: String,
+}
+
+pub struct ValidatedResetPassword {
+    pub token: String,
+    pub password: String,
+}
+
+pub struct ValidatedTwoFactorLogin {
+    pub challenge_token: String,
+    pub code: String,
+}
+
+pub struct ValidatedDisableTwoFactor {
+    pub password: String,
+    pub code: String,
+}
+
+pub struct ValidatedOidcCallback {
+    pub code: String,
+    pub state: String,
+}
+
+pub enum LoginResponse {
+    Authenticated {
+        id: Uuid,
+    },
+    /// The password was right but a second factor is still owed; `setup_required` when the
+    /// account has to enrol before it can log in.
+    TwoFactorRequired {
+        challenge_token: String,
+        setup_required: bool,
+    },
+}
+
+pub struct TwoFactorLoginResponse {
+    pub id: Uuid,
+    /// Set when this login also completed a required enrolment.
+    pub recovery_codes: Option<Vec<String>>,
+}
+
+#[derive(Serialize)]
+pub struct TwoFactorSetup {
+    pub secret: String,
+    pub otpauth_uri: String,
+}
+
+pub struct TotpRecord {
+    pub secret: String,
+    pub confirmed_at: Option<NaiveDateTime>,
+    pub last_used_step: Option<i64>,
+}
+
+pub struct TwoFactorChallenge {
+    pub user_id: Uuid,
+    pub attempts: i32,
+}
+
+pub struct UserCredentials {
     pub id: Uuid,
+    pub password: String,
 }
@@ -6,14 +6,23 @@ use uuid::Uuid;
 
 use crate::{
     auth::{dto::*, messages::AuthMessage},
-    core::login_token_core::login_token_core,
+    core::login_token_core::{login_token_core, revoke_user_tokens_core},
     errors::api_errors::ApiErrors,
-    extractor::{auth_extractor::AuthUser, json_body::RequiredJson, path_id_extractor::PathParam},
+    extractor::{
+        auth_extractor::AuthUser, client_info_extractor::ClientInfo, json_body::RequiredJson,
+        path_id_extractor::PathParam,
+    },
     fields::{
         email::Email, password::Password, phone_number::PhoneNumber, roles::Roles, text::Text,
     },
-    payload_handler::auth_payload_handler::{LoginRequest, RegisterRequest},
+    payload_handler::auth_payload_handler::{
+        ChangePasswordRequest, DisableTwoFactorRequest, ForgotPasswordRequest, LoginRequest,
+        RegisterRequest, ResetPasswordRequest, TwoFactorChallengeRequest, TwoFactorCodeRequest,
+        TwoFactorLoginRequest,
+    },
+    refresh_token::messages::RefreshTokenMessage,
     state::AppState,
+    utils::cookies::clear_refresh_cookie,
 };
 
 pub async fn register(
@@ -88,6 +97,7 @@ pub async fn register(
 
 pub async fn login(
     cookies: Cookies,
+    client: ClientInfo,
     State(state): State<AppState>,
     RequiredJson(payload): RequiredJson<LoginRequest>,
 ) -> Result<Json<serde_json::Value>, ApiErrors> {
@@ -95,14 +105,15 @@ pub async fn login(
 
     let payload_data = payload.validate()?;
 
-    let email = Email::new(&payload_data.email)?;
-    let password = Password::new(&payload_data.password)?;
+    let email = Email::new(&payload_data.email)
+        .map_err(|_| ApiErrors::Unauthorized("Invalid credentials".to_string()))?;
 
     state
         .auth_tx
         .send(AuthMessage::Login {
             email,
-            password,
+            password: payload_data.password,
+            ip: client.ip,
             respond_to: tx,
         })
         .await
@@ -112,7 +123,24 @@ pub async fn login(
         .await
         .map_err(|_| ApiErrors::InternalServerError("Auth failed".to_string()))??;
 
-    let tokens = login_token_core(&state.refresh_token_tx, cookies, response.id).await?;
+    let id = match response {
+        LoginResponse::Authenticated { id } => id,
+        LoginResponse::TwoFactorRequired {
+            challenge_token,
+            setup_required,
+        } => {
+            return Ok(Json(serde_json::json!({
+                "message": "two_factor_required".to_string(),
+                "data": {
+                    "challenge_token": challenge_token,
+                    "setup_required": setup_required,
+                }
+            })));
+        }
+    };
+
+    let tokens =
+        login_token_core(&state.refresh_token_tx, &state.tokens, cookies, id, client).await?;
 
     // let response = ResponseTokenMessage {
     //     message: "success".to_string(),
@@ -127,6 +155,154 @@ pub async fn login(
     })))
 }
 
+pub async fn login_two_factor(
+    cookies: Cookies,
+    client: ClientInfo,
+    State(state): State<AppState>,
+    RequiredJson(payload): RequiredJson<TwoFactorLoginRequest>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    let payload_data = payload.validate()?;
+
+    state
+        .auth_tx
+        .send(AuthMessage::LoginTwoFactor {
+            challenge_token: payload_data.challenge_token,
+            code: payload_data.code,
+            ip: client.ip,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Auth service unavailable".to_string()))?;
+
+    let response = rx
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Auth failed".to_string()))??;
+
+    let tokens = login_token_core(
+        &state.refresh_token_tx,
+        &state.tokens,
+        cookies,
+        response.id,
+        client,
+    )
+    .await?;
+
+    Ok(Json(serde_json::json!({
+        "message": "success".to_string(),
+        "token": tokens.access_token,
+        "recovery_codes": response.recovery_codes,
+    })))
+}
+
+pub async fn setup_login_two_factor(
+    State(state): State<AppState>,
+    RequiredJson(payload): RequiredJson<TwoFactorChallengeRequest>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    let challenge_token = payload.validate()?;
+
+    state
+        .auth_tx
+        .send(AuthMessage::SetupTwoFactorForChallenge {
+            challenge_token,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Auth service unavailable".to_string()))?;
+
+    let setup = rx
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Auth failed".to_string()))??;
+
+    Ok(Json(serde_json::json!({
+        "message": "success".to_string(),
+        "data": setup,
+    })))
+}
+
+pub async fn setup_two_factor(
+    AuthUser { id, .. }: AuthUser,
+    State(state): State<AppState>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    state
+        .auth_tx
+        .send(AuthMessage::SetupTwoFactor {
+            user_id: id,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Auth service unavailable".to_string()))?;
+
+    let setup = rx
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Auth failed".to_string()))??;
+
+    Ok(Json(serde_json::json!({
+        "message": "success".to_string(),
+        "data": setup,
+    })))
+}
+
+pub async fn confirm_two_factor(
+    AuthUser { id, .. }: AuthUser,
+    State(state): State<AppState>,
+    RequiredJson(payload): RequiredJson<TwoFactorCodeRequest>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    let code = payload.validate()?;
+
+    state
+        .auth_tx
+        .send(AuthMessage::ConfirmTwoFactor {
+            user_id: id,
+            code,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Auth service unavailable".to_string()))?;
+
+    let recovery_codes = rx
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Auth failed".to_string()))??;
+
+    Ok(Json(serde_json::json!({
+        "message": "success".to_string(),
+        "data": { "recovery_codes": recovery_codes },
+    })))
+}
+
+pub async fn disable_two_factor(
+    AuthUser { id, .. }: AuthUser,
+    State(state): State<AppState>,
+    RequiredJson(payload): RequiredJson<DisableTwoFactorRequest>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    let payload_data = payload.validate()?;
+
+    state
+        .auth_tx
+        .send(AuthMessage::DisableTwoFactor {
+            user_id: id,
+            password: payload_data.password,
+            code: payload_data.code,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Auth service unavailable".to_string()))?;
+
+    rx.await
+        .map_err(|_| ApiErrors::InternalServerError("Auth failed".to_string()))??;
+
+    Ok(Json(serde_json::json!({"message": "success".to_string(),})))
+}
+
 pub async fn get_current_user(
     AuthUser { id, .. }: AuthUser,
     State(state): State<AppState>,
@@ -152,6 +328,84 @@ pub async fn get_current_user(
     })))
 }
 
+pub async fn update_current_user(
+    AuthUser {
+        id, email, name, ..
+    }: AuthUser,
+    State(state): State<AppState>,
+    RequiredJson(payload): RequiredJson<UpdateProfileRequest>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    let name_data = payload.name.as_deref().map(Text::new).transpose()?;
+
+    let phone_number = payload
+        .phone_number
+        .as_deref()
+        .map(PhoneNumber::new)
+        .transpose()?;
+
+    let user = UpdatedData {
+        user_id: id,
+        name: name_data,
+        phone_number,
+        roles: None,
+        edited_by: id,
+        edited_by_name: name,
+        edited_by_email: email,
+    };
+
+    state
+        .auth_tx
+        .send(AuthMessage::UpdateUser {
+            user,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
+
+    rx.await
+        .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))??;
+
+    Ok(Json(serde_json::json!({"message": "success".to_string(),})))
+}
+
+pub async fn change_current_password(
+    AuthUser { id, .. }: AuthUser,
+    cookies: Cookies,
+    State(state): State<AppState>,
+    RequiredJson(payload): RequiredJson<ChangePasswordRequest>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    let payload_data = payload.validate()?;
+
+    let new_password = Password::new(&payload_data.new_password)?;
+
+    state
+        .auth_tx
+        .send(AuthMessage::ChangePassword {
+            user_id: id,
+            current_password: payload_data.current_password,
+            new_password,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Auth service unavailable".to_string()))?;
+
+    rx.await
+        .map_err(|_| ApiErrors::InternalServerError("Auth failed".to_string()))??;
+
+    // The session making the change stays signed in; every other one is ended.
+    let current_session = cookies
+        .get("refresh_token")
+        .map(|cookie| cookie.value().to_string());
+
+    revoke_user_tokens_core(&state.refresh_token_tx, id, current_session).await?;
+
+    Ok(Json(serde_json::json!({"message": "success".to_string(),})))
+}
+
 pub async fn get_user(
     _: AuthUser,
     State(state): State<AppState>,
@@ -268,16 +522,55 @@ pub async fn update_user(
     Ok(Json(serde_json::json!({"message": "success".to_string(),})))
 }
 
-pub async fn delete_user(
-    _: AuthUser,
+pub async fn unlock_user(
+    AuthUser { roles, .. }: AuthUser,
     State(state): State<AppState>,
     PathParam(user_id): PathParam<Uuid>,
 ) -> Result<Json<serde_json::Value>, ApiErrors> {
     let (tx, rx) = oneshot::channel();
 
+    if roles.as_str() == "normal" {
+        return Err(ApiErrors::BadRequest(
+            "Because of your ADMIN Level you can not unlock a user.".to_string(),
+        ));
+    }
+
     state
         .auth_tx
-        .send(AuthMessage::DeleteUser {
+        .send(AuthMessage::UnlockUser {
+            user_id,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
+
+    let was_locked = rx
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))??;
+
+    Ok(Json(serde_json::json!({
+        "message": "success".to_string(),
+        "data": { "had_failures": was_locked }
+    })))
+}
+
+pub async fn delete_user(
+    AuthUser { roles, .. }: AuthUser,
+    State(state): State<AppState>,
+    PathParam(user_id): PathParam<Uuid>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    if roles.as_str() == "normal" {
+        return Err(ApiErrors::BadRequest(
+            "Because of your ADMIN Level you can not delete a user.".to_string(),
+        ));
+    }
+
+    let (tx, rx) = oneshot::channel();
+
+    // The session side deletes the user so it can end their sessions once that succeeded.
+    state
+        .refresh_token_tx
+        .send(RefreshTokenMessage::DeleteUser {
             user_id,
             respond_to: tx,
         })
@@ -289,3 +582,63 @@ pub async fn delete_user(
 
     Ok(Json(serde_json::json!({"message": "success".to_string(),})))
 }
+
+pub async fn forgot_password(
+    State(state): State<AppState>,
+    RequiredJson(payload): RequiredJson<ForgotPasswordRequest>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    let payload_data = payload.validate()?;
+
+    let email = Email::new(&payload_data.email)?;
+
+    state
+        .auth_tx
+        .send(AuthMessage::ForgotPassword {
+            email,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Auth service unavailable".to_string()))?;
+
+    rx.await
+        .map_err(|_| ApiErrors::InternalServerError("Auth failed".to_string()))??;
+
+    Ok(Json(serde_json::json!({
+        "message": "If the email belongs to an account, a reset link has been sent".to_string(),
+    })))
+}
+
+pub async fn reset_password(
+    cookies: Cookies,
+    State(state): State<AppState>,
+    RequiredJson(payload): RequiredJson<ResetPasswordRequest>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    let payload_data = payload.validate()?;
+
+    let password = Password::new(&payload_data.password)?;
+
+    state
+        .auth_tx
+        .send(AuthMessage::ResetPassword {
+            token: payload_data.token,
+            password,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Auth service unavailable".to_string()))?;
+
+    let user_id = rx
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Auth failed".to_string()))??;
+
+    // Whoever knew the old password may still hold a session.
+    revoke_user_tokens_core(&state.refresh_token_tx, user_id, None).await?;
+
+    cookies.add(clear_refresh_cookie(&state.tokens.cookie));
+
+    Ok(Json(serde_json::json!({"message": "success".to_string(),})))
+}
@@ -1,16 +1,20 @@
+use std::net::IpAddr;
+
 use chrono::NaiveDateTime;
 use tokio::sync::oneshot;
 use uuid::Uuid;
 
 use crate::{
-    auth::dto::{LoginResponse, RegisteredData, UpdatedData},
+    auth::dto::{
+        LoginResponse, RegisteredData, TwoFactorLoginResponse, TwoFactorSetup, UpdatedData,
+    },
     errors::api_errors::ApiErrors,
     fields::{
         email::Email, password::Password, phone_number::PhoneNumber, roles::Roles, text::Text,
     },
 };
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, Clone)]
 pub struct UserResponse {
     pub id: Uuid,
     pub email: Email,
@@ -19,6 +23,9 @@ pub struct UserResponse {
     pub roles: Roles,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
+    /// Matched against the `ver` claim; tokens issued under an older version are rejected.
+    #[serde(skip)]
+    pub token_version: i32,
 }
 
 pub enum AuthMessage {
@@ -26,9 +33,11 @@ pub enum AuthMessage {
         user: RegisteredData,
         respond_to: oneshot::Sender<Result<Uuid, ApiErrors>>,
     },
+    /// `password` is checked as given; strength rules only apply when setting one.
     Login {
         email: Email,
-        password: Password,
+        password: String,
+        ip: Option<IpAddr>,
         respond_to: oneshot::Sender<Result<LoginResponse, ApiErrors>>,
     },
 
@@ -37,6 +46,13 @@ pub enum AuthMessage {
         respond_to: oneshot::Sender<Result<UserResponse, ApiErrors>>,
     },
 
+    /// A login where another proof of identity stands in for the password; the second factor
+    /// still applies. `None` when no account has the email.
+    LoginVerifiedEmail {
+        email: Email,
+        respond_to: oneshot::Sender<Result<Option<LoginResponse>, ApiErrors>>,
+    },
+
     GetAllUsers {
         respond_to: oneshot::Sender<Result<Vec<UserResponse>, ApiErrors>>,
     },
@@ -50,4 +66,70 @@ pub enum AuthMessage {
         user_id: Uuid,
         respond_to: oneshot::Sender<Result<bool, ApiErrors>>,
     },
+
+    /// Invalidates every access token issued to `user_id` so far; answers whether it exists.
+    RevokeAccessTokens {
+        user_id: Uuid,
+        respond_to: oneshot::Sender<Result<bool, ApiErrors>>,
+    },
+
+    /// Lifts a login lockout on `user_id` and answers whether there was one on record.
+    UnlockUser {
+        user_id: Uuid,
+        respond_to: oneshot::Sender<Result<bool, ApiErrors>>,
+    },
+
+    /// Replaces the password of `user_id` after checking `current_password`.
+    ChangePassword {
+        user_id: Uuid,
+        current_password: String,
+        new_password: Password,
+        respond_to: oneshot::Sender<Result<(), ApiErrors>>,
+    },
+
+    /// Emails a reset link if `email` belongs to a user; succeeds either way.
+    ForgotPassword {
+        email: Email,
+        respond_to: oneshot::Sender<Result<(), ApiErrors>>,
+    },
+
+    /// Sets a new password with an emailed token and answers with the user it belonged to.
+    ResetPassword {
+        token: String,
+        password: Password,
+        respond_to: oneshot::Sender<Result<Uuid, ApiErrors>>,
+    },
+
+    /// Finishes a login that answered with a challenge.
+    LoginTwoFactor {
+        challenge_token: String,
+        code: String,
+        ip: Option<IpAddr>,
+        respond_to: oneshot::Sender<Result<TwoFactorLoginResponse, ApiErrors>>,
+    },
+
+    /// Starts the enrolment a login challenge with `setup_required` asked for.
+    SetupTwoFactorForChallenge {
+        challenge_token: String,
+        respond_to: oneshot::Sender<Result<TwoFactorSetup, ApiErrors>>,
+    },
+
+    SetupTwoFactor {
+        user_id: Uuid,
+        respond_to: oneshot::Sender<Result<TwoFactorSetup, ApiErrors>>,
+    },
+
+    /// Answers with the recovery codes once `code` proves the app has the secret.
+    ConfirmTwoFactor {
+        user_id: Uuid,
+        code: String,
+        respond_to: oneshot::Sender<Result<Vec<String>, ApiErrors>>,
+    },
+
+    DisableTwoFactor {
+        user_id: Uuid,
+        password: String,
+        code: String,
+        respond_to: oneshot::Sender<Result<(), ApiErrors>>,
+    },
 }
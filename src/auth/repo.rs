@@ -0,0 +1,104 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use crate::{
+    auth::{
+        dto::{RegisteredData, TotpRecord, TwoFactorChallenge, UpdatedData, UserCredentials},
+        messages::UserResponse,
+    },
+    errors::api_errors::ApiErrors,
+};
+
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+    async fn insert_user(
+        &self,
+        id: Uuid,
+        user: &RegisteredData,
+        password_hash: &str,
+        created_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors>;
+
+    async fn find_credentials_by_email(
+        &self,
+        email: &str,
+    ) -> Result<Option<UserCredentials>, ApiErrors>;
+
+    async fn find_credentials(&self, user_id: Uuid) -> Result<Option<UserCredentials>, ApiErrors>;
+
+    async fn find_user(&self, user_id: Uuid) -> Result<Option<UserResponse>, ApiErrors>;
+
+    async fn list_users(&self) -> Result<Vec<UserResponse>, ApiErrors>;
+
+    /// A change of role also bumps the token version, so tokens claiming the old role stop working.
+    async fn update_user(&self, user: &UpdatedData) -> Result<bool, ApiErrors>;
+
+    async fn bump_token_version(&self, user_id: Uuid) -> Result<bool, ApiErrors>;
+
+    async fn delete_user(&self, user_id: Uuid) -> Result<bool, ApiErrors>;
+
+    async fn update_password(&self, user_id: Uuid, password_hash: &str) -> Result<bool, ApiErrors>;
+
+    async fn insert_reset_token(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        expires_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors>;
+
+    /// Uses up an unexpired reset token, along with any other outstanding ones for the same
+    /// user, and returns whose it was.
+    async fn consume_reset_token(
+        &self,
+        token_hash: &str,
+        now: NaiveDateTime,
+    ) -> Result<Option<Uuid>, ApiErrors>;
+
+    async fn find_totp(&self, user_id: Uuid) -> Result<Option<TotpRecord>, ApiErrors>;
+
+    /// Stores an unconfirmed secret, replacing an earlier unconfirmed one but never a
+    /// confirmed one.
+    async fn save_pending_totp(&self, user_id: Uuid, secret: &str) -> Result<(), ApiErrors>;
+
+    /// Confirms the pending secret with the code at `step` and replaces the recovery codes;
+    /// false when there was no pending secret left to confirm.
+    async fn confirm_totp(
+        &self,
+        user_id: Uuid,
+        step: i64,
+        recovery_code_hashes: &[String],
+        now: NaiveDateTime,
+    ) -> Result<bool, ApiErrors>;
+
+    /// Marks the code at `step` as used; false when it or a later one already was.
+    async fn use_totp_step(&self, user_id: Uuid, step: i64) -> Result<bool, ApiErrors>;
+
+    async fn use_recovery_code(
+        &self,
+        user_id: Uuid,
+        code_hash: &str,
+        now: NaiveDateTime,
+    ) -> Result<bool, ApiErrors>;
+
+    /// Removes the secret and recovery codes of `user_id`.
+    async fn delete_totp(&self, user_id: Uuid) -> Result<(), ApiErrors>;
+
+    async fn insert_two_factor_challenge(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        expires_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors>;
+
+    async fn find_two_factor_challenge(
+        &self,
+        token_hash: &str,
+        now: NaiveDateTime,
+    ) -> Result<Option<TwoFactorChallenge>, ApiErrors>;
+
+    async fn fail_two_factor_challenge(&self, token_hash: &str) -> Result<(), ApiErrors>;
+
+    /// False when the challenge was already gone, e.g. used by a concurrent request.
+    async fn delete_two_factor_challenge(&self, token_hash: &str) -> Result<bool, ApiErrors>;
+}
@@ -0,0 +1,403 @@
+use std::{cmp::Reverse, sync::Mutex};
+
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use crate::{
+    auth::{
+        dto::{RegisteredData, TotpRecord, TwoFactorChallenge, UpdatedData, UserCredentials},
+        messages::UserResponse,
+        repo::UserRepository,
+    },
+    errors::api_errors::ApiErrors,
+};
+
+struct UserRow {
+    user: UserResponse,
+    password: String,
+}
+
+struct ResetTokenRow {
+    user_id: Uuid,
+    token_hash: String,
+    expires_at: NaiveDateTime,
+    used: bool,
+}
+
+struct TotpRow {
+    user_id: Uuid,
+    secret: String,
+    confirmed_at: Option<NaiveDateTime>,
+    last_used_step: Option<i64>,
+}
+
+struct RecoveryCodeRow {
+    user_id: Uuid,
+    code_hash: String,
+    used: bool,
+}
+
+struct ChallengeRow {
+    user_id: Uuid,
+    token_hash: String,
+    expires_at: NaiveDateTime,
+    attempts: i32,
+}
+
+#[derive(Default)]
+pub struct UserRepoMemory {
+    users: Mutex<Vec<UserRow>>,
+    reset_tokens: Mutex<Vec<ResetTokenRow>>,
+    totp: Mutex<Vec<TotpRow>>,
+    recovery_codes: Mutex<Vec<RecoveryCodeRow>>,
+    challenges: Mutex<Vec<ChallengeRow>>,
+}
+
+impl UserRepoMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl UserRepository for UserRepoMemory {
+    async fn insert_user(
+        &self,
+        id: Uuid,
+        user: &RegisteredData,
+        password_hash: &str,
+        created_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors> {
+        let mut users = self.users.lock().unwrap();
+
+        if users
+            .iter()
+            .any(|row| row.user.email.as_str() == user.email.as_str())
+        {
+            return Err(ApiErrors::Conflict("Email already exists".to_string()));
+        }
+
+        users.push(UserRow {
+            user: UserResponse {
+                id,
+                email: user.email.clone(),
+                name: user.name.clone(),
+                phone_number: user.phone_number.clone(),
+                roles: user.roles.clone(),
+                created_at,
+                updated_at: created_at,
+                token_version: 0,
+            },
+            password: password_hash.to_string(),
+        });
+
+        Ok(())
+    }
+
+    async fn find_credentials_by_email(
+        &self,
+        email: &str,
+    ) -> Result<Option<UserCredentials>, ApiErrors> {
+        let users = self.users.lock().unwrap();
+
+        Ok(users
+            .iter()
+            .find(|row| row.user.email.as_str() == email)
+            .map(|row| UserCredentials {
+                id: row.user.id,
+                password: row.password.clone(),
+            }))
+    }
+
+    async fn find_credentials(&self, user_id: Uuid) -> Result<Option<UserCredentials>, ApiErrors> {
+        let users = self.users.lock().unwrap();
+
+        Ok(users
+            .iter()
+            .find(|row| row.user.id == user_id)
+            .map(|row| UserCredentials {
+                id: row.user.id,
+                password: row.password.clone(),
+            }))
+    }
+
+    async fn find_user(&self, user_id: Uuid) -> Result<Option<UserResponse>, ApiErrors> {
+        let users = self.users.lock().unwrap();
+
+        Ok(users
+            .iter()
+            .find(|row| row.user.id == user_id)
+            .map(|row| row.user.clone()))
+    }
+
+    async fn list_users(&self) -> Result<Vec<UserResponse>, ApiErrors> {
+        let users = self.users.lock().unwrap();
+
+        let mut list: Vec<UserResponse> = users.iter().map(|row| row.user.clone()).collect();
+        list.sort_by_key(|item| Reverse(item.created_at));
+
+        Ok(list)
+    }
+
+    async fn update_user(&self, user: &UpdatedData) -> Result<bool, ApiErrors> {
+        let mut users = self.users.lock().unwrap();
+
+        let Some(row) = users.iter_mut().find(|row| row.user.id == user.user_id) else {
+            return Ok(false);
+        };
+
+        if let Some(name) = &user.name {
+            row.user.name = name.clone();
+        }
+
+        if let Some(phone_number) = &user.phone_number {
+            row.user.phone_number = Some(phone_number.clone());
+        }
+
+        if let Some(roles) = &user.roles
+            && roles.as_str() != row.user.roles.as_str()
+        {
+            row.user.roles = roles.clone();
+            row.user.token_version += 1;
+        }
+
+        Ok(true)
+    }
+
+    async fn bump_token_version(&self, user_id: Uuid) -> Result<bool, ApiErrors> {
+        let mut users = self.users.lock().unwrap();
+
+        let Some(row) = users.iter_mut().find(|row| row.user.id == user_id) else {
+            return Ok(false);
+        };
+
+        row.user.token_version += 1;
+
+        Ok(true)
+    }
+
+    async fn delete_user(&self, user_id: Uuid) -> Result<bool, ApiErrors> {
+        let mut users = self.users.lock().unwrap();
+
+        let before = users.len();
+        users.retain(|row| row.user.id != user_id);
+
+        Ok(users.len() < before)
+    }
+
+    async fn update_password(&self, user_id: Uuid, password_hash: &str) -> Result<bool, ApiErrors> {
+        let mut users = self.users.lock().unwrap();
+
+        let Some(row) = users.iter_mut().find(|row| row.user.id == user_id) else {
+            return Ok(false);
+        };
+
+        row.password = password_hash.to_string();
+
+        Ok(true)
+    }
+
+    async fn insert_reset_token(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        expires_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors> {
+        self.reset_tokens.lock().unwrap().push(ResetTokenRow {
+            user_id,
+            token_hash: token_hash.to_string(),
+            expires_at,
+            used: false,
+        });
+
+        Ok(())
+    }
+
+    async fn consume_reset_token(
+        &self,
+        token_hash: &str,
+        now: NaiveDateTime,
+    ) -> Result<Option<Uuid>, ApiErrors> {
+        let mut tokens = self.reset_tokens.lock().unwrap();
+
+        let Some(user_id) = tokens
+            .iter()
+            .find(|row| row.token_hash == token_hash && !row.used && row.expires_at > now)
+            .map(|row| row.user_id)
+        else {
+            return Ok(None);
+        };
+
+        for row in tokens.iter_mut().filter(|row| row.user_id == user_id) {
+            row.used = true;
+        }
+
+        Ok(Some(user_id))
+    }
+
+    async fn find_totp(&self, user_id: Uuid) -> Result<Option<TotpRecord>, ApiErrors> {
+        let totp = self.totp.lock().unwrap();
+
+        Ok(totp
+            .iter()
+            .find(|row| row.user_id == user_id)
+            .map(|row| TotpRecord {
+                secret: row.secret.clone(),
+                confirmed_at: row.confirmed_at,
+                last_used_step: row.last_used_step,
+            }))
+    }
+
+    async fn save_pending_totp(&self, user_id: Uuid, secret: &str) -> Result<(), ApiErrors> {
+        let mut totp = self.totp.lock().unwrap();
+
+        if totp
+            .iter()
+            .any(|row| row.user_id == user_id && row.confirmed_at.is_some())
+        {
+            return Ok(());
+        }
+
+        totp.retain(|row| row.user_id != user_id);
+        totp.push(TotpRow {
+            user_id,
+            secret: secret.to_string(),
+            confirmed_at: None,
+            last_used_step: None,
+        });
+
+        Ok(())
+    }
+
+    async fn confirm_totp(
+        &self,
+        user_id: Uuid,
+        step: i64,
+        recovery_code_hashes: &[String],
+        now: NaiveDateTime,
+    ) -> Result<bool, ApiErrors> {
+        let mut totp = self.totp.lock().unwrap();
+
+        let Some(row) = totp
+            .iter_mut()
+            .find(|row| row.user_id == user_id && row.confirmed_at.is_none())
+        else {
+            return Ok(false);
+        };
+
+        row.confirmed_at = Some(now);
+        row.last_used_step = Some(step);
+
+        let mut codes = self.recovery_codes.lock().unwrap();
+        codes.retain(|row| row.user_id != user_id);
+        codes.extend(recovery_code_hashes.iter().map(|hash| RecoveryCodeRow {
+            user_id,
+            code_hash: hash.clone(),
+            used: false,
+        }));
+
+        Ok(true)
+    }
+
+    async fn use_totp_step(&self, user_id: Uuid, step: i64) -> Result<bool, ApiErrors> {
+        let mut totp = self.totp.lock().unwrap();
+
+        let Some(row) = totp.iter_mut().find(|row| {
+            row.user_id == user_id && row.last_used_step.is_none_or(|last| last < step)
+        }) else {
+            return Ok(false);
+        };
+
+        row.last_used_step = Some(step);
+
+        Ok(true)
+    }
+
+    async fn use_recovery_code(
+        &self,
+        user_id: Uuid,
+        code_hash: &str,
+        _now: NaiveDateTime,
+    ) -> Result<bool, ApiErrors> {
+        let mut codes = self.recovery_codes.lock().unwrap();
+
+        let Some(row) = codes
+            .iter_mut()
+            .find(|row| row.user_id == user_id && row.code_hash == code_hash && !row.used)
+        else {
+            return Ok(false);
+        };
+
+        row.used = true;
+
+        Ok(true)
+    }
+
+    async fn delete_totp(&self, user_id: Uuid) -> Result<(), ApiErrors> {
+        self.totp
+            .lock()
+            .unwrap()
+            .retain(|row| row.user_id != user_id);
+        self.recovery_codes
+            .lock()
+            .unwrap()
+            .retain(|row| row.user_id != user_id);
+
+        Ok(())
+    }
+
+    async fn insert_two_factor_challenge(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        expires_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors> {
+        self.challenges.lock().unwrap().push(ChallengeRow {
+            user_id,
+            token_hash: token_hash.to_string(),
+            expires_at,
+            attempts: 0,
+        });
+
+        Ok(())
+    }
+
+    async fn find_two_factor_challenge(
+        &self,
+        token_hash: &str,
+        now: NaiveDateTime,
+    ) -> Result<Option<TwoFactorChallenge>, ApiErrors> {
+        let challenges = self.challenges.lock().unwrap();
+
+        Ok(challenges
+            .iter()
+            .find(|row| row.token_hash == token_hash && row.expires_at > now)
+            .map(|row| TwoFactorChallenge {
+                user_id: row.user_id,
+                attempts: row.attempts,
+            }))
+    }
+
+    async fn fail_two_factor_challenge(&self, token_hash: &str) -> Result<(), ApiErrors> {
+        let mut challenges = self.challenges.lock().unwrap();
+
+        if let Some(row) = challenges
+            .iter_mut()
+            .find(|row| row.token_hash == token_hash)
+        {
+            row.attempts += 1;
+        }
+
+        Ok(())
+    }
+
+    async fn delete_two_factor_challenge(&self, token_hash: &str) -> Result<bool, ApiErrors> {
+        let mut challenges = self.challenges.lock().unwrap();
+
+        let before = challenges.len();
+        challenges.retain(|row| row.token_hash != token_hash);
+
+        Ok(challenges.len() < before)
+    }
+}
@@ -0,0 +1,422 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    auth::{
+        dto::{RegisteredData, TotpRecord, TwoFactorChallenge, UpdatedData, UserCredentials},
+        messages::UserResponse,
+        repo::UserRepository,
+    },
+    errors::api_errors::ApiErrors,
+    fields::{email::Email, phone_number::PhoneNumber, roles::Roles, text::Text},
+};
+
+pub struct UserRepoSqlx {
+    pub pool: PgPool,
+}
+
+#[async_trait]
+impl UserRepository for UserRepoSqlx {
+    async fn insert_user(
+        &self,
+        id: Uuid,
+        user: &RegisteredData,
+        password_hash: &str,
+        created_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors> {
+        sqlx::query!(
+            "INSERT INTO users (id, email, password, name, phone_number, roles, created_by, created_by_name, created_by_email, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)",
+            id,
+            user.email.as_str(),
+            password_hash,
+            user.name.as_str(),
+            user.phone_number.as_ref().map(|p| p.as_str()),
+            user.roles.as_str(),
+            user.created_by,
+            user.created_by_name.as_str(),
+            user.created_by_email.as_str(),
+            created_at,
+            created_at,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::Conflict("Email already exists".to_string()))?;
+
+        Ok(())
+    }
+
+    async fn find_credentials_by_email(
+        &self,
+        email: &str,
+    ) -> Result<Option<UserCredentials>, ApiErrors> {
+        let record = sqlx::query_as!(
+            UserCredentials,
+            "SELECT id, password FROM users WHERE email = $1",
+            email
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("User lookup failed".to_string()))?;
+
+        Ok(record)
+    }
+
+    async fn find_credentials(&self, user_id: Uuid) -> Result<Option<UserCredentials>, ApiErrors> {
+        sqlx::query_as!(
+            UserCredentials,
+            "SELECT id, password FROM users WHERE id = $1",
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("User lookup failed".to_string()))
+    }
+
+    async fn find_user(&self, user_id: Uuid) -> Result<Option<UserResponse>, ApiErrors> {
+        let user = sqlx::query!("SELECT id, email, name, phone_number, roles, created_at, updated_at, token_version FROM users WHERE id = $1", user_id)
+            .fetch_optional(&self.pool)
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("User lookup failed".to_string()))?;
+
+        let Some(user) = user else {
+            return Ok(None);
+        };
+
+        Ok(Some(UserResponse {
+            id: user.id,
+            email: Email(user.email),
+            name: Text(user.name),
+            phone_number: user.phone_number.map(PhoneNumber),
+            roles: Roles::new(&user.roles)?,
+            created_at: user.created_at,
+            updated_at: user.updated_at,
+            token_version: user.token_version,
+        }))
+    }
+
+    async fn list_users(&self) -> Result<Vec<UserResponse>, ApiErrors> {
+        let users = sqlx::query!("SELECT id, email, name, phone_number, roles, created_at, updated_at, token_version FROM users ORDER BY created_at DESC")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Failed to fetch users".to_string()))?;
+
+        users
+            .into_iter()
+            .map(|u| {
+                Ok(UserResponse {
+                    id: u.id,
+                    email: Email(u.email),
+                    name: Text(u.name),
+                    phone_number: u.phone_number.map(PhoneNumber),
+                    roles: Roles::new(&u.roles)
+                        .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?,
+                    created_at: u.created_at,
+                    updated_at: u.updated_at,
+                    token_version: u.token_version,
+                })
+            })
+            .collect::<Result<Vec<_>, ApiErrors>>()
+    }
+
+    async fn update_user(&self, user: &UpdatedData) -> Result<bool, ApiErrors> {
+        let result = sqlx::query!(r#"UPDATE users SET name = COALESCE($1, name), phone_number = COALESCE($2, phone_number), roles = COALESCE($3, roles), token_version = token_version + CASE WHEN $3 IS DISTINCT FROM roles AND $3 IS NOT NULL THEN 1 ELSE 0 END, edited_by = $4, edited_by_name = $5, edited_by_email = $6, updated_at = NOW() WHERE id = $7"#,
+                user.name.as_ref().map(|n| n.as_str()),
+                user.phone_number.as_ref().map(|p| p.as_str()),
+                user.roles.as_ref().map(|p| p.as_str()),
+                user.edited_by,
+                user.edited_by_name.as_str(),
+                user.edited_by_email.as_str(),
+                user.user_id,
+            )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Update failed".to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_user(&self, user_id: Uuid) -> Result<bool, ApiErrors> {
+        let result = sqlx::query!("DELETE FROM users WHERE id = $1", user_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Delete failed".to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn bump_token_version(&self, user_id: Uuid) -> Result<bool, ApiErrors> {
+        let result = sqlx::query!(
+            "UPDATE users SET token_version = token_version + 1 WHERE id = $1",
+            user_id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Token revocation failed".to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn update_password(&self, user_id: Uuid, password_hash: &str) -> Result<bool, ApiErrors> {
+        let result = sqlx::query!(
+            "UPDATE users SET password = $1, updated_at = NOW() WHERE id = $2",
+            password_hash,
+            user_id
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Password update failed".to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn insert_reset_token(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        expires_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors> {
+        sqlx::query!(
+            "INSERT INTO password_reset_tokens (id, user_id, token_hash, expires_at) VALUES ($1, $2, $3, $4)",
+            Uuid::new_v4(),
+            user_id,
+            token_hash,
+            expires_at,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed to store reset token".to_string()))?;
+
+        Ok(())
+    }
+
+    async fn consume_reset_token(
+        &self,
+        token_hash: &str,
+        now: NaiveDateTime,
+    ) -> Result<Option<Uuid>, ApiErrors> {
+        // A concurrent reset with the same token blocks on the row and then fails the
+        // `used_at IS NULL` re-check, so each token works once.
+        let consumed = sqlx::query_scalar!(
+            r#"
+            UPDATE password_reset_tokens SET used_at = $2
+            WHERE used_at IS NULL
+              AND user_id = (
+                SELECT user_id FROM password_reset_tokens
+                WHERE token_hash = $1 AND used_at IS NULL AND expires_at > $2
+              )
+            RETURNING user_id
+            "#,
+            token_hash,
+            now,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Reset token lookup failed".to_string()))?;
+
+        Ok(consumed.into_iter().next())
+    }
+
+    async fn find_totp(&self, user_id: Uuid) -> Result<Option<TotpRecord>, ApiErrors> {
+        sqlx::query_as!(
+            TotpRecord,
+            "SELECT secret, confirmed_at, last_used_step FROM user_totp WHERE user_id = $1",
+            user_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Two-factor lookup failed".to_string()))
+    }
+
+    async fn save_pending_totp(&self, user_id: Uuid, secret: &str) -> Result<(), ApiErrors> {
+        sqlx::query!(
+            r#"
+            INSERT INTO user_totp (user_id, secret) VALUES ($1, $2)
+            ON CONFLICT (user_id) DO UPDATE
+            SET secret = EXCLUDED.secret, last_used_step = NULL, created_at = NOW()
+            WHERE user_totp.confirmed_at IS NULL
+            "#,
+            user_id,
+            secret,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| {
+            ApiErrors::InternalServerError("Failed to store two-factor secret".to_string())
+        })?;
+
+        Ok(())
+    }
+
+    async fn confirm_totp(
+        &self,
+        user_id: Uuid,
+        step: i64,
+        recovery_code_hashes: &[String],
+        now: NaiveDateTime,
+    ) -> Result<bool, ApiErrors> {
+        let failed = |_| {
+            ApiErrors::InternalServerError("Failed to enable two-factor authentication".to_string())
+        };
+
+        let mut tx = self.pool.begin().await.map_err(failed)?;
+
+        let confirmed = sqlx::query!(
+            "UPDATE user_totp SET confirmed_at = $2, last_used_step = $3 WHERE user_id = $1 AND confirmed_at IS NULL",
+            user_id,
+            now,
+            step,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(failed)?;
+
+        if confirmed.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query!(
+            "DELETE FROM totp_recovery_codes WHERE user_id = $1",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(failed)?;
+
+        for code_hash in recovery_code_hashes {
+            sqlx::query!(
+                "INSERT INTO totp_recovery_codes (id, user_id, code_hash) VALUES ($1, $2, $3)",
+                Uuid::new_v4(),
+                user_id,
+                code_hash,
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(failed)?;
+        }
+
+        tx.commit().await.map_err(failed)?;
+
+        Ok(true)
+    }
+
+    async fn use_totp_step(&self, user_id: Uuid, step: i64) -> Result<bool, ApiErrors> {
+        let used = sqlx::query!(
+            r#"
+            UPDATE user_totp SET last_used_step = $2
+            WHERE user_id = $1 AND (last_used_step IS NULL OR last_used_step < $2)
+            "#,
+            user_id,
+            step,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Two-factor check failed".to_string()))?;
+
+        Ok(used.rows_affected() > 0)
+    }
+
+    async fn use_recovery_code(
+        &self,
+        user_id: Uuid,
+        code_hash: &str,
+        now: NaiveDateTime,
+    ) -> Result<bool, ApiErrors> {
+        let used = sqlx::query!(
+            "UPDATE totp_recovery_codes SET used_at = $3 WHERE user_id = $1 AND code_hash = $2 AND used_at IS NULL",
+            user_id,
+            code_hash,
+            now,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Two-factor check failed".to_string()))?;
+
+        Ok(used.rows_affected() > 0)
+    }
+
+    async fn delete_totp(&self, user_id: Uuid) -> Result<(), ApiErrors> {
+        let failed = |_| {
+            ApiErrors::InternalServerError(
+                "Failed to disable two-factor authentication".to_string(),
+            )
+        };
+
+        let mut tx = self.pool.begin().await.map_err(failed)?;
+
+        sqlx::query!(
+            "DELETE FROM totp_recovery_codes WHERE user_id = $1",
+            user_id
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(failed)?;
+
+        sqlx::query!("DELETE FROM user_totp WHERE user_id = $1", user_id)
+            .execute(&mut *tx)
+            .await
+            .map_err(failed)?;
+
+        tx.commit().await.map_err(failed)
+    }
+
+    async fn insert_two_factor_challenge(
+        &self,
+        user_id: Uuid,
+        token_hash: &str,
+        expires_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors> {
+        sqlx::query!(
+            "INSERT INTO two_factor_challenges (token_hash, user_id, expires_at) VALUES ($1, $2, $3)",
+            token_hash,
+            user_id,
+            expires_at,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed to store login challenge".to_string()))?;
+
+        Ok(())
+    }
+
+    async fn find_two_factor_challenge(
+        &self,
+        token_hash: &str,
+        now: NaiveDateTime,
+    ) -> Result<Option<TwoFactorChallenge>, ApiErrors> {
+        sqlx::query_as!(
+            TwoFactorChallenge,
+            "SELECT user_id, attempts FROM two_factor_challenges WHERE token_hash = $1 AND expires_at > $2",
+            token_hash,
+            now,
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Login challenge lookup failed".to_string()))
+    }
+
+    async fn fail_two_factor_challenge(&self, token_hash: &str) -> Result<(), ApiErrors> {
+        sqlx::query!(
+            "UPDATE two_factor_challenges SET attempts = attempts + 1 WHERE token_hash = $1",
+            token_hash
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Login challenge update failed".to_string()))?;
+
+        Ok(())
+    }
+
+    async fn delete_two_factor_challenge(&self, token_hash: &str) -> Result<bool, ApiErrors> {
+        let deleted = sqlx::query!(
+            "DELETE FROM two_factor_challenges WHERE token_hash = $1",
+            token_hash
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Login challenge update failed".to_string()))?;
+
+        Ok(deleted.rows_affected() > 0)
+    }
+}
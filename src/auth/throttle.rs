@@ -0,0 +1,215 @@
+use std::{
+    collections::HashMap,
+    net::IpAddr,
+    time::{Duration, Instant},
+};
+
+use crate::{config::LoginThrottleConfig, errors::api_errors::ApiErrors};
+
+#[derive(Clone, Copy)]
+struct Failures {
+    count: u32,
+    last_at: Instant,
+    locked_until: Option<Instant>,
+}
+
+impl Failures {
+    fn is_stale(&self, now: Instant, window: Duration) -> bool {
+        match self.locked_until {
+            Some(until) => until <= now,
+            None => now.duration_since(self.last_at) >= window,
+        }
+    }
+
+    fn record(&mut self, now: Instant, max: u32, lockout: Duration) -> bool {
+        self.count += 1;
+        self.last_at = now;
+
+        let locks = self.count >= max;
+
+        if locks {
+            self.locked_until = Some(now + lockout);
+        }
+
+        locks
+    }
+}
+
+/// Failed logins per account and per client address. Each failure on an account pushes its
+/// next attempt further out; enough of them, on an account or from one address, lock it for a
+/// while. Failures are forgotten once `window` passes without another.
+pub struct LoginThrottle {
+    config: LoginThrottleConfig,
+    accounts: HashMap<String, Failures>,
+    ips: HashMap<IpAddr, Failures>,
+}
+
+impl LoginThrottle {
+    pub fn new(config: LoginThrottleConfig) -> Self {
+        Self {
+            config,
+            accounts: HashMap::new(),
+            ips: HashMap::new(),
+        }
+    }
+
+    /// Rejects the attempt with `TooManyRequests` while the account or address is locked or
+    /// the account's delay has not run out.
+    pub fn check(&mut self, email: &str, ip: Option<IpAddr>) -> Result<(), ApiErrors> {
+        let now = Instant::now();
+
+        self.forget_stale(now);
+
+        if let Some(failures) = ip.and_then(|ip| self.ips.get(&ip))
+            && let Some(until) = failures.locked_until
+        {
+            return Err(too_many(
+                "Too many failed logins from this address, try again later",
+                until - now,
+            ));
+        }
+
+        let Some(failures) = self.accounts.get(email) else {
+            return Ok(());
+        };
+
+        if let Some(until) = failures.locked_until {
+            return Err(too_many(
+                "Account temporarily locked after too many failed logins",
+                until - now,
+            ));
+        }
+
+        let ready_at = failures.last_at + self.delay(failures.count);
+
+        if ready_at > now {
+            return Err(too_many(
+                "Too many failed logins, wait before trying again",
+                ready_at - now,
+            ));
+        }
+
+        Ok(())
+    }
+
+    pub fn record_failure(&mut self, email: &str, ip: Option<IpAddr>) {
+        let now = Instant::now();
+        let lockout = self.config.lockout;
+
+        let fresh = Failures {
+            count: 0,
+            last_at: now,
+            locked_until: None,
+        };
+
+        let account = self.accounts.entry(email.to_string()).or_insert(fresh);
+
+        if account.record(now, self.config.max_account_failures, lockout) {
+            println!("login locked for {email} after {} failures", account.count);
+        }
+
+        if let Some(ip) = ip {
+            let address = self.ips.entry(ip).or_insert(fresh);
+
+            if address.record(now, self.config.max_ip_failures, lockout) {
+                println!("login locked for {ip} after {} failures", address.count);
+            }
+        }
+    }
+
+    /// A correct password clears the account's record; the address keeps its count so one
+    /// valid login cannot launder attempts on other accounts.
+    pub fn record_success(&mut self, email: &str) {
+        self.accounts.remove(email);
+    }
+
+    /// Lifts an account lockout early. Returns whether the account had failures on record.
+    pub fn unlock(&mut self, email: &str) -> bool {
+        self.accounts.remove(email).is_some()
+    }
+
+    /// Doubles with every failure from `base_delay`, up to `max_delay`.
+    fn delay(&self, failures: u32) -> Duration {
+        if failures == 0 {
+            return Duration::ZERO;
+        }
+
+        self.config
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(failures - 1))
+            .min(self.config.max_delay)
+    }
+
+    fn forget_stale(&mut self, now: Instant) {
+        let window = self.config.window;
+
+        self.accounts
+            .retain(|_, failures| !failures.is_stale(now, window));
+        self.ips
+            .retain(|_, failures| !failures.is_stale(now, window));
+    }
+}
+
+fn too_many(message: &str, retry_after: Duration) -> ApiErrors {
+    ApiErrors::TooManyRequests {
+        message: message.to_string(),
+        retry_after,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn throttle(base_delay: Duration) -> LoginThrottle {
+        LoginThrottle::new(LoginThrottleConfig {
+            max_account_failures: 3,
+            max_ip_failures: 5,
+            window: Duration::from_secs(60),
+            lockout: Duration::from_secs(60),
+            base_delay,
+            max_delay: Duration::from_secs(4),
+        })
+    }
+
+    #[test]
+    fn delay_doubles_up_to_the_cap() {
+        let throttle = throttle(Duration::from_secs(1));
+
+        let delays: Vec<_> = (0..5).map(|n| throttle.delay(n).as_secs()).collect();
+
+        assert_eq!(delays, [0, 1, 2, 4, 4]);
+    }
+
+    #[test]
+    fn failures_delay_then_lock_the_account() {
+        let mut throttle = throttle(Duration::from_secs(30));
+
+        throttle.record_failure("a@example.com", None);
+        assert!(matches!(
+            throttle.check("a@example.com", None),
+            Err(ApiErrors::TooManyRequests { .. })
+        ));
+        assert!(throttle.check("b@example.com", None).is_ok());
+
+        throttle.record_failure("a@example.com", None);
+        throttle.record_failure("a@example.com", None);
+        assert!(throttle.accounts["a@example.com"].locked_until.is_some());
+
+        assert!(throttle.unlock("a@example.com"));
+        assert!(throttle.check("a@example.com", None).is_ok());
+    }
+
+    #[test]
+    fn one_address_is_locked_across_accounts() {
+        let mut throttle = throttle(Duration::ZERO);
+        let ip: IpAddr = "203.0.113.9".parse().unwrap();
+
+        for n in 0..5 {
+            throttle.record_failure(&format!("user{n}@example.com"), Some(ip));
+        }
+
+        assert!(throttle.check("other@example.com", Some(ip)).is_err());
+        assert!(throttle.check("other@example.com", None).is_ok());
+    }
+}
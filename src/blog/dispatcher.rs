@@ -1,8 +1,11 @@
 use tokio::sync::mpsc;
 
-use crate::blog::{actor::BlogActor, messages::BlogMessage};
+use crate::blog::{actor::BlogActor, messages::BlogMessage, repo::BlogRepository};
 
-pub async fn blog_dispatcher(actor: &BlogActor, mut rx: mpsc::Receiver<BlogMessage>) {
+pub async fn blog_dispatcher<R>(actor: &BlogActor<R>, mut rx: mpsc::Receiver<BlogMessage>)
+where
+    R: BlogRepository + Send + Sync + 'static,
+{
     while let Some(msg) = rx.recv().await {
         match msg {
             BlogMessage::Create { blog, respond_to } => {
@@ -1,7 +1,10 @@
 use serde::Deserialize;
 use uuid::Uuid;
 
-use crate::fields::{email::Email, text::Text};
+use crate::{
+    fields::{email::Email, text::Text},
+    image::messages::ImageVariant,
+};
 
 pub struct CreateBlogData {
     pub title: String,
@@ -10,6 +13,8 @@ pub struct CreateBlogData {
     pub word_count: i32,
     pub image: String,
     pub image_id: String,
+    pub image_variants: Vec<ImageVariant>,
+    pub media_id: Uuid,
     pub created_by: Uuid,
     pub created_by_name: Text,
     pub created_by_email: Email,
@@ -24,6 +29,8 @@ pub struct UpdatedBlogData {
     pub word_count: Option<i32>,
     pub image: Option<String>,
     pub image_id: Option<String>,
+    pub image_variants: Option<Vec<ImageVariant>>,
+    pub media_id: Option<Uuid>,
     pub edited_by: Uuid,
     pub edited_by_name: Text,
     pub edited_by_email: Email,
@@ -34,7 +41,8 @@ pub struct ValidatedCreateBlogData {
     pub description: String,
     pub content: String,
     pub word_count: i32,
-    pub image: String,
+    pub image: Option<String>,
+    pub media_id: Option<Uuid>,
 }
 
 #[derive(Deserialize)]
@@ -44,6 +52,7 @@ pub struct UpdateBlogRequest {
     pub content: Option<String>,
     pub word_count: Option<i32>,
     pub image: Option<String>,
+    pub media_id: Option<Uuid>,
 }
 
 #[derive(Deserialize)]
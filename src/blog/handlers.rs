@@ -10,12 +10,14 @@ use crate::{
         dto::{BlogQuery, CreateBlogData, UpdatedBlogData},
         messages::BlogMessage,
     },
+    core::media_core::resolve_media_core,
     errors::api_errors::ApiErrors,
     extractor::{
         auth_extractor::AuthUser,
         blog_extractor::{BlogCreateInput, BlogUpateInput},
         path_id_extractor::PathParam,
     },
+    media::dto::{MediaEntity, Uploader},
     state::AppState,
 };
 
@@ -26,6 +28,22 @@ pub async fn create_blog(
     State(state): State<AppState>,
     payload: BlogCreateInput,
 ) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let uploader = Uploader {
+        id,
+        name: name.clone(),
+        email: email.clone(),
+    };
+
+    let media = resolve_media_core(
+        MediaEntity::Blog,
+        payload.image,
+        payload.media_id,
+        uploader,
+        &state.media_tx,
+    )
+    .await?
+    .ok_or_else(|| ApiErrors::BadRequest("Image or media_id is required".to_string()))?;
+
     let (tx, rx) = oneshot::channel();
 
     let blog = CreateBlogData {
@@ -33,8 +51,10 @@ pub async fn create_blog(
         description: payload.description,
         content: payload.content,
         word_count: payload.word_count,
-        image: payload.image,
-        image_id: payload.image_id,
+        image: media.url,
+        image_id: media.public_id,
+        image_variants: media.variants.0,
+        media_id: media.id,
         created_by: id,
         created_by_name: name,
         created_by_email: email,
@@ -161,6 +181,31 @@ pub async fn update_blog(
     // TypedHeader(Authorization(bearer)): TypedHeader<Authorization<Bearer>>,
     payload: BlogUpateInput,
 ) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let uploader = Uploader {
+        id,
+        name: name.clone(),
+        email: email.clone(),
+    };
+
+    let media = resolve_media_core(
+        MediaEntity::Blog,
+        payload.image,
+        payload.media_id,
+        uploader,
+        &state.media_tx,
+    )
+    .await?;
+
+    let (image, image_id, image_variants, media_id) = match media {
+        Some(media) => (
+            Some(media.url),
+            Some(media.public_id),
+            Some(media.variants.0),
+            Some(media.id),
+        ),
+        None => (None, None, None, None),
+    };
+
     let (tx, rx) = oneshot::channel();
 
     let blog = UpdatedBlogData {
@@ -169,8 +214,10 @@ pub async fn update_blog(
         description: payload.description,
         content: payload.content,
         word_count: payload.word_count,
-        image: payload.image,
-        image_id: payload.image_id,
+        image,
+        image_id,
+        image_variants,
+        media_id,
         edited_by: id,
         edited_by_name: name,
         edited_by_email: email,
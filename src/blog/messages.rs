@@ -1,14 +1,15 @@
 use chrono::NaiveDateTime;
-use sqlx::prelude::FromRow;
+use sqlx::{prelude::FromRow, types::Json};
 use tokio::sync::oneshot;
 use uuid::Uuid;
 
 use crate::{
     blog::dto::{BlogQuery, CreateBlogData, UpdatedBlogData},
     errors::api_errors::ApiErrors,
+    image::messages::ImageVariant,
 };
 
-#[derive(Debug, serde::Serialize, FromRow)]
+#[derive(Debug, Clone, serde::Serialize, FromRow)]
 pub struct BlogResponse {
     pub id: Uuid,
     pub title: String,
@@ -17,6 +18,8 @@ pub struct BlogResponse {
     pub word_count: i32,
     pub image: String,
     pub image_id: String,
+    pub image_variants: Json<Vec<ImageVariant>>,
+    pub media_id: Option<Uuid>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use crate::{
+    blog::{
+        dto::{BlogQuery, CreateBlogData, UpdatedBlogData},
+        messages::BlogResponse,
+    },
+    errors::api_errors::ApiErrors,
+};
+
+#[async_trait]
+pub trait BlogRepository: Send + Sync {
+    async fn insert_blog(
+        &self,
+        id: Uuid,
+        blog: &CreateBlogData,
+        created_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors>;
+
+    async fn find_blog(&self, blog_id: Uuid) -> Result<Option<BlogResponse>, ApiErrors>;
+
+    async fn list_blogs(&self, query: &BlogQuery) -> Result<(Vec<BlogResponse>, u64), ApiErrors>;
+
+    async fn count_blogs(&self) -> Result<u64, ApiErrors>;
+
+    async fn update_blog(&self, blog: &UpdatedBlogData) -> Result<bool, ApiErrors>;
+
+    async fn delete_blog(&self, blog_id: Uuid) -> Result<bool, ApiErrors>;
+}
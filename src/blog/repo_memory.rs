@@ -0,0 +1,146 @@
+use std::{cmp::Reverse, sync::Mutex};
+
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::types::Json;
+use uuid::Uuid;
+
+use crate::{
+    blog::{
+        dto::{BlogQuery, CreateBlogData, UpdatedBlogData},
+        messages::BlogResponse,
+        repo::BlogRepository,
+    },
+    errors::api_errors::ApiErrors,
+};
+
+#[derive(Default)]
+pub struct BlogRepoMemory {
+    blogs: Mutex<Vec<BlogResponse>>,
+}
+
+impl BlogRepoMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl BlogRepository for BlogRepoMemory {
+    async fn insert_blog(
+        &self,
+        id: Uuid,
+        blog: &CreateBlogData,
+        created_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors> {
+        let mut blogs = self.blogs.lock().unwrap();
+
+        if blogs.iter().any(|b| b.title == blog.title) {
+            return Err(ApiErrors::Conflict("Blog already exists".to_string()));
+        }
+
+        blogs.push(BlogResponse {
+            id,
+            title: blog.title.clone(),
+            description: blog.description.clone(),
+            content: blog.content.clone(),
+            word_count: blog.word_count,
+            image: blog.image.clone(),
+            image_id: blog.image_id.clone(),
+            image_variants: Json(blog.image_variants.clone()),
+            media_id: Some(blog.media_id),
+            created_at,
+            updated_at: created_at,
+        });
+
+        Ok(())
+    }
+
+    async fn find_blog(&self, blog_id: Uuid) -> Result<Option<BlogResponse>, ApiErrors> {
+        let blogs = self.blogs.lock().unwrap();
+
+        Ok(blogs.iter().find(|b| b.id == blog_id).cloned())
+    }
+
+    async fn list_blogs(&self, query: &BlogQuery) -> Result<(Vec<BlogResponse>, u64), ApiErrors> {
+        let blogs = self.blogs.lock().unwrap();
+
+        let page = query.page.unwrap_or(1).max(1) as usize;
+        let limit = query.limit.unwrap_or(10) as usize;
+
+        let mut matched: Vec<BlogResponse> = blogs
+            .iter()
+            .filter(|b| match &query.title {
+                Some(title) => b.title.to_lowercase().contains(&title.to_lowercase()),
+                None => true,
+            })
+            .cloned()
+            .collect();
+        matched.sort_by_key(|item| Reverse(item.created_at));
+
+        let total = matched.len() as u64;
+
+        let page_items = matched
+            .into_iter()
+            .skip((page - 1) * limit)
+            .take(limit)
+            .collect();
+
+        Ok((page_items, total))
+    }
+
+    async fn count_blogs(&self) -> Result<u64, ApiErrors> {
+        Ok(self.blogs.lock().unwrap().len() as u64)
+    }
+
+    async fn update_blog(&self, blog: &UpdatedBlogData) -> Result<bool, ApiErrors> {
+        let mut blogs = self.blogs.lock().unwrap();
+
+        let Some(row) = blogs.iter_mut().find(|b| b.id == blog.blog_id) else {
+            return Ok(false);
+        };
+
+        if let Some(title) = &blog.title {
+            row.title = title.clone();
+        }
+
+        if let Some(description) = &blog.description {
+            row.description = description.clone();
+        }
+
+        if let Some(content) = &blog.content {
+            row.content = content.clone();
+        }
+
+        if let Some(word_count) = blog.word_count {
+            row.word_count = word_count;
+        }
+
+        if let Some(image) = &blog.image {
+            row.image = image.clone();
+        }
+
+        if let Some(image_id) = &blog.image_id {
+            row.image_id = image_id.clone();
+        }
+
+        if let Some(image_variants) = &blog.image_variants {
+            row.image_variants = Json(image_variants.clone());
+        }
+
+        if let Some(media_id) = blog.media_id {
+            row.media_id = Some(media_id);
+        }
+
+        Ok(true)
+    }
+
+    async fn delete_blog(&self, blog_id: Uuid) -> Result<bool, ApiErrors> {
+        let mut blogs = self.blogs.lock().unwrap();
+
+        let before = blogs.len();
+        blogs.retain(|b| b.id != blog_id);
+
+        Ok(blogs.len() < before)
+    }
+}
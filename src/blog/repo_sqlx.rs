@@ -0,0 +1,150 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::{PgPool, Postgres, QueryBuilder, types::Json};
+use uuid::Uuid;
+
+use crate::{
+    blog::{
+        dto::{BlogQuery, CreateBlogData, UpdatedBlogData},
+        messages::BlogResponse,
+        repo::BlogRepository,
+    },
+    errors::api_errors::ApiErrors,
+    image::messages::ImageVariant,
+};
+
+pub struct BlogRepoSqlx {
+    pub pool: PgPool,
+}
+
+#[async_trait]
+impl BlogRepository for BlogRepoSqlx {
+    async fn insert_blog(
+        &self,
+        id: Uuid,
+        blog: &CreateBlogData,
+        created_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors> {
+        sqlx::query!(
+            "INSERT INTO blog (id, title, description, content, word_count, image, image_id, image_variants, media_id, created_by, created_by_name, created_by_email, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)",
+            id,
+            blog.title,
+            blog.description,
+            blog.content,
+            blog.word_count,
+            blog.image,
+            blog.image_id,
+            Json(&blog.image_variants) as _,
+            blog.media_id,
+            blog.created_by,
+            blog.created_by_name.as_str(),
+            blog.created_by_email.as_str(),
+            created_at,
+            created_at,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::Conflict("Blog already exists".to_string()))?;
+
+        Ok(())
+    }
+
+    async fn find_blog(&self, blog_id: Uuid) -> Result<Option<BlogResponse>, ApiErrors> {
+        let blog = sqlx::query_as!(
+            BlogResponse,
+            r#"SELECT id, title, description, content, word_count, image, image_id, image_variants as "image_variants: Json<Vec<ImageVariant>>", media_id, created_at, updated_at FROM blog WHERE id = $1"#,
+            blog_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Blog lookup failed".to_string()))?;
+
+        Ok(blog)
+    }
+
+    async fn list_blogs(&self, query: &BlogQuery) -> Result<(Vec<BlogResponse>, u64), ApiErrors> {
+        let page = query.page.unwrap_or(1);
+        let limit = query.limit.unwrap_or(10);
+        let offset = (page - 1) * limit;
+
+        // 🔹 MAIN QUERY
+        let mut qb = QueryBuilder::<Postgres>::new(
+            "SELECT id, title, description, content, word_count, image, image_id, image_variants, media_id, created_at, updated_at FROM blog",
+        );
+
+        // 🔹 COUNT QUERY (for meta)
+        let mut count_qb = QueryBuilder::<Postgres>::new("SELECT COUNT(*) as count FROM blog");
+
+        // 🔍 FILTER
+        if let Some(title) = &query.title {
+            qb.push(" WHERE title ILIKE ");
+            qb.push_bind(format!("%{}%", title));
+
+            count_qb.push(" WHERE title ILIKE ");
+            count_qb.push_bind(format!("%{}%", title));
+        }
+
+        // 🔹 ORDER + PAGINATION
+        qb.push(" ORDER BY created_at DESC");
+        qb.push(" LIMIT ");
+        qb.push_bind(limit as i64);
+        qb.push(" OFFSET ");
+        qb.push_bind(offset as i64);
+
+        // 🔹 FETCH DATA
+        let blogs = qb
+            .build_query_as::<BlogResponse>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Failed to fetch blog".into()))?;
+
+        // 🔹 FETCH COUNT
+        let total: (i64,) = count_qb
+            .build_query_as()
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Failed to count blog".into()))?;
+
+        Ok((blogs, total.0 as u64))
+    }
+
+    async fn count_blogs(&self) -> Result<u64, ApiErrors> {
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM blog")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Failed to count blog".into()))?;
+
+        Ok(total as u64)
+    }
+
+    async fn update_blog(&self, blog: &UpdatedBlogData) -> Result<bool, ApiErrors> {
+        let result = sqlx::query!(r#"UPDATE blog SET title = COALESCE($1, title), description = COALESCE($2, description), content = COALESCE($3, content), word_count = COALESCE($4, word_count), image = COALESCE($5, image), image_id = COALESCE($6, image_id), image_variants = COALESCE($7, image_variants), media_id = COALESCE($8, media_id), edited_by = $9, edited_by_name = $10, edited_by_email = $11 WHERE id = $12"#, 
+                blog.title,
+                blog.description,
+                blog.content,
+                blog.word_count,
+                blog.image,
+                blog.image_id,
+                blog.image_variants.as_ref().map(Json) as _,
+                blog.media_id,
+                blog.edited_by,
+                blog.edited_by_name.as_str(),
+                blog.edited_by_email.as_str(),
+                blog.blog_id,
+            )
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_blog(&self, blog_id: Uuid) -> Result<bool, ApiErrors> {
+        let result = sqlx::query!("DELETE FROM blog WHERE id = $1", blog_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Blog Delete failed".to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
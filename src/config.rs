@@ -1,14 +1,407 @@
-use std::env;
+use std::{env, time::Duration};
+
+use jsonwebtoken::Algorithm;
+use tower_cookies::cookie::SameSite;
 
 pub struct Config {
     pub database_url: String,
-    pub jwt_secret: String,
+    pub jwt_keys: JwtKeysConfig,
     pub port: u16,
-    pub jwt_expiry_hour: i64,
+    pub tokens: TokenConfig,
     pub db_pool_max_connections: Option<u32>,
-    pub cloud_name: String,
-    pub cloud_api_key: String,
-    pub cloud_api_secret: String,
+    pub image_store: ImageStoreConfig,
+    pub image_reconcile_interval_minutes: Option<u64>,
+    pub image_limits: ImageLimits,
+    pub mailer: MailerConfig,
+    pub password_reset: PasswordResetConfig,
+    pub login_throttle: LoginThrottleConfig,
+    pub two_factor: TwoFactorConfig,
+    pub token_cleanup: TokenCleanupConfig,
+    /// Take the client address from the last `X-Forwarded-For` entry; only safe behind exactly
+    /// one proxy that appends to it.
+    pub trust_forwarded_for: bool,
+    pub rate_limits: RateLimitConfig,
+    pub rate_limit_store: RateLimitStoreConfig,
+    pub token_denylist_store: DenylistStoreConfig,
+    /// Single sign-on; `None` unless `OIDC_ISSUER` is set.
+    pub oidc: Option<OidcConfig>,
+}
+
+/// Selected with `IMAGE_STORE` (`cloudinary`, `local` or `s3`); defaults to `cloudinary`.
+pub enum ImageStoreConfig {
+    Cloudinary {
+        cloud_name: String,
+        api_key: String,
+        api_secret: String,
+        folders: CloudinaryFolders,
+        client: CloudinaryClientConfig,
+    },
+    Local {
+        media_dir: String,
+        public_url: String,
+    },
+    S3 {
+        endpoint: String,
+        bucket: String,
+        region: String,
+        access_key: String,
+        secret_key: String,
+        public_url: Option<String>,
+    },
+}
+
+/// Selected with `JWT_ALGORITHM` (`HS256`, `RS256` or `EdDSA`); defaults to `HS256`, which
+/// signs with `JWT_SECRET`.
+pub enum JwtKeysConfig {
+    Secret(String),
+    /// PEM files; `verification_key_files` are `(kid, path)` pairs and keep retired keys
+    /// accepted after a rotation.
+    Files {
+        algorithm: Algorithm,
+        signing_kid: String,
+        signing_key_file: String,
+        verification_key_files: Vec<(String, String)>,
+    },
+}
+
+/// Selected with `MAILER` (`smtp` or `file`); defaults to `file`, which only logs unless
+/// `MAIL_DIR` is set.
+pub enum MailerConfig {
+    Smtp {
+        host: String,
+        port: u16,
+        username: Option<String>,
+        password: Option<String>,
+        from: String,
+    },
+    File {
+        dir: Option<String>,
+    },
+}
+
+#[derive(Clone, Debug)]
+pub struct PasswordResetConfig {
+    /// Frontend page the emailed link opens; the token is appended as `?token=`.
+    pub url: String,
+    pub ttl: Duration,
+}
+
+impl Default for PasswordResetConfig {
+    fn default() -> Self {
+        Self {
+            url: "http://localhost:5173/reset-password".to_string(),
+            ttl: Duration::from_secs(30 * 60),
+        }
+    }
+}
+
+/// Brute-force protection for `/auth/login`.
+#[derive(Clone, Debug)]
+pub struct LoginThrottleConfig {
+    pub max_account_failures: u32,
+    pub max_ip_failures: u32,
+    /// How long failures are remembered without another one.
+    pub window: Duration,
+    pub lockout: Duration,
+    /// Wait imposed after the first failure on an account; it doubles with each further one.
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for LoginThrottleConfig {
+    fn default() -> Self {
+        Self {
+            max_account_failures: 5,
+            max_ip_failures: 50,
+            window: Duration::from_secs(15 * 60),
+            lockout: Duration::from_secs(15 * 60),
+            base_delay: Duration::from_secs(1),
+            max_delay: Duration::from_secs(30),
+        }
+    }
+}
+
+/// TOTP second factor on login.
+#[derive(Clone, Debug)]
+pub struct TwoFactorConfig {
+    /// Shown as the account's label in authenticator apps.
+    pub issuer: String,
+    /// Make `root` and `mid` users enrol before they can log in.
+    pub required_for_admins: bool,
+    /// How long the challenge from a password login can be answered with a code.
+    pub challenge_ttl: Duration,
+    /// Wrong codes a challenge takes before the password has to be entered again.
+    pub max_attempts: i32,
+}
+
+impl Default for TwoFactorConfig {
+    fn default() -> Self {
+        Self {
+            issuer: "Portfolio".to_string(),
+            required_for_admins: false,
+            challenge_ttl: Duration::from_secs(5 * 60),
+            max_attempts: 5,
+        }
+    }
+}
+
+/// Lifetimes of the access JWT and refresh token, and how the refresh cookie is scoped.
+#[derive(Clone, Debug)]
+pub struct TokenConfig {
+    pub access_ttl: Duration,
+    /// Both the stored token and its cookie expire after this, counted from the last rotation.
+    pub refresh_ttl: Duration,
+    pub cookie: RefreshCookieConfig,
+    /// `iss` of every access token; tokens from any other issuer are rejected.
+    pub issuer: String,
+    /// `aud` of every access token, checked the same way.
+    pub audience: String,
+    /// Build the caller from the token's claims instead of loading the user on every request.
+    /// Faster, but a role change or revocation then only lands once the token expires.
+    pub trust_claims: bool,
+}
+
+impl Default for TokenConfig {
+    fn default() -> Self {
+        Self {
+            access_ttl: Duration::from_secs(60 * 60),
+            refresh_ttl: Duration::from_secs(7 * 24 * 60 * 60),
+            cookie: RefreshCookieConfig::default(),
+            issuer: "portfolio-api".to_string(),
+            audience: "portfolio".to_string(),
+            trust_claims: false,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct RefreshCookieConfig {
+    /// `None` leaves the cookie to the host that set it.
+    pub domain: Option<String>,
+    /// Should be on wherever the API is served over HTTPS.
+    pub secure: bool,
+    pub same_site: SameSite,
+    pub path: String,
+}
+
+impl Default for RefreshCookieConfig {
+    fn default() -> Self {
+        Self {
+            domain: None,
+            secure: false,
+            same_site: SameSite::Lax,
+            path: "/".to_string(),
+        }
+    }
+}
+
+/// OpenID Connect provider for single sign-on with the authorization code flow and PKCE.
+#[derive(Clone, Debug)]
+pub struct OidcConfig {
+    /// Where `/.well-known/openid-configuration` is found; must match the `iss` it reports.
+    pub issuer: String,
+    pub client_id: String,
+    /// `None` for a public client, which relies on PKCE alone.
+    pub client_secret: Option<String>,
+    /// The frontend page the provider sends the browser back to; it hands `code` and `state`
+    /// to `/auth/oidc/callback`.
+    pub redirect_uri: String,
+    pub scopes: String,
+    /// How long a login started at `/auth/oidc/login` can be completed.
+    pub login_ttl: Duration,
+}
+
+impl OidcConfig {
+    /// Defaults for everything but the provider and client.
+    pub fn new(issuer: String, client_id: String, redirect_uri: String) -> Self {
+        Self {
+            issuer,
+            client_id,
+            client_secret: None,
+            redirect_uri,
+            scopes: "openid email profile".to_string(),
+            login_ttl: Duration::from_secs(10 * 60),
+        }
+    }
+}
+
+/// Periodic purge of refresh tokens that can no longer be used.
+#[derive(Clone, Debug)]
+pub struct TokenCleanupConfig {
+    /// `None` turns the purge off.
+    pub interval: Option<Duration>,
+    /// How long revoked tokens are kept after they were issued. While a revoked token is on
+    /// record its reuse is caught and ends its family, so this should outlast token lifetimes.
+    pub revoked_retention: Duration,
+    /// Rows deleted per statement, so a large backlog does not hold locks for long.
+    pub batch_size: u32,
+}
+
+impl Default for TokenCleanupConfig {
+    fn default() -> Self {
+        Self {
+            interval: Some(Duration::from_secs(60 * 60)),
+            revoked_retention: Duration::from_secs(30 * 24 * 60 * 60),
+            batch_size: 1000,
+        }
+    }
+}
+
+/// What a rate limit bucket is kept per.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RateLimitKey {
+    Ip,
+    /// The bearer token's user, falling back to the client address for anonymous requests.
+    User,
+    /// One bucket shared by every caller of the route group.
+    Group,
+}
+
+/// A token bucket allowing `burst` requests at once, refilled at `burst` per `per`.
+#[derive(Clone, Debug)]
+pub struct RateLimitRule {
+    pub burst: u32,
+    pub per: Duration,
+    pub key: RateLimitKey,
+}
+
+impl RateLimitRule {
+    fn new(burst: u32, per_secs: u64, key: RateLimitKey) -> Self {
+        Self {
+            burst,
+            per: Duration::from_secs(per_secs),
+            key,
+        }
+    }
+}
+
+/// Rules per route group; set as `RATE_LIMIT_<GROUP>=<burst>/<seconds>` and
+/// `RATE_LIMIT_<GROUP>_KEY=ip|user|group`.
+#[derive(Clone, Debug)]
+pub struct RateLimitConfig {
+    pub enabled: bool,
+    /// Every API request.
+    pub global: RateLimitRule,
+    /// Login and password reset.
+    pub login: RateLimitRule,
+    pub refresh: RateLimitRule,
+    /// The `/image` upload routes.
+    pub upload: RateLimitRule,
+    /// `GET` requests on blogs, projects and stacks.
+    pub public_read: RateLimitRule,
+}
+
+impl Default for RateLimitConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            global: RateLimitRule::new(600, 60, RateLimitKey::Ip),
+            login: RateLimitRule::new(10, 60, RateLimitKey::Ip),
+            refresh: RateLimitRule::new(30, 60, RateLimitKey::Ip),
+            upload: RateLimitRule::new(30, 60, RateLimitKey::User),
+            public_read: RateLimitRule::new(120, 60, RateLimitKey::Ip),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    /// The longest a bucket takes to refill completely.
+    pub fn longest_period(&self) -> Duration {
+        [
+            &self.global,
+            &self.login,
+            &self.refresh,
+            &self.upload,
+            &self.public_read,
+        ]
+        .iter()
+        .map(|rule| rule.per)
+        .max()
+        .unwrap_or_default()
+    }
+}
+
+/// Selected with `RATE_LIMIT_STORE` (`memory` or `postgres`); defaults to `memory`.
+pub enum RateLimitStoreConfig {
+    Memory,
+    Postgres,
+}
+
+/// Selected with `TOKEN_DENYLIST_STORE` (`memory` or `postgres`); defaults to `postgres` so
+/// revoked access tokens stay revoked across restarts and instances.
+pub enum DenylistStoreConfig {
+    Memory,
+    Postgres,
+}
+
+/// Upload policy enforced before an image reaches the store.
+#[derive(Clone, Debug)]
+pub struct ImageLimits {
+    pub max_bytes: usize,
+    /// Whole request bodies on the upload routes, which may carry several files or base64.
+    pub max_request_bytes: usize,
+    pub max_width: u32,
+    pub max_height: u32,
+    /// SVG can carry scripts, so it is opt-in via `IMAGE_ALLOW_SVG`.
+    pub allow_svg: bool,
+}
+
+impl Default for ImageLimits {
+    fn default() -> Self {
+        Self {
+            max_bytes: 5 * 1024 * 1024,
+            max_request_bytes: 32 * 1024 * 1024,
+            max_width: 8000,
+            max_height: 8000,
+            allow_svg: false,
+        }
+    }
+}
+
+/// Cloudinary folders uploads are filed under, by what they were uploaded for.
+#[derive(Clone, Debug)]
+pub struct CloudinaryFolders {
+    pub blog: String,
+    pub project: String,
+    /// Media library uploads not made for a particular blog or project.
+    pub library: String,
+}
+
+impl Default for CloudinaryFolders {
+    fn default() -> Self {
+        Self {
+            blog: "portfolio/blog".to_string(),
+            project: "portfolio/project".to_string(),
+            library: "portfolio/library".to_string(),
+        }
+    }
+}
+
+/// Timeouts, retries and circuit breaking for calls to the Cloudinary API.
+#[derive(Clone, Debug)]
+pub struct CloudinaryClientConfig {
+    pub api_url: String,
+    pub connect_timeout: Duration,
+    pub request_timeout: Duration,
+    /// Extra attempts after a network error or 5xx; the delay doubles from `retry_base_delay`.
+    pub max_retries: u32,
+    pub retry_base_delay: Duration,
+    pub breaker_threshold: u32,
+    pub breaker_open_for: Duration,
+}
+
+impl Default for CloudinaryClientConfig {
+    fn default() -> Self {
+        Self {
+            api_url: "https://api.cloudinary.com/v1_1".to_string(),
+            connect_timeout: Duration::from_secs(5),
+            request_timeout: Duration::from_secs(30),
+            max_retries: 2,
+            retry_base_delay: Duration::from_millis(200),
+            breaker_threshold: 5,
+            breaker_open_for: Duration::from_secs(30),
+        }
+    }
 }
 
 impl Config {
@@ -17,22 +410,519 @@ impl Config {
 
         Self {
             database_url: env::var("DATABASE_URL").expect("DATABASE_URL must be set"),
-            jwt_secret: env::var("JWT_SECRET").expect("JWT_SECRET must be set"),
+            jwt_keys: JwtKeysConfig::from_env(),
             port: env::var("PORT")
                 .unwrap_or_else(|_| "9400".to_string())
                 .parse::<u16>() // ✅ parse as u16
                 .expect("PORT must be a valid u16"),
-            jwt_expiry_hour: env::var("JWT_EXPIRY_HOUR")
-                .expect("JWT_EXPIRY_HOUR must be set")
-                .parse()
-                .expect("JWT_EXPIRY_SECONDS must be a number"),
+            tokens: TokenConfig::from_env(),
             db_pool_max_connections: env::var("DB_POOL_MAX_CONNECTIONS").ok().map(|s| {
                 s.parse::<u32>()
                     .expect("DB_POOL_MAX_CONNECTIONS must be a number")
             }),
-            cloud_name: env::var("CLOUD_NAME").expect("CLOUD_NAME must be set"),
-            cloud_api_key: env::var("CLOUD_API_KEY").expect("CLOUD_API_KEY must be set"),
-            cloud_api_secret: env::var("CLOUD_API_SECRET").expect("CLOUD_API_SECRET must be set"),
+            image_store: ImageStoreConfig::from_env(),
+            image_reconcile_interval_minutes: env::var("IMAGE_RECONCILE_INTERVAL_MINUTES")
+                .ok()
+                .map(|s| {
+                    s.parse::<u64>()
+                        .expect("IMAGE_RECONCILE_INTERVAL_MINUTES must be a number")
+                }),
+            image_limits: ImageLimits::from_env(),
+            mailer: MailerConfig::from_env(),
+            password_reset: PasswordResetConfig::from_env(),
+            login_throttle: LoginThrottleConfig::from_env(),
+            two_factor: TwoFactorConfig::from_env(),
+            token_cleanup: TokenCleanupConfig::from_env(),
+            trust_forwarded_for: env::var("TRUST_FORWARDED_FOR")
+                .map(|s| {
+                    s.parse()
+                        .expect("TRUST_FORWARDED_FOR must be true or false")
+                })
+                .unwrap_or(false),
+            rate_limits: RateLimitConfig::from_env(),
+            rate_limit_store: match env::var("RATE_LIMIT_STORE")
+                .unwrap_or_else(|_| "memory".to_string())
+                .as_str()
+            {
+                "memory" => RateLimitStoreConfig::Memory,
+                "postgres" => RateLimitStoreConfig::Postgres,
+                other => panic!("RATE_LIMIT_STORE must be memory or postgres (got {other})"),
+            },
+            token_denylist_store: match env::var("TOKEN_DENYLIST_STORE")
+                .unwrap_or_else(|_| "postgres".to_string())
+                .as_str()
+            {
+                "memory" => DenylistStoreConfig::Memory,
+                "postgres" => DenylistStoreConfig::Postgres,
+                other => panic!("TOKEN_DENYLIST_STORE must be memory or postgres (got {other})"),
+            },
+            oidc: OidcConfig::from_env(),
+        }
+    }
+}
+
+impl ImageLimits {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            max_bytes: env::var("IMAGE_MAX_BYTES")
+                .map(|s| s.parse().expect("IMAGE_MAX_BYTES must be a number"))
+                .unwrap_or(defaults.max_bytes),
+            max_request_bytes: env::var("IMAGE_MAX_REQUEST_BYTES")
+                .map(|s| s.parse().expect("IMAGE_MAX_REQUEST_BYTES must be a number"))
+                .unwrap_or(defaults.max_request_bytes),
+            max_width: env::var("IMAGE_MAX_WIDTH")
+                .map(|s| s.parse().expect("IMAGE_MAX_WIDTH must be a number"))
+                .unwrap_or(defaults.max_width),
+            max_height: env::var("IMAGE_MAX_HEIGHT")
+                .map(|s| s.parse().expect("IMAGE_MAX_HEIGHT must be a number"))
+                .unwrap_or(defaults.max_height),
+            allow_svg: env::var("IMAGE_ALLOW_SVG")
+                .map(|s| s.parse().expect("IMAGE_ALLOW_SVG must be true or false"))
+                .unwrap_or(defaults.allow_svg),
+        }
+    }
+}
+
+impl CloudinaryFolders {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            blog: env::var("CLOUDINARY_BLOG_FOLDER").unwrap_or(defaults.blog),
+            project: env::var("CLOUDINARY_PROJECT_FOLDER").unwrap_or(defaults.project),
+            library: env::var("CLOUDINARY_LIBRARY_FOLDER").unwrap_or(defaults.library),
+        }
+    }
+}
+
+impl CloudinaryClientConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        let millis = |name: &str, default: Duration| {
+            env::var(name)
+                .map(|s| {
+                    Duration::from_millis(
+                        s.parse()
+                            .unwrap_or_else(|_| panic!("{name} must be a number")),
+                    )
+                })
+                .unwrap_or(default)
+        };
+
+        Self {
+            api_url: env::var("CLOUDINARY_API_URL").unwrap_or(defaults.api_url),
+            connect_timeout: millis("CLOUDINARY_CONNECT_TIMEOUT_MS", defaults.connect_timeout),
+            request_timeout: millis("CLOUDINARY_REQUEST_TIMEOUT_MS", defaults.request_timeout),
+            max_retries: env::var("CLOUDINARY_MAX_RETRIES")
+                .map(|s| s.parse().expect("CLOUDINARY_MAX_RETRIES must be a number"))
+                .unwrap_or(defaults.max_retries),
+            retry_base_delay: millis("CLOUDINARY_RETRY_DELAY_MS", defaults.retry_base_delay),
+            breaker_threshold: env::var("CLOUDINARY_BREAKER_THRESHOLD")
+                .map(|s| {
+                    s.parse()
+                        .expect("CLOUDINARY_BREAKER_THRESHOLD must be a number")
+                })
+                .unwrap_or(defaults.breaker_threshold),
+            breaker_open_for: millis("CLOUDINARY_BREAKER_OPEN_MS", defaults.breaker_open_for),
+        }
+    }
+}
+
+impl ImageStoreConfig {
+    pub fn from_env() -> Self {
+        match env::var("IMAGE_STORE")
+            .unwrap_or_else(|_| "cloudinary".to_string())
+            .as_str()
+        {
+            "cloudinary" => Self::Cloudinary {
+                cloud_name: env::var("CLOUD_NAME").expect("CLOUD_NAME must be set"),
+                api_key: env::var("CLOUD_API_KEY").expect("CLOUD_API_KEY must be set"),
+                api_secret: env::var("CLOUD_API_SECRET").expect("CLOUD_API_SECRET must be set"),
+                folders: CloudinaryFolders::from_env(),
+                client: CloudinaryClientConfig::from_env(),
+            },
+            "local" => Self::Local {
+                media_dir: env::var("MEDIA_DIR").unwrap_or_else(|_| "./media".to_string()),
+                public_url: env::var("MEDIA_PUBLIC_URL").unwrap_or_default(),
+            },
+            "s3" => Self::S3 {
+                endpoint: env::var("S3_ENDPOINT").expect("S3_ENDPOINT must be set"),
+                bucket: env::var("S3_BUCKET").expect("S3_BUCKET must be set"),
+                region: env::var("S3_REGION").unwrap_or_else(|_| "us-east-1".to_string()),
+                access_key: env::var("S3_ACCESS_KEY").expect("S3_ACCESS_KEY must be set"),
+                secret_key: env::var("S3_SECRET_KEY").expect("S3_SECRET_KEY must be set"),
+                public_url: env::var("S3_PUBLIC_URL").ok(),
+            },
+            other => panic!("IMAGE_STORE must be cloudinary, local or s3 (got {other})"),
+        }
+    }
+}
+
+impl MailerConfig {
+    pub fn from_env() -> Self {
+        match env::var("MAILER")
+            .unwrap_or_else(|_| "file".to_string())
+            .as_str()
+        {
+            "smtp" => Self::Smtp {
+                host: env::var("SMTP_HOST").expect("SMTP_HOST must be set"),
+                port: env::var("SMTP_PORT")
+                    .map(|s| s.parse().expect("SMTP_PORT must be a valid u16"))
+                    .unwrap_or(587),
+                username: env::var("SMTP_USERNAME").ok(),
+                password: env::var("SMTP_PASSWORD").ok(),
+                from: env::var("MAIL_FROM").expect("MAIL_FROM must be set"),
+            },
+            "file" => Self::File {
+                dir: env::var("MAIL_DIR").ok(),
+            },
+            other => panic!("MAILER must be smtp or file (got {other})"),
         }
     }
 }
+
+impl PasswordResetConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            url: env::var("PASSWORD_RESET_URL").unwrap_or(defaults.url),
+            ttl: env::var("PASSWORD_RESET_TTL_MINUTES")
+                .map(|s| {
+                    Duration::from_secs(
+                        s.parse::<u64>()
+                            .expect("PASSWORD_RESET_TTL_MINUTES must be a number")
+                            * 60,
+                    )
+                })
+                .unwrap_or(defaults.ttl),
+        }
+    }
+}
+
+impl TwoFactorConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            issuer: env::var("TWO_FACTOR_ISSUER").unwrap_or(defaults.issuer),
+            required_for_admins: env::var("TWO_FACTOR_REQUIRED_FOR_ADMINS")
+                .map(|s| {
+                    s.parse()
+                        .expect("TWO_FACTOR_REQUIRED_FOR_ADMINS must be true or false")
+                })
+                .unwrap_or(defaults.required_for_admins),
+            challenge_ttl: env::var("TWO_FACTOR_CHALLENGE_TTL_MINUTES")
+                .map(|s| {
+                    Duration::from_secs(
+                        s.parse::<u64>()
+                            .expect("TWO_FACTOR_CHALLENGE_TTL_MINUTES must be a number")
+                            * 60,
+                    )
+                })
+                .unwrap_or(defaults.challenge_ttl),
+            max_attempts: env::var("TWO_FACTOR_MAX_ATTEMPTS")
+                .map(|s| s.parse().expect("TWO_FACTOR_MAX_ATTEMPTS must be a number"))
+                .unwrap_or(defaults.max_attempts),
+        }
+    }
+}
+
+impl JwtKeysConfig {
+    pub fn from_env() -> Self {
+        let algorithm = match env::var("JWT_ALGORITHM")
+            .unwrap_or_else(|_| "HS256".to_string())
+            .as_str()
+        {
+            "HS256" => {
+                return Self::Secret(env::var("JWT_SECRET").expect("JWT_SECRET must be set"));
+            }
+            "RS256" => Algorithm::RS256,
+            "EdDSA" => Algorithm::EdDSA,
+            other => panic!("JWT_ALGORITHM must be HS256, RS256 or EdDSA (got {other})"),
+        };
+
+        Self::Files {
+            algorithm,
+            signing_kid: env::var("JWT_SIGNING_KEY_ID").expect("JWT_SIGNING_KEY_ID must be set"),
+            signing_key_file: env::var("JWT_SIGNING_KEY_FILE")
+                .expect("JWT_SIGNING_KEY_FILE must be set"),
+            // `kid=path` pairs separated by commas, e.g. `2026-10=keys/2026-10.pub.pem`.
+            verification_key_files: env::var("JWT_VERIFICATION_KEYS")
+                .expect("JWT_VERIFICATION_KEYS must be set")
+                .split(',')
+                .map(|pair| {
+                    let (kid, path) = pair
+                        .trim()
+                        .split_once('=')
+                        .expect("JWT_VERIFICATION_KEYS must look like <kid>=<path>,...");
+                    (kid.to_string(), path.to_string())
+                })
+                .collect(),
+        }
+    }
+}
+
+impl OidcConfig {
+    pub fn from_env() -> Option<Self> {
+        let issuer = env::var("OIDC_ISSUER").ok()?;
+
+        let defaults = Self::new(
+            issuer,
+            env::var("OIDC_CLIENT_ID").expect("OIDC_CLIENT_ID must be set"),
+            env::var("OIDC_REDIRECT_URI").expect("OIDC_REDIRECT_URI must be set"),
+        );
+
+        Some(Self {
+            client_secret: env::var("OIDC_CLIENT_SECRET").ok(),
+            scopes: env::var("OIDC_SCOPES").unwrap_or(defaults.scopes.clone()),
+            login_ttl: env::var("OIDC_LOGIN_TTL_MINUTES")
+                .map(|s| {
+                    Duration::from_secs(
+                        s.parse::<u64>()
+                            .expect("OIDC_LOGIN_TTL_MINUTES must be a number")
+                            * 60,
+                    )
+                })
+                .unwrap_or(defaults.login_ttl),
+            ..defaults
+        })
+    }
+}
+
+impl TokenConfig {
+    /// Panics on settings that would hand out unusable tokens or cookies browsers drop.
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        let minutes = |name: &str| {
+            env::var(name).ok().map(|s| {
+                Duration::from_secs(
+                    s.parse::<u64>()
+                        .unwrap_or_else(|_| panic!("{name} must be a number"))
+                        * 60,
+                )
+            })
+        };
+
+        let config = Self {
+            // `JWT_EXPIRY_HOUR` is what older deployments set.
+            access_ttl: minutes("ACCESS_TOKEN_TTL_MINUTES")
+                .or_else(|| minutes("JWT_EXPIRY_HOUR").map(|ttl| ttl * 60))
+                .unwrap_or(defaults.access_ttl),
+            refresh_ttl: env::var("REFRESH_TOKEN_TTL_DAYS")
+                .map(|s| {
+                    Duration::from_secs(
+                        s.parse::<u64>()
+                            .expect("REFRESH_TOKEN_TTL_DAYS must be a number")
+                            * 24
+                            * 60
+                            * 60,
+                    )
+                })
+                .unwrap_or(defaults.refresh_ttl),
+            cookie: RefreshCookieConfig {
+                domain: env::var("COOKIE_DOMAIN").ok().filter(|s| !s.is_empty()),
+                secure: env::var("COOKIE_SECURE")
+                    .map(|s| s.parse().expect("COOKIE_SECURE must be true or false"))
+                    .unwrap_or(defaults.cookie.secure),
+                same_site: match env::var("COOKIE_SAME_SITE") {
+                    Ok(s) => match s.to_lowercase().as_str() {
+                        "strict" => SameSite::Strict,
+                        "lax" => SameSite::Lax,
+                        "none" => SameSite::None,
+                        _ => panic!("COOKIE_SAME_SITE must be strict, lax or none (got {s})"),
+                    },
+                    Err(_) => defaults.cookie.same_site,
+                },
+                path: env::var("COOKIE_PATH").unwrap_or(defaults.cookie.path),
+            },
+            issuer: env::var("JWT_ISSUER").unwrap_or(defaults.issuer),
+            audience: env::var("JWT_AUDIENCE").unwrap_or(defaults.audience),
+            trust_claims: env::var("JWT_TRUST_CLAIMS")
+                .map(|s| s.parse().expect("JWT_TRUST_CLAIMS must be true or false"))
+                .unwrap_or(defaults.trust_claims),
+        };
+
+        if let Err(e) = config.validate() {
+            panic!("{e}");
+        }
+
+        config
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+        if self.access_ttl.is_zero() {
+            return Err("ACCESS_TOKEN_TTL_MINUTES must be more than 0".to_string());
+        }
+
+        if self.issuer.is_empty() || self.audience.is_empty() {
+            return Err("JWT_ISSUER and JWT_AUDIENCE must not be empty".to_string());
+        }
+
+        if self.refresh_ttl <= self.access_ttl {
+            return Err(
+                "REFRESH_TOKEN_TTL_DAYS must be longer than the access token lifetime".to_string(),
+            );
+        }
+
+        if self.cookie.same_site == SameSite::None && !self.cookie.secure {
+            return Err("COOKIE_SAME_SITE=none needs COOKIE_SECURE=true".to_string());
+        }
+
+        if !self.cookie.path.starts_with('/') {
+            return Err(format!(
+                "COOKIE_PATH must start with / (got {})",
+                self.cookie.path
+            ));
+        }
+
+        if let Some(domain) = &self.cookie.domain
+            && domain.contains(['/', ':'])
+        {
+            return Err(format!(
+                "COOKIE_DOMAIN must be a bare host name (got {domain})"
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+impl TokenCleanupConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            interval: match env::var("TOKEN_CLEANUP_INTERVAL_MINUTES") {
+                Ok(s) => match s
+                    .parse::<u64>()
+                    .expect("TOKEN_CLEANUP_INTERVAL_MINUTES must be a number")
+                {
+                    0 => None,
+                    minutes => Some(Duration::from_secs(minutes * 60)),
+                },
+                Err(_) => defaults.interval,
+            },
+            revoked_retention: env::var("REVOKED_TOKEN_RETENTION_DAYS")
+                .map(|s| {
+                    Duration::from_secs(
+                        s.parse::<u64>()
+                            .expect("REVOKED_TOKEN_RETENTION_DAYS must be a number")
+                            * 24
+                            * 60
+                            * 60,
+                    )
+                })
+                .unwrap_or(defaults.revoked_retention),
+            batch_size: env::var("TOKEN_CLEANUP_BATCH_SIZE")
+                .map(|s| {
+                    s.parse()
+                        .expect("TOKEN_CLEANUP_BATCH_SIZE must be a number")
+                })
+                .unwrap_or(defaults.batch_size),
+        }
+    }
+}
+
+impl LoginThrottleConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        let millis = |name: &str, default: Duration| {
+            env::var(name)
+                .map(|s| {
+                    Duration::from_millis(
+                        s.parse()
+                            .unwrap_or_else(|_| panic!("{name} must be a number")),
+                    )
+                })
+                .unwrap_or(default)
+        };
+
+        let seconds = |name: &str, default: Duration| {
+            env::var(name)
+                .map(|s| {
+                    Duration::from_secs(
+                        s.parse()
+                            .unwrap_or_else(|_| panic!("{name} must be a number")),
+                    )
+                })
+                .unwrap_or(default)
+        };
+
+        Self {
+            max_account_failures: env::var("LOGIN_MAX_ACCOUNT_FAILURES")
+                .map(|s| {
+                    s.parse()
+                        .expect("LOGIN_MAX_ACCOUNT_FAILURES must be a number")
+                })
+                .unwrap_or(defaults.max_account_failures),
+            max_ip_failures: env::var("LOGIN_MAX_IP_FAILURES")
+                .map(|s| s.parse().expect("LOGIN_MAX_IP_FAILURES must be a number"))
+                .unwrap_or(defaults.max_ip_failures),
+            window: seconds("LOGIN_FAILURE_WINDOW_SECS", defaults.window),
+            lockout: seconds("LOGIN_LOCKOUT_SECS", defaults.lockout),
+            base_delay: millis("LOGIN_DELAY_BASE_MS", defaults.base_delay),
+            max_delay: millis("LOGIN_DELAY_MAX_MS", defaults.max_delay),
+        }
+    }
+}
+
+impl RateLimitConfig {
+    pub fn from_env() -> Self {
+        let defaults = Self::default();
+
+        Self {
+            enabled: env::var("RATE_LIMIT_ENABLED")
+                .map(|s| s.parse().expect("RATE_LIMIT_ENABLED must be true or false"))
+                .unwrap_or(defaults.enabled),
+            global: RateLimitRule::from_env("GLOBAL", defaults.global),
+            login: RateLimitRule::from_env("LOGIN", defaults.login),
+            refresh: RateLimitRule::from_env("REFRESH", defaults.refresh),
+            upload: RateLimitRule::from_env("UPLOAD", defaults.upload),
+            public_read: RateLimitRule::from_env("PUBLIC_READ", defaults.public_read),
+        }
+    }
+}
+
+impl RateLimitRule {
+    fn from_env(group: &str, default: Self) -> Self {
+        let name = format!("RATE_LIMIT_{group}");
+
+        let (burst, per) = match env::var(&name) {
+            Ok(value) => {
+                let (burst, seconds) = value
+                    .split_once('/')
+                    .unwrap_or_else(|| panic!("{name} must look like <burst>/<seconds>"));
+
+                (
+                    burst
+                        .trim()
+                        .parse()
+                        .unwrap_or_else(|_| panic!("{name} burst must be a number")),
+                    Duration::from_secs(
+                        seconds
+                            .trim()
+                            .parse()
+                            .unwrap_or_else(|_| panic!("{name} seconds must be a number")),
+                    ),
+                )
+            }
+            Err(_) => (default.burst, default.per),
+        };
+
+        let key = match env::var(format!("{name}_KEY")).as_deref() {
+            Ok("ip") => RateLimitKey::Ip,
+            Ok("user") => RateLimitKey::User,
+            Ok("group") => RateLimitKey::Group,
+            Ok(other) => panic!("{name}_KEY must be ip, user or group (got {other})"),
+            Err(_) => default.key,
+        };
+
+        Self { burst, per, key }
+    }
+}
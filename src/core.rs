@@ -1,5 +1,9 @@
 pub mod image_core;
 pub mod jwt;
+pub mod jwt_keys;
 pub mod login_token_core;
+pub mod media_core;
 pub mod password_core;
 pub mod stack_identifier_core;
+pub mod totp_core;
+pub mod well_known_handlers;
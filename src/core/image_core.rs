@@ -1,19 +1,23 @@
 use tokio::sync::{mpsc::Sender, oneshot};
+use uuid::Uuid;
 
 use crate::{
     errors::api_errors::ApiErrors,
-    image::messages::{ImageMessage, ImageUploadResult},
+    image::{
+        dto::ImageUpload,
+        messages::{ImageMessage, ImageUploadResult, IssuedTicket},
+    },
 };
 
-pub async fn base64_image_uploader_core(
-    base64: String,
+pub async fn upload_image_core(
+    image: ImageUpload,
     image_tx: &Sender<ImageMessage>,
 ) -> Result<ImageUploadResult, ApiErrors> {
     let (tx, rx) = oneshot::channel();
 
     image_tx
-        .send(ImageMessage::UploadBase64 {
-            base64,
+        .send(ImageMessage::Upload {
+            image,
             respond_to: tx,
         })
         .await
@@ -26,6 +30,174 @@ pub async fn base64_image_uploader_core(
     Ok(result)
 }
 
+pub async fn delete_image_core(
+    public_id: String,
+    image_tx: &Sender<ImageMessage>,
+) -> Result<(), ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    image_tx
+        .send(ImageMessage::Delete {
+            public_id,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Image Service unavailable".to_string()))?;
+
+    rx.await
+        .map_err(|_| ApiErrors::InternalServerError("Image Delete failed".to_string()))?
+}
+
+pub async fn issue_ticket_core(
+    content_type: String,
+    owner: Uuid,
+    image_tx: &Sender<ImageMessage>,
+) -> Result<IssuedTicket, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    image_tx
+        .send(ImageMessage::IssueTicket {
+            content_type,
+            owner,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Image Service unavailable".to_string()))?;
+
+    rx.await
+        .map_err(|_| ApiErrors::InternalServerError("Upload ticket failed".to_string()))?
+}
+
+pub async fn verify_confirmation_core(
+    public_id: String,
+    owner: Uuid,
+    confirmation: String,
+    image_tx: &Sender<ImageMessage>,
+) -> Result<(), ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    image_tx
+        .send(ImageMessage::VerifyConfirmation {
+            public_id,
+            owner,
+            confirmation,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Image Service unavailable".to_string()))?;
+
+    rx.await
+        .map_err(|_| ApiErrors::InternalServerError("Confirmation check failed".to_string()))?
+}
+
+pub async fn verify_ticket_core(
+    public_id: String,
+    expires: i64,
+    signature: String,
+    image_tx: &Sender<ImageMessage>,
+) -> Result<(), ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    image_tx
+        .send(ImageMessage::VerifyTicket {
+            public_id,
+            expires,
+            signature,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Image Service unavailable".to_string()))?;
+
+    rx.await
+        .map_err(|_| ApiErrors::InternalServerError("Ticket check failed".to_string()))?
+}
+
+pub async fn receive_image_core(
+    public_id: String,
+    expires: i64,
+    signature: String,
+    image: ImageUpload,
+    image_tx: &Sender<ImageMessage>,
+) -> Result<(), ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    image_tx
+        .send(ImageMessage::Receive {
+            public_id,
+            expires,
+            signature,
+            image,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Image Service unavailable".to_string()))?;
+
+    rx.await
+        .map_err(|_| ApiErrors::InternalServerError("Image Upload failed".to_string()))?
+}
+
+pub async fn fetch_image_core(
+    public_id: String,
+    max_bytes: usize,
+    image_tx: &Sender<ImageMessage>,
+) -> Result<Vec<u8>, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    image_tx
+        .send(ImageMessage::Fetch {
+            public_id,
+            max_bytes,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Image Service unavailable".to_string()))?;
+
+    rx.await
+        .map_err(|_| ApiErrors::InternalServerError("Image Fetch failed".to_string()))?
+}
+
+pub async fn register_image_core(
+    public_id: String,
+    image: ImageUpload,
+    image_tx: &Sender<ImageMessage>,
+) -> Result<ImageUploadResult, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    image_tx
+        .send(ImageMessage::Register {
+            public_id,
+            image,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Image Service unavailable".to_string()))?;
+
+    rx.await
+        .map_err(|_| ApiErrors::InternalServerError("Image Register failed".to_string()))?
+}
+
+pub async fn tag_image_core(
+    public_id: String,
+    tag: String,
+    attach: bool,
+    image_tx: &Sender<ImageMessage>,
+) -> Result<(), ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    image_tx
+        .send(ImageMessage::Tag {
+            public_id,
+            tag,
+            attach,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Image Service unavailable".to_string()))?;
+
+    rx.await
+        .map_err(|_| ApiErrors::InternalServerError("Image Tag failed".to_string()))?
+}
+
 // pub async fn file_image_uploader_core(
 //     base64: String,
 //     image_tx: &Sender<ImageMessage>,
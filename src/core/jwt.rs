@@ -1,62 +1,127 @@
-use jsonwebtoken::{Algorithm, DecodingKey, EncodingKey, Header, Validation, decode, encode};
+use chrono::NaiveDateTime;
+use jsonwebtoken::{Validation, decode, decode_header, encode};
 use serde::{Deserialize, Serialize};
 use tokio::sync::{mpsc::Sender, oneshot};
 use uuid::Uuid;
 
 use crate::{
     auth::messages::{AuthMessage, UserResponse},
+    config::TokenConfig,
+    core::jwt_keys::JwtKeys,
     errors::api_errors::ApiErrors,
+    extractor::auth_extractor::AuthUser,
+    fields::{email::Email, roles::Roles, text::Text},
+    token_denylist::denylist::TokenDenylist,
+    utils::tokens::generate_opaque_token,
 };
 
+/// Claims of an access token. `roles`, `email` and `name` let [`TokenConfig::trust_claims`]
+/// build the caller without a lookup; `ver` is the user's token version at issue time.
 #[derive(Serialize, Deserialize)]
 pub struct Claims {
     pub sub: Uuid,
+    pub iss: String,
+    pub aud: String,
+    pub iat: usize,
+    pub nbf: usize,
     pub exp: usize,
+    pub jti: Uuid,
+    pub roles: Vec<String>,
+    pub email: String,
+    pub name: String,
+    pub ver: i32,
 }
 
+impl Claims {
+    /// `exp` as a timestamp, for as long as a denylist entry for `jti` is needed.
+    pub fn expires_at(&self) -> NaiveDateTime {
+        chrono::DateTime::from_timestamp(self.exp as i64, 0)
+            .unwrap_or_default()
+            .naive_utc()
+    }
+}
+
+/// The signed token and its claims, whose `jti` and `exp` are needed to deny it later.
 pub fn generate_token(
-    user_id: Uuid,
-    jwt_secret: &str,
-    jwt_expiry_hour: i64,
-) -> Result<String, ApiErrors> {
+    user: &UserResponse,
+    keys: &JwtKeys,
+    config: &TokenConfig,
+) -> Result<(String, Claims), ApiErrors> {
+    let now = chrono::Utc::now().timestamp() as usize;
+
     let claims = Claims {
-        sub: user_id,
-        exp: (chrono::Utc::now() + chrono::Duration::hours(jwt_expiry_hour)).timestamp() as usize,
+        sub: user.id,
+        iss: config.issuer.clone(),
+        aud: config.audience.clone(),
+        iat: now,
+        nbf: now,
+        exp: now + config.access_ttl.as_secs() as usize,
+        jti: Uuid::new_v4(),
+        roles: vec![user.roles.as_str().to_string()],
+        email: user.email.as_str().to_string(),
+        name: user.name.as_str().to_string(),
+        ver: user.token_version,
     };
 
-    encode(
-        &Header::default(),
-        &claims,
-        &EncodingKey::from_secret(jwt_secret.as_bytes()),
-    )
-    .map_err(|_| ApiErrors::InternalServerError("Token generation failed".to_string()))
+    let token = encode(&keys.header(), &claims, keys.signing_key())
+        .map_err(|_| ApiErrors::InternalServerError("Token generation failed".to_string()))?;
+
+    Ok((token, claims))
 }
 
-pub fn decode_token(token: &str, jwt_secret: &str) -> Result<Claims, ApiErrors> {
-    let mut validation = Validation::new(Algorithm::HS256);
+/// Checks the signature with the key the token's `kid` names, so tokens signed before a
+/// rotation stay valid for as long as their key is still configured.
+pub fn decode_token(
+    token: &str,
+    keys: &JwtKeys,
+    config: &TokenConfig,
+) -> Result<Claims, ApiErrors> {
+    let invalid = || ApiErrors::Unauthorized("Invalid or expired token".to_string());
+
+    let header = decode_header(token).map_err(|_| invalid())?;
+    let key = keys
+        .verification_key(header.kid.as_deref())
+        .ok_or_else(invalid)?;
+
+    let mut validation = Validation::new(keys.algorithm());
     validation.validate_exp = true;
+    validation.validate_nbf = true;
+    validation.set_issuer(&[&config.issuer]);
+    validation.set_audience(&[&config.audience]);
+    validation.set_required_spec_claims(&["exp", "nbf", "iss", "aud", "sub"]);
 
-    decode::<Claims>(
-        token,
-        &DecodingKey::from_secret(jwt_secret.as_bytes()),
-        &validation,
-    )
-    .map(|data| data.claims)
-    .map_err(|_| ApiErrors::Unauthorized("Invalid or expired token".to_string()))
+    decode::<Claims>(token, key, &validation)
+        .map(|data| data.claims)
+        .map_err(|_| invalid())
 }
 
+/// The caller a token stands for. Denied tokens are refused even when claims are trusted;
+/// otherwise the user is loaded so that deleted users, changed roles and bumped token
+/// versions take effect at once.
 pub async fn validate_user_token(
     token: &str,
-    jwt_secret: &str,
+    keys: &JwtKeys,
+    config: &TokenConfig,
+    denylist: &TokenDenylist,
     auth_tx: &Sender<AuthMessage>,
-) -> Result<UserResponse, ApiErrors> {
-    let (tx, rx) = oneshot::channel();
+) -> Result<AuthUser, ApiErrors> {
+    let claims = decode_token(token, keys, config)?;
 
-    let claim = decode_token(token, jwt_secret)?;
+    if denylist.is_denied(claims.jti) {
+        return Err(ApiErrors::Unauthorized(
+            "Token has been revoked".to_string(),
+        ));
+    }
+
+    if config.trust_claims {
+        return auth_user_from_claims(claims);
+    }
+
+    let (tx, rx) = oneshot::channel();
 
     auth_tx
         .send(AuthMessage::GetUser {
-            user_id: claim.sub,
+            user_id: claims.sub,
             respond_to: tx,
         })
         .await
@@ -66,9 +131,36 @@ pub async fn validate_user_token(
         .await
         .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))??;
 
-    Ok(user)
+    if user.token_version != claims.ver {
+        return Err(ApiErrors::Unauthorized(
+            "Token has been revoked".to_string(),
+        ));
+    }
+
+    Ok(AuthUser {
+        id: user.id,
+        email: user.email,
+        name: user.name,
+        roles: user.roles,
+    })
+}
+
+fn auth_user_from_claims(claims: Claims) -> Result<AuthUser, ApiErrors> {
+    let role = claims
+        .roles
+        .first()
+        .ok_or_else(|| ApiErrors::Unauthorized("Invalid or expired token".to_string()))?;
+
+    Ok(AuthUser {
+        id: claims.sub,
+        email: Email(claims.email),
+        name: Text(claims.name),
+        roles: Roles::new(role)
+            .map_err(|_| ApiErrors::Unauthorized("Invalid or expired token".to_string()))?,
+    })
 }
 
+/// 256 random bits; only its [`crate::utils::tokens::hash_opaque_token`] is ever stored.
 pub fn generate_refresh_token() -> String {
-    Uuid::new_v4().to_string()
+    generate_opaque_token()
 }
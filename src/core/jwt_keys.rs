@@ -0,0 +1,191 @@
+use std::fs;
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, EncodingKey, Header,
+    jwk::{
+        AlgorithmParameters, CommonParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm,
+        OctetKeyPairParameters, OctetKeyPairType, PublicKeyUse, RSAKeyParameters, RSAKeyType,
+    },
+};
+use rsa::{RsaPublicKey, pkcs8::DecodePublicKey, traits::PublicKeyParts};
+
+use crate::config::JwtKeysConfig;
+
+/// The key access tokens are signed with and every key they are still accepted under.
+/// Rotating means signing with a new key while the previous one stays in `verification`
+/// until the tokens it signed have expired.
+pub struct JwtKeys {
+    algorithm: Algorithm,
+    signing: EncodingKey,
+    signing_kid: Option<String>,
+    verification: Vec<VerificationKey>,
+}
+
+struct VerificationKey {
+    kid: Option<String>,
+    key: DecodingKey,
+    /// `None` for a shared secret, which must never be published.
+    jwk: Option<Jwk>,
+}
+
+impl JwtKeys {
+    /// HS256 with one shared secret: nothing to publish and no `kid`.
+    pub fn from_secret(secret: &str) -> Self {
+        Self {
+            algorithm: Algorithm::HS256,
+            signing: EncodingKey::from_secret(secret.as_bytes()),
+            signing_kid: None,
+            verification: vec![VerificationKey {
+                kid: None,
+                key: DecodingKey::from_secret(secret.as_bytes()),
+                jwk: None,
+            }],
+        }
+    }
+
+    /// Reads PEM files: a PKCS#8 private key to sign with and SPKI public keys to verify with.
+    /// The public half of the signing key has to be among the verification keys under the
+    /// same id.
+    pub fn from_files(
+        algorithm: Algorithm,
+        signing_kid: &str,
+        signing_key_file: &str,
+        verification_key_files: &[(String, String)],
+    ) -> Result<Self, String> {
+        let read = |path: &str| {
+            fs::read_to_string(path).map_err(|e| format!("Could not read key file {path}: {e}"))
+        };
+
+        let signing_pem = read(signing_key_file)?;
+        let signing = match algorithm {
+            Algorithm::RS256 => EncodingKey::from_rsa_pem(signing_pem.as_bytes()),
+            Algorithm::EdDSA => EncodingKey::from_ed_pem(signing_pem.as_bytes()),
+            other => return Err(format!("JWT_ALGORITHM {other:?} is not supported")),
+        }
+        .map_err(|e| format!("Invalid signing key {signing_key_file}: {e}"))?;
+
+        let verification = verification_key_files
+            .iter()
+            .map(|(kid, path)| {
+                let jwk = public_jwk(algorithm, kid, &read(path)?)
+                    .map_err(|e| format!("Invalid verification key {path}: {e}"))?;
+
+                Ok(VerificationKey {
+                    kid: Some(kid.clone()),
+                    key: DecodingKey::from_jwk(&jwk)
+                        .map_err(|e| format!("Invalid verification key {path}: {e}"))?,
+                    jwk: Some(jwk),
+                })
+            })
+            .collect::<Result<Vec<_>, String>>()?;
+
+        if !verification
+            .iter()
+            .any(|key| key.kid.as_deref() == Some(signing_kid))
+        {
+            return Err(format!(
+                "JWT_VERIFICATION_KEYS must include the signing key {signing_kid}"
+            ));
+        }
+
+        Ok(Self {
+            algorithm,
+            signing,
+            signing_kid: Some(signing_kid.to_string()),
+            verification,
+        })
+    }
+
+    pub fn from_config(config: JwtKeysConfig) -> Result<Self, String> {
+        match config {
+            JwtKeysConfig::Secret(secret) => Ok(Self::from_secret(&secret)),
+            JwtKeysConfig::Files {
+                algorithm,
+                signing_kid,
+                signing_key_file,
+                verification_key_files,
+            } => Self::from_files(
+                algorithm,
+                &signing_kid,
+                &signing_key_file,
+                &verification_key_files,
+            ),
+        }
+    }
+
+    pub fn algorithm(&self) -> Algorithm {
+        self.algorithm
+    }
+
+    /// The header new tokens are signed under, naming the key with `kid`.
+    pub fn header(&self) -> Header {
+        let mut header = Header::new(self.algorithm);
+        header.kid = self.signing_kid.clone();
+        header
+    }
+
+    pub fn signing_key(&self) -> &EncodingKey {
+        &self.signing
+    }
+
+    /// The key a token's `kid` names; a token without one only matches an unnamed secret.
+    pub fn verification_key(&self, kid: Option<&str>) -> Option<&DecodingKey> {
+        self.verification
+            .iter()
+            .find(|key| key.kid.as_deref() == kid)
+            .map(|key| &key.key)
+    }
+
+    /// The public keys, for other services to verify tokens with.
+    pub fn jwks(&self) -> JwkSet {
+        JwkSet {
+            keys: self
+                .verification
+                .iter()
+                .filter_map(|key| key.jwk.clone())
+                .collect(),
+        }
+    }
+}
+
+fn public_jwk(algorithm: Algorithm, kid: &str, pem: &str) -> Result<Jwk, String> {
+    let (key_algorithm, parameters) = match algorithm {
+        Algorithm::RS256 => {
+            let key = RsaPublicKey::from_public_key_pem(pem).map_err(|e| e.to_string())?;
+
+            (
+                KeyAlgorithm::RS256,
+                AlgorithmParameters::RSA(RSAKeyParameters {
+                    key_type: RSAKeyType::RSA,
+                    n: URL_SAFE_NO_PAD.encode(key.n().to_bytes_be()),
+                    e: URL_SAFE_NO_PAD.encode(key.e().to_bytes_be()),
+                }),
+            )
+        }
+        Algorithm::EdDSA => {
+            let key =
+                ed25519_dalek::VerifyingKey::from_public_key_pem(pem).map_err(|e| e.to_string())?;
+
+            (
+                KeyAlgorithm::EdDSA,
+                AlgorithmParameters::OctetKeyPair(OctetKeyPairParameters {
+                    key_type: OctetKeyPairType::OctetKeyPair,
+                    curve: EllipticCurve::Ed25519,
+                    x: URL_SAFE_NO_PAD.encode(key.as_bytes()),
+                }),
+            )
+        }
+        other => return Err(format!("{other:?} is not supported")),
+    };
+
+    Ok(Jwk {
+        common: CommonParameters {
+            public_key_use: Some(PublicKeyUse::Signature),
+            key_algorithm: Some(key_algorithm),
+            key_id: Some(kid.to_string()),
+            ..CommonParameters::default()
+        },
+        algorithm: parameters,
+    })
+}
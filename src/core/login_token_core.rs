@@ -3,21 +3,26 @@ use tower_cookies::Cookies;
 use uuid::Uuid;
 
 use crate::{
+    config::TokenConfig,
     errors::api_errors::ApiErrors,
+    extractor::client_info_extractor::ClientInfo,
     refresh_token::messages::{RefreshTokenMessage, TokenPair},
     utils::cookies::set_refresh_cookie,
 };
 
 pub async fn login_token_core(
     refresh_token_tx: &Sender<RefreshTokenMessage>,
+    tokens_config: &TokenConfig,
     cookies: Cookies,
     user_id: Uuid,
+    client: ClientInfo,
 ) -> Result<TokenPair, ApiErrors> {
     let (tx, rx) = oneshot::channel();
 
     refresh_token_tx
         .send(RefreshTokenMessage::Login {
             user_id,
+            client,
             respond_to: tx,
         })
         .await
@@ -27,7 +32,33 @@ pub async fn login_token_core(
         .await
         .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))??;
 
-    cookies.add(set_refresh_cookie(tokens.refresh_token.clone()));
+    cookies.add(set_refresh_cookie(
+        tokens.refresh_token.clone(),
+        &tokens_config.cookie,
+        tokens_config.refresh_ttl,
+    ));
 
     Ok(tokens)
 }
+
+/// Revokes every refresh token of `user_id` but `except`, signing them out everywhere else once
+/// their access tokens lapse.
+pub async fn revoke_user_tokens_core(
+    refresh_token_tx: &Sender<RefreshTokenMessage>,
+    user_id: Uuid,
+    except: Option<String>,
+) -> Result<(), ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    refresh_token_tx
+        .send(RefreshTokenMessage::RevokeAll {
+            user_id,
+            except,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
+
+    rx.await
+        .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))?
+}
@@ -0,0 +1,119 @@
+use tokio::sync::{mpsc::Sender, oneshot};
+use uuid::Uuid;
+
+use crate::{
+    errors::api_errors::ApiErrors,
+    media::{
+        dto::{ConfirmedUpload, MediaEntity, MediaSource, MediaUpload, Uploader},
+        messages::{MediaMessage, MediaResponse},
+    },
+};
+
+pub async fn upload_media_core(
+    upload: MediaUpload,
+    media_tx: &Sender<MediaMessage>,
+) -> Result<MediaResponse, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    media_tx
+        .send(MediaMessage::Upload {
+            upload,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Media Service unavailable".to_string()))?;
+
+    rx.await
+        .map_err(|_| ApiErrors::InternalServerError("Media Upload failed".to_string()))?
+}
+
+pub async fn confirm_upload_core(
+    upload: ConfirmedUpload,
+    media_tx: &Sender<MediaMessage>,
+) -> Result<MediaResponse, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    media_tx
+        .send(MediaMessage::ConfirmUpload {
+            upload,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Media Service unavailable".to_string()))?;
+
+    rx.await
+        .map_err(|_| ApiErrors::InternalServerError("Media Confirm failed".to_string()))?
+}
+
+pub async fn get_media_core(
+    media_id: Uuid,
+    media_tx: &Sender<MediaMessage>,
+) -> Result<MediaResponse, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    media_tx
+        .send(MediaMessage::GetSingleMedia {
+            media_id,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Media Service unavailable".to_string()))?;
+
+    let detail = rx
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Media lookup failed".to_string()))??;
+
+    Ok(detail.media)
+}
+
+/// Turns the `image` (base64) / `media_id` pair of a blog or project payload into a media
+/// item, uploading inline images for `entity` on behalf of `uploader`.
+pub async fn resolve_media_core(
+    entity: MediaEntity,
+    image: Option<String>,
+    media_id: Option<Uuid>,
+    uploader: Uploader,
+    media_tx: &Sender<MediaMessage>,
+) -> Result<Option<MediaResponse>, ApiErrors> {
+    match (image, media_id) {
+        (Some(_), Some(_)) => Err(ApiErrors::BadRequest(
+            "Provide either image or media_id, not both".to_string(),
+        )),
+        (Some(base64), None) => {
+            let upload = MediaUpload {
+                source: MediaSource::Base64(base64),
+                entity: Some(entity),
+                alt: None,
+                caption: None,
+                retain: false,
+                uploaded_by: Some(uploader),
+            };
+
+            upload_media_core(upload, media_tx).await.map(Some)
+        }
+        (None, Some(media_id)) => get_media_core(media_id, media_tx).await.map(Some),
+        (None, None) => Ok(None),
+    }
+}
+
+pub async fn set_media_usage_core(
+    entity: MediaEntity,
+    entity_id: Uuid,
+    media_id: Option<Uuid>,
+    media_tx: &Sender<MediaMessage>,
+) -> Result<(), ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    media_tx
+        .send(MediaMessage::SetUsage {
+            entity,
+            entity_id,
+            media_id,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Media Service unavailable".to_string()))?;
+
+    rx.await
+        .map_err(|_| ApiErrors::InternalServerError("Media usage update failed".to_string()))?
+}
@@ -1,3 +1,5 @@
+use once_cell::sync::Lazy;
+
 use argon2::{
     Argon2, PasswordHash, PasswordHasher, PasswordVerifier,
     password_hash::{SaltString, rand_core::OsRng},
@@ -19,3 +21,12 @@ pub fn hash_password(password: &str) -> Result<String, &'static str> {
         .map_err(|_| "Password hashing failed")
         .map(|hash| hash.to_string())
 }
+
+/// Hash of a throwaway password, verified against when a login names no known user so that
+/// the response takes as long as a wrong password would.
+static DUMMY_HASH: Lazy<String> =
+    Lazy::new(|| hash_password("dummy-password-never-matches").expect("dummy hash must build"));
+
+pub fn verify_dummy_password(plain_password: &str) {
+    let _ = verify_password(plain_password, &DUMMY_HASH);
+}
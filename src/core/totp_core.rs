@@ -0,0 +1,196 @@
+use hmac::{Hmac, Mac};
+use sha1::Sha1;
+use sha2::{Digest, Sha256};
+use url::Url;
+
+/// Seconds each code is valid for, as authenticator apps assume.
+pub const TOTP_STEP: i64 = 30;
+
+const TOTP_DIGITS: u32 = 6;
+
+const BASE32_ALPHABET: &[u8; 32] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZ234567";
+
+/// A random 160-bit shared secret, base32 encoded the way authenticator apps expect it.
+pub fn generate_totp_secret() -> String {
+    base32_encode(&rand::random::<[u8; 20]>())
+}
+
+/// The URI an authenticator app scans from a QR code.
+pub fn otpauth_uri(issuer: &str, account: &str, secret: &str) -> String {
+    let mut uri = Url::parse("otpauth://totp/").expect("static otpauth url");
+
+    uri.set_path(&format!("{issuer}:{account}"));
+    uri.query_pairs_mut()
+        .append_pair("secret", secret)
+        .append_pair("issuer", issuer)
+        .append_pair("algorithm", "SHA1")
+        .append_pair("digits", &TOTP_DIGITS.to_string())
+        .append_pair("period", &TOTP_STEP.to_string());
+
+    uri.to_string()
+}
+
+/// The time step `code` belongs to, if it is right for the step at `now` or either neighbour
+/// (to allow for clock drift) and later than `last_used_step`, so a code works only once.
+pub fn verify_totp(secret: &str, code: &str, now: i64, last_used_step: Option<i64>) -> Option<i64> {
+    let key = base32_decode(secret)?;
+
+    let code = code.trim();
+    if code.len() != TOTP_DIGITS as usize || !code.bytes().all(|b| b.is_ascii_digit()) {
+        return None;
+    }
+    let code: u32 = code.parse().ok()?;
+
+    let current = now.div_euclid(TOTP_STEP);
+
+    (current - 1..=current + 1)
+        .filter(|step| last_used_step.is_none_or(|last| *step > last))
+        .find(|step| totp_code(&key, *step) == code)
+}
+
+/// The code an authenticator app shows at `now`.
+#[cfg(test)]
+pub fn current_totp(secret: &str, now: i64) -> String {
+    let key = base32_decode(secret).expect("valid base32 secret");
+
+    format!("{:06}", totp_code(&key, now.div_euclid(TOTP_STEP)))
+}
+
+fn totp_code(key: &[u8], step: i64) -> u32 {
+    let mut mac = Hmac::<Sha1>::new_from_slice(key).expect("hmac accepts any key length");
+    mac.update(&step.to_be_bytes());
+    let digest = mac.finalize().into_bytes();
+
+    let offset = (digest[digest.len() - 1] & 0x0f) as usize;
+    let binary = u32::from_be_bytes([
+        digest[offset] & 0x7f,
+        digest[offset + 1],
+        digest[offset + 2],
+        digest[offset + 3],
+    ]);
+
+    binary % 10u32.pow(TOTP_DIGITS)
+}
+
+/// Ten single-use codes of 80 random bits each, grouped for reading off paper.
+pub fn generate_recovery_codes() -> Vec<String> {
+    (0..10)
+        .map(|_| {
+            let code = base32_encode(&rand::random::<[u8; 10]>()).to_lowercase();
+            format!(
+                "{}-{}-{}-{}",
+                &code[..4],
+                &code[4..8],
+                &code[8..12],
+                &code[12..]
+            )
+        })
+        .collect()
+}
+
+/// Recovery codes are stored hashed; they carry enough entropy that a plain SHA-256 holds up.
+/// Dashes and case are ignored so a code copied by hand still matches.
+pub fn hash_recovery_code(code: &str) -> String {
+    let normalized: String = code
+        .chars()
+        .filter(|c| c.is_ascii_alphanumeric())
+        .map(|c| c.to_ascii_lowercase())
+        .collect();
+
+    hex::encode(Sha256::digest(normalized.as_bytes()))
+}
+
+fn base32_encode(bytes: &[u8]) -> String {
+    let mut out = String::with_capacity(bytes.len().div_ceil(5) * 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for &byte in bytes {
+        buffer = (buffer << 8) | byte as u32;
+        bits += 8;
+
+        while bits >= 5 {
+            bits -= 5;
+            out.push(BASE32_ALPHABET[((buffer >> bits) & 0x1f) as usize] as char);
+        }
+    }
+
+    if bits > 0 {
+        out.push(BASE32_ALPHABET[((buffer << (5 - bits)) & 0x1f) as usize] as char);
+    }
+
+    out
+}
+
+fn base32_decode(value: &str) -> Option<Vec<u8>> {
+    let mut out = Vec::with_capacity(value.len() * 5 / 8);
+    let mut buffer: u32 = 0;
+    let mut bits = 0;
+
+    for c in value.chars().filter(|c| *c != '=' && !c.is_whitespace()) {
+        let index = BASE32_ALPHABET
+            .iter()
+            .position(|&a| a as char == c.to_ascii_uppercase())?;
+
+        buffer = (buffer << 5) | index as u32;
+        bits += 5;
+
+        if bits >= 8 {
+            bits -= 8;
+            out.push((buffer >> bits) as u8);
+        }
+    }
+
+    Some(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The SHA-1 seed from RFC 6238, appendix B.
+    const RFC_SECRET: &str = "GEZDGNBVGY3TQOJQGEZDGNBVGY3TQOJQ";
+
+    #[test]
+    fn matches_the_rfc_6238_vectors() {
+        let key = base32_decode(RFC_SECRET).unwrap();
+        assert_eq!(key, b"12345678901234567890");
+
+        assert_eq!(totp_code(&key, 59 / TOTP_STEP), 287082);
+        assert_eq!(totp_code(&key, 1111111109 / TOTP_STEP), 81804);
+        assert_eq!(totp_code(&key, 2000000000 / TOTP_STEP), 279037);
+    }
+
+    #[test]
+    fn accepts_a_neighbouring_step_once() {
+        let now = 1111111109;
+
+        let step = verify_totp(RFC_SECRET, "081804", now + TOTP_STEP, None).unwrap();
+        assert_eq!(step, now / TOTP_STEP);
+
+        assert_eq!(verify_totp(RFC_SECRET, "081804", now, Some(step)), None);
+        assert_eq!(verify_totp(RFC_SECRET, "81804", now, None), None);
+        assert_eq!(
+            verify_totp(RFC_SECRET, "081804", now + 3 * TOTP_STEP, None),
+            None
+        );
+    }
+
+    #[test]
+    fn secrets_round_trip_through_base32() {
+        let secret = generate_totp_secret();
+        assert_eq!(secret.len(), 32);
+        assert_eq!(base32_decode(&secret).map(|key| key.len()), Some(20));
+        assert_eq!(base32_encode(b"12345678901234567890"), RFC_SECRET);
+    }
+
+    #[test]
+    fn recovery_codes_hash_the_same_however_they_are_typed() {
+        let code = &generate_recovery_codes()[0];
+
+        assert_eq!(
+            hash_recovery_code(code),
+            hash_recovery_code(&code.replace('-', "").to_uppercase())
+        );
+    }
+}
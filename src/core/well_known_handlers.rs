@@ -0,0 +1,12 @@
+use axum::{Json, extract::State, http::header, response::IntoResponse};
+
+use crate::state::AppState;
+
+/// Public keys access tokens are verified with, in the standard JWK Set format rather than
+/// the usual envelope so off-the-shelf JWT libraries can read it. Empty under HS256.
+pub async fn jwks(State(state): State<AppState>) -> impl IntoResponse {
+    (
+        [(header::CACHE_CONTROL, "public, max-age=300")],
+        Json(state.jwt_keys.jwks()),
+    )
+}
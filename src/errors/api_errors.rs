@@ -1,8 +1,11 @@
 use axum::{
     body::Body,
+    extract::multipart::MultipartError,
     http::{Response, StatusCode},
     response::IntoResponse,
 };
+use std::time::Duration;
+
 use thiserror::Error;
 
 use crate::response::general_response::ErrorResponse;
@@ -35,30 +38,73 @@ pub enum ApiErrors {
 
     #[error("Error: {0}")]
     MethodNotAllowed(String),
+
+    #[error("Payload Too Large: {0}")]
+    PayloadTooLarge(String),
+
+    #[error("Unsupported Media Type: {0}")]
+    UnsupportedMediaType(String),
+
+    #[error("Service Unavailable: {0}")]
+    ServiceUnavailable(String),
+
+    /// Answered with a `Retry-After` header.
+    #[error("Too Many Requests: {message}")]
+    TooManyRequests {
+        message: String,
+        retry_after: Duration,
+    },
 }
 
 impl IntoResponse for ApiErrors {
     fn into_response(self) -> Response<Body> {
+        let mut retry_after = None;
+
         let (status, message) = match self {
             ApiErrors::Unauthorized(msg) => (StatusCode::UNAUTHORIZED, msg),
             ApiErrors::NotFound(msg) => (StatusCode::NOT_FOUND, msg),
-            ApiErrors::PasswordFail(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
+            ApiErrors::PasswordFail(msg) => (StatusCode::BAD_REQUEST, msg),
             ApiErrors::Conflict(msg) => (StatusCode::CONFLICT, msg),
             ApiErrors::InternalServerError(msg) => (StatusCode::INTERNAL_SERVER_ERROR, msg),
             ApiErrors::EmailValidation(msg) => (StatusCode::BAD_REQUEST, msg),
             ApiErrors::TextValidation(msg) => (StatusCode::BAD_REQUEST, msg),
             ApiErrors::BadRequest(msg) => (StatusCode::BAD_REQUEST, msg),
             ApiErrors::MethodNotAllowed(msg) => (StatusCode::METHOD_NOT_ALLOWED, msg),
+            ApiErrors::PayloadTooLarge(msg) => (StatusCode::PAYLOAD_TOO_LARGE, msg),
+            ApiErrors::UnsupportedMediaType(msg) => (StatusCode::UNSUPPORTED_MEDIA_TYPE, msg),
+            ApiErrors::ServiceUnavailable(msg) => (StatusCode::SERVICE_UNAVAILABLE, msg),
+            ApiErrors::TooManyRequests {
+                message,
+                retry_after: wait,
+            } => {
+                retry_after = Some(wait);
+                (StatusCode::TOO_MANY_REQUESTS, message)
+            }
         };
 
         let body = serde_json::to_string(&ErrorResponse { message })
             .unwrap_or_else(|_| "{\"message\":\"Internal error\"}".to_string());
 
-        // (status, body).into_response()
-        Response::builder()
+        let mut response = Response::builder()
             .status(status)
-            .header("Content-Type", "application/json")
-            .body(Body::from(body))
-            .unwrap()
+            .header("Content-Type", "application/json");
+
+        if let Some(wait) = retry_after {
+            // Whole seconds, rounded up so clients never retry early.
+            let seconds = wait.as_secs() + u64::from(wait.subsec_nanos() > 0);
+            response = response.header("Retry-After", seconds.max(1));
+        }
+
+        // (status, body).into_response()
+        response.body(Body::from(body)).unwrap()
+    }
+}
+
+impl From<MultipartError> for ApiErrors {
+    fn from(err: MultipartError) -> Self {
+        match err.status() {
+            StatusCode::PAYLOAD_TOO_LARGE => ApiErrors::PayloadTooLarge(err.body_text()),
+            _ => ApiErrors::BadRequest(format!("Invalid form: {}", err.body_text())),
+        }
     }
 }
@@ -1,5 +1,7 @@
+pub mod api_key_extractor;
 pub mod auth_extractor;
 pub mod blog_extractor;
+pub mod client_info_extractor;
 pub mod json_body;
 pub mod path_id_extractor;
 pub mod project_extractor;
@@ -0,0 +1,100 @@
+use axum::http::{Method, request::Parts};
+use tokio::sync::oneshot;
+
+use crate::{
+    api_key::{dto::ApiScope, messages::ApiKeyMessage},
+    auth::messages::AuthMessage,
+    errors::api_errors::ApiErrors,
+    extractor::auth_extractor::AuthUser,
+    state::AppState,
+};
+
+pub const API_KEY_HEADER: &str = "x-api-key";
+
+/// The scope an API key needs on a router, added to it as an `Extension`: `read` for GET,
+/// `write` for everything else. Routers without one turn API keys away.
+#[derive(Clone, Copy)]
+pub struct ApiKeyScopes {
+    pub read: ApiScope,
+    pub write: ApiScope,
+}
+
+impl ApiKeyScopes {
+    pub fn new(read: ApiScope, write: ApiScope) -> Self {
+        Self { read, write }
+    }
+
+    /// The same scope whatever the method.
+    pub fn only(scope: ApiScope) -> Self {
+        Self::new(scope, scope)
+    }
+
+    fn required(&self, method: &Method) -> ApiScope {
+        if method == Method::GET || method == Method::HEAD {
+            self.read
+        } else {
+            self.write
+        }
+    }
+}
+
+/// The admin behind the key in `X-Api-Key`, shaped like a login's [`AuthUser`] so handlers
+/// take either. The key has to carry the scope the route asks for.
+pub async fn api_key_user(
+    key: &str,
+    parts: &Parts,
+    state: &AppState,
+) -> Result<AuthUser, ApiErrors> {
+    let scopes = parts.extensions.get::<ApiKeyScopes>().ok_or_else(|| {
+        ApiErrors::Unauthorized("API keys are not accepted on this route".to_string())
+    })?;
+    let required = scopes.required(&parts.method);
+
+    let (tx, rx) = oneshot::channel();
+
+    state
+        .api_key_tx
+        .send(ApiKeyMessage::Authenticate {
+            key: key.to_string(),
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
+
+    let api_key = rx
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))??;
+
+    if !api_key.scopes.contains(&required) {
+        return Err(ApiErrors::Unauthorized(format!(
+            "API key lacks the {} scope",
+            required.as_str()
+        )));
+    }
+
+    let (tx, rx) = oneshot::channel();
+
+    state
+        .auth_tx
+        .send(AuthMessage::GetUser {
+            user_id: api_key.user_id,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
+
+    let user = rx
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))?
+        .map_err(|e| match e {
+            ApiErrors::NotFound(_) => ApiErrors::Unauthorized("Invalid API key".to_string()),
+            e => e,
+        })?;
+
+    Ok(AuthUser {
+        id: user.id,
+        email: user.email,
+        name: user.name,
+        roles: user.roles,
+    })
+}
@@ -1,4 +1,7 @@
-use axum::{extract::FromRequestParts, http::request::Parts};
+use axum::{
+    extract::FromRequestParts,
+    http::{header, request::Parts},
+};
 
 use axum_extra::{
     TypedHeader,
@@ -11,6 +14,7 @@ use uuid::Uuid;
 use crate::{
     core::jwt::validate_user_token,
     errors::api_errors::ApiErrors,
+    extractor::api_key_extractor::{API_KEY_HEADER, api_key_user},
     fields::{email::Email, roles::Roles, text::Text},
     state::AppState,
 };
@@ -23,6 +27,8 @@ pub struct AuthUser {
     pub roles: Roles,
 }
 
+/// The caller, from a bearer access token or, on routers that take them, an `X-Api-Key`.
+/// A request carrying both is refused rather than run as whichever one wins.
 impl FromRequestParts<AppState> for AuthUser {
     type Rejection = ApiErrors;
 
@@ -30,18 +36,33 @@ impl FromRequestParts<AppState> for AuthUser {
         parts: &mut Parts,
         state: &AppState,
     ) -> Result<Self, Self::Rejection> {
+        if let Some(key) = parts.headers.get(API_KEY_HEADER) {
+            if parts.headers.contains_key(header::AUTHORIZATION) {
+                return Err(ApiErrors::BadRequest(
+                    "Send either an API key or an Authorization header, not both".into(),
+                ));
+            }
+
+            let key = key
+                .to_str()
+                .map_err(|_| ApiErrors::Unauthorized("Invalid API key".into()))?
+                .to_string();
+
+            return api_key_user(&key, parts, state).await;
+        }
+
         let TypedHeader(Authorization(bearer)) =
             TypedHeader::<Authorization<Bearer>>::from_request_parts(parts, state)
                 .await
                 .map_err(|_| ApiErrors::Unauthorized("Missing token".into()))?;
 
-        let user = validate_user_token(bearer.token(), &state.jwt_secret, &state.auth_tx).await?;
-
-        Ok(AuthUser {
-            id: user.id,
-            email: user.email,
-            name: user.name,
-            roles: user.roles,
-        })
+        validate_user_token(
+            bearer.token(),
+            &state.jwt_keys,
+            &state.tokens,
+            &state.denylist,
+            &state.auth_tx,
+        )
+        .await
     }
 }
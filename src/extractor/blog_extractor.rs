@@ -3,10 +3,11 @@ use axum::{
     extract::{FromRequest, Request},
 };
 
+use uuid::Uuid;
+
 use crate::{
-    blog::dto::UpdateBlogRequest, core::image_core::base64_image_uploader_core,
-    errors::api_errors::ApiErrors, payload_handler::blog_payload_handler::BlogCreateRequest,
-    state::AppState,
+    blog::dto::UpdateBlogRequest, errors::api_errors::ApiErrors,
+    payload_handler::blog_payload_handler::BlogCreateRequest, state::AppState,
 };
 
 #[derive(Debug)]
@@ -15,8 +16,9 @@ pub struct BlogCreateInput {
     pub description: String,
     pub content: String,
     pub word_count: i32,
-    pub image: String,
-    pub image_id: String,
+    /// Base64 image to upload inline; exclusive with `media_id`.
+    pub image: Option<String>,
+    pub media_id: Option<Uuid>,
 }
 
 impl FromRequest<AppState> for BlogCreateInput {
@@ -29,15 +31,13 @@ impl FromRequest<AppState> for BlogCreateInput {
 
         let payload_data = payload.validate()?;
 
-        let image = base64_image_uploader_core(payload_data.image, &state.image_tx).await?;
-
         Ok(BlogCreateInput {
             title: payload_data.title,
             description: payload_data.description,
             content: payload_data.content,
             word_count: payload_data.word_count,
-            image: image.url,
-            image_id: image.public_id,
+            image: payload_data.image,
+            media_id: payload_data.media_id,
         })
     }
 }
@@ -48,7 +48,7 @@ pub struct BlogUpateInput {
     pub content: Option<String>,
     pub word_count: Option<i32>,
     pub image: Option<String>,
-    pub image_id: Option<String>,
+    pub media_id: Option<Uuid>,
 }
 
 impl FromRequest<AppState> for BlogUpateInput {
@@ -59,24 +59,13 @@ impl FromRequest<AppState> for BlogUpateInput {
             .await
             .map_err(|_| ApiErrors::BadRequest("Invalid request body".into()))?;
 
-        let image_data = if let Some(base64) = payload.image {
-            Some(base64_image_uploader_core(base64, &state.image_tx).await?)
-        } else {
-            None
-        };
-
-        let (image, image_id) = match image_data {
-            Some(upload) => (Some(upload.url), Some(upload.public_id)),
-            None => (None, None),
-        };
-
         Ok(BlogUpateInput {
             title: payload.title,
             description: payload.description,
             content: payload.content,
             word_count: payload.word_count,
-            image,
-            image_id,
+            image: payload.image,
+            media_id: payload.media_id,
         })
     }
 }
@@ -0,0 +1,64 @@
+use std::{
+    convert::Infallible,
+    net::{IpAddr, SocketAddr},
+};
+
+use axum::{
+    extract::{ConnectInfo, FromRequestParts},
+    http::{Extensions, HeaderMap, header, request::Parts},
+};
+
+use crate::state::AppState;
+
+/// Longest user agent kept for a session; anything past it is noise.
+const MAX_USER_AGENT_LEN: usize = 512;
+
+/// Where a request came from and with what. `ip` is the last `X-Forwarded-For` hop when the
+/// app is configured to trust it, otherwise the peer address.
+#[derive(Clone, Debug, Default)]
+pub struct ClientInfo {
+    pub ip: Option<IpAddr>,
+    pub user_agent: Option<String>,
+}
+
+impl FromRequestParts<AppState> for ClientInfo {
+    type Rejection = Infallible;
+
+    async fn from_request_parts(
+        parts: &mut Parts,
+        state: &AppState,
+    ) -> Result<Self, Self::Rejection> {
+        let user_agent = parts
+            .headers
+            .get(header::USER_AGENT)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.chars().take(MAX_USER_AGENT_LEN).collect());
+
+        Ok(ClientInfo {
+            ip: client_ip(&parts.headers, &parts.extensions, state.trust_forwarded_for),
+            user_agent,
+        })
+    }
+}
+
+/// Only the rightmost `X-Forwarded-For` entry was added by our proxy; everything to its left
+/// came from the client and may be made up.
+pub fn client_ip(
+    headers: &HeaderMap,
+    extensions: &Extensions,
+    trust_forwarded_for: bool,
+) -> Option<IpAddr> {
+    let forwarded = headers
+        .get("x-forwarded-for")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.rsplit(',').next())
+        .and_then(|hop| hop.trim().parse().ok());
+
+    if trust_forwarded_for && forwarded.is_some() {
+        return forwarded;
+    }
+
+    extensions
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(addr)| addr.ip())
+}
@@ -4,13 +4,12 @@ use axum::{
 };
 
 use chrono::NaiveDate;
+use uuid::Uuid;
 
 use crate::{
-    core::{image_core::base64_image_uploader_core, stack_identifier_core::ensure_stack_exists},
-    errors::api_errors::ApiErrors,
+    core::stack_identifier_core::ensure_stack_exists, errors::api_errors::ApiErrors,
     payload_handler::project_payload_handler::ProjectCreateRequest,
-    project::dto::UpdateProjectRequest,
-    state::AppState,
+    project::dto::UpdateProjectRequest, state::AppState,
 };
 
 pub struct ProjectCreateInput {
@@ -25,8 +24,9 @@ pub struct ProjectCreateInput {
     pub stack: String,
     pub content: String,
     pub word_count: i32,
-    pub image: String,
-    pub image_id: String,
+    /// Base64 image to upload inline; exclusive with `media_id`.
+    pub image: Option<String>,
+    pub media_id: Option<Uuid>,
 }
 
 impl FromRequest<AppState> for ProjectCreateInput {
@@ -41,8 +41,6 @@ impl FromRequest<AppState> for ProjectCreateInput {
 
         ensure_stack_exists(payload_data.stack.clone(), &state.stack_tx).await?;
 
-        let image = base64_image_uploader_core(payload_data.image, &state.image_tx).await?;
-
         Ok(ProjectCreateInput {
             title: payload_data.title,
             description: payload_data.description,
@@ -55,8 +53,8 @@ impl FromRequest<AppState> for ProjectCreateInput {
             stack: payload_data.stack,
             content: payload_data.content,
             word_count: payload_data.word_count,
-            image: image.url,
-            image_id: image.public_id,
+            image: payload_data.image,
+            media_id: payload_data.media_id,
         })
     }
 }
@@ -74,7 +72,7 @@ pub struct ProjectUpateInput {
     pub content: Option<String>,
     pub word_count: Option<i32>,
     pub image: Option<String>,
-    pub image_id: Option<String>,
+    pub media_id: Option<Uuid>,
 }
 
 impl FromRequest<AppState> for ProjectUpateInput {
@@ -89,17 +87,6 @@ impl FromRequest<AppState> for ProjectUpateInput {
             ensure_stack_exists(title, &state.stack_tx).await?;
         }
 
-        let image_data = if let Some(base64) = payload.image {
-            Some(base64_image_uploader_core(base64, &state.image_tx).await?)
-        } else {
-            None
-        };
-
-        let (image, image_id) = match image_data {
-            Some(upload) => (Some(upload.url), Some(upload.public_id)),
-            None => (None, None),
-        };
-
         Ok(ProjectUpateInput {
             description: payload.description,
             company: payload.company,
@@ -111,8 +98,8 @@ impl FromRequest<AppState> for ProjectUpateInput {
             stack: payload.stack,
             content: payload.content,
             word_count: payload.word_count,
-            image,
-            image_id,
+            image: payload.image,
+            media_id: payload.media_id,
         })
     }
 }
@@ -0,0 +1,23 @@
+use axum::{Json, extract::State};
+use serde_json::json;
+
+use crate::{image::circuit_breaker::CircuitState, state::AppState};
+
+/// Liveness plus the state of upstream dependencies; answers without touching any actor.
+pub async fn health(State(state): State<AppState>) -> Json<serde_json::Value> {
+    let image_circuit = state
+        .image_circuit
+        .as_ref()
+        .map(|breaker| breaker.snapshot());
+
+    let degraded = image_circuit
+        .as_ref()
+        .is_some_and(|snapshot| snapshot.state == CircuitState::Open);
+
+    Json(json!({
+        "status": if degraded { "degraded" } else { "ok" },
+        "image_store": {
+            "circuit": image_circuit,
+        },
+    }))
+}
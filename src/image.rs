@@ -1,5 +1,14 @@
 pub mod actor;
+pub mod circuit_breaker;
 pub mod dispatcher;
 pub mod dto;
 pub mod handlers;
 pub mod messages;
+pub mod reconcile;
+pub mod spool;
+pub mod store;
+pub mod store_cloudinary;
+pub mod store_local;
+pub mod store_s3;
+pub mod validation;
+pub mod variants;
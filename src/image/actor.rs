@@ -1,104 +1,142 @@
 use chrono::Utc;
-use reqwest::{Client, multipart};
-use sha1::{Digest, Sha1};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
 use tokio::sync::mpsc;
+use uuid::Uuid;
 
 use crate::{
     errors::api_errors::ApiErrors,
     image::{
         dispatcher::image_dispatcher,
-        dto::CloudinaryResponse,
-        messages::{ImageMessage, ImageUploadResult},
+        dto::ImageUpload,
+        messages::{ImageMessage, ImageUploadResult, IssuedTicket},
+        store::{ImageStore, UPLOAD_TICKET_TTL},
     },
 };
 
+type HmacSha256 = Hmac<Sha256>;
+
+/// Tickets are confirmed with a signature generated at startup, so unconfirmed uploads do not
+/// survive a restart.
 pub struct ImageActor {
-    client: Client,
-    cloud_name: String,
-    api_key: String,
-    api_secret: String,
+    store: Box<dyn ImageStore>,
+    confirm_key: [u8; 32],
 }
 
 impl ImageActor {
-    pub fn new(cloud_name: String, api_key: String, api_secret: String) -> Self {
+    pub fn new(store: Box<dyn ImageStore>) -> Self {
         Self {
-            client: Client::new(),
-            cloud_name,
-            api_key,
-            api_secret,
+            store,
+            confirm_key: rand::random(),
         }
     }
 
+    fn confirm_mac(&self, public_id: &str, owner: Uuid, expires: i64) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.confirm_key).expect("HMAC accepts keys of any length");
+        mac.update(format!("{public_id}:{owner}:{expires}").as_bytes());
+        mac
+    }
+
     pub async fn run(self, rx: mpsc::Receiver<ImageMessage>) {
         image_dispatcher(&self, rx).await;
     }
 
-    // pub async fn upload(&self, file: String) -> Result<ImageUploadResult, String> {
-
-    //     let url = format!(
-    //         "https://api.cloudinary.com/v1_1/{}/image/upload",
-    //         self.cloud_name
-    //     );
+    pub async fn upload(&self, image: ImageUpload) -> Result<ImageUploadResult, ApiErrors> {
+        self.store.upload(image).await
+    }
 
-    //     let res = self
-    //         .client
-    //         .post(url)
-    //         .basic_auth(&self.api_key, Some(&self.api_secret))
-    //         .form(&[("file", file)])
-    //         .send()
-    //         .await
-    //         .map_err(|_| "Upload failed")?;
+    pub async fn delete(&self, public_id: &str) -> Result<(), ApiErrors> {
+        self.store.delete(public_id).await
+    }
 
-    //     let body: CloudinaryResponse = res.json().await.map_err(|_| "Invalid response")?;
+    pub async fn list(&self) -> Result<Vec<String>, ApiErrors> {
+        self.store.list().await
+    }
 
-    //     Ok(ImageUploadResult {
-    //         url: body.secure_url,
-    //         public_id: body.public_id,
-    //     })
-    // }
+    /// A store ticket plus a confirmation for `owner`, which stays valid one ticket lifetime
+    /// past the ticket so a late finishing upload can still be confirmed.
+    pub async fn issue_ticket(
+        &self,
+        content_type: &str,
+        owner: Uuid,
+    ) -> Result<IssuedTicket, ApiErrors> {
+        let ticket = self.store.upload_ticket(content_type).await?;
+
+        let expires = (ticket.expires_at + UPLOAD_TICKET_TTL).timestamp();
+        let signature = hex::encode(
+            self.confirm_mac(&ticket.public_id, owner, expires)
+                .finalize()
+                .into_bytes(),
+        );
 
-    pub async fn upload(&self, file: String) -> Result<ImageUploadResult, ApiErrors> {
-        let timestamp = Utc::now().timestamp();
+        Ok(IssuedTicket {
+            ticket,
+            confirmation: format!("{expires}.{signature}"),
+        })
+    }
 
-        let mut hasher = Sha1::new();
-        hasher.update(format!("timestamp={}{}", timestamp, self.api_secret));
-        let signature = format!("{:x}", hasher.finalize());
+    pub fn verify_confirmation(
+        &self,
+        public_id: &str,
+        owner: Uuid,
+        confirmation: &str,
+    ) -> Result<(), ApiErrors> {
+        let invalid = || ApiErrors::Unauthorized("Invalid upload confirmation".to_string());
+
+        let (expires, signature) = confirmation.split_once('.').ok_or_else(invalid)?;
+        let expires: i64 = expires.parse().map_err(|_| invalid())?;
+        let signature = hex::decode(signature).map_err(|_| invalid())?;
+
+        self.confirm_mac(public_id, owner, expires)
+            .verify_slice(&signature)
+            .map_err(|_| invalid())?;
+
+        if expires < Utc::now().timestamp() {
+            return Err(ApiErrors::Unauthorized(
+                "Upload confirmation expired".to_string(),
+            ));
+        }
 
-        let url = format!(
-            "https://api.cloudinary.com/v1_1/{}/image/upload",
-            self.cloud_name
-        );
+        Ok(())
+    }
 
-        let form = multipart::Form::new()
-            .text("file", file)
-            .text("api_key", self.api_key.clone())
-            .text("timestamp", timestamp.to_string())
-            .text("signature", signature);
-
-        let res = self
-            .client
-            .post(url)
-            .basic_auth(&self.api_key, Some(&self.api_secret))
-            .multipart(form)
-            .send()
+    pub async fn verify_ticket(
+        &self,
+        public_id: &str,
+        expires: i64,
+        signature: &str,
+    ) -> Result<(), ApiErrors> {
+        self.store
+            .verify_ticket(public_id, expires, signature)
             .await
-            .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+    }
 
-        println!("image upload: {res:?}");
+    pub async fn receive(
+        &self,
+        public_id: &str,
+        expires: i64,
+        signature: &str,
+        image: ImageUpload,
+    ) -> Result<(), ApiErrors> {
+        self.store
+            .receive(public_id, expires, signature, image)
+            .await
+    }
 
-        if !res.status().is_success() {
-            return Err(ApiErrors::InternalServerError(
-                "Cloudinary rejected upload".to_string(),
-            ));
-        }
+    pub async fn fetch(&self, public_id: &str, max_bytes: usize) -> Result<Vec<u8>, ApiErrors> {
+        self.store.fetch(public_id, max_bytes).await
+    }
 
-        let body: CloudinaryResponse = res.json().await.map_err(|_| {
-            ApiErrors::InternalServerError("Invalid Cloudinary response".to_string())
-        })?;
+    pub async fn register(
+        &self,
+        public_id: &str,
+        image: ImageUpload,
+    ) -> Result<ImageUploadResult, ApiErrors> {
+        self.store.register(public_id, &image).await
+    }
 
-        Ok(ImageUploadResult {
-            url: body.secure_url,
-            public_id: body.public_id,
-        })
+    pub async fn tag(&self, public_id: &str, tag: &str, attach: bool) -> Result<(), ApiErrors> {
+        self.store.tag(public_id, tag, attach).await
     }
 }
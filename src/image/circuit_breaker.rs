@@ -0,0 +1,153 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde::Serialize;
+
+use crate::errors::api_errors::ApiErrors;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    Closed,
+    Open,
+    /// The cool-down has elapsed and calls are let through to probe the backend again.
+    HalfOpen,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct CircuitSnapshot {
+    pub name: &'static str,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+}
+
+struct Inner {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+}
+
+/// Trips after `failure_threshold` consecutive failures and rejects calls for `open_for`.
+pub struct CircuitBreaker {
+    name: &'static str,
+    failure_threshold: u32,
+    open_for: Duration,
+    inner: Mutex<Inner>,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: &'static str, failure_threshold: u32, open_for: Duration) -> Self {
+        Self {
+            name,
+            failure_threshold: failure_threshold.max(1),
+            open_for,
+            inner: Mutex::new(Inner {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+            }),
+        }
+    }
+
+    /// Fails fast with `ServiceUnavailable` while the circuit is open.
+    pub fn check(&self) -> Result<(), ApiErrors> {
+        let mut inner = self.inner.lock().unwrap();
+
+        if inner.state == CircuitState::Open {
+            let cooled_down = inner
+                .opened_at
+                .is_none_or(|opened_at| opened_at.elapsed() >= self.open_for);
+
+            if !cooled_down {
+                return Err(ApiErrors::ServiceUnavailable(format!(
+                    "{} is unavailable, try again later",
+                    self.name
+                )));
+            }
+
+            inner.state = CircuitState::HalfOpen;
+        }
+
+        Ok(())
+    }
+
+    pub fn record_success(&self) {
+        let mut inner = self.inner.lock().unwrap();
+
+        inner.state = CircuitState::Closed;
+        inner.consecutive_failures = 0;
+        inner.opened_at = None;
+    }
+
+    pub fn record_failure(&self) {
+        let mut inner = self.inner.lock().unwrap();
+
+        inner.consecutive_failures += 1;
+
+        // A failed probe re-opens the circuit straight away.
+        if inner.state == CircuitState::HalfOpen
+            || inner.consecutive_failures >= self.failure_threshold
+        {
+            if inner.state != CircuitState::Open {
+                println!(
+                    "{} circuit opened after {} consecutive failures",
+                    self.name, inner.consecutive_failures
+                );
+            }
+
+            inner.state = CircuitState::Open;
+            inner.opened_at = Some(Instant::now());
+        }
+    }
+
+    pub fn snapshot(&self) -> CircuitSnapshot {
+        let inner = self.inner.lock().unwrap();
+
+        CircuitSnapshot {
+            name: self.name,
+            state: inner.state,
+            consecutive_failures: inner.consecutive_failures,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn opens_after_threshold_and_rejects_calls() {
+        let breaker = CircuitBreaker::new("store", 2, Duration::from_secs(60));
+
+        breaker.record_failure();
+        assert!(breaker.check().is_ok());
+
+        breaker.record_failure();
+        assert_eq!(breaker.snapshot().state, CircuitState::Open);
+        assert!(matches!(
+            breaker.check(),
+            Err(ApiErrors::ServiceUnavailable(_))
+        ));
+    }
+
+    #[test]
+    fn half_open_probe_closes_or_reopens() {
+        let breaker = CircuitBreaker::new("store", 1, Duration::ZERO);
+
+        breaker.record_failure();
+        assert!(breaker.check().is_ok());
+        assert_eq!(breaker.snapshot().state, CircuitState::HalfOpen);
+
+        breaker.record_failure();
+        assert_eq!(breaker.snapshot().state, CircuitState::Open);
+
+        assert!(breaker.check().is_ok());
+        breaker.record_success();
+
+        let snapshot = breaker.snapshot();
+        assert_eq!(snapshot.state, CircuitState::Closed);
+        assert_eq!(snapshot.consecutive_failures, 0);
+    }
+}
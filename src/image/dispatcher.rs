@@ -1,4 +1,3 @@
-use base64::{Engine as _, engine::general_purpose};
 use tokio::sync::mpsc;
 
 use crate::image::{actor::ImageActor, messages::ImageMessage};
@@ -6,17 +5,81 @@ use crate::image::{actor::ImageActor, messages::ImageMessage};
 pub async fn image_dispatcher(actor: &ImageActor, mut rx: mpsc::Receiver<ImageMessage>) {
     while let Some(msg) = rx.recv().await {
         match msg {
-            ImageMessage::UploadBase64 { base64, respond_to } => {
-                let res = actor.upload(base64).await;
-                let _ = respond_to.send(res);
+            ImageMessage::Upload { image, respond_to } => {
+                let _ = respond_to.send(actor.upload(image).await);
             }
 
-            ImageMessage::UploadBytes { bytes, respond_to } => {
-                let encoded = general_purpose::STANDARD.encode(&bytes);
-                let base64 = format!("data:image/png;base64,{encoded}");
+            ImageMessage::Delete {
+                public_id,
+                respond_to,
+            } => {
+                let _ = respond_to.send(actor.delete(&public_id).await);
+            }
+
+            ImageMessage::ListAssets { respond_to } => {
+                let _ = respond_to.send(actor.list().await);
+            }
+
+            ImageMessage::IssueTicket {
+                content_type,
+                owner,
+                respond_to,
+            } => {
+                let _ = respond_to.send(actor.issue_ticket(&content_type, owner).await);
+            }
+
+            ImageMessage::VerifyConfirmation {
+                public_id,
+                owner,
+                confirmation,
+                respond_to,
+            } => {
+                let _ =
+                    respond_to.send(actor.verify_confirmation(&public_id, owner, &confirmation));
+            }
 
-                let res = actor.upload(base64).await;
-                let _ = respond_to.send(res);
+            ImageMessage::VerifyTicket {
+                public_id,
+                expires,
+                signature,
+                respond_to,
+            } => {
+                let _ = respond_to.send(actor.verify_ticket(&public_id, expires, &signature).await);
+            }
+
+            ImageMessage::Receive {
+                public_id,
+                expires,
+                signature,
+                image,
+                respond_to,
+            } => {
+                let _ =
+                    respond_to.send(actor.receive(&public_id, expires, &signature, image).await);
+            }
+
+            ImageMessage::Fetch {
+                public_id,
+                max_bytes,
+                respond_to,
+            } => {
+                let _ = respond_to.send(actor.fetch(&public_id, max_bytes).await);
+            }
+
+            ImageMessage::Register {
+                public_id,
+                image,
+                respond_to,
+            } => {
+                let _ = respond_to.send(actor.register(&public_id, image).await);
+            }
+            ImageMessage::Tag {
+                public_id,
+                tag,
+                attach,
+                respond_to,
+            } => {
+                let _ = respond_to.send(actor.tag(&public_id, &tag, attach).await);
             }
         }
     }
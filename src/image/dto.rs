@@ -1,12 +1,215 @@
+use std::path::Path;
+
+use base64::{Engine as _, engine::general_purpose};
 use serde::Deserialize;
+use sha2::{Digest, Sha256};
+
+use crate::{
+    config::ImageLimits,
+    errors::api_errors::ApiErrors,
+    image::{
+        spool::SpooledFile,
+        validation::{validate_head, validate_image},
+    },
+    media::dto::MediaEntity,
+};
 
 #[derive(Deserialize)]
 pub struct CloudinaryResponse {
     pub secure_url: String,
     pub public_id: String,
+    #[serde(default)]
+    pub eager: Vec<CloudinaryEager>,
+}
+
+#[derive(Deserialize)]
+pub struct CloudinaryEager {
+    pub secure_url: String,
+    pub width: u32,
+}
+
+#[derive(Deserialize)]
+pub struct CloudinaryDestroyResponse {
+    pub result: String,
+}
+
+#[derive(Deserialize)]
+pub struct CloudinaryResource {
+    pub public_id: String,
+}
+
+#[derive(Deserialize)]
+pub struct CloudinaryAssetDetails {
+    pub secure_url: String,
+    pub bytes: u64,
+}
+
+#[derive(Deserialize)]
+pub struct CloudinaryResourcesResponse {
+    pub resources: Vec<CloudinaryResource>,
+    pub next_cursor: Option<String>,
 }
 
 #[derive(Deserialize)]
 pub struct Base64Upload {
     pub image: String,
 }
+
+#[derive(Deserialize)]
+pub struct UploadTicketRequest {
+    pub content_type: String,
+}
+
+#[derive(Deserialize)]
+pub struct ConfirmUploadRequest {
+    pub public_id: String,
+    /// The `confirmation` of the ticket `public_id` came from.
+    pub confirmation: String,
+    pub alt: Option<String>,
+    pub caption: Option<String>,
+}
+
+/// Query string of a ticketed `PUT`.
+#[derive(Deserialize)]
+pub struct DirectUploadQuery {
+    pub expires: i64,
+    pub signature: String,
+}
+
+/// Where an upload's bytes live: decoded base64 in memory, multipart files spooled to disk.
+pub enum ImageBody {
+    Memory(Vec<u8>),
+    Spooled(SpooledFile),
+}
+
+pub struct ImageUpload {
+    pub body: ImageBody,
+    pub content_type: String,
+    pub dimensions: Option<(u32, u32)>,
+    pub byte_size: u64,
+    /// Hex SHA-256 of the bytes.
+    pub content_hash: String,
+    /// What the image was uploaded for, if anything in particular; picks the storage folder.
+    pub entity: Option<MediaEntity>,
+}
+
+impl ImageUpload {
+    /// Accepts either a `data:image/<type>;base64,<payload>` URL or a bare base64 string; the
+    /// content type is always sniffed from the decoded bytes.
+    pub fn from_base64(value: &str, limits: &ImageLimits) -> Result<Self, ApiErrors> {
+        let (declared, payload) = match value.strip_prefix("data:") {
+            Some(rest) => {
+                let (meta, payload) = rest
+                    .split_once(',')
+                    .ok_or_else(|| ApiErrors::BadRequest("Invalid image data URL".to_string()))?;
+
+                let declared = meta.strip_suffix(";base64").ok_or_else(|| {
+                    ApiErrors::BadRequest("Image must be base64 encoded".to_string())
+                })?;
+
+                if !declared.starts_with("image/") || declared.contains(';') {
+                    return Err(ApiErrors::UnsupportedMediaType(format!(
+                        "Unsupported data URL type: {declared}"
+                    )));
+                }
+
+                (Some(declared), payload)
+            }
+            None => (None, value),
+        };
+
+        let payload = payload.trim();
+
+        // Refuse before decoding: base64 inflates the payload by a third.
+        if payload.len() / 4 * 3 > limits.max_bytes + 3 {
+            return Err(ApiErrors::PayloadTooLarge(format!(
+                "Image exceeds the {} byte limit",
+                limits.max_bytes
+            )));
+        }
+
+        let bytes = general_purpose::STANDARD
+            .decode(payload)
+            .map_err(|_| ApiErrors::BadRequest("Invalid base64 image".to_string()))?;
+
+        Self::from_bytes(bytes, declared, limits)
+    }
+
+    pub fn from_bytes(
+        bytes: Vec<u8>,
+        declared: Option<&str>,
+        limits: &ImageLimits,
+    ) -> Result<Self, ApiErrors> {
+        let validated = validate_image(&bytes, declared, limits)?;
+
+        Ok(Self {
+            content_type: validated.content_type.to_string(),
+            dimensions: validated.dimensions,
+            byte_size: bytes.len() as u64,
+            content_hash: hex::encode(Sha256::digest(&bytes)),
+            body: ImageBody::Memory(bytes),
+            entity: None,
+        })
+    }
+
+    /// Validates a streamed upload from its leading bytes without reading it back, except for
+    /// SVG whose whole text has to be checked for scripts.
+    pub async fn from_spooled(
+        file: SpooledFile,
+        declared: Option<&str>,
+        limits: &ImageLimits,
+    ) -> Result<Self, ApiErrors> {
+        let validated = validate_head(file.head(), file.len(), declared, limits)?;
+
+        if validated.content_type == "image/svg+xml" {
+            let bytes = tokio::fs::read(file.path())
+                .await
+                .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+            return Self::from_bytes(bytes, declared, limits);
+        }
+
+        Ok(Self {
+            content_type: validated.content_type.to_string(),
+            dimensions: validated.dimensions,
+            byte_size: file.len(),
+            content_hash: file.content_hash().to_string(),
+            body: ImageBody::Spooled(file),
+            entity: None,
+        })
+    }
+
+    /// A request body carrying the image, streamed from disk when it was spooled.
+    pub fn body(&self) -> Result<reqwest::Body, ApiErrors> {
+        match &self.body {
+            ImageBody::Memory(bytes) => Ok(reqwest::Body::from(bytes.clone())),
+            ImageBody::Spooled(file) => file.body(),
+        }
+    }
+
+    pub async fn save_to(&self, path: &Path) -> Result<(), ApiErrors> {
+        let result = match &self.body {
+            ImageBody::Memory(bytes) => tokio::fs::write(path, bytes).await,
+            ImageBody::Spooled(file) => tokio::fs::copy(file.path(), path).await.map(|_| ()),
+        };
+
+        result.map_err(|e| ApiErrors::InternalServerError(e.to_string()))
+    }
+
+    pub fn extension(&self) -> &'static str {
+        extension_for(&self.content_type).unwrap_or("png")
+    }
+}
+
+/// File extension for an accepted image content type.
+pub fn extension_for(content_type: &str) -> Option<&'static str> {
+    match content_type {
+        "image/png" => Some("png"),
+        "image/jpeg" | "image/jpg" => Some("jpg"),
+        "image/webp" => Some("webp"),
+        "image/gif" => Some("gif"),
+        "image/svg+xml" => Some("svg"),
+        "image/avif" => Some("avif"),
+        _ => None,
+    }
+}
@@ -1,67 +1,179 @@
 use axum::{
     Json,
-    extract::{Multipart, State},
+    body::Body,
+    extract::{Multipart, Query, State},
+    http::{HeaderMap, header},
 };
-use tokio::sync::oneshot;
 
 use crate::{
+    core::{
+        image_core::{issue_ticket_core, receive_image_core, verify_ticket_core},
+        media_core::{confirm_upload_core, upload_media_core},
+    },
     errors::api_errors::ApiErrors,
-    extractor::json_body::RequiredJson,
-    image::{dto::Base64Upload, messages::ImageMessage},
+    extractor::{auth_extractor::AuthUser, json_body::RequiredJson, path_id_extractor::PathParam},
+    image::{
+        dto::{
+            Base64Upload, ConfirmUploadRequest, DirectUploadQuery, ImageUpload, UploadTicketRequest,
+        },
+        messages::IssuedTicket,
+        spool::spool_body,
+    },
+    media::{
+        dto::{ConfirmedUpload, MediaSource, MediaUpload, Uploader},
+        messages::MediaResponse,
+    },
     state::AppState,
 };
 
 pub async fn upload_base64(
+    user: AuthUser,
     State(state): State<AppState>,
     RequiredJson(payload): RequiredJson<Base64Upload>,
 ) -> Result<Json<serde_json::Value>, ApiErrors> {
-    let (tx, rx) = oneshot::channel();
-
-    state
-        .image_tx
-        .send(ImageMessage::UploadBase64 {
-            base64: payload.image,
-            respond_to: tx,
-        })
-        .await
-        .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
-
-    let result = rx
-        .await
-        .map_err(|_| ApiErrors::InternalServerError("Upload failed".to_string()))??;
-
-    Ok(Json(serde_json::json!(result)))
+    let media = upload_media_core(
+        library_upload(MediaSource::Base64(payload.image), &user),
+        &state.media_tx,
+    )
+    .await?;
+
+    Ok(Json(upload_result(media)))
 }
 
 pub async fn upload_form(
+    user: AuthUser,
     State(state): State<AppState>,
     mut multipart: Multipart,
 ) -> Result<Json<serde_json::Value>, ApiErrors> {
     let field = multipart
         .next_field()
-        .await
-        .map_err(|_| ApiErrors::InternalServerError("Invalid form".to_string()))?
+        .await?
         .ok_or_else(|| ApiErrors::BadRequest("No file provided".to_string()))?;
 
-    let bytes = field
-        .bytes()
-        .await
-        .map_err(|_| ApiErrors::InternalServerError("Failed to read file".to_string()))?;
+    let source = MediaSource::from_field(field, &state.image_limits).await?;
+
+    let media = upload_media_core(library_upload(source, &user), &state.media_tx).await?;
+
+    Ok(Json(upload_result(media)))
+}
+
+/// Stores every file field of the form, in order. All files are streamed to disk and checked
+/// before the first one is stored.
+pub async fn upload_files(
+    user: AuthUser,
+    State(state): State<AppState>,
+    mut multipart: Multipart,
+) -> Result<Json<Vec<serde_json::Value>>, ApiErrors> {
+    let mut sources = Vec::new();
+
+    while let Some(field) = multipart.next_field().await? {
+        sources.push(MediaSource::from_field(field, &state.image_limits).await?);
+    }
+
+    if sources.is_empty() {
+        return Err(ApiErrors::BadRequest("No file provided".to_string()));
+    }
+
+    let mut results = Vec::new();
+
+    for source in sources {
+        let media = upload_media_core(library_upload(source, &user), &state.media_tx).await?;
+        results.push(upload_result(media));
+    }
+
+    Ok(Json(results))
+}
+
+/// Signed instructions for uploading one file straight to the image store.
+pub async fn issue_ticket(
+    AuthUser { id, .. }: AuthUser,
+    State(state): State<AppState>,
+    RequiredJson(payload): RequiredJson<UploadTicketRequest>,
+) -> Result<Json<IssuedTicket>, ApiErrors> {
+    let ticket = issue_ticket_core(payload.content_type, id, &state.image_tx).await?;
+
+    Ok(Json(ticket))
+}
+
+/// Adds a file uploaded with a ticket to the media library.
+pub async fn confirm_upload(
+    AuthUser {
+        id, email, name, ..
+    }: AuthUser,
+    State(state): State<AppState>,
+    RequiredJson(payload): RequiredJson<ConfirmUploadRequest>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let upload = ConfirmedUpload {
+        public_id: payload.public_id,
+        confirmation: payload.confirmation,
+        alt: payload.alt,
+        caption: payload.caption,
+        uploaded_by: Uploader { id, name, email },
+    };
 
-    let (tx, rx) = oneshot::channel();
+    let media = confirm_upload_core(upload, &state.media_tx).await?;
 
-    state
-        .image_tx
-        .send(ImageMessage::UploadBytes {
-            bytes: bytes.to_vec(),
-            respond_to: tx,
-        })
-        .await
-        .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
+    Ok(Json(upload_result(media)))
+}
 
-    let result = rx
-        .await
-        .map_err(|_| ApiErrors::InternalServerError("Upload failed".to_string()))??;
+/// Target of local store tickets; the signature in the query string stands in for a login.
+pub async fn receive_direct(
+    State(state): State<AppState>,
+    PathParam(public_id): PathParam<String>,
+    Query(query): Query<DirectUploadQuery>,
+    headers: HeaderMap,
+    body: Body,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let content_type = headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .filter(|content_type| content_type.starts_with("image/"));
+
+    // Refuse forged or spent tickets before reading the body.
+    verify_ticket_core(
+        public_id.clone(),
+        query.expires,
+        query.signature.clone(),
+        &state.image_tx,
+    )
+    .await?;
+
+    let file = spool_body(body, &state.image_limits).await?;
+    let image = ImageUpload::from_spooled(file, content_type, &state.image_limits).await?;
+
+    receive_image_core(
+        public_id.clone(),
+        query.expires,
+        query.signature,
+        image,
+        &state.image_tx,
+    )
+    .await?;
+
+    Ok(Json(serde_json::json!({ "public_id": public_id })))
+}
+
+/// Uploads land in the media library like any other, credited to whoever sent them.
+fn library_upload(source: MediaSource, user: &AuthUser) -> MediaUpload {
+    MediaUpload {
+        source,
+        entity: None,
+        alt: None,
+        caption: None,
+        retain: true,
+        uploaded_by: Some(Uploader {
+            id: user.id,
+            name: user.name.clone(),
+            email: user.email.clone(),
+        }),
+    }
+}
 
-    Ok(Json(serde_json::json!(result)))
+fn upload_result(media: MediaResponse) -> serde_json::Value {
+    serde_json::json!({
+        "url": media.url,
+        "public_id": media.public_id,
+        "variants": media.variants,
+        "media_id": media.id,
+    })
 }
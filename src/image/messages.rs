@@ -1,21 +1,108 @@
-use serde::Serialize;
+use std::collections::BTreeMap;
+
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
 use tokio::sync::oneshot;
 
-use crate::errors::api_errors::ApiErrors;
+use uuid::Uuid;
+
+use crate::{errors::api_errors::ApiErrors, image::dto::ImageUpload};
 
 #[derive(Debug, Serialize)]
 pub struct ImageUploadResult {
     pub url: String,
     pub public_id: String,
+    pub variants: Vec<ImageVariant>,
+}
+
+/// One resized rendition of an image; `url` and `width` map directly onto a `srcset` entry.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ImageVariant {
+    pub name: String,
+    pub format: String,
+    pub width: u32,
+    pub url: String,
+}
+
+/// Everything a browser needs to send one file straight to the storage backend.
+#[derive(Debug, Serialize)]
+pub struct UploadTicket {
+    /// Identifies the upload when it is confirmed.
+    pub public_id: String,
+    pub method: &'static str,
+    pub url: String,
+    /// Form fields sent alongside a `file` field, for form based (`POST`) uploads.
+    pub fields: BTreeMap<String, String>,
+    /// Headers the upload must carry, for raw (`PUT`) uploads.
+    pub headers: BTreeMap<String, String>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// A ticket as handed to the user who asked for it.
+#[derive(Debug, Serialize)]
+pub struct IssuedTicket {
+    #[serde(flatten)]
+    pub ticket: UploadTicket,
+    /// Sent back with the confirmation; only the user the ticket was issued to can use it.
+    pub confirmation: String,
 }
 
 pub enum ImageMessage {
-    UploadBase64 {
-        base64: String,
+    /// Stores an already validated image; uploads are validated by the media actor.
+    Upload {
+        image: ImageUpload,
         respond_to: oneshot::Sender<Result<ImageUploadResult, ApiErrors>>,
     },
-    UploadBytes {
-        bytes: Vec<u8>,
+    Delete {
+        public_id: String,
+        respond_to: oneshot::Sender<Result<(), ApiErrors>>,
+    },
+    ListAssets {
+        respond_to: oneshot::Sender<Result<Vec<String>, ApiErrors>>,
+    },
+    IssueTicket {
+        content_type: String,
+        owner: Uuid,
+        respond_to: oneshot::Sender<Result<IssuedTicket, ApiErrors>>,
+    },
+    /// Checks that `public_id` came from a ticket issued to `owner`.
+    VerifyConfirmation {
+        public_id: String,
+        owner: Uuid,
+        confirmation: String,
+        respond_to: oneshot::Sender<Result<(), ApiErrors>>,
+    },
+    VerifyTicket {
+        public_id: String,
+        expires: i64,
+        signature: String,
+        respond_to: oneshot::Sender<Result<(), ApiErrors>>,
+    },
+    /// Accepts the body of a ticketed `PUT` on backends that serve tickets themselves.
+    Receive {
+        public_id: String,
+        expires: i64,
+        signature: String,
+        image: ImageUpload,
+        respond_to: oneshot::Sender<Result<(), ApiErrors>>,
+    },
+    /// Reads back a directly uploaded asset so it can be validated.
+    Fetch {
+        public_id: String,
+        max_bytes: usize,
+        respond_to: oneshot::Sender<Result<Vec<u8>, ApiErrors>>,
+    },
+    /// Renders variants for a directly uploaded asset once it passed validation.
+    Register {
+        public_id: String,
+        image: ImageUpload,
         respond_to: oneshot::Sender<Result<ImageUploadResult, ApiErrors>>,
     },
+    /// Attaches or detaches a tag naming what the asset is used by.
+    Tag {
+        public_id: String,
+        tag: String,
+        attach: bool,
+        respond_to: oneshot::Sender<Result<(), ApiErrors>>,
+    },
 }
@@ -0,0 +1,63 @@
+use std::{collections::HashSet, time::Duration};
+
+use tokio::sync::oneshot;
+
+use crate::{
+    errors::api_errors::ApiErrors, image::messages::ImageMessage, media::messages::MediaMessage,
+    state::AppState,
+};
+
+/// Lists the assets held by the image store that no media item references.
+pub async fn find_orphaned_images(state: &AppState) -> Result<Vec<String>, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+    state
+        .image_tx
+        .send(ImageMessage::ListAssets { respond_to: tx })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Image Service unavailable".to_string()))?;
+    let assets = rx
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Image listing failed".to_string()))??;
+
+    let (tx, rx) = oneshot::channel();
+    state
+        .media_tx
+        .send(MediaMessage::GetImageIds { respond_to: tx })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Media Service unavailable".to_string()))?;
+    let referenced: HashSet<String> = rx
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed to fetch media images".to_string()))??
+        .into_iter()
+        .collect();
+
+    Ok(assets
+        .into_iter()
+        .filter(|asset| !referenced.contains(asset))
+        .collect())
+}
+
+/// Periodically logs orphaned assets. Nothing is deleted here: an asset can be stored a moment
+/// before its media row is written.
+pub fn spawn_image_reconciler(state: AppState, every: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+
+            match find_orphaned_images(&state).await {
+                Ok(orphans) if orphans.is_empty() => {}
+                Ok(orphans) => {
+                    println!(
+                        "🧹 {} orphaned image(s): {}",
+                        orphans.len(),
+                        orphans.join(", ")
+                    )
+                }
+                Err(e) => println!("image reconciliation failed: {e}"),
+            }
+        }
+    });
+}
@@ -0,0 +1,141 @@
+use std::path::{Path, PathBuf};
+
+use axum::{
+    body::{Body, Bytes},
+    extract::multipart::Field,
+};
+use futures_util::{Stream, TryStreamExt};
+use sha2::{Digest, Sha256};
+use tokio::io::AsyncWriteExt;
+use tokio_util::io::ReaderStream;
+use uuid::Uuid;
+
+use crate::{
+    config::ImageLimits, errors::api_errors::ApiErrors, image::validation::sniff_content_type,
+};
+
+/// Leading bytes kept in memory for sniffing and header parsing; JPEG frame headers can sit
+/// behind up to 64 KiB of EXIF.
+pub const HEAD_LEN: usize = 64 * 1024;
+
+/// Enough leading bytes to recognise every accepted format.
+const SNIFF_LEN: usize = 1024;
+
+/// An upload streamed to a temporary file. The file is removed when this is dropped.
+pub struct SpooledFile {
+    path: PathBuf,
+    len: u64,
+    head: Vec<u8>,
+    content_hash: String,
+}
+
+impl SpooledFile {
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    pub fn len(&self) -> u64 {
+        self.len
+    }
+
+    pub fn head(&self) -> &[u8] {
+        &self.head
+    }
+
+    /// Hex SHA-256 of the whole file, computed while it streamed in.
+    pub fn content_hash(&self) -> &str {
+        &self.content_hash
+    }
+
+    /// A fresh request body streaming the file from disk.
+    pub fn body(&self) -> Result<reqwest::Body, ApiErrors> {
+        let file = std::fs::File::open(&self.path)
+            .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+        Ok(reqwest::Body::wrap_stream(ReaderStream::new(
+            tokio::fs::File::from_std(file),
+        )))
+    }
+}
+
+impl Drop for SpooledFile {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+/// Streams a multipart field to disk; see [`spool_stream`].
+pub async fn spool_field(field: Field<'_>, limits: &ImageLimits) -> Result<SpooledFile, ApiErrors> {
+    spool_stream(field, limits).await
+}
+
+/// Streams a raw request body to disk; see [`spool_stream`].
+pub async fn spool_body(body: Body, limits: &ImageLimits) -> Result<SpooledFile, ApiErrors> {
+    let stream = body
+        .into_data_stream()
+        .map_err(|e| ApiErrors::BadRequest(format!("Invalid body: {e}")));
+
+    spool_stream(stream, limits).await
+}
+
+/// Writes `stream` to disk chunk by chunk, hashing it on the way. Oversized and non-image
+/// files are refused as soon as that is known rather than after the whole upload.
+async fn spool_stream<S, E>(mut stream: S, limits: &ImageLimits) -> Result<SpooledFile, ApiErrors>
+where
+    S: Stream<Item = Result<Bytes, E>> + Unpin,
+    ApiErrors: From<E>,
+{
+    let mut spooled = SpooledFile {
+        path: std::env::temp_dir().join(format!("portfolio-upload-{}", Uuid::new_v4())),
+        len: 0,
+        head: Vec::new(),
+        content_hash: String::new(),
+    };
+
+    let mut file = tokio::fs::File::create(&spooled.path)
+        .await
+        .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+    let mut hasher = Sha256::new();
+
+    while let Some(chunk) = stream.try_next().await? {
+        spooled.len += chunk.len() as u64;
+
+        if spooled.len > limits.max_bytes as u64 {
+            return Err(ApiErrors::PayloadTooLarge(format!(
+                "Image exceeds the {} byte limit",
+                limits.max_bytes
+            )));
+        }
+
+        let sniffed_before = spooled.head.len() >= SNIFF_LEN;
+
+        if spooled.head.len() < HEAD_LEN {
+            let take = chunk.len().min(HEAD_LEN - spooled.head.len());
+            spooled.head.extend_from_slice(&chunk[..take]);
+        }
+
+        if !sniffed_before
+            && spooled.head.len() >= SNIFF_LEN
+            && sniff_content_type(&spooled.head).is_none()
+        {
+            return Err(ApiErrors::UnsupportedMediaType(
+                "Only PNG, JPEG, WebP, GIF and SVG images are accepted".to_string(),
+            ));
+        }
+
+        hasher.update(&chunk);
+
+        file.write_all(&chunk)
+            .await
+            .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+    }
+
+    file.flush()
+        .await
+        .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+    spooled.content_hash = hex::encode(hasher.finalize());
+
+    Ok(spooled)
+}
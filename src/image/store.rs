@@ -0,0 +1,185 @@
+use std::{path::PathBuf, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+
+use crate::{
+    config::ImageStoreConfig,
+    errors::api_errors::ApiErrors,
+    image::{
+        circuit_breaker::CircuitBreaker,
+        dto::ImageUpload,
+        messages::{ImageUploadResult, UploadTicket},
+        store_cloudinary::CloudinaryStore,
+        store_local::LocalImageStore,
+        store_s3::S3ImageStore,
+    },
+};
+
+#[async_trait]
+pub trait ImageStore: Send + Sync {
+    async fn upload(&self, image: ImageUpload) -> Result<ImageUploadResult, ApiErrors>;
+
+    async fn delete(&self, public_id: &str) -> Result<(), ApiErrors>;
+
+    /// Public ids of every asset currently held by the backend.
+    async fn list(&self) -> Result<Vec<String>, ApiErrors>;
+
+    /// Signed instructions for uploading one image of `content_type` without going through
+    /// the API. The file only becomes a media item once it is confirmed.
+    async fn upload_ticket(&self, _content_type: &str) -> Result<UploadTicket, ApiErrors> {
+        Err(direct_uploads_unsupported())
+    }
+
+    /// Checks a ticket pointing back at the API before its body is read.
+    async fn verify_ticket(
+        &self,
+        _public_id: &str,
+        _expires: i64,
+        _signature: &str,
+    ) -> Result<(), ApiErrors> {
+        Err(direct_uploads_unsupported())
+    }
+
+    /// Stores the body of a ticketed upload, for backends whose tickets point back at the API.
+    async fn receive(
+        &self,
+        _public_id: &str,
+        _expires: i64,
+        _signature: &str,
+        _image: ImageUpload,
+    ) -> Result<(), ApiErrors> {
+        Err(direct_uploads_unsupported())
+    }
+
+    /// Bytes of a directly uploaded asset; anything over `max_bytes` is refused unread.
+    async fn fetch(&self, _public_id: &str, _max_bytes: usize) -> Result<Vec<u8>, ApiErrors> {
+        Err(direct_uploads_unsupported())
+    }
+
+    /// Renders variants for a validated direct upload and reports where it is served.
+    async fn register(
+        &self,
+        _public_id: &str,
+        _image: &ImageUpload,
+    ) -> Result<ImageUploadResult, ApiErrors> {
+        Err(direct_uploads_unsupported())
+    }
+
+    /// Attaches (or with `attach` unset, detaches) `tag` to an asset, on backends with tags.
+    async fn tag(&self, _public_id: &str, _tag: &str, _attach: bool) -> Result<(), ApiErrors> {
+        Ok(())
+    }
+
+    /// Directory the API should serve under `/media`, for backends that keep files on local disk.
+    fn media_root(&self) -> Option<PathBuf> {
+        None
+    }
+
+    /// Breaker guarding a remote backend, so its state can be reported without queueing on the actor.
+    fn circuit_breaker(&self) -> Option<Arc<CircuitBreaker>> {
+        None
+    }
+}
+
+/// How long an upload ticket stays valid on backends that let us choose.
+pub const UPLOAD_TICKET_TTL: Duration = Duration::from_secs(10 * 60);
+
+/// Reads a download chunk by chunk, giving up as soon as it passes `max_bytes` whatever
+/// length the response claimed.
+pub async fn read_limited(
+    mut res: reqwest::Response,
+    max_bytes: usize,
+) -> Result<Vec<u8>, ApiErrors> {
+    let too_large =
+        || ApiErrors::PayloadTooLarge(format!("Image exceeds the {max_bytes} byte limit"));
+
+    if res
+        .content_length()
+        .is_some_and(|length| length > max_bytes as u64)
+    {
+        return Err(too_large());
+    }
+
+    let mut bytes = Vec::new();
+
+    while let Some(chunk) = res
+        .chunk()
+        .await
+        .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?
+    {
+        if bytes.len() + chunk.len() > max_bytes {
+            return Err(too_large());
+        }
+
+        bytes.extend_from_slice(&chunk);
+    }
+
+    Ok(bytes)
+}
+
+fn direct_uploads_unsupported() -> ApiErrors {
+    ApiErrors::BadRequest("This image store does not support direct uploads".to_string())
+}
+
+pub fn image_store_from_config(config: ImageStoreConfig) -> Box<dyn ImageStore> {
+    match config {
+        ImageStoreConfig::Cloudinary {
+            cloud_name,
+            api_key,
+            api_secret,
+            folders,
+            client,
+        } => Box::new(CloudinaryStore::new(
+            cloud_name, api_key, api_secret, folders, client,
+        )),
+
+        ImageStoreConfig::Local {
+            media_dir,
+            public_url,
+        } => Box::new(LocalImageStore::new(media_dir, public_url)),
+
+        ImageStoreConfig::S3 {
+            endpoint,
+            bucket,
+            region,
+            access_key,
+            secret_key,
+            public_url,
+        } => Box::new(S3ImageStore::new(
+            endpoint, bucket, region, access_key, secret_key, public_url,
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use axum::{Router, body::Body, routing::get};
+    use futures_util::stream;
+
+    use super::*;
+
+    // A chunked body carries no length, so only the running total can stop it.
+    #[tokio::test]
+    async fn read_limited_stops_chunked_bodies_past_the_limit() {
+        let app = Router::new().route(
+            "/",
+            get(|| async {
+                let chunks = (0..4).map(|_| Ok::<_, std::io::Error>(vec![0u8; 1024]));
+                Body::from_stream(stream::iter(chunks))
+            }),
+        );
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let url = format!("http://{}/", listener.local_addr().unwrap());
+        tokio::spawn(async move { axum::serve(listener, app).await.unwrap() });
+
+        let res = reqwest::get(&url).await.unwrap();
+        assert_eq!(res.content_length(), None);
+        assert!(matches!(
+            read_limited(res, 2048).await,
+            Err(ApiErrors::PayloadTooLarge(_))
+        ));
+
+        let res = reqwest::get(&url).await.unwrap();
+        assert_eq!(read_limited(res, 4096).await.unwrap().len(), 4096);
+    }
+}
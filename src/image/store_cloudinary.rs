@@ -0,0 +1,521 @@
+use std::{collections::BTreeMap, sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use reqwest::{Client, RequestBuilder, Response, multipart};
+use sha1::{Digest, Sha1};
+use url::Url;
+use uuid::Uuid;
+
+use crate::{
+    config::{CloudinaryClientConfig, CloudinaryFolders},
+    errors::api_errors::ApiErrors,
+    image::{
+        circuit_breaker::CircuitBreaker,
+        dto::{
+            CloudinaryAssetDetails, CloudinaryDestroyResponse, CloudinaryResourcesResponse,
+            CloudinaryResponse, ImageUpload, extension_for,
+        },
+        messages::{ImageUploadResult, ImageVariant, UploadTicket},
+        store::{ImageStore, read_limited},
+        variants::{VARIANT_FORMATS, VARIANT_SIZES, cloudinary_eager},
+    },
+    media::dto::MediaEntity,
+};
+
+/// Parameters Cloudinary leaves out of the string it signs.
+const UNSIGNED_PARAMS: [&str; 5] = [
+    "api_key",
+    "cloud_name",
+    "file",
+    "resource_type",
+    "signature",
+];
+
+/// Cloudinary's request signature: the signed parameters sorted by name, `&`-joined as
+/// `key=value`, followed by the API secret and SHA-1 hashed. Empty values are not signed.
+pub fn sign_params(params: &[(&str, &str)], api_secret: &str) -> String {
+    let mut signed: Vec<_> = params
+        .iter()
+        .filter(|(key, value)| !value.is_empty() && !UNSIGNED_PARAMS.contains(key))
+        .collect();
+
+    signed.sort_by_key(|(key, _)| *key);
+
+    let to_sign = signed
+        .iter()
+        .map(|(key, value)| format!("{key}={value}"))
+        .collect::<Vec<_>>()
+        .join("&");
+
+    let mut hasher = Sha1::new();
+    hasher.update(format!("{to_sign}{api_secret}"));
+    format!("{:x}", hasher.finalize())
+}
+
+pub struct CloudinaryStore {
+    client: Client,
+    api_url: String,
+    cloud_name: String,
+    api_key: String,
+    api_secret: String,
+    folders: CloudinaryFolders,
+    max_retries: u32,
+    retry_base_delay: Duration,
+    breaker: Arc<CircuitBreaker>,
+}
+
+impl CloudinaryStore {
+    pub fn new(
+        cloud_name: String,
+        api_key: String,
+        api_secret: String,
+        folders: CloudinaryFolders,
+        config: CloudinaryClientConfig,
+    ) -> Self {
+        let client = Client::builder()
+            .connect_timeout(config.connect_timeout)
+            .timeout(config.request_timeout)
+            .build()
+            .expect("Cloudinary HTTP client must build");
+
+        Self {
+            client,
+            api_url: config.api_url.trim_end_matches('/').to_string(),
+            cloud_name,
+            api_key,
+            api_secret,
+            folders,
+            max_retries: config.max_retries,
+            retry_base_delay: config.retry_base_delay,
+            breaker: Arc::new(CircuitBreaker::new(
+                "Cloudinary",
+                config.breaker_threshold,
+                config.breaker_open_for,
+            )),
+        }
+    }
+
+    /// Form fields for a signed request: `params` plus the API key and their signature.
+    fn signed_form(&self, params: &[(&str, &str)]) -> multipart::Form {
+        let signature = sign_params(params, &self.api_secret);
+
+        params
+            .iter()
+            .fold(multipart::Form::new(), |form, (key, value)| {
+                form.text(key.to_string(), value.to_string())
+            })
+            .text("api_key", self.api_key.clone())
+            .text("signature", signature)
+    }
+
+    fn folder_for(&self, entity: Option<MediaEntity>) -> &str {
+        match entity {
+            Some(MediaEntity::Blog) => &self.folders.blog,
+            Some(MediaEntity::Project) => &self.folders.project,
+            None => &self.folders.library,
+        }
+    }
+
+    async fn asset_details(&self, public_id: &str) -> Result<CloudinaryAssetDetails, ApiErrors> {
+        let url = self.endpoint(&format!("resources/image/upload/{public_id}"));
+
+        let res = self
+            .send(|| {
+                Ok(self
+                    .client
+                    .get(&url)
+                    .basic_auth(&self.api_key, Some(&self.api_secret)))
+            })
+            .await?;
+
+        if res.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(ApiErrors::NotFound("Uploaded image not found".to_string()));
+        }
+
+        if !res.status().is_success() {
+            return Err(ApiErrors::InternalServerError(
+                "Cloudinary rejected resource lookup".to_string(),
+            ));
+        }
+
+        res.json()
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Invalid Cloudinary response".to_string()))
+    }
+
+    fn endpoint(&self, path: &str) -> String {
+        format!("{}/{}/{}", self.api_url, self.cloud_name, path)
+    }
+
+    /// Sends the request built by `request`, retrying network errors and 5xx responses with
+    /// exponential backoff. Exhausted retries count as one failure against the breaker.
+    async fn send(
+        &self,
+        request: impl Fn() -> Result<RequestBuilder, ApiErrors>,
+    ) -> Result<Response, ApiErrors> {
+        self.breaker.check()?;
+
+        let mut attempt = 0;
+
+        loop {
+            let reason = match request()?.send().await {
+                Ok(res) if !res.status().is_server_error() => {
+                    self.breaker.record_success();
+                    return Ok(res);
+                }
+                Ok(res) => format!("responded with {}", res.status()),
+                Err(e) => format!("request failed: {e}"),
+            };
+
+            if attempt >= self.max_retries {
+                self.breaker.record_failure();
+
+                return Err(ApiErrors::ServiceUnavailable(format!(
+                    "Cloudinary {reason}"
+                )));
+            }
+
+            println!("Cloudinary {reason}, retrying");
+
+            tokio::time::sleep(self.retry_base_delay * 2u32.saturating_pow(attempt)).await;
+            attempt += 1;
+        }
+    }
+}
+
+#[async_trait]
+impl ImageStore for CloudinaryStore {
+    async fn upload(&self, image: ImageUpload) -> Result<ImageUploadResult, ApiErrors> {
+        let timestamp = Utc::now().timestamp().to_string();
+        let eager = cloudinary_eager();
+        let folder = self.folder_for(image.entity);
+
+        let params = [
+            ("eager", eager.as_str()),
+            ("folder", folder),
+            ("timestamp", timestamp.as_str()),
+        ];
+
+        let url = self.endpoint("image/upload");
+
+        // A multipart form is consumed by sending it, so every attempt builds a fresh one
+        // streaming the file from the start.
+        let res = self
+            .send(|| {
+                let file = image.body().and_then(|body| {
+                    multipart::Part::stream_with_length(body, image.byte_size)
+                        .file_name(format!("upload.{}", image.extension()))
+                        .mime_str(&image.content_type)
+                        .map_err(|e| ApiErrors::InternalServerError(e.to_string()))
+                })?;
+
+                let form = self.signed_form(&params).part("file", file);
+
+                Ok(self.client.post(&url).multipart(form))
+            })
+            .await?;
+
+        println!("image upload: {res:?}");
+
+        if !res.status().is_success() {
+            return Err(ApiErrors::InternalServerError(
+                "Cloudinary rejected upload".to_string(),
+            ));
+        }
+
+        let body: CloudinaryResponse = res.json().await.map_err(|_| {
+            ApiErrors::InternalServerError("Invalid Cloudinary response".to_string())
+        })?;
+
+        // Cloudinary answers with the eager renditions in the order they were requested.
+        let specs = VARIANT_SIZES.iter().flat_map(|(name, _)| {
+            VARIANT_FORMATS
+                .iter()
+                .map(move |format| (name.to_string(), format.to_string()))
+        });
+
+        let variants = specs
+            .zip(body.eager)
+            .map(|((name, format), eager)| ImageVariant {
+                name,
+                format,
+                width: eager.width,
+                url: eager.secure_url,
+            })
+            .collect();
+
+        Ok(ImageUploadResult {
+            url: body.secure_url,
+            public_id: body.public_id,
+            variants,
+        })
+    }
+
+    async fn delete(&self, public_id: &str) -> Result<(), ApiErrors> {
+        let timestamp = Utc::now().timestamp().to_string();
+        let params = [("public_id", public_id), ("timestamp", timestamp.as_str())];
+
+        let url = self.endpoint("image/destroy");
+
+        let res = self
+            .send(|| Ok(self.client.post(&url).multipart(self.signed_form(&params))))
+            .await?;
+
+        if !res.status().is_success() {
+            return Err(ApiErrors::InternalServerError(
+                "Cloudinary rejected delete".to_string(),
+            ));
+        }
+
+        let body: CloudinaryDestroyResponse = res.json().await.map_err(|_| {
+            ApiErrors::InternalServerError("Invalid Cloudinary response".to_string())
+        })?;
+
+        // "not found" means the asset is already gone, which is what we wanted.
+        match body.result.as_str() {
+            "ok" | "not found" => Ok(()),
+            other => Err(ApiErrors::InternalServerError(format!(
+                "Cloudinary delete failed: {other}"
+            ))),
+        }
+    }
+
+    async fn list(&self) -> Result<Vec<String>, ApiErrors> {
+        let url = self.endpoint("resources/image/upload");
+
+        let mut public_ids = Vec::new();
+        let mut next_cursor: Option<String> = None;
+
+        loop {
+            let mut page_url =
+                Url::parse_with_params(&url, &[("max_results", "500")]).map_err(|_| {
+                    ApiErrors::InternalServerError("Invalid Cloudinary url".to_string())
+                })?;
+
+            if let Some(cursor) = &next_cursor {
+                page_url
+                    .query_pairs_mut()
+                    .append_pair("next_cursor", cursor);
+            }
+
+            let res = self
+                .send(|| {
+                    Ok(self
+                        .client
+                        .get(page_url.clone())
+                        .basic_auth(&self.api_key, Some(&self.api_secret)))
+                })
+                .await?;
+
+            if !res.status().is_success() {
+                return Err(ApiErrors::InternalServerError(
+                    "Cloudinary rejected resource listing".to_string(),
+                ));
+            }
+
+            let body: CloudinaryResourcesResponse = res.json().await.map_err(|_| {
+                ApiErrors::InternalServerError("Invalid Cloudinary response".to_string())
+            })?;
+
+            public_ids.extend(body.resources.into_iter().map(|r| r.public_id));
+
+            match body.next_cursor {
+                Some(cursor) => next_cursor = Some(cursor),
+                None => break,
+            }
+        }
+
+        Ok(public_ids)
+    }
+
+    /// Signed form fields for posting straight to Cloudinary. Cloudinary accepts a signature for
+    /// an hour after its timestamp, so the ticket lives that long.
+    async fn upload_ticket(&self, content_type: &str) -> Result<UploadTicket, ApiErrors> {
+        if extension_for(content_type).is_none() {
+            return Err(ApiErrors::UnsupportedMediaType(format!(
+                "Unsupported content type: {content_type}"
+            )));
+        }
+
+        let now = Utc::now();
+        let timestamp = now.timestamp().to_string();
+        let public_id = format!("{}/{}", self.folders.library, Uuid::new_v4());
+        let eager = cloudinary_eager();
+
+        let signature = sign_params(
+            &[
+                ("eager", &eager),
+                ("public_id", &public_id),
+                ("timestamp", &timestamp),
+            ],
+            &self.api_secret,
+        );
+
+        Ok(UploadTicket {
+            method: "POST",
+            url: self.endpoint("image/upload"),
+            fields: BTreeMap::from([
+                ("api_key".to_string(), self.api_key.clone()),
+                ("eager".to_string(), eager),
+                ("public_id".to_string(), public_id.clone()),
+                ("signature".to_string(), signature),
+                ("timestamp".to_string(), timestamp),
+            ]),
+            headers: BTreeMap::new(),
+            public_id,
+            expires_at: now + chrono::Duration::hours(1),
+        })
+    }
+
+    async fn fetch(&self, public_id: &str, max_bytes: usize) -> Result<Vec<u8>, ApiErrors> {
+        let details = self.asset_details(public_id).await?;
+
+        if details.bytes > max_bytes as u64 {
+            return Err(ApiErrors::PayloadTooLarge(format!(
+                "Image exceeds the {max_bytes} byte limit"
+            )));
+        }
+
+        let res = self
+            .send(|| Ok(self.client.get(&details.secure_url)))
+            .await?;
+
+        if !res.status().is_success() {
+            return Err(ApiErrors::InternalServerError(
+                "Cloudinary rejected download".to_string(),
+            ));
+        }
+
+        read_limited(res, max_bytes).await
+    }
+
+    /// The ticket asked for the eager renditions, so their delivery URLs only need building.
+    async fn register(
+        &self,
+        public_id: &str,
+        image: &ImageUpload,
+    ) -> Result<ImageUploadResult, ApiErrors> {
+        let details = self.asset_details(public_id).await?;
+
+        let (prefix, rest) = details.secure_url.split_once("/upload/").ok_or_else(|| {
+            ApiErrors::InternalServerError("Unexpected Cloudinary url".to_string())
+        })?;
+
+        let rest = rest.rsplit_once('.').map_or(rest, |(stem, _)| stem);
+        let original_width = image.dimensions.map_or(u32::MAX, |(width, _)| width);
+
+        let variants = VARIANT_SIZES
+            .iter()
+            .flat_map(|(name, width)| {
+                VARIANT_FORMATS.iter().map(move |format| ImageVariant {
+                    name: name.to_string(),
+                    format: format.to_string(),
+                    width: (*width).min(original_width),
+                    url: format!("{prefix}/upload/c_limit,w_{width}/f_{format}/{rest}"),
+                })
+            })
+            .collect();
+
+        Ok(ImageUploadResult {
+            url: details.secure_url,
+            public_id: public_id.to_string(),
+            variants,
+        })
+    }
+
+    async fn tag(&self, public_id: &str, tag: &str, attach: bool) -> Result<(), ApiErrors> {
+        let timestamp = Utc::now().timestamp().to_string();
+        let command = if attach { "add" } else { "remove" };
+
+        let url = self.endpoint("image/tags");
+
+        // Cloudinary signs the `public_ids[]` array under its bare name.
+        let signature = sign_params(
+            &[
+                ("command", command),
+                ("public_ids", public_id),
+                ("tag", tag),
+                ("timestamp", &timestamp),
+            ],
+            &self.api_secret,
+        );
+
+        let res = self
+            .send(|| {
+                let form = multipart::Form::new()
+                    .text("command", command)
+                    .text("public_ids[]", public_id.to_string())
+                    .text("tag", tag.to_string())
+                    .text("timestamp", timestamp.clone())
+                    .text("api_key", self.api_key.clone())
+                    .text("signature", signature.clone());
+
+                Ok(self.client.post(&url).multipart(form))
+            })
+            .await?;
+
+        if !res.status().is_success() {
+            return Err(ApiErrors::InternalServerError(
+                "Cloudinary rejected tagging".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    fn circuit_breaker(&self) -> Option<Arc<CircuitBreaker>> {
+        Some(self.breaker.clone())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Vectors from Cloudinary's signed upload documentation.
+    #[test]
+    fn signs_documented_examples() {
+        assert_eq!(
+            sign_params(
+                &[
+                    ("timestamp", "1315060510"),
+                    ("public_id", "sample_image"),
+                    ("eager", "w_400,h_300,c_pad|w_260,h_200,c_crop"),
+                ],
+                "abcd",
+            ),
+            "bfd09f95f331f558cbd1320e67aa8d488770583e"
+        );
+
+        assert_eq!(
+            sign_params(
+                &[("public_id", "sample_image"), ("timestamp", "1315060510")],
+                "abcd",
+            ),
+            "b4ad47fb4e25c7bf5f92a20089f9db59bc302313"
+        );
+    }
+
+    #[test]
+    fn skips_unsigned_and_empty_params() {
+        let plain = sign_params(
+            &[("public_id", "sample_image"), ("timestamp", "1315060510")],
+            "abcd",
+        );
+
+        let noisy = sign_params(
+            &[
+                ("api_key", "1234"),
+                ("timestamp", "1315060510"),
+                ("folder", ""),
+                ("resource_type", "image"),
+                ("public_id", "sample_image"),
+                ("file", "data"),
+            ],
+            "abcd",
+        );
+
+        assert_eq!(plain, noisy);
+    }
+}
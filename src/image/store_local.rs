@@ -0,0 +1,252 @@
+use std::{collections::BTreeMap, path::PathBuf};
+
+use async_trait::async_trait;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use uuid::Uuid;
+
+use crate::{
+    errors::api_errors::ApiErrors,
+    image::{
+        dto::{ImageUpload, extension_for},
+        messages::{ImageUploadResult, ImageVariant, UploadTicket},
+        store::{ImageStore, UPLOAD_TICKET_TTL},
+        variants::{is_variant_key, render_variants, variant_key, variant_keys},
+    },
+};
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Writes uploads to `media_dir`; the API serves that directory under `/media`.
+///
+/// Upload tickets point at `PUT /api/v1/image/direct/{public_id}` and are signed with a key
+/// generated at startup, so they do not survive a restart.
+pub struct LocalImageStore {
+    media_dir: PathBuf,
+    public_url: String,
+    ticket_key: [u8; 32],
+}
+
+impl LocalImageStore {
+    pub fn new(media_dir: impl Into<PathBuf>, public_url: String) -> Self {
+        Self {
+            media_dir: media_dir.into(),
+            public_url: public_url.trim_end_matches('/').to_string(),
+            ticket_key: rand::random(),
+        }
+    }
+
+    fn ticket_mac(&self, public_id: &str, expires: i64) -> HmacSha256 {
+        let mut mac =
+            HmacSha256::new_from_slice(&self.ticket_key).expect("HMAC accepts keys of any length");
+        mac.update(format!("{public_id}:{expires}").as_bytes());
+        mac
+    }
+
+    /// Public ids are bare file names; anything that could leave `media_dir` is refused.
+    fn path_of(&self, public_id: &str) -> Result<PathBuf, ApiErrors> {
+        if public_id.is_empty() || public_id.contains(['/', '\\']) || public_id.starts_with('.') {
+            return Err(ApiErrors::BadRequest("Invalid image id".to_string()));
+        }
+
+        Ok(self.media_dir.join(public_id))
+    }
+
+    async fn store_variants(
+        &self,
+        file_name: &str,
+        image: &ImageUpload,
+    ) -> Result<Vec<ImageVariant>, ApiErrors> {
+        let mut variants = Vec::new();
+
+        for rendered in render_variants(image).await? {
+            let key = variant_key(file_name, rendered.name, rendered.format);
+
+            tokio::fs::write(self.media_dir.join(&key), &rendered.bytes)
+                .await
+                .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+            variants.push(ImageVariant {
+                name: rendered.name.to_string(),
+                format: rendered.format.to_string(),
+                width: rendered.width,
+                url: format!("{}/media/{}", self.public_url, key),
+            });
+        }
+
+        Ok(variants)
+    }
+}
+
+#[async_trait]
+impl ImageStore for LocalImageStore {
+    async fn upload(&self, image: ImageUpload) -> Result<ImageUploadResult, ApiErrors> {
+        let file_name = format!("{}.{}", Uuid::new_v4(), image.extension());
+
+        tokio::fs::create_dir_all(&self.media_dir)
+            .await
+            .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+        image.save_to(&self.media_dir.join(&file_name)).await?;
+
+        let variants = self.store_variants(&file_name, &image).await?;
+
+        Ok(ImageUploadResult {
+            url: format!("{}/media/{}", self.public_url, file_name),
+            public_id: file_name,
+            variants,
+        })
+    }
+
+    async fn delete(&self, public_id: &str) -> Result<(), ApiErrors> {
+        self.path_of(public_id)?;
+
+        for key in variant_keys(public_id)
+            .into_iter()
+            .chain([public_id.to_string()])
+        {
+            match tokio::fs::remove_file(self.media_dir.join(key)).await {
+                Ok(()) => {}
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+                Err(e) => return Err(ApiErrors::InternalServerError(e.to_string())),
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn list(&self) -> Result<Vec<String>, ApiErrors> {
+        let mut entries = match tokio::fs::read_dir(&self.media_dir).await {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(ApiErrors::InternalServerError(e.to_string())),
+        };
+
+        let mut public_ids = Vec::new();
+
+        while let Some(entry) = entries
+            .next_entry()
+            .await
+            .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?
+        {
+            if let Some(name) = entry.file_name().to_str()
+                && !is_variant_key(name)
+            {
+                public_ids.push(name.to_string());
+            }
+        }
+
+        Ok(public_ids)
+    }
+
+    async fn upload_ticket(&self, content_type: &str) -> Result<UploadTicket, ApiErrors> {
+        let extension = extension_for(content_type).ok_or_else(|| {
+            ApiErrors::UnsupportedMediaType(format!("Unsupported content type: {content_type}"))
+        })?;
+
+        let public_id = format!("{}.{}", Uuid::new_v4(), extension);
+        let expires_at = Utc::now() + UPLOAD_TICKET_TTL;
+        let expires = expires_at.timestamp();
+        let signature = hex::encode(self.ticket_mac(&public_id, expires).finalize().into_bytes());
+
+        Ok(UploadTicket {
+            url: format!(
+                "{}/api/v1/image/direct/{public_id}?expires={expires}&signature={signature}",
+                self.public_url
+            ),
+            public_id,
+            method: "PUT",
+            fields: BTreeMap::new(),
+            headers: BTreeMap::from([("content-type".to_string(), content_type.to_string())]),
+            expires_at,
+        })
+    }
+
+    async fn verify_ticket(
+        &self,
+        public_id: &str,
+        expires: i64,
+        signature: &str,
+    ) -> Result<(), ApiErrors> {
+        let signature = hex::decode(signature)
+            .map_err(|_| ApiErrors::Unauthorized("Invalid upload signature".to_string()))?;
+
+        self.ticket_mac(public_id, expires)
+            .verify_slice(&signature)
+            .map_err(|_| ApiErrors::Unauthorized("Invalid upload signature".to_string()))?;
+
+        if expires < Utc::now().timestamp() {
+            return Err(ApiErrors::Unauthorized("Upload ticket expired".to_string()));
+        }
+
+        if tokio::fs::try_exists(self.path_of(public_id)?)
+            .await
+            .unwrap_or(false)
+        {
+            return Err(ApiErrors::Conflict(
+                "Upload ticket already used".to_string(),
+            ));
+        }
+
+        Ok(())
+    }
+
+    async fn receive(
+        &self,
+        public_id: &str,
+        expires: i64,
+        signature: &str,
+        image: ImageUpload,
+    ) -> Result<(), ApiErrors> {
+        self.verify_ticket(public_id, expires, signature).await?;
+
+        let path = self.path_of(public_id)?;
+
+        tokio::fs::create_dir_all(&self.media_dir)
+            .await
+            .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+        image.save_to(&path).await
+    }
+
+    async fn fetch(&self, public_id: &str, max_bytes: usize) -> Result<Vec<u8>, ApiErrors> {
+        let path = self.path_of(public_id)?;
+
+        let metadata = match tokio::fs::metadata(&path).await {
+            Ok(metadata) => metadata,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+                return Err(ApiErrors::NotFound("Uploaded image not found".to_string()));
+            }
+            Err(e) => return Err(ApiErrors::InternalServerError(e.to_string())),
+        };
+
+        if metadata.len() > max_bytes as u64 {
+            return Err(ApiErrors::PayloadTooLarge(format!(
+                "Image exceeds the {max_bytes} byte limit"
+            )));
+        }
+
+        tokio::fs::read(&path)
+            .await
+            .map_err(|e| ApiErrors::InternalServerError(e.to_string()))
+    }
+
+    async fn register(
+        &self,
+        public_id: &str,
+        image: &ImageUpload,
+    ) -> Result<ImageUploadResult, ApiErrors> {
+        self.path_of(public_id)?;
+
+        Ok(ImageUploadResult {
+            url: format!("{}/media/{}", self.public_url, public_id),
+            public_id: public_id.to_string(),
+            variants: self.store_variants(public_id, image).await?,
+        })
+    }
+
+    fn media_root(&self) -> Option<PathBuf> {
+        Some(self.media_dir.clone())
+    }
+}
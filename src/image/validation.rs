@@ -0,0 +1,508 @@
+use crate::{config::ImageLimits, errors::api_errors::ApiErrors};
+
+const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+
+/// Identifies an image from its leading bytes; the client-supplied type is never trusted.
+pub fn sniff_content_type(bytes: &[u8]) -> Option<&'static str> {
+    if bytes.starts_with(PNG_SIGNATURE) {
+        Some("image/png")
+    } else if bytes.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some("image/jpeg")
+    } else if bytes.starts_with(b"GIF87a") || bytes.starts_with(b"GIF89a") {
+        Some("image/gif")
+    } else if bytes.len() >= 12 && &bytes[..4] == b"RIFF" && &bytes[8..12] == b"WEBP" {
+        Some("image/webp")
+    } else if looks_like_svg(bytes) {
+        Some("image/svg+xml")
+    } else {
+        None
+    }
+}
+
+pub struct ValidatedImage {
+    pub content_type: &'static str,
+    /// Pixel size; `None` for SVG.
+    pub dimensions: Option<(u32, u32)>,
+}
+
+/// Checks an upload against `limits` and returns its sniffed content type and size.
+/// `declared` is the type claimed by a data URL, which must agree with the content.
+pub fn validate_image(
+    bytes: &[u8],
+    declared: Option<&str>,
+    limits: &ImageLimits,
+) -> Result<ValidatedImage, ApiErrors> {
+    validate_head(bytes, bytes.len() as u64, declared, limits)
+}
+
+/// Like [`validate_image`] for an upload of `len` bytes of which only the leading `bytes` are
+/// at hand. Raster formats keep their size in the header; SVG is only checked within `bytes`.
+pub fn validate_head(
+    bytes: &[u8],
+    len: u64,
+    declared: Option<&str>,
+    limits: &ImageLimits,
+) -> Result<ValidatedImage, ApiErrors> {
+    if len == 0 {
+        return Err(ApiErrors::BadRequest("Image is empty".to_string()));
+    }
+
+    if len > limits.max_bytes as u64 {
+        return Err(ApiErrors::PayloadTooLarge(format!(
+            "Image exceeds the {} byte limit",
+            limits.max_bytes
+        )));
+    }
+
+    let content_type = sniff_content_type(bytes).ok_or_else(|| {
+        ApiErrors::UnsupportedMediaType(
+            "Only PNG, JPEG, WebP, GIF and SVG images are accepted".to_string(),
+        )
+    })?;
+
+    if let Some(declared) = declared {
+        let declared = if declared == "image/jpg" {
+            "image/jpeg"
+        } else {
+            declared
+        };
+
+        if declared != content_type {
+            return Err(ApiErrors::UnsupportedMediaType(format!(
+                "Image declared as {declared} but contains {content_type}"
+            )));
+        }
+    }
+
+    if content_type == "image/svg+xml" {
+        return check_svg(bytes, limits).map(|_| ValidatedImage {
+            content_type,
+            dimensions: None,
+        });
+    }
+
+    let (width, height) = dimensions(content_type, bytes)
+        .filter(|&(width, height)| width > 0 && height > 0)
+        .ok_or_else(|| ApiErrors::BadRequest("Image header is corrupt".to_string()))?;
+
+    if width > limits.max_width || height > limits.max_height {
+        return Err(ApiErrors::PayloadTooLarge(format!(
+            "Image is {width}x{height}, the limit is {}x{}",
+            limits.max_width, limits.max_height
+        )));
+    }
+
+    Ok(ValidatedImage {
+        content_type,
+        dimensions: Some((width, height)),
+    })
+}
+
+fn check_svg(bytes: &[u8], limits: &ImageLimits) -> Result<(), ApiErrors> {
+    if !limits.allow_svg {
+        return Err(ApiErrors::UnsupportedMediaType(
+            "SVG uploads are disabled".to_string(),
+        ));
+    }
+
+    let text = String::from_utf8_lossy(bytes).to_ascii_lowercase();
+
+    if svg_is_inert(&text) {
+        Ok(())
+    } else {
+        Err(ApiErrors::UnsupportedMediaType(
+            "SVG images may only contain drawing elements, without scripts, event handlers or \
+             external references"
+                .to_string(),
+        ))
+    }
+}
+
+/// Elements that only draw. Anything else, scripts, `foreignObject`, animations that can
+/// rewrite attributes and editor metadata included, is refused rather than sanitised.
+const SVG_ELEMENTS: &[&str] = &[
+    "svg",
+    "g",
+    "defs",
+    "title",
+    "desc",
+    "symbol",
+    "use",
+    "path",
+    "rect",
+    "circle",
+    "ellipse",
+    "line",
+    "polyline",
+    "polygon",
+    "text",
+    "tspan",
+    "textpath",
+    "lineargradient",
+    "radialgradient",
+    "stop",
+    "clippath",
+    "mask",
+    "pattern",
+    "marker",
+    "filter",
+    "style",
+];
+
+/// Walks the tags of lowercased SVG `text`: every element must be a drawing element (or a
+/// filter primitive), no attribute may be an `on*` event handler and links may only point
+/// into the document itself. DTDs are refused so entities cannot smuggle anything past this.
+fn svg_is_inert(text: &str) -> bool {
+    let mut rest = text;
+
+    while let Some(start) = rest.find('<') {
+        rest = &rest[start + 1..];
+
+        if let Some(comment) = rest.strip_prefix("!--") {
+            let Some(end) = comment.find("-->") else {
+                return false;
+            };
+            rest = &comment[end + 3..];
+            continue;
+        }
+
+        if rest.starts_with('!') || (rest.starts_with('?') && !rest.starts_with("?xml ")) {
+            return false;
+        }
+
+        let Some(end) = tag_end(rest) else {
+            return false;
+        };
+        let tag = &rest[..end];
+        rest = &rest[end + 1..];
+
+        if tag.starts_with('/') || tag.starts_with('?') {
+            continue;
+        }
+
+        let name_len = tag
+            .find(|c: char| c.is_ascii_whitespace() || c == '/')
+            .unwrap_or(tag.len());
+        let name = local_name(&tag[..name_len]);
+        if !SVG_ELEMENTS.contains(&name) && !name.starts_with("fe") {
+            return false;
+        }
+
+        if !svg_attributes_are_inert(&tag[name_len..]) {
+            return false;
+        }
+    }
+
+    true
+}
+
+/// Where the tag starting after `<` closes, skipping `>` inside quoted attribute values.
+fn tag_end(tag: &str) -> Option<usize> {
+    let mut quote = None;
+
+    for (i, c) in tag.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '>') => return Some(i),
+            _ => {}
+        }
+    }
+
+    None
+}
+
+fn svg_attributes_are_inert(mut attributes: &str) -> bool {
+    loop {
+        attributes = attributes.trim_start_matches(|c: char| c.is_ascii_whitespace() || c == '/');
+        if attributes.is_empty() {
+            return true;
+        }
+
+        let name_len = attributes
+            .find(|c: char| c.is_ascii_whitespace() || c == '=' || c == '/')
+            .unwrap_or(attributes.len());
+        let name = local_name(&attributes[..name_len]);
+        attributes = attributes[name_len..].trim_start();
+
+        let mut value = "";
+        if let Some(after) = attributes.strip_prefix('=') {
+            let after = after.trim_start();
+            let (parsed, remaining) = match after.chars().next() {
+                Some(quote @ ('"' | '\'')) => match after[1..].find(quote) {
+                    Some(end) => (&after[1..end + 1], &after[end + 2..]),
+                    None => return false,
+                },
+                _ => {
+                    let end = after
+                        .find(|c: char| c.is_ascii_whitespace())
+                        .unwrap_or(after.len());
+                    (&after[..end], &after[end..])
+                }
+            };
+            value = parsed;
+            attributes = remaining;
+        }
+
+        if name.starts_with("on") {
+            return false;
+        }
+
+        if name == "href" && !value.trim_start().starts_with('#') {
+            return false;
+        }
+
+        if value.contains("javascript:") {
+            return false;
+        }
+    }
+}
+
+/// `name` without its namespace prefix, so `svg:script` and `xlink:href` are seen for what
+/// they are.
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+fn looks_like_svg(bytes: &[u8]) -> bool {
+    let head = &bytes[..bytes.len().min(1024)];
+    let text = String::from_utf8_lossy(head);
+    let text = text.trim_start_matches('\u{feff}').trim_start();
+
+    (text.starts_with("<?xml") || text.starts_with("<svg") || text.starts_with("<!--"))
+        && text.contains("<svg")
+}
+
+fn dimensions(content_type: &str, bytes: &[u8]) -> Option<(u32, u32)> {
+    match content_type {
+        "image/png" => png_dimensions(bytes),
+        "image/jpeg" => jpeg_dimensions(bytes),
+        "image/gif" => gif_dimensions(bytes),
+        "image/webp" => webp_dimensions(bytes),
+        _ => None,
+    }
+}
+
+fn be_u16(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 2)?;
+    Some(u16::from_be_bytes([b[0], b[1]]) as u32)
+}
+
+fn le_u16(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 2)?;
+    Some(u16::from_le_bytes([b[0], b[1]]) as u32)
+}
+
+fn le_u24(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 3)?;
+    Some(u32::from_le_bytes([b[0], b[1], b[2], 0]))
+}
+
+fn be_u32(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 4)?;
+    Some(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn png_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    if bytes.get(12..16)? != b"IHDR" {
+        return None;
+    }
+
+    Some((be_u32(bytes, 16)?, be_u32(bytes, 20)?))
+}
+
+fn gif_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    Some((le_u16(bytes, 6)?, le_u16(bytes, 8)?))
+}
+
+fn jpeg_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    let mut at = 2;
+
+    loop {
+        while *bytes.get(at)? == 0xFF && *bytes.get(at + 1)? == 0xFF {
+            at += 1;
+        }
+
+        if *bytes.get(at)? != 0xFF {
+            return None;
+        }
+
+        let marker = *bytes.get(at + 1)?;
+
+        match marker {
+            // Start-of-frame markers carry the dimensions; C4, C8 and CC share the range but
+            // are not frames.
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                return Some((be_u16(bytes, at + 7)?, be_u16(bytes, at + 5)?));
+            }
+            0x01 | 0xD0..=0xD8 => at += 2,
+            0xD9 | 0xDA => return None,
+            _ => at += 2 + be_u16(bytes, at + 2)? as usize,
+        }
+    }
+}
+
+fn webp_dimensions(bytes: &[u8]) -> Option<(u32, u32)> {
+    match bytes.get(12..16)? {
+        b"VP8 " => Some((le_u16(bytes, 26)? & 0x3FFF, le_u16(bytes, 28)? & 0x3FFF)),
+        b"VP8L" => {
+            let b = bytes.get(21..25)?;
+            let (b0, b1, b2, b3) = (b[0] as u32, b[1] as u32, b[2] as u32, b[3] as u32);
+
+            Some((
+                1 + (((b1 & 0x3F) << 8) | b0),
+                1 + (((b3 & 0x0F) << 10) | (b2 << 2) | ((b1 & 0xC0) >> 6)),
+            ))
+        }
+        b"VP8X" => Some((1 + le_u24(bytes, 24)?, 1 + le_u24(bytes, 27)?)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn limits() -> ImageLimits {
+        ImageLimits {
+            max_bytes: 1024,
+            max_request_bytes: 4096,
+            max_width: 100,
+            max_height: 100,
+            allow_svg: false,
+        }
+    }
+
+    fn png(width: u32, height: u32) -> Vec<u8> {
+        let mut bytes = PNG_SIGNATURE.to_vec();
+        bytes.extend_from_slice(&13u32.to_be_bytes());
+        bytes.extend_from_slice(b"IHDR");
+        bytes.extend_from_slice(&width.to_be_bytes());
+        bytes.extend_from_slice(&height.to_be_bytes());
+        bytes.extend_from_slice(&[8, 6, 0, 0, 0]);
+        bytes
+    }
+
+    #[test]
+    fn sniffs_each_supported_format() {
+        assert_eq!(sniff_content_type(&png(1, 1)), Some("image/png"));
+        assert_eq!(
+            sniff_content_type(&[0xFF, 0xD8, 0xFF, 0xE0]),
+            Some("image/jpeg")
+        );
+        assert_eq!(
+            sniff_content_type(b"GIF89a\x01\x00\x01\x00"),
+            Some("image/gif")
+        );
+        assert_eq!(
+            sniff_content_type(b"RIFF\x00\x00\x00\x00WEBPVP8 "),
+            Some("image/webp")
+        );
+        assert_eq!(
+            sniff_content_type(b"<?xml version=\"1.0\"?>\n<svg></svg>"),
+            Some("image/svg+xml")
+        );
+        assert_eq!(sniff_content_type(b"hello world"), None);
+    }
+
+    #[test]
+    fn reads_dimensions_from_headers() {
+        assert_eq!(png_dimensions(&png(640, 480)), Some((640, 480)));
+        assert_eq!(gif_dimensions(b"GIF89a\x80\x02\xe0\x01"), Some((640, 480)));
+
+        let jpeg = [
+            0xFF, 0xD8, 0xFF, 0xE0, 0x00, 0x04, 0x00, 0x00, 0xFF, 0xC0, 0x00, 0x11, 0x08, 0x01,
+            0xE0, 0x02, 0x80,
+        ];
+        assert_eq!(jpeg_dimensions(&jpeg), Some((640, 480)));
+
+        let mut webp = b"RIFF\x00\x00\x00\x00WEBPVP8X".to_vec();
+        webp.extend_from_slice(&[0; 8]);
+        webp.extend_from_slice(&[0x7F, 0x02, 0x00, 0xDF, 0x01, 0x00]);
+        assert_eq!(webp_dimensions(&webp), Some((640, 480)));
+    }
+
+    #[test]
+    fn enforces_size_and_dimension_limits() {
+        let image = validate_image(&png(10, 10), None, &limits()).unwrap();
+        assert_eq!(image.content_type, "image/png");
+        assert_eq!(image.dimensions, Some((10, 10)));
+
+        assert!(matches!(
+            validate_image(&png(101, 10), None, &limits()),
+            Err(ApiErrors::PayloadTooLarge(_))
+        ));
+
+        let mut big = png(10, 10);
+        big.resize(2048, 0);
+        assert!(matches!(
+            validate_image(&big, None, &limits()),
+            Err(ApiErrors::PayloadTooLarge(_))
+        ));
+    }
+
+    #[test]
+    fn rejects_mismatched_or_disallowed_types() {
+        assert!(matches!(
+            validate_image(&png(10, 10), Some("image/gif"), &limits()),
+            Err(ApiErrors::UnsupportedMediaType(_))
+        ));
+
+        let svg = b"<svg xmlns=\"http://www.w3.org/2000/svg\"></svg>";
+        assert!(matches!(
+            validate_image(svg, None, &limits()),
+            Err(ApiErrors::UnsupportedMediaType(_))
+        ));
+
+        let allow_svg = ImageLimits {
+            allow_svg: true,
+            ..limits()
+        };
+        assert!(validate_image(svg, None, &allow_svg).is_ok());
+        assert!(matches!(
+            validate_image(b"<svg><script>alert(1)</script></svg>", None, &allow_svg),
+            Err(ApiErrors::UnsupportedMediaType(_))
+        ));
+    }
+
+    #[test]
+    fn svgs_with_handlers_or_external_links_are_refused() {
+        let allow_svg = ImageLimits {
+            allow_svg: true,
+            ..limits()
+        };
+
+        let drawing = br##"<?xml version="1.0"?>
+<!-- logo -->
+<svg xmlns="http://www.w3.org/2000/svg" xmlns:xlink="http://www.w3.org/1999/xlink">
+  <defs><linearGradient id="a"><stop offset="0" stop-color="#fff"/></linearGradient></defs>
+  <path id="p" d="M0 0h10v10z" fill="url(#a)"/>
+  <use xlink:href="#p" x="12"/>
+  <filter id="f"><feGaussianBlur stdDeviation="2"/></filter>
+  <text>a &gt; b</text>
+</svg>"##;
+        assert!(validate_image(drawing, None, &allow_svg).is_ok());
+
+        for payload in [
+            &br#"<svg onload="alert(1)"></svg>"#[..],
+            br#"<svg><rect ONLOAD = 'alert(1)'/></svg>"#,
+            br#"<svg><animate onbegin="alert(1)" attributeName="x"/></svg>"#,
+            br#"<svg><set attributeName="href" to="javascript:alert(1)"/></svg>"#,
+            br#"<svg><use href="data:image/svg+xml;base64,PHN2Zz4="/></svg>"#,
+            br#"<svg><a xlink:href="&#106;avascript:alert(1)"><rect/></a></svg>"#,
+            br#"<svg><svg:script xmlns:svg="http://www.w3.org/2000/svg">alert(1)</svg:script></svg>"#,
+            br#"<!DOCTYPE svg [<!ENTITY x "alert(1)">]><svg></svg>"#,
+            br#"<svg><foreignObject><body/></foreignObject></svg>"#,
+            br#"<svg><rect title="x>" onclick="alert(1)"/></svg>"#,
+        ] {
+            assert!(
+                matches!(
+                    validate_image(payload, None, &allow_svg),
+                    Err(ApiErrors::UnsupportedMediaType(_))
+                ),
+                "accepted {}",
+                String::from_utf8_lossy(payload)
+            );
+        }
+    }
+}
@@ -0,0 +1,174 @@
+use std::{io::Cursor, path::PathBuf};
+
+use image::{
+    DynamicImage, ImageEncoder, ImageReader, ImageResult,
+    codecs::{avif::AvifEncoder, webp::WebPEncoder},
+    imageops::FilterType,
+};
+
+use crate::{
+    errors::api_errors::ApiErrors,
+    image::dto::{ImageBody, ImageUpload},
+};
+
+/// Named widths every upload is rendered at; images are never upscaled past their original.
+pub const VARIANT_SIZES: [(&str, u32); 3] = [("thumbnail", 320), ("card", 640), ("hero", 1600)];
+
+pub const VARIANT_FORMATS: [&str; 2] = ["webp", "avif"];
+
+pub struct RenderedVariant {
+    pub name: &'static str,
+    pub format: &'static str,
+    pub width: u32,
+    pub bytes: Vec<u8>,
+}
+
+impl RenderedVariant {
+    pub fn content_type(&self) -> String {
+        format!("image/{}", self.format)
+    }
+}
+
+/// Storage key of a variant: `<stem>_<name>.<format>` next to the original `<stem>.<ext>`.
+pub fn variant_key(public_id: &str, name: &str, format: &str) -> String {
+    let stem = public_id
+        .rsplit_once('.')
+        .map_or(public_id, |(stem, _)| stem);
+
+    format!("{stem}_{name}.{format}")
+}
+
+/// Every key [`variant_key`] can produce for `public_id`, for clean-up.
+pub fn variant_keys(public_id: &str) -> Vec<String> {
+    VARIANT_SIZES
+        .iter()
+        .flat_map(|(name, _)| {
+            VARIANT_FORMATS
+                .iter()
+                .map(move |format| variant_key(public_id, name, format))
+        })
+        .collect()
+}
+
+/// Originals are named by a bare UUID, so an underscore marks a derived file.
+pub fn is_variant_key(key: &str) -> bool {
+    key.contains('_')
+}
+
+/// The `eager` upload parameter asking Cloudinary for the same set of variants.
+pub fn cloudinary_eager() -> String {
+    VARIANT_SIZES
+        .iter()
+        .flat_map(|(_, width)| {
+            VARIANT_FORMATS
+                .iter()
+                .map(move |format| format!("c_limit,w_{width}/f_{format}"))
+        })
+        .collect::<Vec<_>>()
+        .join("|")
+}
+
+/// Resizes and re-encodes `image` on the blocking pool. SVGs scale on their own and get none.
+pub async fn render_variants(image: &ImageUpload) -> Result<Vec<RenderedVariant>, ApiErrors> {
+    if image.content_type == "image/svg+xml" {
+        return Ok(Vec::new());
+    }
+
+    let source = match &image.body {
+        ImageBody::Memory(bytes) => DecodeSource::Memory(bytes.clone()),
+        ImageBody::Spooled(file) => DecodeSource::File(file.path().to_path_buf()),
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let original = decode(source)
+            .map_err(|_| ApiErrors::BadRequest("Image could not be decoded".to_string()))?;
+
+        let mut variants = Vec::new();
+
+        for (name, width) in VARIANT_SIZES {
+            let resized = if original.width() > width {
+                original.resize(width, u32::MAX, FilterType::Lanczos3)
+            } else {
+                original.clone()
+            };
+
+            for format in VARIANT_FORMATS {
+                variants.push(RenderedVariant {
+                    name,
+                    format,
+                    width: resized.width(),
+                    bytes: encode(&resized, format)?,
+                });
+            }
+        }
+
+        Ok(variants)
+    })
+    .await
+    .map_err(|_| ApiErrors::InternalServerError("Image processing failed".to_string()))?
+}
+
+enum DecodeSource {
+    Memory(Vec<u8>),
+    /// Spooled uploads are decoded straight from their file.
+    File(PathBuf),
+}
+
+fn decode(source: DecodeSource) -> ImageResult<DynamicImage> {
+    match source {
+        DecodeSource::Memory(bytes) => image::load_from_memory(&bytes),
+        DecodeSource::File(path) => ImageReader::open(path)?.with_guessed_format()?.decode(),
+    }
+}
+
+fn encode(image: &DynamicImage, format: &str) -> Result<Vec<u8>, ApiErrors> {
+    let rgba = image.to_rgba8();
+    let mut out = Cursor::new(Vec::new());
+
+    let result = match format {
+        "webp" => WebPEncoder::new_lossless(&mut out).write_image(
+            &rgba,
+            rgba.width(),
+            rgba.height(),
+            image::ExtendedColorType::Rgba8,
+        ),
+        _ => AvifEncoder::new_with_speed_quality(&mut out, 10, 70).write_image(
+            &rgba,
+            rgba.width(),
+            rgba.height(),
+            image::ExtendedColorType::Rgba8,
+        ),
+    };
+
+    result.map_err(|e| ApiErrors::InternalServerError(format!("Image encoding failed: {e}")))?;
+
+    Ok(out.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variant_keys_sit_next_to_the_original() {
+        assert_eq!(
+            variant_key("3f2a.png", "card", "webp"),
+            "3f2a_card.webp".to_string()
+        );
+        assert_eq!(variant_keys("3f2a.png").len(), 6);
+        assert!(
+            variant_keys("3f2a.png")
+                .iter()
+                .all(|key| is_variant_key(key))
+        );
+        assert!(!is_variant_key("3f2a.png"));
+    }
+
+    #[test]
+    fn cloudinary_eager_lists_every_size_and_format() {
+        assert_eq!(
+            cloudinary_eager(),
+            "c_limit,w_320/f_webp|c_limit,w_320/f_avif|c_limit,w_640/f_webp|c_limit,w_640/f_avif|c_limit,w_1600/f_webp|c_limit,w_1600/f_avif"
+        );
+    }
+}
@@ -0,0 +1,3 @@
+pub mod mailer;
+pub mod mailer_file;
+pub mod mailer_smtp;
@@ -0,0 +1,36 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+
+use crate::{
+    config::MailerConfig,
+    errors::api_errors::ApiErrors,
+    mail::{mailer_file::FileMailer, mailer_smtp::SmtpMailer},
+};
+
+/// A plain-text email.
+#[derive(Debug, Clone)]
+pub struct Mail {
+    pub to: String,
+    pub subject: String,
+    pub body: String,
+}
+
+/// Where outgoing email goes. Swapped for a file/log stand-in outside production.
+#[async_trait]
+pub trait Mailer: Send + Sync {
+    async fn send(&self, mail: Mail) -> Result<(), ApiErrors>;
+}
+
+pub fn mailer_from_config(config: MailerConfig) -> Arc<dyn Mailer> {
+    match config {
+        MailerConfig::Smtp {
+            host,
+            port,
+            username,
+            password,
+            from,
+        } => Arc::new(SmtpMailer::new(&host, port, username, password, &from)),
+        MailerConfig::File { dir } => Arc::new(FileMailer::new(dir.map(Into::into))),
+    }
+}
@@ -0,0 +1,60 @@
+use std::path::PathBuf;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use uuid::Uuid;
+
+use crate::{
+    errors::api_errors::ApiErrors,
+    mail::mailer::{Mail, Mailer},
+};
+
+/// Prints every email and, given a directory, also writes it there as `<timestamp>-<id>.eml`
+/// so links in it can be followed during local development.
+pub struct FileMailer {
+    dir: Option<PathBuf>,
+}
+
+impl FileMailer {
+    pub fn new(dir: Option<PathBuf>) -> Self {
+        Self { dir }
+    }
+}
+
+#[async_trait]
+impl Mailer for FileMailer {
+    async fn send(&self, mail: Mail) -> Result<(), ApiErrors> {
+        let message = format!(
+            "To: {}\r\nSubject: {}\r\n\r\n{}\r\n",
+            mail.to, mail.subject, mail.body
+        );
+
+        let Some(dir) = &self.dir else {
+            println!("📧 mail to {}: {}\n{}", mail.to, mail.subject, mail.body);
+            return Ok(());
+        };
+
+        tokio::fs::create_dir_all(dir)
+            .await
+            .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+        let path = dir.join(format!(
+            "{}-{}.eml",
+            Utc::now().format("%Y%m%dT%H%M%S%.3f"),
+            Uuid::new_v4()
+        ));
+
+        // Written aside and renamed into place, so a reader never sees half an email.
+        let partial = path.with_extension("eml.part");
+        tokio::fs::write(&partial, message)
+            .await
+            .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+        tokio::fs::rename(&partial, &path)
+            .await
+            .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+        println!("📧 mail to {} written to {}", mail.to, path.display());
+
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use lettre::{
+    AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor,
+    message::{Mailbox, header::ContentType},
+    transport::smtp::authentication::Credentials,
+};
+
+use crate::{
+    errors::api_errors::ApiErrors,
+    mail::mailer::{Mail, Mailer},
+};
+
+/// How long one conversation with the relay may take before the send is given up.
+const SMTP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Sends through an SMTP relay over STARTTLS.
+pub struct SmtpMailer {
+    transport: AsyncSmtpTransport<Tokio1Executor>,
+    from: Mailbox,
+}
+
+impl SmtpMailer {
+    pub fn new(
+        host: &str,
+        port: u16,
+        username: Option<String>,
+        password: Option<String>,
+        from: &str,
+    ) -> Self {
+        let mut transport = AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+            .expect("SMTP_HOST must be a valid host")
+            .port(port)
+            .timeout(Some(SMTP_TIMEOUT));
+
+        if let (Some(username), Some(password)) = (username, password) {
+            transport = transport.credentials(Credentials::new(username, password));
+        }
+
+        Self {
+            transport: transport.build(),
+            from: from.parse().expect("MAIL_FROM must be a valid mailbox"),
+        }
+    }
+}
+
+#[async_trait]
+impl Mailer for SmtpMailer {
+    async fn send(&self, mail: Mail) -> Result<(), ApiErrors> {
+        let to: Mailbox = mail
+            .to
+            .parse()
+            .map_err(|_| ApiErrors::BadRequest("Invalid recipient".to_string()))?;
+
+        let message = Message::builder()
+            .from(self.from.clone())
+            .to(to)
+            .subject(mail.subject)
+            .header(ContentType::TEXT_PLAIN)
+            .body(mail.body)
+            .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+        self.transport
+            .send(message)
+            .await
+            .map_err(|e| ApiErrors::ServiceUnavailable(format!("SMTP send failed: {e}")))?;
+
+        Ok(())
+    }
+}
@@ -1,4 +1,5 @@
 mod api;
+mod api_key;
 mod auth;
 mod blog;
 mod config;
@@ -6,36 +7,48 @@ mod core;
 mod errors;
 mod extractor;
 mod fields;
+mod health;
 mod image;
+mod mail;
+mod media;
+mod oidc;
 mod payload_handler;
 mod project;
+mod rate_limit;
 mod refresh_token;
 mod response;
 mod stack;
 mod state;
+#[cfg(test)]
+mod tests;
+mod token_denylist;
 mod utils;
 
-use std::net::SocketAddr;
+use std::{net::SocketAddr, time::Duration};
 
 use sqlx::postgres::PgPoolOptions;
-use tokio::{net::TcpListener, sync::mpsc};
+use tokio::net::TcpListener;
 
-use auth::{actor::AuthActor, messages::AuthMessage};
 use axum::http::header::{AUTHORIZATION, CONTENT_TYPE};
-use axum::http::{HeaderValue, Method};
+use axum::http::{HeaderName, HeaderValue, Method};
 use tower_http::cors::CorsLayer;
 
 use crate::{
-    api::app_apis,
-    blog::{actor::BlogActor, messages::BlogMessage},
+    api::{AppApisBuilder, AppRepositories},
+    api_key::repo_sqlx::ApiKeyRepoSqlx,
+    auth::repo_sqlx::UserRepoSqlx,
+    blog::repo_sqlx::BlogRepoSqlx,
     config::Config,
-    image::{actor::ImageActor, messages::ImageMessage},
-    project::{actor::ProjectActor, messages::ProjectMessage},
-    refresh_token::{
-        actor::RefreshTokenActor, messages::RefreshTokenMessage, repo_sqlx::RefreshTokenRepoSqlx,
-    },
-    stack::{actor::StackActor, messages::StackMessage},
-    state::AppState,
+    core::jwt_keys::JwtKeys,
+    extractor::api_key_extractor::API_KEY_HEADER,
+    image::store::image_store_from_config,
+    mail::mailer::mailer_from_config,
+    media::repo_sqlx::MediaRepoSqlx,
+    project::repo_sqlx::ProjectRepoSqlx,
+    rate_limit::store::rate_limit_store_from_config,
+    refresh_token::repo_sqlx::RefreshTokenRepoSqlx,
+    stack::repo_sqlx::StackRepoSqlx,
+    token_denylist::store::denylist_store_from_config,
 };
 
 #[tokio::main]
@@ -48,56 +61,44 @@ async fn main() {
         .await
         .unwrap();
 
-    let (auth_tx, auth_rx) = mpsc::channel::<AuthMessage>(32);
-
-    let (stack_tx, stack_rx) = mpsc::channel::<StackMessage>(32);
-
-    let (image_tx, image_rx) = mpsc::channel::<ImageMessage>(32);
-
-    let (blog_tx, blog_rx) = mpsc::channel::<BlogMessage>(32);
-
-    let (project_tx, project_rx) = mpsc::channel::<ProjectMessage>(32);
-
-    let (refresh_token_tx, refresh_token_rx) = mpsc::channel::<RefreshTokenMessage>(32);
-
-    tokio::spawn(AuthActor::new(pool.clone()).run(auth_rx));
-
-    tokio::spawn(StackActor::new(pool.clone()).run(stack_rx));
-
-    tokio::spawn(
-        ImageActor::new(
-            config.cloud_name,
-            config.cloud_api_key,
-            config.cloud_api_secret,
-        )
-        .run(image_rx),
-    );
-
-    tokio::spawn(BlogActor::new(pool.clone()).run(blog_rx));
-
-    tokio::spawn(ProjectActor::new(pool.clone()).run(project_rx));
-
-    let refresh_token_repo = RefreshTokenRepoSqlx { pool: pool.clone() };
-
-    tokio::spawn(
-        RefreshTokenActor::new(
-            refresh_token_repo,
-            config.jwt_secret.clone(),
-            config.jwt_expiry_hour,
-        )
-        .run(refresh_token_rx),
-    );
-
-    let app_state = AppState {
-        auth_tx,
-        stack_tx,
-        image_tx,
-        blog_tx,
-        project_tx,
-        refresh_token_tx,
-        jwt_secret: config.jwt_secret.clone(),
+    let repos = AppRepositories {
+        users: UserRepoSqlx { pool: pool.clone() },
+        stacks: StackRepoSqlx { pool: pool.clone() },
+        blogs: BlogRepoSqlx { pool: pool.clone() },
+        projects: ProjectRepoSqlx { pool: pool.clone() },
+        refresh_tokens: RefreshTokenRepoSqlx { pool: pool.clone() },
+        media: MediaRepoSqlx { pool: pool.clone() },
+        api_keys: ApiKeyRepoSqlx { pool: pool.clone() },
     };
 
+    let image_store = image_store_from_config(config.image_store);
+
+    let jwt_keys = JwtKeys::from_config(config.jwt_keys).unwrap_or_else(|e| panic!("{e}"));
+
+    let mut app_apis = AppApisBuilder::new(repos, image_store, jwt_keys)
+        .tokens(config.tokens)
+        .image_limits(config.image_limits)
+        .mailer(mailer_from_config(config.mailer))
+        .password_reset(config.password_reset)
+        .login_throttle(config.login_throttle)
+        .two_factor(config.two_factor)
+        .token_cleanup(config.token_cleanup)
+        .trust_forwarded_for(config.trust_forwarded_for)
+        .rate_limits(config.rate_limits)
+        .rate_limit_store(rate_limit_store_from_config(config.rate_limit_store, &pool))
+        .denylist_store(denylist_store_from_config(
+            config.token_denylist_store,
+            &pool,
+        ));
+
+    if let Some(oidc) = config.oidc {
+        app_apis = app_apis.oidc(oidc);
+    }
+
+    if let Some(minutes) = config.image_reconcile_interval_minutes {
+        app_apis = app_apis.image_reconcile_interval(Duration::from_secs(minutes * 60));
+    }
+
     let allowed_origins = [
         "http://localhost:5173",
         "http://localhost:5175",
@@ -121,7 +122,11 @@ async fn main() {
             Method::DELETE,
             Method::OPTIONS,
         ])
-        .allow_headers([AUTHORIZATION, CONTENT_TYPE])
+        .allow_headers([
+            AUTHORIZATION,
+            CONTENT_TYPE,
+            HeaderName::from_static(API_KEY_HEADER),
+        ])
         .allow_credentials(true);
 
     // let cors = CorsLayer::new()
@@ -140,7 +145,7 @@ async fn main() {
     //     ])
     //     .allow_headers(tower_http::cors::Any);
 
-    let app = app_apis(app_state).layer(cors);
+    let app = app_apis.build().layer(cors);
 
     let port = config.port;
 
@@ -150,5 +155,10 @@ async fn main() {
 
     println!("🚀 Server runnings at http://{addr}");
 
-    axum::serve(listener, app).await.unwrap();
+    axum::serve(
+        listener,
+        app.into_make_service_with_connect_info::<SocketAddr>(),
+    )
+    .await
+    .unwrap();
 }
@@ -0,0 +1,367 @@
+use tokio::sync::mpsc::{self, Sender};
+use uuid::Uuid;
+
+use crate::{
+    config::ImageLimits,
+    core::image_core::{
+        delete_image_core, fetch_image_core, register_image_core, tag_image_core,
+        upload_image_core, verify_confirmation_core,
+    },
+    errors::api_errors::ApiErrors,
+    image::{
+        dto::ImageUpload,
+        messages::{ImageMessage, ImageUploadResult},
+    },
+    media::{
+        dispatcher::media_dispatcher,
+        dto::{
+            ConfirmedUpload, CreateMediaData, MediaEntity, MediaQuery, MediaSource, MediaUpload,
+            UpdatedMediaData, Uploader,
+        },
+        messages::{MediaDetail, MediaMessage, MediaResponse},
+        repo::MediaRepository,
+    },
+};
+
+pub struct MediaActor<R>
+where
+    R: MediaRepository + Send + Sync + 'static,
+{
+    pub repo: R,
+    pub image_tx: Sender<ImageMessage>,
+    pub limits: ImageLimits,
+}
+
+impl<R> MediaActor<R>
+where
+    R: MediaRepository + Send + Sync + 'static,
+{
+    pub fn new(repo: R, image_tx: Sender<ImageMessage>, limits: ImageLimits) -> Self {
+        Self {
+            repo,
+            image_tx,
+            limits,
+        }
+    }
+
+    pub async fn run(self, rx: mpsc::Receiver<MediaMessage>) {
+        media_dispatcher(&self, rx).await;
+    }
+
+    /// Validates and stores an upload, or returns the existing item holding the same bytes.
+    pub async fn upload(&self, upload: MediaUpload) -> Result<MediaResponse, ApiErrors> {
+        let mut image = match upload.source {
+            MediaSource::Base64(base64) => ImageUpload::from_base64(&base64, &self.limits)?,
+            MediaSource::File { file, content_type } => {
+                ImageUpload::from_spooled(file, content_type.as_deref(), &self.limits).await?
+            }
+        };
+
+        image.entity = upload.entity;
+
+        if let Some(existing) = self
+            .existing_copy(&image, upload.retain, &upload.alt, &upload.caption)
+            .await?
+        {
+            return Ok(existing);
+        }
+
+        let facts = ImageFacts::of(&image);
+
+        let stored = upload_image_core(image, &self.image_tx).await?;
+
+        self.insert(
+            facts,
+            stored,
+            upload.alt,
+            upload.caption,
+            upload.retain,
+            upload.uploaded_by,
+        )
+        .await
+    }
+
+    /// Validates an image the browser uploaded with a ticket and adds it to the library. Only
+    /// the user the ticket was issued to can confirm it, and only once; an asset that fails
+    /// validation, or duplicates an existing item, is deleted again.
+    pub async fn confirm_upload(
+        &self,
+        upload: ConfirmedUpload,
+    ) -> Result<MediaResponse, ApiErrors> {
+        let public_id = upload.public_id;
+
+        // Nothing below may touch an asset that was not ticketed to this caller.
+        verify_confirmation_core(
+            public_id.clone(),
+            upload.uploaded_by.id,
+            upload.confirmation,
+            &self.image_tx,
+        )
+        .await?;
+
+        if self
+            .repo
+            .find_media_by_public_id(&public_id)
+            .await?
+            .is_some()
+        {
+            return Err(ApiErrors::Conflict("Upload already confirmed".to_string()));
+        }
+
+        let image = match fetch_image_core(public_id.clone(), self.limits.max_bytes, &self.image_tx)
+            .await
+            .and_then(|bytes| ImageUpload::from_bytes(bytes, None, &self.limits))
+        {
+            Ok(image) => image,
+            Err(ApiErrors::NotFound(msg)) => return Err(ApiErrors::NotFound(msg)),
+            Err(e) => {
+                self.discard_asset(&public_id).await;
+                return Err(e);
+            }
+        };
+
+        // Tickets name the key after the declared type, which is what the file is served as.
+        if let Some((_, extension)) = public_id.rsplit_once('.')
+            && extension != image.extension()
+        {
+            self.discard_asset(&public_id).await;
+
+            return Err(ApiErrors::UnsupportedMediaType(format!(
+                "Upload was declared as .{extension} but contains {}",
+                image.content_type
+            )));
+        }
+
+        if let Some(existing) = self
+            .existing_copy(&image, true, &upload.alt, &upload.caption)
+            .await?
+        {
+            self.discard_asset(&public_id).await;
+
+            return Ok(existing);
+        }
+
+        let facts = ImageFacts::of(&image);
+
+        let stored = register_image_core(public_id, image, &self.image_tx).await?;
+
+        self.insert(
+            facts,
+            stored,
+            upload.alt,
+            upload.caption,
+            true,
+            Some(upload.uploaded_by),
+        )
+        .await
+    }
+
+    /// The item already holding `image`'s bytes, promoted to the library when `retain` is set.
+    async fn existing_copy(
+        &self,
+        image: &ImageUpload,
+        retain: bool,
+        alt: &Option<String>,
+        caption: &Option<String>,
+    ) -> Result<Option<MediaResponse>, ApiErrors> {
+        let Some(existing) = self.repo.find_media_by_hash(&image.content_hash).await? else {
+            return Ok(None);
+        };
+
+        if retain && !existing.retain {
+            self.repo
+                .update_media(&UpdatedMediaData {
+                    media_id: existing.id,
+                    alt: alt.clone(),
+                    caption: caption.clone(),
+                    retain: Some(true),
+                })
+                .await?;
+
+            return self.get_media(existing.id).await.map(Some);
+        }
+
+        Ok(Some(existing))
+    }
+
+    async fn insert(
+        &self,
+        facts: ImageFacts,
+        stored: ImageUploadResult,
+        alt: Option<String>,
+        caption: Option<String>,
+        retain: bool,
+        uploaded_by: Option<Uploader>,
+    ) -> Result<MediaResponse, ApiErrors> {
+        let id = Uuid::new_v4();
+
+        let media = CreateMediaData {
+            public_id: stored.public_id,
+            url: stored.url,
+            variants: stored.variants,
+            content_type: facts.content_type,
+            byte_size: facts.byte_size,
+            width: facts.width,
+            height: facts.height,
+            content_hash: facts.content_hash,
+            alt,
+            caption,
+            retain,
+            uploaded_by,
+        };
+
+        self.repo
+            .insert_media(id, &media, chrono::Utc::now().naive_utc())
+            .await?;
+
+        self.get_media(id).await
+    }
+
+    pub async fn get_media(&self, media_id: Uuid) -> Result<MediaResponse, ApiErrors> {
+        self.repo
+            .find_media(media_id)
+            .await?
+            .ok_or_else(|| ApiErrors::NotFound("Media not found".to_string()))
+    }
+
+    pub async fn get_single_media(&self, media_id: Uuid) -> Result<MediaDetail, ApiErrors> {
+        let media = self.get_media(media_id).await?;
+        let usages = self.repo.list_usages(media_id).await?;
+
+        Ok(MediaDetail { media, usages })
+    }
+
+    pub async fn get_all_media(
+        &self,
+        query: MediaQuery,
+    ) -> Result<(Vec<MediaResponse>, u64), ApiErrors> {
+        self.repo.list_media(&query).await
+    }
+
+    pub async fn update_media(&self, media: UpdatedMediaData) -> Result<bool, ApiErrors> {
+        if !self.repo.update_media(&media).await? {
+            return Err(ApiErrors::NotFound("Media not found".to_string()));
+        }
+
+        Ok(true)
+    }
+
+    pub async fn delete_media(&self, media_id: Uuid) -> Result<bool, ApiErrors> {
+        let media = self.get_media(media_id).await?;
+
+        if !self.repo.list_usages(media_id).await?.is_empty() {
+            return Err(ApiErrors::Conflict(
+                "Media is still used by a blog or project".to_string(),
+            ));
+        }
+
+        self.remove(media).await?;
+
+        Ok(true)
+    }
+
+    pub async fn set_usage(
+        &self,
+        entity: MediaEntity,
+        entity_id: Uuid,
+        media_id: Option<Uuid>,
+    ) -> Result<(), ApiErrors> {
+        let previous = self.repo.set_usage(entity, entity_id, media_id).await?;
+
+        if previous == media_id {
+            return Ok(());
+        }
+
+        let tag = format!("{}_{}", entity.as_str(), entity_id);
+
+        if let Some(media_id) = media_id {
+            self.tag_asset(media_id, &tag, true).await?;
+        }
+
+        if let Some(previous) = previous {
+            self.tag_asset(previous, &tag, false).await?;
+            self.release(previous).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Keeps the store's tags in line with usages; they are a convenience for browsing the
+    /// store, so failures are only logged.
+    async fn tag_asset(&self, media_id: Uuid, tag: &str, attach: bool) -> Result<(), ApiErrors> {
+        let Some(media) = self.repo.find_media(media_id).await? else {
+            return Ok(());
+        };
+
+        if let Err(e) = tag_image_core(
+            media.public_id.clone(),
+            tag.to_string(),
+            attach,
+            &self.image_tx,
+        )
+        .await
+        {
+            println!("failed to tag image {}: {e}", media.public_id);
+        }
+
+        Ok(())
+    }
+
+    pub async fn get_image_ids(&self) -> Result<Vec<String>, ApiErrors> {
+        self.repo.list_image_ids().await
+    }
+
+    /// Drops an inline upload once its last user lets go of it; library items stay.
+    async fn release(&self, media_id: Uuid) -> Result<(), ApiErrors> {
+        let Some(media) = self.repo.find_media(media_id).await? else {
+            return Ok(());
+        };
+
+        if media.retain || !self.repo.list_usages(media_id).await?.is_empty() {
+            return Ok(());
+        }
+
+        self.remove(media).await
+    }
+
+    async fn remove(&self, media: MediaResponse) -> Result<(), ApiErrors> {
+        self.repo.delete_media(media.id).await?;
+
+        self.discard_asset(&media.public_id).await;
+
+        Ok(())
+    }
+
+    /// A leftover asset is reported by the reconciliation job, so failures are only logged.
+    async fn discard_asset(&self, public_id: &str) {
+        if let Err(e) = delete_image_core(public_id.to_string(), &self.image_tx).await {
+            println!("failed to delete image {public_id}: {e}");
+        }
+    }
+}
+
+/// What the media row records about an image, taken before the image is handed to the store.
+struct ImageFacts {
+    content_type: String,
+    byte_size: i64,
+    width: Option<i32>,
+    height: Option<i32>,
+    content_hash: String,
+}
+
+impl ImageFacts {
+    fn of(image: &ImageUpload) -> Self {
+        let (width, height) = match image.dimensions {
+            Some((width, height)) => (Some(width as i32), Some(height as i32)),
+            None => (None, None),
+        };
+
+        Self {
+            content_type: image.content_type.clone(),
+            byte_size: image.byte_size as i64,
+            width,
+            height,
+            content_hash: image.content_hash.clone(),
+        }
+    }
+}
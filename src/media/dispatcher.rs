@@ -0,0 +1,55 @@
+use tokio::sync::mpsc;
+
+use crate::media::{actor::MediaActor, messages::MediaMessage, repo::MediaRepository};
+
+pub async fn media_dispatcher<R>(actor: &MediaActor<R>, mut rx: mpsc::Receiver<MediaMessage>)
+where
+    R: MediaRepository + Send + Sync + 'static,
+{
+    while let Some(msg) = rx.recv().await {
+        match msg {
+            MediaMessage::Upload { upload, respond_to } => {
+                let _ = respond_to.send(actor.upload(upload).await);
+            }
+
+            MediaMessage::ConfirmUpload { upload, respond_to } => {
+                let _ = respond_to.send(actor.confirm_upload(upload).await);
+            }
+
+            MediaMessage::GetSingleMedia {
+                media_id,
+                respond_to,
+            } => {
+                let _ = respond_to.send(actor.get_single_media(media_id).await);
+            }
+
+            MediaMessage::GetAllMedia { query, respond_to } => {
+                let _ = respond_to.send(actor.get_all_media(query).await);
+            }
+
+            MediaMessage::UpdateMedia { media, respond_to } => {
+                let _ = respond_to.send(actor.update_media(media).await);
+            }
+
+            MediaMessage::DeleteMedia {
+                media_id,
+                respond_to,
+            } => {
+                let _ = respond_to.send(actor.delete_media(media_id).await);
+            }
+
+            MediaMessage::SetUsage {
+                entity,
+                entity_id,
+                media_id,
+                respond_to,
+            } => {
+                let _ = respond_to.send(actor.set_usage(entity, entity_id, media_id).await);
+            }
+
+            MediaMessage::GetImageIds { respond_to } => {
+                let _ = respond_to.send(actor.get_image_ids().await);
+            }
+        }
+    }
+}
@@ -0,0 +1,131 @@
+use axum::extract::multipart::Field;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{
+    config::ImageLimits,
+    errors::api_errors::ApiErrors,
+    fields::{email::Email, text::Text},
+    image::{
+        messages::ImageVariant,
+        spool::{SpooledFile, spool_field},
+    },
+};
+
+/// What a media item can be attached to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum MediaEntity {
+    Blog,
+    Project,
+}
+
+impl MediaEntity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MediaEntity::Blog => "blog",
+            MediaEntity::Project => "project",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        match value {
+            "blog" => Some(MediaEntity::Blog),
+            "project" => Some(MediaEntity::Project),
+            _ => None,
+        }
+    }
+}
+
+pub struct Uploader {
+    pub id: Uuid,
+    pub name: Text,
+    pub email: Email,
+}
+
+pub enum MediaSource {
+    Base64(String),
+    File {
+        file: SpooledFile,
+        /// The multipart field's declared type, checked against the sniffed one.
+        content_type: Option<String>,
+    },
+}
+
+impl MediaSource {
+    /// Streams a multipart file field to disk.
+    pub async fn from_field(field: Field<'_>, limits: &ImageLimits) -> Result<Self, ApiErrors> {
+        // Generic types such as `application/octet-stream` are left to content sniffing.
+        let content_type = field
+            .content_type()
+            .filter(|content_type| content_type.starts_with("image/"))
+            .map(str::to_string);
+
+        let file = spool_field(field, limits).await?;
+
+        Ok(MediaSource::File { file, content_type })
+    }
+}
+
+pub struct MediaUpload {
+    pub source: MediaSource,
+    /// Set for images uploaded inline with a blog or project.
+    pub entity: Option<MediaEntity>,
+    pub alt: Option<String>,
+    pub caption: Option<String>,
+    /// Library uploads are kept when unused; images uploaded inline with a blog or project
+    /// are removed once nothing references them.
+    pub retain: bool,
+    pub uploaded_by: Option<Uploader>,
+}
+
+/// An image the browser uploaded straight to storage with a ticket.
+pub struct ConfirmedUpload {
+    pub public_id: String,
+    pub confirmation: String,
+    pub alt: Option<String>,
+    pub caption: Option<String>,
+    /// The user confirming, who must be the one the ticket was issued to.
+    pub uploaded_by: Uploader,
+}
+
+pub struct CreateMediaData {
+    pub public_id: String,
+    pub url: String,
+    pub variants: Vec<ImageVariant>,
+    pub content_type: String,
+    pub byte_size: i64,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub content_hash: String,
+    pub alt: Option<String>,
+    pub caption: Option<String>,
+    pub retain: bool,
+    pub uploaded_by: Option<Uploader>,
+}
+
+pub struct UpdatedMediaData {
+    pub media_id: Uuid,
+    pub alt: Option<String>,
+    pub caption: Option<String>,
+    pub retain: Option<bool>,
+}
+
+#[derive(Deserialize)]
+pub struct MediaUploadRequest {
+    pub image: Option<String>,
+    pub alt: Option<String>,
+    pub caption: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct UpdateMediaRequest {
+    pub alt: Option<String>,
+    pub caption: Option<String>,
+}
+
+#[derive(Deserialize)]
+pub struct MediaQuery {
+    pub page: Option<u32>,
+    pub limit: Option<u32>,
+}
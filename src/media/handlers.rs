@@ -0,0 +1,192 @@
+use axum::{
+    Json,
+    extract::{Multipart, Query, State},
+};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::{
+    core::media_core::upload_media_core,
+    errors::api_errors::ApiErrors,
+    extractor::{auth_extractor::AuthUser, json_body::RequiredJson, path_id_extractor::PathParam},
+    media::{
+        dto::{
+            MediaQuery, MediaSource, MediaUpload, MediaUploadRequest, UpdateMediaRequest,
+            UpdatedMediaData, Uploader,
+        },
+        messages::MediaMessage,
+    },
+    state::AppState,
+};
+
+pub async fn upload_media(
+    AuthUser {
+        id, email, name, ..
+    }: AuthUser,
+    State(state): State<AppState>,
+    RequiredJson(payload): RequiredJson<MediaUploadRequest>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let image = payload
+        .image
+        .ok_or_else(|| ApiErrors::BadRequest("Image is required".to_string()))?;
+
+    let upload = MediaUpload {
+        source: MediaSource::Base64(image),
+        entity: None,
+        alt: payload.alt,
+        caption: payload.caption,
+        retain: true,
+        uploaded_by: Some(Uploader { id, name, email }),
+    };
+
+    let media = upload_media_core(upload, &state.media_tx).await?;
+
+    Ok(Json(serde_json::json!({
+        "message": "success".to_string(),
+        "data": media,
+    })))
+}
+
+/// Accepts a `file` field plus optional `alt` and `caption` text fields, in any order.
+pub async fn upload_media_form(
+    AuthUser {
+        id, email, name, ..
+    }: AuthUser,
+    State(state): State<AppState>,
+    mut multipart: Multipart,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let mut file = None;
+    let mut alt = None;
+    let mut caption = None;
+
+    while let Some(field) = multipart.next_field().await? {
+        match field.name() {
+            Some("alt") => alt = Some(field.text().await?),
+            Some("caption") => caption = Some(field.text().await?),
+            _ if file.is_none() => {
+                file = Some(MediaSource::from_field(field, &state.image_limits).await?);
+            }
+            _ => {}
+        }
+    }
+
+    let source = file.ok_or_else(|| ApiErrors::BadRequest("No file provided".to_string()))?;
+
+    let upload = MediaUpload {
+        source,
+        entity: None,
+        alt,
+        caption,
+        retain: true,
+        uploaded_by: Some(Uploader { id, name, email }),
+    };
+
+    let media = upload_media_core(upload, &state.media_tx).await?;
+
+    Ok(Json(serde_json::json!({
+        "message": "success".to_string(),
+        "data": media,
+    })))
+}
+
+pub async fn get_all_media(
+    State(state): State<AppState>,
+    Query(query): Query<MediaQuery>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    state
+        .media_tx
+        .send(MediaMessage::GetAllMedia {
+            query,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
+
+    let media = rx
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))??;
+
+    Ok(Json(serde_json::json!( {
+        "message": "success".to_string(),
+        "data": {"media": media.0, "total": media.1},
+    })))
+}
+
+pub async fn get_single_media(
+    State(state): State<AppState>,
+    PathParam(media_id): PathParam<Uuid>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    state
+        .media_tx
+        .send(MediaMessage::GetSingleMedia {
+            media_id,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
+
+    let media = rx
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))??;
+
+    Ok(Json(serde_json::json!( {
+        "message": "success".to_string(),
+        "data": media,
+    })))
+}
+
+pub async fn update_media(
+    _: AuthUser,
+    State(state): State<AppState>,
+    PathParam(media_id): PathParam<Uuid>,
+    RequiredJson(payload): RequiredJson<UpdateMediaRequest>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    let media = UpdatedMediaData {
+        media_id,
+        alt: payload.alt,
+        caption: payload.caption,
+        retain: None,
+    };
+
+    state
+        .media_tx
+        .send(MediaMessage::UpdateMedia {
+            media,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
+
+    rx.await
+        .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))??;
+
+    Ok(Json(serde_json::json!({"message": "success".to_string(),})))
+}
+
+pub async fn delete_media(
+    _: AuthUser,
+    State(state): State<AppState>,
+    PathParam(media_id): PathParam<Uuid>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    state
+        .media_tx
+        .send(MediaMessage::DeleteMedia {
+            media_id,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
+
+    rx.await
+        .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))??;
+
+    Ok(Json(serde_json::json!({"message": "success".to_string(),})))
+}
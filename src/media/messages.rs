@@ -0,0 +1,88 @@
+use chrono::NaiveDateTime;
+use serde::Serialize;
+use sqlx::{prelude::FromRow, types::Json};
+use tokio::sync::oneshot;
+use uuid::Uuid;
+
+use crate::{
+    errors::api_errors::ApiErrors,
+    image::messages::ImageVariant,
+    media::dto::{ConfirmedUpload, MediaEntity, MediaQuery, MediaUpload, UpdatedMediaData},
+};
+
+#[derive(Debug, Clone, Serialize, FromRow)]
+pub struct MediaResponse {
+    pub id: Uuid,
+    pub public_id: String,
+    pub url: String,
+    pub variants: Json<Vec<ImageVariant>>,
+    pub content_type: Option<String>,
+    pub byte_size: Option<i64>,
+    pub width: Option<i32>,
+    pub height: Option<i32>,
+    pub content_hash: Option<String>,
+    pub alt: Option<String>,
+    pub caption: Option<String>,
+    pub retain: bool,
+    pub uploaded_by: Option<Uuid>,
+    pub uploaded_by_name: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub updated_at: NaiveDateTime,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct MediaUsage {
+    pub entity: MediaEntity,
+    pub entity_id: Uuid,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MediaDetail {
+    #[serde(flatten)]
+    pub media: MediaResponse,
+    pub usages: Vec<MediaUsage>,
+}
+
+pub enum MediaMessage {
+    Upload {
+        upload: MediaUpload,
+        respond_to: oneshot::Sender<Result<MediaResponse, ApiErrors>>,
+    },
+
+    ConfirmUpload {
+        upload: ConfirmedUpload,
+        respond_to: oneshot::Sender<Result<MediaResponse, ApiErrors>>,
+    },
+
+    GetSingleMedia {
+        media_id: Uuid,
+        respond_to: oneshot::Sender<Result<MediaDetail, ApiErrors>>,
+    },
+
+    GetAllMedia {
+        query: MediaQuery,
+        respond_to: oneshot::Sender<Result<(Vec<MediaResponse>, u64), ApiErrors>>,
+    },
+
+    UpdateMedia {
+        media: UpdatedMediaData,
+        respond_to: oneshot::Sender<Result<bool, ApiErrors>>,
+    },
+
+    DeleteMedia {
+        media_id: Uuid,
+        respond_to: oneshot::Sender<Result<bool, ApiErrors>>,
+    },
+
+    /// Points `entity_id` at `media_id` (or at nothing), releasing whatever it used before.
+    SetUsage {
+        entity: MediaEntity,
+        entity_id: Uuid,
+        media_id: Option<Uuid>,
+        respond_to: oneshot::Sender<Result<(), ApiErrors>>,
+    },
+
+    GetImageIds {
+        respond_to: oneshot::Sender<Result<Vec<String>, ApiErrors>>,
+    },
+}
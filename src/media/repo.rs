@@ -0,0 +1,48 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use crate::{
+    errors::api_errors::ApiErrors,
+    media::{
+        dto::{CreateMediaData, MediaEntity, MediaQuery, UpdatedMediaData},
+        messages::{MediaResponse, MediaUsage},
+    },
+};
+
+#[async_trait]
+pub trait MediaRepository: Send + Sync {
+    async fn insert_media(
+        &self,
+        id: Uuid,
+        media: &CreateMediaData,
+        created_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors>;
+
+    async fn find_media(&self, media_id: Uuid) -> Result<Option<MediaResponse>, ApiErrors>;
+
+    async fn find_media_by_hash(&self, hash: &str) -> Result<Option<MediaResponse>, ApiErrors>;
+
+    async fn find_media_by_public_id(
+        &self,
+        public_id: &str,
+    ) -> Result<Option<MediaResponse>, ApiErrors>;
+
+    async fn list_media(&self, query: &MediaQuery) -> Result<(Vec<MediaResponse>, u64), ApiErrors>;
+
+    async fn update_media(&self, media: &UpdatedMediaData) -> Result<bool, ApiErrors>;
+
+    async fn delete_media(&self, media_id: Uuid) -> Result<bool, ApiErrors>;
+
+    async fn list_usages(&self, media_id: Uuid) -> Result<Vec<MediaUsage>, ApiErrors>;
+
+    /// Replaces the usage recorded for an entity and returns the media it pointed at before.
+    async fn set_usage(
+        &self,
+        entity: MediaEntity,
+        entity_id: Uuid,
+        media_id: Option<Uuid>,
+    ) -> Result<Option<Uuid>, ApiErrors>;
+
+    async fn list_image_ids(&self) -> Result<Vec<String>, ApiErrors>;
+}
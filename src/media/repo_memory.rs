@@ -0,0 +1,207 @@
+use std::{cmp::Reverse, sync::Mutex};
+
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::types::Json;
+use uuid::Uuid;
+
+use crate::{
+    errors::api_errors::ApiErrors,
+    media::{
+        dto::{CreateMediaData, MediaEntity, MediaQuery, UpdatedMediaData},
+        messages::{MediaResponse, MediaUsage},
+        repo::MediaRepository,
+    },
+};
+
+struct UsageRow {
+    entity: MediaEntity,
+    entity_id: Uuid,
+    media_id: Uuid,
+}
+
+#[derive(Default)]
+pub struct MediaRepoMemory {
+    media: Mutex<Vec<MediaResponse>>,
+    usages: Mutex<Vec<UsageRow>>,
+}
+
+impl MediaRepoMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl MediaRepository for MediaRepoMemory {
+    async fn insert_media(
+        &self,
+        id: Uuid,
+        media: &CreateMediaData,
+        created_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors> {
+        let mut rows = self.media.lock().unwrap();
+
+        if rows
+            .iter()
+            .any(|m| m.content_hash.as_deref() == Some(media.content_hash.as_str()))
+        {
+            return Err(ApiErrors::Conflict("Media already exists".to_string()));
+        }
+
+        rows.push(MediaResponse {
+            id,
+            public_id: media.public_id.clone(),
+            url: media.url.clone(),
+            variants: Json(media.variants.clone()),
+            content_type: Some(media.content_type.clone()),
+            byte_size: Some(media.byte_size),
+            width: media.width,
+            height: media.height,
+            content_hash: Some(media.content_hash.clone()),
+            alt: media.alt.clone(),
+            caption: media.caption.clone(),
+            retain: media.retain,
+            uploaded_by: media.uploaded_by.as_ref().map(|u| u.id),
+            uploaded_by_name: media
+                .uploaded_by
+                .as_ref()
+                .map(|u| u.name.as_str().to_string()),
+            created_at,
+            updated_at: created_at,
+        });
+
+        Ok(())
+    }
+
+    async fn find_media(&self, media_id: Uuid) -> Result<Option<MediaResponse>, ApiErrors> {
+        let rows = self.media.lock().unwrap();
+
+        Ok(rows.iter().find(|m| m.id == media_id).cloned())
+    }
+
+    async fn find_media_by_hash(&self, hash: &str) -> Result<Option<MediaResponse>, ApiErrors> {
+        let rows = self.media.lock().unwrap();
+
+        Ok(rows
+            .iter()
+            .find(|m| m.content_hash.as_deref() == Some(hash))
+            .cloned())
+    }
+
+    async fn find_media_by_public_id(
+        &self,
+        public_id: &str,
+    ) -> Result<Option<MediaResponse>, ApiErrors> {
+        let rows = self.media.lock().unwrap();
+
+        Ok(rows.iter().find(|m| m.public_id == public_id).cloned())
+    }
+
+    async fn list_media(&self, query: &MediaQuery) -> Result<(Vec<MediaResponse>, u64), ApiErrors> {
+        let rows = self.media.lock().unwrap();
+
+        let page = query.page.unwrap_or(1).max(1) as usize;
+        let limit = query.limit.unwrap_or(20) as usize;
+
+        let mut all: Vec<MediaResponse> = rows.clone();
+        all.sort_by_key(|item| Reverse(item.created_at));
+
+        let total = all.len() as u64;
+
+        let page_items = all
+            .into_iter()
+            .skip((page - 1) * limit)
+            .take(limit)
+            .collect();
+
+        Ok((page_items, total))
+    }
+
+    async fn update_media(&self, media: &UpdatedMediaData) -> Result<bool, ApiErrors> {
+        let mut rows = self.media.lock().unwrap();
+
+        let Some(row) = rows.iter_mut().find(|m| m.id == media.media_id) else {
+            return Ok(false);
+        };
+
+        if let Some(alt) = &media.alt {
+            row.alt = Some(alt.clone());
+        }
+
+        if let Some(caption) = &media.caption {
+            row.caption = Some(caption.clone());
+        }
+
+        if let Some(retain) = media.retain {
+            row.retain = retain;
+        }
+
+        row.updated_at = chrono::Utc::now().naive_utc();
+
+        Ok(true)
+    }
+
+    async fn delete_media(&self, media_id: Uuid) -> Result<bool, ApiErrors> {
+        let mut rows = self.media.lock().unwrap();
+
+        let before = rows.len();
+        rows.retain(|m| m.id != media_id);
+
+        self.usages
+            .lock()
+            .unwrap()
+            .retain(|u| u.media_id != media_id);
+
+        Ok(rows.len() < before)
+    }
+
+    async fn list_usages(&self, media_id: Uuid) -> Result<Vec<MediaUsage>, ApiErrors> {
+        let usages = self.usages.lock().unwrap();
+
+        Ok(usages
+            .iter()
+            .filter(|u| u.media_id == media_id)
+            .map(|u| MediaUsage {
+                entity: u.entity,
+                entity_id: u.entity_id,
+            })
+            .collect())
+    }
+
+    async fn set_usage(
+        &self,
+        entity: MediaEntity,
+        entity_id: Uuid,
+        media_id: Option<Uuid>,
+    ) -> Result<Option<Uuid>, ApiErrors> {
+        if let Some(media_id) = media_id
+            && !self.media.lock().unwrap().iter().any(|m| m.id == media_id)
+        {
+            return Err(ApiErrors::NotFound("Media not found".to_string()));
+        }
+
+        let mut usages = self.usages.lock().unwrap();
+
+        let previous = usages
+            .iter()
+            .position(|u| u.entity == entity && u.entity_id == entity_id)
+            .map(|index| usages.remove(index).media_id);
+
+        if let Some(media_id) = media_id {
+            usages.push(UsageRow {
+                entity,
+                entity_id,
+                media_id,
+            });
+        }
+
+        Ok(previous)
+    }
+
+    async fn list_image_ids(&self) -> Result<Vec<String>, ApiErrors> {
+        let rows = self.media.lock().unwrap();
+
+        Ok(rows.iter().map(|m| m.public_id.clone()).collect())
+    }
+}
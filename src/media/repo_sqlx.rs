@@ -0,0 +1,214 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::{PgPool, types::Json};
+use uuid::Uuid;
+
+use crate::{
+    errors::api_errors::ApiErrors,
+    image::messages::ImageVariant,
+    media::{
+        dto::{CreateMediaData, MediaEntity, MediaQuery, UpdatedMediaData},
+        messages::{MediaResponse, MediaUsage},
+        repo::MediaRepository,
+    },
+};
+
+pub struct MediaRepoSqlx {
+    pub pool: PgPool,
+}
+
+#[async_trait]
+impl MediaRepository for MediaRepoSqlx {
+    async fn insert_media(
+        &self,
+        id: Uuid,
+        media: &CreateMediaData,
+        created_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors> {
+        let uploader = media.uploaded_by.as_ref();
+
+        sqlx::query!(
+            "INSERT INTO media (id, public_id, url, variants, content_type, byte_size, width, height, content_hash, alt, caption, retain, uploaded_by, uploaded_by_name, uploaded_by_email, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)",
+            id,
+            media.public_id,
+            media.url,
+            Json(&media.variants) as _,
+            media.content_type,
+            media.byte_size,
+            media.width,
+            media.height,
+            media.content_hash,
+            media.alt,
+            media.caption,
+            media.retain,
+            uploader.map(|u| u.id),
+            uploader.map(|u| u.name.as_str()),
+            uploader.map(|u| u.email.as_str()),
+            created_at,
+            created_at,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::Conflict("Media already exists".to_string()))?;
+
+        Ok(())
+    }
+
+    async fn find_media(&self, media_id: Uuid) -> Result<Option<MediaResponse>, ApiErrors> {
+        let media = sqlx::query_as!(
+            MediaResponse,
+            r#"SELECT id, public_id, url, variants as "variants: Json<Vec<ImageVariant>>", content_type, byte_size, width, height, content_hash, alt, caption, retain, uploaded_by, uploaded_by_name, created_at, updated_at FROM media WHERE id = $1"#,
+            media_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Media lookup failed".to_string()))?;
+
+        Ok(media)
+    }
+
+    async fn find_media_by_public_id(
+        &self,
+        public_id: &str,
+    ) -> Result<Option<MediaResponse>, ApiErrors> {
+        let media = sqlx::query_as!(
+            MediaResponse,
+            r#"SELECT id, public_id, url, variants as "variants: Json<Vec<ImageVariant>>", content_type, byte_size, width, height, content_hash, alt, caption, retain, uploaded_by, uploaded_by_name, created_at, updated_at FROM media WHERE public_id = $1"#,
+            public_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Media lookup failed".to_string()))?;
+
+        Ok(media)
+    }
+
+    async fn find_media_by_hash(&self, hash: &str) -> Result<Option<MediaResponse>, ApiErrors> {
+        let media = sqlx::query_as!(
+            MediaResponse,
+            r#"SELECT id, public_id, url, variants as "variants: Json<Vec<ImageVariant>>", content_type, byte_size, width, height, content_hash, alt, caption, retain, uploaded_by, uploaded_by_name, created_at, updated_at FROM media WHERE content_hash = $1"#,
+            hash
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Media lookup failed".to_string()))?;
+
+        Ok(media)
+    }
+
+    async fn list_media(&self, query: &MediaQuery) -> Result<(Vec<MediaResponse>, u64), ApiErrors> {
+        let page = query.page.unwrap_or(1).max(1);
+        let limit = query.limit.unwrap_or(20);
+        let offset = (page - 1) * limit;
+
+        let media = sqlx::query_as!(
+            MediaResponse,
+            r#"SELECT id, public_id, url, variants as "variants: Json<Vec<ImageVariant>>", content_type, byte_size, width, height, content_hash, alt, caption, retain, uploaded_by, uploaded_by_name, created_at, updated_at FROM media ORDER BY created_at DESC LIMIT $1 OFFSET $2"#,
+            limit as i64,
+            offset as i64,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed to fetch media".into()))?;
+
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM media")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Failed to count media".into()))?;
+
+        Ok((media, total as u64))
+    }
+
+    async fn update_media(&self, media: &UpdatedMediaData) -> Result<bool, ApiErrors> {
+        let result = sqlx::query!(
+            "UPDATE media SET alt = COALESCE($1, alt), caption = COALESCE($2, caption), retain = COALESCE($3, retain), updated_at = NOW() WHERE id = $4",
+            media.alt,
+            media.caption,
+            media.retain,
+            media.media_id,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_media(&self, media_id: Uuid) -> Result<bool, ApiErrors> {
+        let result = sqlx::query!("DELETE FROM media WHERE id = $1", media_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Media Delete failed".to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn list_usages(&self, media_id: Uuid) -> Result<Vec<MediaUsage>, ApiErrors> {
+        let rows = sqlx::query!(
+            "SELECT entity, entity_id FROM media_usage WHERE media_id = $1 ORDER BY entity, entity_id",
+            media_id
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed to fetch media usage".into()))?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|row| {
+                MediaEntity::parse(&row.entity).map(|entity| MediaUsage {
+                    entity,
+                    entity_id: row.entity_id,
+                })
+            })
+            .collect())
+    }
+
+    async fn set_usage(
+        &self,
+        entity: MediaEntity,
+        entity_id: Uuid,
+        media_id: Option<Uuid>,
+    ) -> Result<Option<Uuid>, ApiErrors> {
+        let mut tx = self
+            .pool
+            .begin()
+            .await
+            .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+        let previous = sqlx::query_scalar!(
+            "DELETE FROM media_usage WHERE entity = $1 AND entity_id = $2 RETURNING media_id",
+            entity.as_str(),
+            entity_id
+        )
+        .fetch_optional(&mut *tx)
+        .await
+        .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+        if let Some(media_id) = media_id {
+            sqlx::query!(
+                "INSERT INTO media_usage (entity, entity_id, media_id) VALUES ($1, $2, $3)",
+                entity.as_str(),
+                entity_id,
+                media_id
+            )
+            .execute(&mut *tx)
+            .await
+            .map_err(|_| ApiErrors::NotFound("Media not found".to_string()))?;
+        }
+
+        tx.commit()
+            .await
+            .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+        Ok(previous)
+    }
+
+    async fn list_image_ids(&self) -> Result<Vec<String>, ApiErrors> {
+        let ids = sqlx::query_scalar!("SELECT public_id FROM media")
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Failed to fetch image ids".into()))?;
+
+        Ok(ids)
+    }
+}
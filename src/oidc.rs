@@ -0,0 +1,5 @@
+pub mod actor;
+pub mod client;
+pub mod dispatcher;
+pub mod handlers;
+pub mod messages;
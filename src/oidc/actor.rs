@@ -0,0 +1,129 @@
+use std::{collections::HashMap, sync::Mutex, time::Instant};
+
+use tokio::sync::{
+    mpsc::{self, Sender},
+    oneshot,
+};
+
+use crate::{
+    auth::{dto::LoginResponse, messages::AuthMessage},
+    errors::api_errors::ApiErrors,
+    fields::email::Email,
+    oidc::{
+        client::OidcClient,
+        dispatcher::oidc_dispatcher,
+        messages::{OidcMessage, StartedLogin},
+    },
+    utils::tokens::generate_opaque_token,
+};
+
+/// What a login started with, kept until the browser comes back with its `state`.
+struct PendingLogin {
+    nonce: String,
+    code_verifier: String,
+    expires_at: Instant,
+}
+
+/// Single sign-on through an OpenID provider. Logins in flight live in memory: they last
+/// minutes, and one lost to a restart only means signing in again.
+pub struct OidcActor {
+    client: OidcClient,
+    pending: Mutex<HashMap<String, PendingLogin>>,
+    /// Provider identities map onto existing users by email, which the auth actor owns.
+    auth_tx: Sender<AuthMessage>,
+}
+
+impl OidcActor {
+    pub fn new(client: OidcClient, auth_tx: Sender<AuthMessage>) -> Self {
+        Self {
+            client,
+            pending: Mutex::new(HashMap::new()),
+            auth_tx,
+        }
+    }
+
+    pub async fn run(self, rx: mpsc::Receiver<OidcMessage>) {
+        oidc_dispatcher(&self, rx).await;
+    }
+
+    pub async fn begin(&self) -> Result<StartedLogin, ApiErrors> {
+        let state = generate_opaque_token();
+        let nonce = generate_opaque_token();
+        let code_verifier = generate_opaque_token();
+
+        let url = self
+            .client
+            .authorization_url(&state, &nonce, &code_verifier)
+            .await?;
+
+        let now = Instant::now();
+        let mut pending = self.pending.lock().unwrap();
+        pending.retain(|_, login| login.expires_at > now);
+        pending.insert(
+            state.clone(),
+            PendingLogin {
+                nonce,
+                code_verifier,
+                expires_at: now + self.client.login_ttl(),
+            },
+        );
+
+        Ok(StartedLogin {
+            url,
+            state,
+            expires_in: self.client.login_ttl(),
+        })
+    }
+
+    /// A `state` is good for one attempt, whatever its outcome.
+    pub async fn complete(&self, code: String, state: String) -> Result<LoginResponse, ApiErrors> {
+        let login = self
+            .pending
+            .lock()
+            .unwrap()
+            .remove(&state)
+            .filter(|login| login.expires_at > Instant::now())
+            .ok_or_else(|| ApiErrors::Unauthorized("Invalid or expired login".to_string()))?;
+
+        let claims = self
+            .client
+            .sign_in(&code, &login.code_verifier, &login.nonce)
+            .await?;
+
+        // An unverified address could belong to anyone who typed it in at the provider.
+        let email = claims
+            .email
+            .filter(|_| claims.email_verified == Some(true))
+            .ok_or_else(|| {
+                ApiErrors::Unauthorized("The identity provider has not verified this email".into())
+            })?;
+
+        let unknown = || ApiErrors::Unauthorized("No account matches this identity".to_string());
+
+        let email = Email::new(&email).map_err(|_| unknown())?;
+
+        let (tx, rx) = oneshot::channel();
+
+        self.auth_tx
+            .send(AuthMessage::LoginVerifiedEmail {
+                email,
+                respond_to: tx,
+            })
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
+
+        let response = rx
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))??
+            .ok_or_else(unknown)?;
+
+        if let LoginResponse::Authenticated { id } = &response {
+            println!(
+                "oidc: signed in user {id} as provider subject {}",
+                claims.sub
+            );
+        }
+
+        Ok(response)
+    }
+}
@@ -0,0 +1,318 @@
+use std::{str::FromStr, time::Duration};
+
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use jsonwebtoken::{
+    Algorithm, DecodingKey, Validation, decode, decode_header,
+    jwk::{AlgorithmParameters, EllipticCurve, Jwk, JwkSet, KeyAlgorithm},
+};
+use reqwest::Client;
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use tokio::sync::OnceCell;
+use url::Url;
+
+use crate::{config::OidcConfig, errors::api_errors::ApiErrors};
+
+/// The parts of the provider's discovery document the code flow needs.
+#[derive(Deserialize)]
+struct ProviderMetadata {
+    issuer: String,
+    authorization_endpoint: String,
+    token_endpoint: String,
+    jwks_uri: String,
+    /// Required by the spec, but RS256 is what it defaults to for providers that leave it out.
+    #[serde(default = "default_signing_algs")]
+    id_token_signing_alg_values_supported: Vec<String>,
+}
+
+fn default_signing_algs() -> Vec<String> {
+    vec!["RS256".to_string()]
+}
+
+#[derive(Deserialize)]
+struct TokenResponse {
+    id_token: String,
+}
+
+/// Claims of the provider's ID token that sign-in relies on.
+#[derive(Deserialize)]
+pub struct IdTokenClaims {
+    pub sub: String,
+    pub email: Option<String>,
+    pub email_verified: Option<bool>,
+    pub nonce: Option<String>,
+}
+
+/// Talks to the OpenID provider. Discovery is fetched once; the provider's keys are fetched
+/// for every sign-in so its rotations are picked up without a restart.
+pub struct OidcClient {
+    config: OidcConfig,
+    http: Client,
+    metadata: OnceCell<ProviderMetadata>,
+}
+
+impl OidcClient {
+    pub fn new(config: OidcConfig) -> Self {
+        Self {
+            config,
+            http: Client::builder()
+                .timeout(Duration::from_secs(10))
+                .build()
+                .expect("static reqwest client"),
+            metadata: OnceCell::new(),
+        }
+    }
+
+    pub fn login_ttl(&self) -> Duration {
+        self.config.login_ttl
+    }
+
+    async fn metadata(&self) -> Result<&ProviderMetadata, ApiErrors> {
+        self.metadata
+            .get_or_try_init(|| async {
+                let url = format!(
+                    "{}/.well-known/openid-configuration",
+                    self.config.issuer.trim_end_matches('/')
+                );
+
+                let metadata: ProviderMetadata = self
+                    .http
+                    .get(&url)
+                    .send()
+                    .await
+                    .and_then(|res| res.error_for_status())
+                    .map_err(|_| unavailable())?
+                    .json()
+                    .await
+                    .map_err(|_| unavailable())?;
+
+                if metadata.issuer.trim_end_matches('/') != self.config.issuer.trim_end_matches('/')
+                {
+                    println!(
+                        "oidc: discovery at {url} reports issuer {}, expected {}",
+                        metadata.issuer, self.config.issuer
+                    );
+                    return Err(unavailable());
+                }
+
+                Ok(metadata)
+            })
+            .await
+    }
+
+    /// Where to send the browser to sign in, bound to `state`, `nonce` and the PKCE challenge
+    /// derived from `code_verifier`.
+    pub async fn authorization_url(
+        &self,
+        state: &str,
+        nonce: &str,
+        code_verifier: &str,
+    ) -> Result<String, ApiErrors> {
+        let metadata = self.metadata().await?;
+
+        let mut url = Url::parse(&metadata.authorization_endpoint).map_err(|_| unavailable())?;
+        url.query_pairs_mut()
+            .append_pair("response_type", "code")
+            .append_pair("client_id", &self.config.client_id)
+            .append_pair("redirect_uri", &self.config.redirect_uri)
+            .append_pair("scope", &self.config.scopes)
+            .append_pair("state", state)
+            .append_pair("nonce", nonce)
+            .append_pair("code_challenge", &pkce_challenge(code_verifier))
+            .append_pair("code_challenge_method", "S256");
+
+        Ok(url.to_string())
+    }
+
+    /// Redeems `code` at the token endpoint and checks the ID token that comes back: signed
+    /// by the provider, issued to us and carrying the `nonce` this login started with.
+    pub async fn sign_in(
+        &self,
+        code: &str,
+        code_verifier: &str,
+        nonce: &str,
+    ) -> Result<IdTokenClaims, ApiErrors> {
+        let metadata = self.metadata().await?;
+
+        let mut form = vec![
+            ("grant_type", "authorization_code"),
+            ("code", code),
+            ("redirect_uri", &self.config.redirect_uri),
+            ("client_id", &self.config.client_id),
+            ("code_verifier", code_verifier),
+        ];
+        if let Some(secret) = &self.config.client_secret {
+            form.push(("client_secret", secret));
+        }
+
+        let res = self
+            .http
+            .post(&metadata.token_endpoint)
+            .form(&form)
+            .send()
+            .await
+            .map_err(|_| unavailable())?;
+
+        if res.status().is_client_error() {
+            return Err(rejected());
+        }
+
+        let tokens: TokenResponse = res
+            .error_for_status()
+            .map_err(|_| unavailable())?
+            .json()
+            .await
+            .map_err(|_| unavailable())?;
+
+        let claims = self.verify_id_token(metadata, &tokens.id_token).await?;
+
+        if claims.nonce.as_deref() != Some(nonce) {
+            return Err(rejected());
+        }
+
+        Ok(claims)
+    }
+
+    async fn verify_id_token(
+        &self,
+        metadata: &ProviderMetadata,
+        id_token: &str,
+    ) -> Result<IdTokenClaims, ApiErrors> {
+        let header = decode_header(id_token).map_err(|_| rejected())?;
+
+        let jwks: JwkSet = self
+            .http
+            .get(&metadata.jwks_uri)
+            .send()
+            .await
+            .and_then(|res| res.error_for_status())
+            .map_err(|_| unavailable())?
+            .json()
+            .await
+            .map_err(|_| unavailable())?;
+
+        let jwk = match &header.kid {
+            Some(kid) => jwks.find(kid),
+            None => jwks.keys.first(),
+        }
+        .ok_or_else(rejected)?;
+
+        // The key decides the algorithm, never the token.
+        let advertised: Vec<Algorithm> = metadata
+            .id_token_signing_alg_values_supported
+            .iter()
+            .filter_map(|alg| Algorithm::from_str(alg).ok())
+            .collect();
+        if !key_algorithms(jwk, &advertised).contains(&header.alg) {
+            return Err(rejected());
+        }
+
+        let key = DecodingKey::from_jwk(jwk).map_err(|_| rejected())?;
+
+        let mut validation = Validation::new(header.alg);
+        validation.set_issuer(&[&metadata.issuer]);
+        validation.set_audience(&[&self.config.client_id]);
+        validation.set_required_spec_claims(&["exp", "iss", "aud", "sub"]);
+
+        decode::<IdTokenClaims>(id_token, &key, &validation)
+            .map(|data| data.claims)
+            .map_err(|_| rejected())
+    }
+}
+
+/// What `jwk` may verify: the algorithm it names, or else those of its key type the provider
+/// advertises. Shared secrets never qualify, as anyone holding the client secret could sign.
+fn key_algorithms(jwk: &Jwk, advertised: &[Algorithm]) -> Vec<Algorithm> {
+    if let Some(named) = jwk.common.key_algorithm {
+        return match named {
+            KeyAlgorithm::RS256 => vec![Algorithm::RS256],
+            KeyAlgorithm::RS384 => vec![Algorithm::RS384],
+            KeyAlgorithm::RS512 => vec![Algorithm::RS512],
+            KeyAlgorithm::PS256 => vec![Algorithm::PS256],
+            KeyAlgorithm::PS384 => vec![Algorithm::PS384],
+            KeyAlgorithm::PS512 => vec![Algorithm::PS512],
+            KeyAlgorithm::ES256 => vec![Algorithm::ES256],
+            KeyAlgorithm::ES384 => vec![Algorithm::ES384],
+            KeyAlgorithm::EdDSA => vec![Algorithm::EdDSA],
+            _ => Vec::new(),
+        };
+    }
+
+    let of_key_type: &[Algorithm] = match &jwk.algorithm {
+        AlgorithmParameters::RSA(_) => &[
+            Algorithm::RS256,
+            Algorithm::RS384,
+            Algorithm::RS512,
+            Algorithm::PS256,
+            Algorithm::PS384,
+            Algorithm::PS512,
+        ],
+        AlgorithmParameters::EllipticCurve(params) => match params.curve {
+            EllipticCurve::P256 => &[Algorithm::ES256],
+            EllipticCurve::P384 => &[Algorithm::ES384],
+            _ => &[],
+        },
+        AlgorithmParameters::OctetKeyPair(params) if params.curve == EllipticCurve::Ed25519 => {
+            &[Algorithm::EdDSA]
+        }
+        _ => &[],
+    };
+
+    of_key_type
+        .iter()
+        .filter(|alg| advertised.contains(alg))
+        .copied()
+        .collect()
+}
+
+/// The S256 code challenge: base64url of the verifier's SHA-256, without padding.
+pub fn pkce_challenge(code_verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(code_verifier.as_bytes()))
+}
+
+fn unavailable() -> ApiErrors {
+    ApiErrors::ServiceUnavailable("Identity provider unavailable".to_string())
+}
+
+fn rejected() -> ApiErrors {
+    ApiErrors::Unauthorized("Single sign-on failed".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use serde_json::json;
+
+    use super::*;
+
+    fn jwk(value: serde_json::Value) -> Jwk {
+        serde_json::from_value(value).unwrap()
+    }
+
+    #[test]
+    fn the_key_not_the_token_picks_the_algorithm() {
+        let named = jwk(json!({ "kty": "RSA", "alg": "RS256", "n": "AQAB", "e": "AQAB" }));
+        assert_eq!(
+            key_algorithms(&named, &[Algorithm::PS256]),
+            [Algorithm::RS256]
+        );
+
+        let unnamed = jwk(json!({ "kty": "RSA", "n": "AQAB", "e": "AQAB" }));
+        assert_eq!(
+            key_algorithms(
+                &unnamed,
+                &[Algorithm::HS256, Algorithm::RS256, Algorithm::ES256]
+            ),
+            [Algorithm::RS256]
+        );
+
+        let ed25519 = jwk(json!({ "kty": "OKP", "crv": "Ed25519", "x": "AAAA" }));
+        assert_eq!(
+            key_algorithms(&ed25519, &[Algorithm::RS256, Algorithm::EdDSA]),
+            [Algorithm::EdDSA]
+        );
+        assert!(key_algorithms(&ed25519, &[Algorithm::RS256]).is_empty());
+
+        let secret = jwk(json!({ "kty": "oct", "alg": "HS256", "k": "c2VjcmV0" }));
+        assert!(key_algorithms(&secret, &[Algorithm::HS256]).is_empty());
+    }
+}
@@ -0,0 +1,21 @@
+use tokio::sync::mpsc;
+
+use crate::oidc::{actor::OidcActor, messages::OidcMessage};
+
+pub async fn oidc_dispatcher(actor: &OidcActor, mut rx: mpsc::Receiver<OidcMessage>) {
+    while let Some(msg) = rx.recv().await {
+        match msg {
+            OidcMessage::Begin { respond_to } => {
+                let _ = respond_to.send(actor.begin().await);
+            }
+
+            OidcMessage::Complete {
+                code,
+                state,
+                respond_to,
+            } => {
+                let _ = respond_to.send(actor.complete(code, state).await);
+            }
+        }
+    }
+}
@@ -0,0 +1,125 @@
+use axum::{
+    Json,
+    extract::State,
+    response::{IntoResponse, Redirect},
+};
+use tokio::sync::{mpsc::Sender, oneshot};
+use tower_cookies::Cookies;
+
+use crate::{
+    auth::dto::LoginResponse,
+    core::login_token_core::login_token_core,
+    errors::api_errors::ApiErrors,
+    extractor::{client_info_extractor::ClientInfo, json_body::RequiredJson},
+    oidc::messages::OidcMessage,
+    payload_handler::auth_payload_handler::OidcCallbackRequest,
+    state::AppState,
+    utils::{
+        cookies::{OIDC_STATE_COOKIE, clear_oidc_state_cookie, set_oidc_state_cookie},
+        tokens::tokens_match,
+    },
+};
+
+fn oidc_tx(state: &AppState) -> Result<&Sender<OidcMessage>, ApiErrors> {
+    state
+        .oidc_tx
+        .as_ref()
+        .ok_or_else(|| ApiErrors::NotFound("Single sign-on is not configured".to_string()))
+}
+
+/// Sends the browser to the identity provider to sign in, remembering in a cookie which login
+/// it started.
+pub async fn oidc_login(
+    cookies: Cookies,
+    State(state): State<AppState>,
+) -> Result<impl IntoResponse, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    oidc_tx(&state)?
+        .send(OidcMessage::Begin { respond_to: tx })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
+
+    let started = rx
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))??;
+
+    cookies.add(set_oidc_state_cookie(
+        started.state,
+        &state.tokens.cookie,
+        started.expires_in,
+    ));
+
+    Ok(Redirect::to(&started.url))
+}
+
+/// Takes the `code` and `state` the provider redirected back with and signs the matching user
+/// in like a password login, two-factor challenge included. Only the browser that started the
+/// login, holding its `state` cookie, can finish it.
+pub async fn oidc_callback(
+    cookies: Cookies,
+    client: ClientInfo,
+    State(state): State<AppState>,
+    RequiredJson(payload): RequiredJson<OidcCallbackRequest>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let oidc_tx = oidc_tx(&state)?;
+
+    let payload_data = payload.validate()?;
+
+    // Without this, a callback carrying someone else's code and state could sign this browser
+    // in as them.
+    let started_here = cookies
+        .get(OIDC_STATE_COOKIE)
+        .is_some_and(|cookie| tokens_match(cookie.value(), &payload_data.state));
+    cookies.add(clear_oidc_state_cookie(&state.tokens.cookie));
+
+    if !started_here {
+        return Err(ApiErrors::Unauthorized(
+            "Invalid or expired login".to_string(),
+        ));
+    }
+
+    let (tx, rx) = oneshot::channel();
+
+    oidc_tx
+        .send(OidcMessage::Complete {
+            code: payload_data.code,
+            state: payload_data.state,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
+
+    let user_id = match rx
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))??
+    {
+        LoginResponse::Authenticated { id } => id,
+        LoginResponse::TwoFactorRequired {
+            challenge_token,
+            setup_required,
+        } => {
+            return Ok(Json(serde_json::json!({
+                "message": "two_factor_required".to_string(),
+                "data": {
+                    "challenge_token": challenge_token,
+                    "setup_required": setup_required,
+                }
+            })));
+        }
+    };
+
+    let tokens = login_token_core(
+        &state.refresh_token_tx,
+        &state.tokens,
+        cookies,
+        user_id,
+        client,
+    )
+    .await?;
+
+    Ok(Json(serde_json::json!({
+        "message": "success".to_string(),
+        "token": tokens.access_token,
+    })))
+}
@@ -0,0 +1,26 @@
+use std::time::Duration;
+
+use tokio::sync::oneshot;
+
+use crate::{auth::dto::LoginResponse, errors::api_errors::ApiErrors};
+
+pub struct StartedLogin {
+    /// The provider URL to send the browser to.
+    pub url: String,
+    /// Also kept in the browser, which has to present it again on the way back.
+    pub state: String,
+    pub expires_in: Duration,
+}
+
+pub enum OidcMessage {
+    Begin {
+        respond_to: oneshot::Sender<Result<StartedLogin, ApiErrors>>,
+    },
+
+    /// Finishes the login `state` belongs to, answering like a password login would.
+    Complete {
+        code: String,
+        state: String,
+        respond_to: oneshot::Sender<Result<LoginResponse, ApiErrors>>,
+    },
+}
@@ -1,3 +1,4 @@
+pub mod api_key_payload_handler;
 pub mod auth_payload_handler;
 pub mod blog_payload_handler;
 pub mod project_payload_handler;
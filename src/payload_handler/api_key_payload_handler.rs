@@ -0,0 +1,48 @@
+use chrono::Duration;
+use serde::Deserialize;
+
+use crate::{
+    api_key::dto::{ApiScope, ValidatedCreateApiKey},
+    errors::api_errors::ApiErrors,
+    fields::text::Text,
+};
+
+#[derive(Deserialize)]
+pub struct CreateApiKeyRequest {
+    pub name: Option<String>,
+    pub scopes: Option<Vec<String>>,
+    /// Left out for a key that never expires.
+    pub expires_in_days: Option<u32>,
+}
+
+impl CreateApiKeyRequest {
+    pub fn validate(self) -> Result<ValidatedCreateApiKey, ApiErrors> {
+        let name = self
+            .name
+            .ok_or_else(|| ApiErrors::BadRequest("Name is required".to_string()))?;
+
+        let scopes = self
+            .scopes
+            .filter(|scopes| !scopes.is_empty())
+            .ok_or_else(|| ApiErrors::BadRequest("At least one scope is required".to_string()))?;
+
+        let expires_at = match self.expires_in_days {
+            Some(0) => {
+                return Err(ApiErrors::BadRequest(
+                    "expires_in_days must be at least 1".to_string(),
+                ));
+            }
+            Some(days) => Some(chrono::Utc::now().naive_utc() + Duration::days(i64::from(days))),
+            None => None,
+        };
+
+        Ok(ValidatedCreateApiKey {
+            name: Text::new(&name)?,
+            scopes: scopes
+                .iter()
+                .map(|scope| ApiScope::new(scope))
+                .collect::<Result<_, _>>()?,
+            expires_at,
+        })
+    }
+}
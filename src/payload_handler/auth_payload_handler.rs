@@ -1,5 +1,9 @@
 use crate::{
-    auth::dto::{ValidatedLogin, ValidatedRegister},
+    auth::dto::{
+        ValidatedChangePassword, ValidatedDisableTwoFactor, ValidatedForgotPassword,
+        ValidatedLogin, ValidatedOidcCallback, ValidatedRegister, ValidatedResetPassword,
+        ValidatedTwoFactorLogin,
+    },
     errors::api_errors::ApiErrors,
 };
 use serde::Deserialize;
@@ -60,3 +64,148 @@ impl LoginRequest {
         Ok(ValidatedLogin { email, password })
     }
 }
+
+#[derive(Deserialize)]
+pub struct ForgotPasswordRequest {
+    pub email: Option<String>,
+}
+
+impl ForgotPasswordRequest {
+    pub fn validate(self) -> Result<ValidatedForgotPassword, ApiErrors> {
+        let email = self
+            .email
+            .ok_or_else(|| ApiErrors::BadRequest("Email is required".to_string()))?;
+
+        Ok(ValidatedForgotPassword { email })
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ResetPasswordRequest {
+    pub token: Option<String>,
+    pub password: Option<String>,
+}
+
+impl ResetPasswordRequest {
+    pub fn validate(self) -> Result<ValidatedResetPassword, ApiErrors> {
+        let token = self
+            .token
+            .ok_or_else(|| ApiErrors::BadRequest("Token is required".to_string()))?;
+
+        let password = self
+            .password
+            .ok_or_else(|| ApiErrors::BadRequest("Password is required".to_string()))?;
+
+        Ok(ValidatedResetPassword { token, password })
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ChangePasswordRequest {
+    pub current_password: Option<String>,
+    pub new_password: Option<String>,
+}
+
+impl ChangePasswordRequest {
+    pub fn validate(self) -> Result<ValidatedChangePassword, ApiErrors> {
+        let current_password = self
+            .current_password
+            .ok_or_else(|| ApiErrors::BadRequest("Current password is required".to_string()))?;
+
+        let new_password = self
+            .new_password
+            .ok_or_else(|| ApiErrors::BadRequest("New password is required".to_string()))?;
+
+        Ok(ValidatedChangePassword {
+            current_password,
+            new_password,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TwoFactorLoginRequest {
+    pub challenge_token: Option<String>,
+    pub code: Option<String>,
+}
+
+impl TwoFactorLoginRequest {
+    pub fn validate(self) -> Result<ValidatedTwoFactorLogin, ApiErrors> {
+        let challenge_token = self
+            .challenge_token
+            .ok_or_else(|| ApiErrors::BadRequest("Challenge token is required".to_string()))?;
+
+        let code = self
+            .code
+            .ok_or_else(|| ApiErrors::BadRequest("Code is required".to_string()))?;
+
+        Ok(ValidatedTwoFactorLogin {
+            challenge_token,
+            code,
+        })
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TwoFactorChallengeRequest {
+    pub challenge_token: Option<String>,
+}
+
+impl TwoFactorChallengeRequest {
+    pub fn validate(self) -> Result<String, ApiErrors> {
+        self.challenge_token
+            .ok_or_else(|| ApiErrors::BadRequest("Challenge token is required".to_string()))
+    }
+}
+
+#[derive(Deserialize)]
+pub struct TwoFactorCodeRequest {
+    pub code: Option<String>,
+}
+
+impl TwoFactorCodeRequest {
+    pub fn validate(self) -> Result<String, ApiErrors> {
+        self.code
+            .ok_or_else(|| ApiErrors::BadRequest("Code is required".to_string()))
+    }
+}
+
+#[derive(Deserialize)]
+pub struct DisableTwoFactorRequest {
+    pub password: Option<String>,
+    pub code: Option<String>,
+}
+
+impl DisableTwoFactorRequest {
+    pub fn validate(self) -> Result<ValidatedDisableTwoFactor, ApiErrors> {
+        let password = self
+            .password
+            .ok_or_else(|| ApiErrors::BadRequest("Password is required".to_string()))?;
+
+        let code = self
+            .code
+            .ok_or_else(|| ApiErrors::BadRequest("Code is required".to_string()))?;
+
+        Ok(ValidatedDisableTwoFactor { password, code })
+    }
+}
+
+#[derive(Deserialize)]
+pub struct OidcCallbackRequest {
+    pub code: Option<String>,
+    pub state: Option<String>,
+}
+
+impl OidcCallbackRequest {
+    pub fn validate(self) -> Result<ValidatedOidcCallback, ApiErrors> {
+        let code = self
+            .code
+            .ok_or_else(|| ApiErrors::BadRequest("Code is required".to_string()))?;
+
+        let state = self
+            .state
+            .ok_or_else(|| ApiErrors::BadRequest("State is required".to_string()))?;
+
+        Ok(ValidatedOidcCallback { code, state })
+    }
+}
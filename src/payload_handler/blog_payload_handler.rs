@@ -1,4 +1,5 @@
 use serde::Deserialize;
+use uuid::Uuid;
 
 use crate::{blog::dto::ValidatedCreateBlogData, errors::api_errors::ApiErrors};
 
@@ -9,6 +10,7 @@ pub struct BlogCreateRequest {
     pub content: Option<String>,
     pub word_count: Option<i32>,
     pub image: Option<String>,
+    pub media_id: Option<Uuid>,
 }
 
 impl BlogCreateRequest {
@@ -31,16 +33,19 @@ impl BlogCreateRequest {
             .word_count
             .ok_or_else(|| ApiErrors::BadRequest("Word Count is required".to_string()))?;
 
-        let image = self
-            .image
-            .ok_or_else(|| ApiErrors::BadRequest("Image is required".to_string()))?;
+        if self.image.is_none() && self.media_id.is_none() {
+            return Err(ApiErrors::BadRequest(
+                "Image or media_id is required".to_string(),
+            ));
+        }
 
         Ok(ValidatedCreateBlogData {
             title,
             description,
             content,
             word_count,
-            image,
+            image: self.image,
+            media_id: self.media_id,
         })
     }
 }
@@ -1,6 +1,7 @@
 use chrono::NaiveDate;
 use serde::Deserialize;
 use url::Url;
+use uuid::Uuid;
 
 use crate::{errors::api_errors::ApiErrors, project::dto::ValidatedCreateProjectData};
 
@@ -18,6 +19,7 @@ pub struct ProjectCreateRequest {
     pub content: Option<String>,
     pub word_count: Option<i32>,
     pub image: Option<String>,
+    pub media_id: Option<Uuid>,
 }
 
 impl ProjectCreateRequest {
@@ -62,9 +64,11 @@ impl ProjectCreateRequest {
             .word_count
             .ok_or_else(|| ApiErrors::BadRequest("Word Count is required".to_string()))?;
 
-        let image = self
-            .image
-            .ok_or_else(|| ApiErrors::BadRequest("Image is required".to_string()))?;
+        if self.image.is_none() && self.media_id.is_none() {
+            return Err(ApiErrors::BadRequest(
+                "Image or media_id is required".to_string(),
+            ));
+        }
 
         Url::parse(&link).map_err(|_| {
             ApiErrors::BadRequest("Invalid link format. Must be a valid URL".to_string())
@@ -90,7 +94,8 @@ impl ProjectCreateRequest {
             stack,
             content,
             word_count,
-            image,
+            image: self.image,
+            media_id: self.media_id,
         })
     }
 }
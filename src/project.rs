@@ -3,3 +3,7 @@ pub mod dispatcher;
 pub mod dto;
 pub mod handlers;
 pub mod messages;
+pub mod repo;
+#[cfg(test)]
+pub mod repo_memory;
+pub mod repo_sqlx;
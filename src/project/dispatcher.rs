@@ -1,8 +1,11 @@
 use tokio::sync::mpsc;
 
-use crate::project::{actor::ProjectActor, messages::ProjectMessage};
+use crate::project::{actor::ProjectActor, messages::ProjectMessage, repo::ProjectRepository};
 
-pub async fn project_dispatcher(actor: &ProjectActor, mut rx: mpsc::Receiver<ProjectMessage>) {
+pub async fn project_dispatcher<R>(actor: &ProjectActor<R>, mut rx: mpsc::Receiver<ProjectMessage>)
+where
+    R: ProjectRepository + Send + Sync + 'static,
+{
     while let Some(msg) = rx.recv().await {
         match msg {
             ProjectMessage::Create {
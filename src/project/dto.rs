@@ -3,7 +3,10 @@ use uuid::Uuid;
 
 use chrono::NaiveDate;
 
-use crate::fields::{email::Email, text::Text};
+use crate::{
+    fields::{email::Email, text::Text},
+    image::messages::ImageVariant,
+};
 
 pub struct CreateProjectData {
     pub title: String,
@@ -19,6 +22,8 @@ pub struct CreateProjectData {
     pub word_count: i32,
     pub image: String,
     pub image_id: String,
+    pub image_variants: Vec<ImageVariant>,
+    pub media_id: Uuid,
     pub created_by: Uuid,
     pub created_by_name: Text,
     pub created_by_email: Email,
@@ -39,6 +44,8 @@ pub struct UpdatedProjectData {
     pub word_count: Option<i32>,
     pub image: Option<String>,
     pub image_id: Option<String>,
+    pub image_variants: Option<Vec<ImageVariant>>,
+    pub media_id: Option<Uuid>,
     pub edited_by: Uuid,
     pub edited_by_name: Text,
     pub edited_by_email: Email,
@@ -56,7 +63,8 @@ pub struct ValidatedCreateProjectData {
     pub stack: String,
     pub content: String,
     pub word_count: i32,
-    pub image: String,
+    pub image: Option<String>,
+    pub media_id: Option<Uuid>,
 }
 
 #[derive(Deserialize)]
@@ -72,6 +80,7 @@ pub struct UpdateProjectRequest {
     pub content: Option<String>,
     pub word_count: Option<i32>,
     pub image: Option<String>,
+    pub media_id: Option<Uuid>,
 }
 
 #[derive(Deserialize)]
@@ -6,12 +6,14 @@ use tokio::sync::oneshot;
 use uuid::Uuid;
 
 use crate::{
+    core::media_core::resolve_media_core,
     errors::api_errors::ApiErrors,
     extractor::{
         auth_extractor::AuthUser,
         path_id_extractor::PathParam,
         project_extractor::{ProjectCreateInput, ProjectUpateInput},
     },
+    media::dto::{MediaEntity, Uploader},
     project::{
         dto::{CreateProjectData, ProjectQuery, UpdatedProjectData},
         messages::ProjectMessage,
@@ -26,6 +28,22 @@ pub async fn create_project(
     State(state): State<AppState>,
     payload: ProjectCreateInput,
 ) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let uploader = Uploader {
+        id,
+        name: name.clone(),
+        email: email.clone(),
+    };
+
+    let media = resolve_media_core(
+        MediaEntity::Project,
+        payload.image,
+        payload.media_id,
+        uploader,
+        &state.media_tx,
+    )
+    .await?
+    .ok_or_else(|| ApiErrors::BadRequest("Image or media_id is required".to_string()))?;
+
     let (tx, rx) = oneshot::channel();
 
     let project = CreateProjectData {
@@ -40,8 +58,10 @@ pub async fn create_project(
         stack: payload.stack,
         content: payload.content,
         word_count: payload.word_count,
-        image: payload.image,
-        image_id: payload.image_id,
+        image: media.url,
+        image_id: media.public_id,
+        image_variants: media.variants.0,
+        media_id: media.id,
         created_by: id,
         created_by_name: name,
         created_by_email: email,
@@ -167,6 +187,31 @@ pub async fn update_project(
     PathParam(project_id): PathParam<Uuid>,
     payload: ProjectUpateInput,
 ) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let uploader = Uploader {
+        id,
+        name: name.clone(),
+        email: email.clone(),
+    };
+
+    let media = resolve_media_core(
+        MediaEntity::Project,
+        payload.image,
+        payload.media_id,
+        uploader,
+        &state.media_tx,
+    )
+    .await?;
+
+    let (image, image_id, image_variants, media_id) = match media {
+        Some(media) => (
+            Some(media.url),
+            Some(media.public_id),
+            Some(media.variants.0),
+            Some(media.id),
+        ),
+        None => (None, None, None, None),
+    };
+
     let (tx, rx) = oneshot::channel();
 
     let project = UpdatedProjectData {
@@ -181,8 +226,10 @@ pub async fn update_project(
         stack: payload.stack,
         content: payload.content,
         word_count: payload.word_count,
-        image: payload.image,
-        image_id: payload.image_id,
+        image,
+        image_id,
+        image_variants,
+        media_id,
         edited_by: id,
         edited_by_name: name,
         edited_by_email: email,
@@ -1,15 +1,16 @@
 use chrono::{NaiveDate, NaiveDateTime};
 use serde::Serialize;
-use sqlx::prelude::FromRow;
+use sqlx::{prelude::FromRow, types::Json};
 use tokio::sync::oneshot;
 use uuid::Uuid;
 
 use crate::{
     errors::api_errors::ApiErrors,
+    image::messages::ImageVariant,
     project::dto::{CreateProjectData, ProjectQuery, UpdatedProjectData},
 };
 
-#[derive(Debug, Serialize, FromRow)]
+#[derive(Debug, Clone, Serialize, FromRow)]
 pub struct ProjectResponse {
     pub id: Uuid,
     pub title: String,
@@ -25,6 +26,8 @@ pub struct ProjectResponse {
     pub word_count: i32,
     pub image: String,
     pub image_id: String,
+    pub image_variants: Json<Vec<ImageVariant>>,
+    pub media_id: Option<Uuid>,
     pub created_at: NaiveDateTime,
     pub updated_at: NaiveDateTime,
 }
@@ -0,0 +1,34 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use crate::{
+    errors::api_errors::ApiErrors,
+    project::{
+        dto::{CreateProjectData, ProjectQuery, UpdatedProjectData},
+        messages::ProjectResponse,
+    },
+};
+
+#[async_trait]
+pub trait ProjectRepository: Send + Sync {
+    async fn insert_project(
+        &self,
+        id: Uuid,
+        project: &CreateProjectData,
+        created_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors>;
+
+    async fn find_project(&self, project_id: Uuid) -> Result<Option<ProjectResponse>, ApiErrors>;
+
+    async fn list_projects(
+        &self,
+        query: &ProjectQuery,
+    ) -> Result<(Vec<ProjectResponse>, u64), ApiErrors>;
+
+    async fn count_projects(&self) -> Result<u64, ApiErrors>;
+
+    async fn update_project(&self, project: &UpdatedProjectData) -> Result<bool, ApiErrors>;
+
+    async fn delete_project(&self, project_id: Uuid) -> Result<bool, ApiErrors>;
+}
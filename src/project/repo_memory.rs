@@ -0,0 +1,180 @@
+use std::{cmp::Reverse, sync::Mutex};
+
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::types::Json;
+use uuid::Uuid;
+
+use crate::{
+    errors::api_errors::ApiErrors,
+    project::{
+        dto::{CreateProjectData, ProjectQuery, UpdatedProjectData},
+        messages::ProjectResponse,
+        repo::ProjectRepository,
+    },
+};
+
+#[derive(Default)]
+pub struct ProjectRepoMemory {
+    projects: Mutex<Vec<ProjectResponse>>,
+}
+
+impl ProjectRepoMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl ProjectRepository for ProjectRepoMemory {
+    async fn insert_project(
+        &self,
+        id: Uuid,
+        project: &CreateProjectData,
+        created_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors> {
+        let mut projects = self.projects.lock().unwrap();
+
+        if projects.iter().any(|p| p.title == project.title) {
+            return Err(ApiErrors::Conflict("Project already exists".to_string()));
+        }
+
+        projects.push(ProjectResponse {
+            id,
+            title: project.title.clone(),
+            description: project.description.clone(),
+            company: project.company.clone(),
+            role: project.role.clone(),
+            start_date: project.start_date,
+            end_date: project.end_date,
+            tag: project.tag.clone(),
+            link: project.link.clone(),
+            stack: project.stack.clone(),
+            content: project.content.clone(),
+            word_count: project.word_count,
+            image: project.image.clone(),
+            image_id: project.image_id.clone(),
+            image_variants: Json(project.image_variants.clone()),
+            media_id: Some(project.media_id),
+            created_at,
+            updated_at: created_at,
+        });
+
+        Ok(())
+    }
+
+    async fn find_project(&self, project_id: Uuid) -> Result<Option<ProjectResponse>, ApiErrors> {
+        let projects = self.projects.lock().unwrap();
+
+        Ok(projects.iter().find(|p| p.id == project_id).cloned())
+    }
+
+    async fn list_projects(
+        &self,
+        query: &ProjectQuery,
+    ) -> Result<(Vec<ProjectResponse>, u64), ApiErrors> {
+        let projects = self.projects.lock().unwrap();
+
+        let page = query.page.unwrap_or(1).max(1) as usize;
+        let limit = query.limit.unwrap_or(10) as usize;
+
+        let mut matched: Vec<ProjectResponse> = projects
+            .iter()
+            .filter(|p| match &query.title {
+                Some(title) => p.title.to_lowercase().contains(&title.to_lowercase()),
+                None => true,
+            })
+            .cloned()
+            .collect();
+        matched.sort_by_key(|item| Reverse(item.created_at));
+
+        let total = matched.len() as u64;
+
+        let page_items = matched
+            .into_iter()
+            .skip((page - 1) * limit)
+            .take(limit)
+            .collect();
+
+        Ok((page_items, total))
+    }
+
+    async fn count_projects(&self) -> Result<u64, ApiErrors> {
+        Ok(self.projects.lock().unwrap().len() as u64)
+    }
+
+    async fn update_project(&self, project: &UpdatedProjectData) -> Result<bool, ApiErrors> {
+        let mut projects = self.projects.lock().unwrap();
+
+        let Some(row) = projects.iter_mut().find(|p| p.id == project.project_id) else {
+            return Ok(false);
+        };
+
+        if let Some(description) = &project.description {
+            row.description = description.clone();
+        }
+
+        if let Some(company) = &project.company {
+            row.company = company.clone();
+        }
+
+        if let Some(role) = &project.role {
+            row.role = role.clone();
+        }
+
+        if let Some(start_date) = project.start_date {
+            row.start_date = start_date;
+        }
+
+        if let Some(end_date) = project.end_date {
+            row.end_date = Some(end_date);
+        }
+
+        if let Some(tag) = &project.tag {
+            row.tag = tag.clone();
+        }
+
+        if let Some(link) = &project.link {
+            row.link = link.clone();
+        }
+
+        if let Some(stack) = &project.stack {
+            row.stack = stack.clone();
+        }
+
+        if let Some(content) = &project.content {
+            row.content = content.clone();
+        }
+
+        if let Some(word_count) = project.word_count {
+            row.word_count = word_count;
+        }
+
+        if let Some(image) = &project.image {
+            row.image = image.clone();
+        }
+
+        if let Some(image_id) = &project.image_id {
+            row.image_id = image_id.clone();
+        }
+
+        if let Some(image_variants) = &project.image_variants {
+            row.image_variants = Json(image_variants.clone());
+        }
+
+        if let Some(media_id) = project.media_id {
+            row.media_id = Some(media_id);
+        }
+
+        Ok(true)
+    }
+
+    async fn delete_project(&self, project_id: Uuid) -> Result<bool, ApiErrors> {
+        let mut projects = self.projects.lock().unwrap();
+
+        let before = projects.len();
+        projects.retain(|p| p.id != project_id);
+
+        Ok(projects.len() < before)
+    }
+}
@@ -0,0 +1,166 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::{PgPool, Postgres, QueryBuilder, types::Json};
+use uuid::Uuid;
+
+use crate::{
+    errors::api_errors::ApiErrors,
+    image::messages::ImageVariant,
+    project::{
+        dto::{CreateProjectData, ProjectQuery, UpdatedProjectData},
+        messages::ProjectResponse,
+        repo::ProjectRepository,
+    },
+};
+
+pub struct ProjectRepoSqlx {
+    pub pool: PgPool,
+}
+
+#[async_trait]
+impl ProjectRepository for ProjectRepoSqlx {
+    async fn insert_project(
+        &self,
+        id: Uuid,
+        project: &CreateProjectData,
+        created_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors> {
+        sqlx::query!(
+            "INSERT INTO project (id, title, description, company, role, start_date, end_date, tag, link, stack, content, word_count, image, image_id, image_variants, media_id, created_by, created_by_name, created_by_email, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21)",
+            id,
+            project.title,
+            project.description,
+            project.company,
+            project.role,
+            project.start_date,
+            project.end_date,
+            project.tag,
+            project.link,
+            project.stack,
+            project.content,
+            project.word_count,
+            project.image,
+            project.image_id,
+            Json(&project.image_variants) as _,
+            project.media_id,
+            project.created_by,
+            project.created_by_name.as_str(),
+            project.created_by_email.as_str(),
+            created_at,
+            created_at,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::Conflict("Project already exists".to_string()))?;
+
+        Ok(())
+    }
+
+    async fn find_project(&self, project_id: Uuid) -> Result<Option<ProjectResponse>, ApiErrors> {
+        let project = sqlx::query_as!(
+            ProjectResponse,
+            r#"SELECT id, title, description, company, role, start_date, end_date, tag, link, stack, content, word_count, image, image_id, image_variants as "image_variants: Json<Vec<ImageVariant>>", media_id, created_at, updated_at FROM project WHERE id = $1"#,
+            project_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Project lookup failed".to_string()))?;
+
+        Ok(project)
+    }
+
+    async fn list_projects(
+        &self,
+        query: &ProjectQuery,
+    ) -> Result<(Vec<ProjectResponse>, u64), ApiErrors> {
+        let page = query.page.unwrap_or(1);
+        let limit = query.limit.unwrap_or(10);
+        let offset = (page - 1) * limit;
+
+        // 🔹 MAIN QUERY
+        let mut qb = QueryBuilder::<Postgres>::new(
+            "SELECT id, title, description, company, role, start_date, end_date, tag, link, stack, content, word_count, image, image_id, image_variants, media_id, created_at, updated_at FROM project",
+        );
+
+        // 🔹 COUNT QUERY (for meta)
+        let mut count_qb = QueryBuilder::<Postgres>::new("SELECT COUNT(*) as count FROM project");
+
+        // 🔍 FILTER
+        if let Some(title) = &query.title {
+            qb.push(" WHERE title ILIKE ");
+            qb.push_bind(format!("%{}%", title));
+
+            count_qb.push(" WHERE title ILIKE ");
+            count_qb.push_bind(format!("%{}%", title));
+        }
+
+        // 🔹 ORDER + PAGINATION
+        qb.push(" ORDER BY created_at DESC");
+        qb.push(" LIMIT ");
+        qb.push_bind(limit as i64);
+        qb.push(" OFFSET ");
+        qb.push_bind(offset as i64);
+
+        // 🔹 FETCH DATA
+        let projects = qb
+            .build_query_as::<ProjectResponse>()
+            .fetch_all(&self.pool)
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Failed to fetch project".into()))?;
+
+        // 🔹 FETCH COUNT
+        let total: (i64,) = count_qb
+            .build_query_as()
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Failed to count project".into()))?;
+
+        Ok((projects, total.0 as u64))
+    }
+
+    async fn count_projects(&self) -> Result<u64, ApiErrors> {
+        let total: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM project")
+            .fetch_one(&self.pool)
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Failed to count project".into()))?;
+
+        Ok(total as u64)
+    }
+
+    async fn update_project(&self, project: &UpdatedProjectData) -> Result<bool, ApiErrors> {
+        let result = sqlx::query!(r#"UPDATE project SET description = COALESCE($1, description), company = COALESCE($2, company), role = COALESCE($3, role), start_date = COALESCE($4, start_date), end_date = COALESCE($5, end_date), tag = COALESCE($6, tag), link = COALESCE($7, link), stack = COALESCE($8, stack), content = COALESCE($9, content), word_count = COALESCE($10, word_count), image = COALESCE($11, image), image_id = COALESCE($12, image_id), image_variants = COALESCE($13, image_variants), media_id = COALESCE($14, media_id), edited_by = $15, edited_by_name = $16, edited_by_email = $17 WHERE id = $18"#, 
+                project.description,
+                project.company,
+                project.role,
+                project.start_date,
+                project.end_date,
+                project.tag,
+                project.link,
+                project.stack,
+                project.content,
+                project.word_count,
+                project.image,
+                project.image_id,
+                project.image_variants.as_ref().map(Json) as _,
+                project.media_id,
+                project.edited_by,
+                project.edited_by_name.as_str(),
+                project.edited_by_email.as_str(),
+                project.project_id,
+            )
+            .execute(&self.pool)
+            .await
+            .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_project(&self, project_id: Uuid) -> Result<bool, ApiErrors> {
+        let result = sqlx::query!("DELETE FROM project WHERE id = $1", project_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Project Delete failed".to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -0,0 +1,5 @@
+pub mod bucket;
+pub mod middleware;
+pub mod store;
+pub mod store_memory;
+pub mod store_postgres;
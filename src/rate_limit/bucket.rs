@@ -0,0 +1,90 @@
+use std::time::Duration;
+
+use crate::config::RateLimitRule;
+
+/// Outcome of taking a token, with what the `RateLimit-*` headers report.
+#[derive(Debug, Clone, Copy)]
+pub struct RateLimitDecision {
+    pub allowed: bool,
+    pub limit: u32,
+    pub remaining: u32,
+    /// Until the next token, when none was left.
+    pub retry_after: Duration,
+    /// Until the bucket is full again.
+    pub reset_after: Duration,
+}
+
+/// Refills a bucket holding `tokens` for the `elapsed` time since it was last touched and takes
+/// a token if a whole one is there. Returns the tokens left alongside the decision.
+pub fn take_token(
+    tokens: f64,
+    elapsed: Duration,
+    rule: &RateLimitRule,
+) -> (f64, RateLimitDecision) {
+    let capacity = f64::from(rule.burst);
+    let per_second = capacity / rule.per.as_secs_f64().max(f64::EPSILON);
+
+    let mut tokens = (tokens + elapsed.as_secs_f64() * per_second).min(capacity);
+
+    let allowed = tokens >= 1.0;
+
+    if allowed {
+        tokens -= 1.0;
+    }
+
+    let retry_after = if allowed {
+        Duration::ZERO
+    } else {
+        Duration::from_secs_f64((1.0 - tokens) / per_second)
+    };
+
+    let decision = RateLimitDecision {
+        allowed,
+        limit: rule.burst,
+        remaining: tokens.floor() as u32,
+        retry_after,
+        reset_after: Duration::from_secs_f64((capacity - tokens) / per_second),
+    };
+
+    (tokens, decision)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::RateLimitKey;
+
+    fn rule() -> RateLimitRule {
+        RateLimitRule {
+            burst: 2,
+            per: Duration::from_secs(10),
+            key: RateLimitKey::Ip,
+        }
+    }
+
+    #[test]
+    fn spends_the_burst_then_refuses() {
+        let (tokens, first) = take_token(2.0, Duration::ZERO, &rule());
+        assert!(first.allowed);
+        assert_eq!(first.remaining, 1);
+
+        let (tokens, second) = take_token(tokens, Duration::ZERO, &rule());
+        assert!(second.allowed);
+        assert_eq!(second.remaining, 0);
+        assert_eq!(second.reset_after, Duration::from_secs(10));
+
+        let (_, third) = take_token(tokens, Duration::ZERO, &rule());
+        assert!(!third.allowed);
+        assert_eq!(third.retry_after, Duration::from_secs(5));
+    }
+
+    #[test]
+    fn refills_over_time_up_to_the_burst() {
+        let (tokens, decision) = take_token(0.0, Duration::from_secs(5), &rule());
+        assert!(decision.allowed);
+        assert_eq!(tokens, 0.0);
+
+        let (tokens, _) = take_token(0.0, Duration::from_secs(600), &rule());
+        assert_eq!(tokens, 1.0);
+    }
+}
@@ -0,0 +1,145 @@
+use std::sync::Arc;
+
+use axum::{
+    extract::{Request, State},
+    http::{HeaderMap, HeaderValue, Method, header},
+    middleware::Next,
+    response::{IntoResponse, Response},
+};
+
+use crate::{
+    config::{RateLimitConfig, RateLimitKey, RateLimitRule},
+    core::jwt::decode_token,
+    errors::api_errors::ApiErrors,
+    extractor::client_info_extractor::client_ip,
+    rate_limit::{bucket::RateLimitDecision, store::RateLimitStore},
+    state::AppState,
+};
+
+#[derive(Clone)]
+pub struct RateLimits {
+    pub config: RateLimitConfig,
+    pub store: Arc<dyn RateLimitStore>,
+}
+
+#[derive(Clone, Copy, Debug)]
+pub enum RouteGroup {
+    Global,
+    Login,
+    Refresh,
+    Upload,
+    PublicRead,
+}
+
+impl RouteGroup {
+    fn name(&self) -> &'static str {
+        match self {
+            RouteGroup::Global => "global",
+            RouteGroup::Login => "login",
+            RouteGroup::Refresh => "refresh",
+            RouteGroup::Upload => "upload",
+            RouteGroup::PublicRead => "public_read",
+        }
+    }
+
+    fn rule<'a>(&self, config: &'a RateLimitConfig) -> &'a RateLimitRule {
+        match self {
+            RouteGroup::Global => &config.global,
+            RouteGroup::Login => &config.login,
+            RouteGroup::Refresh => &config.refresh,
+            RouteGroup::Upload => &config.upload,
+            RouteGroup::PublicRead => &config.public_read,
+        }
+    }
+
+    /// Public read limits sit on whole routers, so writes on the same paths pass through.
+    fn applies_to(&self, method: &Method) -> bool {
+        match self {
+            RouteGroup::PublicRead => method == Method::GET,
+            _ => true,
+        }
+    }
+}
+
+/// Token bucket rate limiting for one route group, layered with
+/// `middleware::from_fn_with_state((state, group), rate_limit)`. The store failing lets the
+/// request through rather than taking the API down with it.
+pub async fn rate_limit(
+    State((state, group)): State<(AppState, RouteGroup)>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let limits = &state.rate_limits;
+
+    if !limits.config.enabled || !group.applies_to(request.method()) {
+        return next.run(request).await;
+    }
+
+    let rule = group.rule(&limits.config);
+    let key = bucket_key(group, rule.key, &request, &state);
+
+    let decision = match limits.store.take(&key, rule).await {
+        Ok(decision) => decision,
+        Err(e) => {
+            println!("rate limiting skipped for {key}: {e}");
+            return next.run(request).await;
+        }
+    };
+
+    let mut response = if decision.allowed {
+        next.run(request).await
+    } else {
+        ApiErrors::TooManyRequests {
+            message: "Too many requests, slow down".to_string(),
+            retry_after: decision.retry_after,
+        }
+        .into_response()
+    };
+
+    set_headers(response.headers_mut(), rule, &decision);
+
+    response
+}
+
+fn bucket_key(group: RouteGroup, key: RateLimitKey, request: &Request, state: &AppState) -> String {
+    let ip = || {
+        client_ip(
+            request.headers(),
+            request.extensions(),
+            state.trust_forwarded_for,
+        )
+        .map_or_else(|| "ip:unknown".to_string(), |ip| format!("ip:{ip}"))
+    };
+
+    let subject = match key {
+        RateLimitKey::Ip => ip(),
+        RateLimitKey::User => request
+            .headers()
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.strip_prefix("Bearer "))
+            .and_then(|token| decode_token(token, &state.jwt_keys, &state.tokens).ok())
+            .map_or_else(ip, |claims| format!("user:{}", claims.sub)),
+        RateLimitKey::Group => "all".to_string(),
+    };
+
+    format!("{}:{subject}", group.name())
+}
+
+/// An inner, more specific group has already set its headers by the time an outer one gets
+/// here, and those are the ones worth reporting.
+fn set_headers(headers: &mut HeaderMap, rule: &RateLimitRule, decision: &RateLimitDecision) {
+    if headers.contains_key("ratelimit-limit") {
+        return;
+    }
+
+    let reset = decision.reset_after.as_secs_f64().ceil() as u64;
+
+    headers.insert("ratelimit-limit", HeaderValue::from(decision.limit));
+    headers.insert("ratelimit-remaining", HeaderValue::from(decision.remaining));
+    headers.insert("ratelimit-reset", HeaderValue::from(reset));
+
+    if let Ok(policy) = HeaderValue::from_str(&format!("{};w={}", rule.burst, rule.per.as_secs())) {
+        headers.insert("ratelimit-policy", policy);
+    }
+}
@@ -0,0 +1,51 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use sqlx::PgPool;
+
+use crate::{
+    config::{RateLimitRule, RateLimitStoreConfig},
+    errors::api_errors::ApiErrors,
+    rate_limit::{
+        bucket::RateLimitDecision, store_memory::MemoryRateLimitStore,
+        store_postgres::PostgresRateLimitStore,
+    },
+};
+
+/// Where token buckets live. The in-memory store limits each instance on its own; the
+/// Postgres one shares buckets between instances.
+#[async_trait]
+pub trait RateLimitStore: Send + Sync {
+    /// Takes a token from the bucket under `key`, which starts out full.
+    async fn take(&self, key: &str, rule: &RateLimitRule) -> Result<RateLimitDecision, ApiErrors>;
+
+    /// Forgets buckets untouched for `idle`, returning how many went.
+    async fn prune(&self, idle: Duration) -> Result<u64, ApiErrors>;
+}
+
+pub fn rate_limit_store_from_config(
+    config: RateLimitStoreConfig,
+    pool: &PgPool,
+) -> Arc<dyn RateLimitStore> {
+    match config {
+        RateLimitStoreConfig::Memory => Arc::new(MemoryRateLimitStore::new()),
+        RateLimitStoreConfig::Postgres => Arc::new(PostgresRateLimitStore { pool: pool.clone() }),
+    }
+}
+
+/// Periodically drops buckets idle for longer than `idle`; by then they are full again, so
+/// forgetting them changes nothing.
+pub fn spawn_rate_limit_pruner(store: Arc<dyn RateLimitStore>, idle: Duration, every: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = store.prune(idle).await {
+                println!("rate limit pruning failed: {e}");
+            }
+        }
+    });
+}
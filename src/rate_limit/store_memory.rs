@@ -0,0 +1,55 @@
+use std::{
+    collections::HashMap,
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use async_trait::async_trait;
+
+use crate::{
+    config::RateLimitRule,
+    errors::api_errors::ApiErrors,
+    rate_limit::{
+        bucket::{RateLimitDecision, take_token},
+        store::RateLimitStore,
+    },
+};
+
+#[derive(Default)]
+pub struct MemoryRateLimitStore {
+    buckets: Mutex<HashMap<String, (f64, Instant)>>,
+}
+
+impl MemoryRateLimitStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl RateLimitStore for MemoryRateLimitStore {
+    async fn take(&self, key: &str, rule: &RateLimitRule) -> Result<RateLimitDecision, ApiErrors> {
+        let now = Instant::now();
+        let mut buckets = self.buckets.lock().unwrap();
+
+        let (tokens, touched_at) = buckets
+            .entry(key.to_string())
+            .or_insert((f64::from(rule.burst), now));
+
+        let (left, decision) = take_token(*tokens, now.duration_since(*touched_at), rule);
+
+        *tokens = left;
+        *touched_at = now;
+
+        Ok(decision)
+    }
+
+    async fn prune(&self, idle: Duration) -> Result<u64, ApiErrors> {
+        let mut buckets = self.buckets.lock().unwrap();
+
+        let before = buckets.len();
+        buckets.retain(|_, (_, touched_at)| touched_at.elapsed() < idle);
+
+        Ok((before - buckets.len()) as u64)
+    }
+}
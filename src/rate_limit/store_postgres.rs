@@ -0,0 +1,82 @@
+use std::time::Duration;
+
+use async_trait::async_trait;
+use chrono::Utc;
+use sqlx::PgPool;
+
+use crate::{
+    config::RateLimitRule,
+    errors::api_errors::ApiErrors,
+    rate_limit::{
+        bucket::{RateLimitDecision, take_token},
+        store::RateLimitStore,
+    },
+};
+
+pub struct PostgresRateLimitStore {
+    pub pool: PgPool,
+}
+
+#[async_trait]
+impl RateLimitStore for PostgresRateLimitStore {
+    async fn take(&self, key: &str, rule: &RateLimitRule) -> Result<RateLimitDecision, ApiErrors> {
+        let failed = |_| ApiErrors::InternalServerError("Rate limit lookup failed".to_string());
+
+        let now = Utc::now().naive_utc();
+
+        let mut tx = self.pool.begin().await.map_err(failed)?;
+
+        sqlx::query!(
+            "INSERT INTO rate_limit_buckets (key, tokens, updated_at) VALUES ($1, $2, $3) ON CONFLICT (key) DO NOTHING",
+            key,
+            f64::from(rule.burst),
+            now,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(failed)?;
+
+        // The row lock serialises instances taking from the same bucket.
+        let bucket = sqlx::query!(
+            "SELECT tokens, updated_at FROM rate_limit_buckets WHERE key = $1 FOR UPDATE",
+            key
+        )
+        .fetch_one(&mut *tx)
+        .await
+        .map_err(failed)?;
+
+        let elapsed = (now - bucket.updated_at).to_std().unwrap_or(Duration::ZERO);
+
+        let (left, decision) = take_token(bucket.tokens, elapsed, rule);
+
+        sqlx::query!(
+            "UPDATE rate_limit_buckets SET tokens = $1, updated_at = $2 WHERE key = $3",
+            left,
+            now,
+            key,
+        )
+        .execute(&mut *tx)
+        .await
+        .map_err(failed)?;
+
+        tx.commit().await.map_err(failed)?;
+
+        Ok(decision)
+    }
+
+    async fn prune(&self, idle: Duration) -> Result<u64, ApiErrors> {
+        let cutoff = Utc::now().naive_utc()
+            - chrono::Duration::from_std(idle)
+                .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+        let result = sqlx::query!(
+            "DELETE FROM rate_limit_buckets WHERE updated_at < $1",
+            cutoff
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Rate limit pruning failed".to_string()))?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -1,7 +1,10 @@
 pub mod actor;
+pub mod cleanup;
 pub mod dispatch;
 pub mod dto;
 pub mod handlers;
 pub mod messages;
 pub mod repo;
+#[cfg(test)]
+pub mod repo_memory;
 pub mod repo_sqlx;
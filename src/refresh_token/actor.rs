@@ -1,13 +1,29 @@
-use tokio::sync::mpsc;
+use std::sync::Arc;
+
+use chrono::NaiveDateTime;
+use tokio::sync::{
+    mpsc::{self, Sender},
+    oneshot,
+};
+use uuid::Uuid;
 
 use crate::{
-    core::jwt::{generate_refresh_token, generate_token},
+    auth::messages::{AuthMessage, UserResponse},
+    config::{TokenCleanupConfig, TokenConfig},
+    core::{
+        jwt::{generate_refresh_token, generate_token},
+        jwt_keys::JwtKeys,
+    },
     errors::api_errors::ApiErrors,
+    extractor::client_info_extractor::ClientInfo,
     refresh_token::{
         dispatch::refresh_token_dispatcher,
-        messages::{RefreshTokenMessage, TokenPair},
+        dto::RefreshTokenRecord,
+        messages::{RefreshTokenMessage, SessionResponse, TokenPair},
         repo::RefreshTokenRepository,
     },
+    token_denylist::denylist::TokenDenylist,
+    utils::tokens::hash_opaque_token,
 };
 
 pub struct RefreshTokenActor<R>
@@ -15,19 +31,34 @@ where
     R: RefreshTokenRepository + Send + Sync + 'static,
 {
     pub repo: R,
-    pub jwt_secret: String,
-    pub jwt_expiry_hour: i64,
+    pub jwt_keys: Arc<JwtKeys>,
+    pub tokens: TokenConfig,
+    pub cleanup: TokenCleanupConfig,
+    /// Access tokens carry the user's roles and token version, which the auth actor owns.
+    pub auth_tx: Sender<AuthMessage>,
+    /// Ending a session denies the access token last issued on it.
+    pub denylist: Arc<TokenDenylist>,
 }
 
 impl<R> RefreshTokenActor<R>
 where
     R: RefreshTokenRepository + Send + Sync + 'static,
 {
-    pub fn new(repo: R, jwt_secret: String, jwt_expiry_hour: i64) -> Self {
+    pub fn new(
+        repo: R,
+        jwt_keys: Arc<JwtKeys>,
+        tokens: TokenConfig,
+        cleanup: TokenCleanupConfig,
+        auth_tx: Sender<AuthMessage>,
+        denylist: Arc<TokenDenylist>,
+    ) -> Self {
         Self {
             repo,
-            jwt_secret,
-            jwt_expiry_hour,
+            jwt_keys,
+            tokens,
+            cleanup,
+            auth_tx,
+            denylist,
         }
     }
 
@@ -35,13 +66,34 @@ where
         refresh_token_dispatcher(&self, rx).await;
     }
 
-    pub async fn handle_login(&self, user_id: uuid::Uuid) -> Result<TokenPair, ApiErrors> {
-        let access_token = generate_token(user_id, &self.jwt_secret, self.jwt_expiry_hour)?;
+    pub async fn handle_login(
+        &self,
+        user_id: Uuid,
+        client: ClientInfo,
+    ) -> Result<TokenPair, ApiErrors> {
+        let user = self.load_user(user_id).await?;
+
+        let (access_token, claims) = generate_token(&user, &self.jwt_keys, &self.tokens)?;
 
         let refresh_token = generate_refresh_token();
 
+        let session_id = Uuid::new_v4();
+
+        self.repo
+            .create_session(session_id, user_id, &client)
+            .await?;
+
         self.repo
-            .store_refresh_token(user_id, &refresh_token)
+            .record_access_token(session_id, claims.jti, claims.expires_at())
+            .await?;
+
+        self.repo
+            .store_refresh_token(
+                user_id,
+                session_id,
+                &hash_opaque_token(&refresh_token),
+                self.refresh_expiry()?,
+            )
             .await?;
 
         Ok(TokenPair {
@@ -50,14 +102,19 @@ where
         })
     }
 
-    pub async fn handle_refresh(&self, token: String) -> Result<TokenPair, ApiErrors> {
+    pub async fn handle_refresh(
+        &self,
+        token: String,
+        client: ClientInfo,
+    ) -> Result<TokenPair, ApiErrors> {
         let record = self
             .repo
-            .find_refresh_token(&token)
+            .find_refresh_token(&hash_opaque_token(&token))
             .await?
             .ok_or(ApiErrors::Unauthorized("Invalid refresh token".into()))?;
 
         if record.revoked.unwrap_or(false) {
+            self.revoke_family(&record, &client).await?;
             return Err(ApiErrors::Unauthorized("Token revoked".into()));
         }
 
@@ -65,16 +122,52 @@ where
             return Err(ApiErrors::Unauthorized("Token expired".into()));
         }
 
+        let user = self.load_user(record.user_id).await?;
+
         // 🔁 ROTATION
-        self.repo.revoke_refresh_token(record.id).await?;
+        // Losing the race to another rotation of the same token means it was presented twice.
+        if !self.repo.revoke_refresh_token(record.id).await? {
+            self.revoke_family(&record, &client).await?;
+            return Err(ApiErrors::Unauthorized("Token revoked".into()));
+        }
 
         let new_refresh = generate_refresh_token();
 
+        // Tokens from before sessions were recorded get one on their first rotation.
+        let session_id = match record.session_id {
+            Some(session_id) => {
+                self.repo
+                    .touch_session(session_id, &client, chrono::Utc::now().naive_utc())
+                    .await?;
+                // The session only remembers its newest access token, so the one being
+                // replaced is denied now rather than left valid and untracked.
+                self.deny_access_tokens(record.user_id, |id| id == session_id)
+                    .await?;
+                session_id
+            }
+            None => {
+                let session_id = Uuid::new_v4();
+                self.repo
+                    .create_session(session_id, record.user_id, &client)
+                    .await?;
+                session_id
+            }
+        };
+
         self.repo
-            .store_refresh_token(record.user_id, &new_refresh)
+            .store_refresh_token(
+                record.user_id,
+                session_id,
+                &hash_opaque_token(&new_refresh),
+                self.refresh_expiry()?,
+            )
             .await?;
 
-        let access = generate_token(record.user_id, &self.jwt_secret, self.jwt_expiry_hour)?;
+        let (access, claims) = generate_token(&user, &self.jwt_keys, &self.tokens)?;
+
+        self.repo
+            .record_access_token(session_id, claims.jti, claims.expires_at())
+            .await?;
 
         Ok(TokenPair {
             access_token: access,
@@ -82,9 +175,418 @@ where
         })
     }
 
+    /// A revoked token coming back means two parties hold the family: the owner and whoever
+    /// copied it. There is no telling which is which, so every token in it goes.
+    async fn revoke_family(
+        &self,
+        record: &RefreshTokenRecord,
+        client: &ClientInfo,
+    ) -> Result<(), ApiErrors> {
+        println!(
+            "security: revoked refresh token reused for user {} (session {:?}) from ip {:?}, user agent {:?}; revoking the token family",
+            record.user_id, record.session_id, client.ip, client.user_agent
+        );
+
+        match record.session_id {
+            Some(session_id) => {
+                self.repo.revoke_session(record.user_id, session_id).await?;
+                self.deny_access_tokens(record.user_id, |id| id == session_id)
+                    .await?;
+            }
+            // Without a session the family cannot be told apart, so all of the user's go.
+            None => {
+                self.repo
+                    .revoke_user_refresh_tokens(record.user_id, None)
+                    .await?;
+                self.deny_access_tokens(record.user_id, |_| true).await?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Denies the live access tokens of the sessions of `user_id` that `ended` picks.
+    async fn deny_access_tokens(
+        &self,
+        user_id: Uuid,
+        ended: impl Fn(Uuid) -> bool,
+    ) -> Result<(), ApiErrors> {
+        let access_tokens = self
+            .repo
+            .access_tokens(user_id, chrono::Utc::now().naive_utc())
+            .await?;
+
+        for access in access_tokens
+            .into_iter()
+            .filter(|access| ended(access.session_id))
+        {
+            self.denylist.deny(access.jti, access.expires_at).await?;
+        }
+
+        Ok(())
+    }
+
     pub async fn handle_logout(&self, token: String) -> Result<(), ApiErrors> {
-        self.repo.revoke_refresh_token_by_value(&token).await?;
+        let token_hash = hash_opaque_token(&token);
+
+        self.repo.revoke_refresh_token_by_hash(&token_hash).await?;
+
+        if let Some(record) = self.repo.find_refresh_token(&token_hash).await?
+            && let Some(session_id) = record.session_id
+        {
+            self.deny_access_tokens(record.user_id, |id| id == session_id)
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    pub async fn handle_revoke_all(
+        &self,
+        user_id: Uuid,
+        except: Option<String>,
+    ) -> Result<(), ApiErrors> {
+        let except_hash = except.map(|token| hash_opaque_token(&token));
+
+        let kept_session = match &except_hash {
+            Some(hash) => self
+                .repo
+                .find_refresh_token(hash)
+                .await?
+                .filter(|record| record.user_id == user_id)
+                .and_then(|record| record.session_id),
+            None => None,
+        };
+
+        self.repo
+            .revoke_user_refresh_tokens(user_id, except_hash.as_deref())
+            .await?;
+
+        self.deny_access_tokens(user_id, |id| Some(id) != kept_session)
+            .await
+    }
+
+    pub async fn handle_delete_user(&self, user_id: Uuid) -> Result<(), ApiErrors> {
+        // Sessions are deleted along with the user, so their access tokens are looked up first.
+        let access_tokens = self
+            .repo
+            .access_tokens(user_id, chrono::Utc::now().naive_utc())
+            .await?;
+
+        let (tx, rx) = oneshot::channel();
+
+        self.auth_tx
+            .send(AuthMessage::DeleteUser {
+                user_id,
+                respond_to: tx,
+            })
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
+
+        if !rx
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))??
+        {
+            return Err(ApiErrors::NotFound("User not found".to_string()));
+        }
+
+        self.repo.revoke_user_refresh_tokens(user_id, None).await?;
+
+        for access in access_tokens {
+            self.denylist.deny(access.jti, access.expires_at).await?;
+        }
 
         Ok(())
     }
+
+    async fn load_user(&self, user_id: Uuid) -> Result<UserResponse, ApiErrors> {
+        let (tx, rx) = oneshot::channel();
+
+        self.auth_tx
+            .send(AuthMessage::GetUser {
+                user_id,
+                respond_to: tx,
+            })
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
+
+        rx.await
+            .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))?
+            .map_err(|e| match e {
+                ApiErrors::NotFound(_) => ApiErrors::Unauthorized("User no longer exists".into()),
+                e => e,
+            })
+    }
+
+    /// Every rotation starts the lifetime over, so an active session never runs out.
+    fn refresh_expiry(&self) -> Result<NaiveDateTime, ApiErrors> {
+        let ttl = chrono::Duration::from_std(self.tokens.refresh_ttl)
+            .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+        Ok(chrono::Utc::now().naive_utc() + ttl)
+    }
+
+    pub async fn handle_purge_expired(&self) -> Result<u64, ApiErrors> {
+        let now = chrono::Utc::now().naive_utc();
+
+        let revoked_before = now
+            - chrono::Duration::from_std(self.cleanup.revoked_retention)
+                .map_err(|e| ApiErrors::InternalServerError(e.to_string()))?;
+
+        self.repo
+            .purge_expired(now, revoked_before, self.cleanup.batch_size)
+            .await
+    }
+
+    pub async fn handle_list_sessions(
+        &self,
+        user_id: Uuid,
+        current: Option<String>,
+    ) -> Result<Vec<SessionResponse>, ApiErrors> {
+        let current_session = match current {
+            Some(token) => self
+                .repo
+                .find_refresh_token(&hash_opaque_token(&token))
+                .await?
+                .and_then(|record| record.session_id),
+            None => None,
+        };
+
+        let sessions = self
+            .repo
+            .list_sessions(user_id, chrono::Utc::now().naive_utc())
+            .await?;
+
+        Ok(sessions
+            .into_iter()
+            .map(|session| SessionResponse {
+                current: Some(session.id) == current_session,
+                id: session.id,
+                user_agent: session.user_agent,
+                ip: session.ip,
+                created_at: session.created_at,
+                last_used_at: session.last_used_at,
+            })
+            .collect())
+    }
+
+    pub async fn handle_revoke_session(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+    ) -> Result<(), ApiErrors> {
+        if !self.repo.revoke_session(user_id, session_id).await? {
+            return Err(ApiErrors::NotFound("Session not found".to_string()));
+        }
+
+        self.deny_access_tokens(user_id, |id| id == session_id)
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        fields::{email::Email, roles::Roles, text::Text},
+        refresh_token::repo_memory::RefreshTokenRepoMemory,
+    };
+
+    /// Answers every user lookup with a normal user of the asked-for id.
+    fn auth_stub() -> Sender<AuthMessage> {
+        let (tx, mut rx) = mpsc::channel::<AuthMessage>(8);
+
+        tokio::spawn(async move {
+            while let Some(message) = rx.recv().await {
+                if let AuthMessage::GetUser {
+                    user_id,
+                    respond_to,
+                } = message
+                {
+                    let now = chrono::Utc::now().naive_utc();
+                    let _ = respond_to.send(Ok(UserResponse {
+                        id: user_id,
+                        email: Email("user@example.com".to_string()),
+                        name: Text("User".to_string()),
+                        phone_number: None,
+                        roles: Roles::Normal,
+                        created_at: now,
+                        updated_at: now,
+                        token_version: 0,
+                    }));
+                }
+            }
+        });
+
+        tx
+    }
+
+    fn actor_with(cleanup: TokenCleanupConfig) -> RefreshTokenActor<RefreshTokenRepoMemory> {
+        RefreshTokenActor::new(
+            RefreshTokenRepoMemory::new(),
+            Arc::new(JwtKeys::from_secret("secret")),
+            TokenConfig::default(),
+            cleanup,
+            auth_stub(),
+            Arc::new(TokenDenylist::new(None)),
+        )
+    }
+
+    fn actor() -> RefreshTokenActor<RefreshTokenRepoMemory> {
+        actor_with(TokenCleanupConfig::default())
+    }
+
+    #[tokio::test]
+    async fn only_the_hash_of_a_refresh_token_is_stored() {
+        let actor = actor();
+
+        let login = actor
+            .handle_login(Uuid::new_v4(), ClientInfo::default())
+            .await
+            .unwrap();
+        assert_eq!(login.refresh_token.len(), 64);
+
+        let by_value = actor.repo.find_refresh_token(&login.refresh_token).await;
+        assert!(by_value.unwrap().is_none());
+
+        let by_hash = actor
+            .repo
+            .find_refresh_token(&hash_opaque_token(&login.refresh_token))
+            .await;
+        assert!(by_hash.unwrap().is_some());
+    }
+
+    #[tokio::test]
+    async fn reusing_a_rotated_token_revokes_its_family() {
+        let actor = actor();
+        let user_id = Uuid::new_v4();
+
+        let login = actor
+            .handle_login(user_id, ClientInfo::default())
+            .await
+            .unwrap();
+        let rotated = actor
+            .handle_refresh(login.refresh_token.clone(), ClientInfo::default())
+            .await
+            .unwrap();
+
+        let reused = actor
+            .handle_refresh(login.refresh_token, ClientInfo::default())
+            .await;
+        assert!(matches!(reused, Err(ApiErrors::Unauthorized(_))));
+
+        let successor = actor
+            .handle_refresh(rotated.refresh_token, ClientInfo::default())
+            .await;
+        assert!(matches!(successor, Err(ApiErrors::Unauthorized(_))));
+    }
+
+    #[tokio::test]
+    async fn reuse_leaves_other_families_alone() {
+        let actor = actor();
+        let user_id = Uuid::new_v4();
+
+        let stolen = actor
+            .handle_login(user_id, ClientInfo::default())
+            .await
+            .unwrap();
+        let other = actor
+            .handle_login(user_id, ClientInfo::default())
+            .await
+            .unwrap();
+
+        actor
+            .handle_refresh(stolen.refresh_token.clone(), ClientInfo::default())
+            .await
+            .unwrap();
+        let _ = actor
+            .handle_refresh(stolen.refresh_token, ClientInfo::default())
+            .await;
+
+        let sessions = actor
+            .repo
+            .list_sessions(user_id, chrono::Utc::now().naive_utc())
+            .await
+            .unwrap();
+        assert_eq!(sessions.len(), 1);
+
+        assert!(
+            actor
+                .handle_refresh(other.refresh_token, ClientInfo::default())
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn tokens_revoked_late_in_life_are_kept_for_the_retention_window() {
+        let actor = actor_with(TokenCleanupConfig {
+            revoked_retention: std::time::Duration::from_secs(60 * 60),
+            ..TokenCleanupConfig::default()
+        });
+
+        let login = actor
+            .handle_login(Uuid::new_v4(), ClientInfo::default())
+            .await
+            .unwrap();
+        actor.repo.backdate_tokens(chrono::Duration::hours(2));
+
+        let rotated = actor
+            .handle_refresh(login.refresh_token.clone(), ClientInfo::default())
+            .await
+            .unwrap();
+
+        assert_eq!(actor.handle_purge_expired().await.unwrap(), 0);
+
+        // Still known, so replaying it is caught as reuse rather than passing as a stranger.
+        let reused = actor
+            .handle_refresh(login.refresh_token, ClientInfo::default())
+            .await;
+        assert!(matches!(reused, Err(ApiErrors::Unauthorized(_))));
+
+        let successor = actor
+            .handle_refresh(rotated.refresh_token, ClientInfo::default())
+            .await;
+        assert!(matches!(successor, Err(ApiErrors::Unauthorized(_))));
+    }
+
+    #[tokio::test]
+    async fn purging_drops_revoked_tokens_one_batch_at_a_time() {
+        let actor = actor_with(TokenCleanupConfig {
+            revoked_retention: std::time::Duration::ZERO,
+            batch_size: 1,
+            ..TokenCleanupConfig::default()
+        });
+        let user_id = Uuid::new_v4();
+
+        let rotated = actor
+            .handle_login(user_id, ClientInfo::default())
+            .await
+            .unwrap();
+        let logged_out = actor
+            .handle_login(user_id, ClientInfo::default())
+            .await
+            .unwrap();
+
+        let live = actor
+            .handle_refresh(rotated.refresh_token, ClientInfo::default())
+            .await
+            .unwrap();
+        actor
+            .repo
+            .revoke_refresh_token_by_hash(&hash_opaque_token(&logged_out.refresh_token))
+            .await
+            .unwrap();
+
+        assert_eq!(actor.handle_purge_expired().await.unwrap(), 1);
+        assert_eq!(actor.handle_purge_expired().await.unwrap(), 1);
+        assert_eq!(actor.handle_purge_expired().await.unwrap(), 0);
+
+        assert!(
+            actor
+                .handle_refresh(live.refresh_token, ClientInfo::default())
+                .await
+                .is_ok()
+        );
+    }
 }
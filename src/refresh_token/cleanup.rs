@@ -0,0 +1,47 @@
+use std::time::Duration;
+
+use tokio::sync::{mpsc::Sender, oneshot};
+
+use crate::{errors::api_errors::ApiErrors, refresh_token::messages::RefreshTokenMessage};
+
+/// Purges dead refresh tokens every `every`. Each batch is its own message, so refreshes
+/// queued behind a large backlog are served in between.
+pub fn spawn_token_cleanup(refresh_token_tx: Sender<RefreshTokenMessage>, every: Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+        interval.tick().await;
+
+        loop {
+            interval.tick().await;
+
+            match purge_all(&refresh_token_tx).await {
+                Ok(0) => {}
+                Ok(purged) => println!("🧹 purged {purged} expired or revoked refresh token(s)"),
+                Err(e) => println!("refresh token cleanup failed: {e}"),
+            }
+        }
+    });
+}
+
+async fn purge_all(refresh_token_tx: &Sender<RefreshTokenMessage>) -> Result<u64, ApiErrors> {
+    let mut total = 0;
+
+    loop {
+        let (tx, rx) = oneshot::channel();
+
+        refresh_token_tx
+            .send(RefreshTokenMessage::PurgeExpired { respond_to: tx })
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
+
+        let purged = rx
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))??;
+
+        if purged == 0 {
+            return Ok(total);
+        }
+
+        total += purged;
+    }
+}
@@ -14,17 +14,19 @@ pub async fn refresh_token_dispatcher<R>(
         match msg {
             RefreshTokenMessage::Login {
                 user_id,
+                client,
                 respond_to,
             } => {
-                let res = actor.handle_login(user_id).await;
+                let res = actor.handle_login(user_id, client).await;
                 let _ = respond_to.send(res);
             }
 
             RefreshTokenMessage::Refresh {
                 refresh_token,
+                client,
                 respond_to,
             } => {
-                let res = actor.handle_refresh(refresh_token).await;
+                let res = actor.handle_refresh(refresh_token, client).await;
                 let _ = respond_to.send(res);
             }
 
@@ -35,6 +37,46 @@ pub async fn refresh_token_dispatcher<R>(
                 let res = actor.handle_logout(refresh_token).await;
                 let _ = respond_to.send(res);
             }
+
+            RefreshTokenMessage::RevokeAll {
+                user_id,
+                except,
+                respond_to,
+            } => {
+                let res = actor.handle_revoke_all(user_id, except).await;
+                let _ = respond_to.send(res);
+            }
+
+            RefreshTokenMessage::DeleteUser {
+                user_id,
+                respond_to,
+            } => {
+                let res = actor.handle_delete_user(user_id).await;
+                let _ = respond_to.send(res);
+            }
+
+            RefreshTokenMessage::ListSessions {
+                user_id,
+                current,
+                respond_to,
+            } => {
+                let res = actor.handle_list_sessions(user_id, current).await;
+                let _ = respond_to.send(res);
+            }
+
+            RefreshTokenMessage::RevokeSession {
+                user_id,
+                session_id,
+                respond_to,
+            } => {
+                let res = actor.handle_revoke_session(user_id, session_id).await;
+                let _ = respond_to.send(res);
+            }
+
+            RefreshTokenMessage::PurgeExpired { respond_to } => {
+                let res = actor.handle_purge_expired().await;
+                let _ = respond_to.send(res);
+            }
         }
     }
 }
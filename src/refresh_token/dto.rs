@@ -1,9 +1,29 @@
 use chrono::NaiveDateTime;
+use serde::Serialize;
 use uuid::Uuid;
 
 pub struct RefreshTokenRecord {
     pub id: Uuid,
     pub user_id: Uuid,
+    /// The session, which is also the rotation family: every token rotated from the one issued
+    /// at login shares it. `None` for tokens issued before sessions were recorded.
+    pub session_id: Option<Uuid>,
     pub expires_at: NaiveDateTime,
     pub revoked: Option<bool>,
 }
+
+#[derive(Serialize, Clone)]
+pub struct SessionRecord {
+    pub id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: NaiveDateTime,
+}
+
+/// The access token last issued on a session, which ending the session denies.
+pub struct AccessTokenRecord {
+    pub session_id: Uuid,
+    pub jti: Uuid,
+    pub expires_at: NaiveDateTime,
+}
@@ -2,16 +2,23 @@ use axum::{Json, extract::State};
 
 use tokio::sync::oneshot;
 use tower_cookies::Cookies;
+use uuid::Uuid;
 
 use crate::{
+    auth::messages::AuthMessage,
+    core::login_token_core::revoke_user_tokens_core,
     errors::api_errors::ApiErrors,
+    extractor::{
+        auth_extractor::AuthUser, client_info_extractor::ClientInfo, path_id_extractor::PathParam,
+    },
     refresh_token::messages::RefreshTokenMessage,
     state::AppState,
-    utils::cookies::{clear_refresh_cookies, set_refresh_cookie},
+    utils::cookies::{clear_refresh_cookie, set_refresh_cookie},
 };
 
 pub async fn refresh(
     State(state): State<AppState>,
+    client: ClientInfo,
     cookies: Cookies,
 ) -> Result<Json<serde_json::Value>, ApiErrors> {
     let refresh = cookies
@@ -26,6 +33,7 @@ pub async fn refresh(
         .refresh_token_tx
         .send(RefreshTokenMessage::Refresh {
             refresh_token: refresh,
+            client,
             respond_to: tx,
         })
         .await
@@ -35,7 +43,11 @@ pub async fn refresh(
         .await
         .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))??;
 
-    cookies.add(set_refresh_cookie(tokens.refresh_token));
+    cookies.add(set_refresh_cookie(
+        tokens.refresh_token,
+        &state.tokens.cookie,
+        state.tokens.refresh_ttl,
+    ));
 
     Ok(Json(
         serde_json::json!({ "access_token": tokens.access_token }),
@@ -59,11 +71,113 @@ pub async fn logout(State(state): State<AppState>, cookies: Cookies) -> Result<(
             .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))??;
     }
 
-    cookies.remove(clear_refresh_cookies());
+    cookies.add(clear_refresh_cookie(&state.tokens.cookie));
 
     Ok(())
 }
 
+pub async fn list_sessions(
+    AuthUser { id, .. }: AuthUser,
+    State(state): State<AppState>,
+    cookies: Cookies,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    state
+        .refresh_token_tx
+        .send(RefreshTokenMessage::ListSessions {
+            user_id: id,
+            current: cookies
+                .get("refresh_token")
+                .map(|cookie| cookie.value().to_string()),
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
+
+    let sessions = rx
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))??;
+
+    Ok(Json(serde_json::json!({
+        "message": "success".to_string(),
+        "data": sessions,
+    })))
+}
+
+pub async fn revoke_session(
+    AuthUser { id, .. }: AuthUser,
+    State(state): State<AppState>,
+    PathParam(session_id): PathParam<Uuid>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let (tx, rx) = oneshot::channel();
+
+    state
+        .refresh_token_tx
+        .send(RefreshTokenMessage::RevokeSession {
+            user_id: id,
+            session_id,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Service unavailable".to_string()))?;
+
+    rx.await
+        .map_err(|_| ApiErrors::InternalServerError("Failed".to_string()))??;
+
+    Ok(Json(serde_json::json!({"message": "success".to_string(),})))
+}
+
+/// Signs the caller out everywhere but the session making the request.
+pub async fn revoke_other_sessions(
+    AuthUser { id, .. }: AuthUser,
+    State(state): State<AppState>,
+    cookies: Cookies,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    let current_session = cookies
+        .get("refresh_token")
+        .map(|cookie| cookie.value().to_string());
+
+    revoke_user_tokens_core(&state.refresh_token_tx, id, current_session).await?;
+
+    Ok(Json(serde_json::json!({"message": "success".to_string(),})))
+}
+
+pub async fn force_logout_user(
+    AuthUser { roles, .. }: AuthUser,
+    State(state): State<AppState>,
+    PathParam(user_id): PathParam<Uuid>,
+) -> Result<Json<serde_json::Value>, ApiErrors> {
+    if roles.as_str() == "normal" {
+        return Err(ApiErrors::BadRequest(
+            "Because of your ADMIN Level you can not log out a user.".to_string(),
+        ));
+    }
+
+    let (tx, rx) = oneshot::channel();
+
+    // Access tokens already handed out would otherwise outlive the sessions until they expire.
+    state
+        .auth_tx
+        .send(AuthMessage::RevokeAccessTokens {
+            user_id,
+            respond_to: tx,
+        })
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Auth service unavailable".to_string()))?;
+
+    if !rx
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Auth failed".to_string()))??
+    {
+        return Err(ApiErrors::NotFound("User not found".to_string()));
+    }
+
+    revoke_user_tokens_core(&state.refresh_token_tx, user_id, None).await?;
+
+    Ok(Json(serde_json::json!({"message": "success".to_string(),})))
+}
+
 // pub async fn login(
 //     State(state): State<AppState>,
 //     Json(payload): Json<LoginRequest>,
@@ -1,8 +1,9 @@
+use chrono::NaiveDateTime;
 use serde::Serialize;
 use tokio::sync::oneshot;
 use uuid::Uuid;
 
-use crate::errors::api_errors::ApiErrors;
+use crate::{errors::api_errors::ApiErrors, extractor::client_info_extractor::ClientInfo};
 
 #[derive(Serialize)]
 pub struct TokenPair {
@@ -10,14 +11,28 @@ pub struct TokenPair {
     pub refresh_token: String,
 }
 
+#[derive(Serialize)]
+pub struct SessionResponse {
+    pub id: Uuid,
+    pub user_agent: Option<String>,
+    pub ip: Option<String>,
+    pub created_at: NaiveDateTime,
+    pub last_used_at: NaiveDateTime,
+    /// Whether this is the session the request came from.
+    pub current: bool,
+}
+
 pub enum RefreshTokenMessage {
+    /// Starts a new session for `user_id` from `client`.
     Login {
         user_id: Uuid,
+        client: ClientInfo,
         respond_to: oneshot::Sender<Result<TokenPair, ApiErrors>>,
     },
 
     Refresh {
         refresh_token: String,
+        client: ClientInfo,
         respond_to: oneshot::Sender<Result<TokenPair, ApiErrors>>,
     },
 
@@ -25,4 +40,37 @@ pub enum RefreshTokenMessage {
         refresh_token: String,
         respond_to: oneshot::Sender<Result<(), ApiErrors>>,
     },
+
+    /// Ends every session of a user but the one holding `except`, e.g. after their password
+    /// changed.
+    RevokeAll {
+        user_id: Uuid,
+        except: Option<String>,
+        respond_to: oneshot::Sender<Result<(), ApiErrors>>,
+    },
+
+    /// Deletes `user_id` through the auth actor and only then ends their sessions, denying the
+    /// access tokens those held.
+    DeleteUser {
+        user_id: Uuid,
+        respond_to: oneshot::Sender<Result<(), ApiErrors>>,
+    },
+
+    /// Lists the live sessions of `user_id`, marking the one holding `current`.
+    ListSessions {
+        user_id: Uuid,
+        current: Option<String>,
+        respond_to: oneshot::Sender<Result<Vec<SessionResponse>, ApiErrors>>,
+    },
+
+    RevokeSession {
+        user_id: Uuid,
+        session_id: Uuid,
+        respond_to: oneshot::Sender<Result<(), ApiErrors>>,
+    },
+
+    /// Deletes one batch of dead tokens and answers with how many went.
+    PurgeExpired {
+        respond_to: oneshot::Sender<Result<u64, ApiErrors>>,
+    },
 }
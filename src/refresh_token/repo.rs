@@ -1,18 +1,90 @@
 use async_trait::async_trait;
+use chrono::NaiveDateTime;
 use uuid::Uuid;
 
-use crate::{errors::api_errors::ApiErrors, refresh_token::dto::RefreshTokenRecord};
+use crate::{
+    errors::api_errors::ApiErrors,
+    extractor::client_info_extractor::ClientInfo,
+    refresh_token::dto::{AccessTokenRecord, RefreshTokenRecord, SessionRecord},
+};
 
 #[async_trait]
 pub trait RefreshTokenRepository: Send + Sync {
-    async fn store_refresh_token(&self, user_id: Uuid, token: &str) -> Result<(), ApiErrors>;
+    async fn create_session(
+        &self,
+        session_id: Uuid,
+        user_id: Uuid,
+        client: &ClientInfo,
+    ) -> Result<(), ApiErrors>;
+
+    /// Records that the session was just used, from wherever `client` is now.
+    async fn touch_session(
+        &self,
+        session_id: Uuid,
+        client: &ClientInfo,
+        now: NaiveDateTime,
+    ) -> Result<(), ApiErrors>;
+
+    /// Sessions of `user_id` still holding an unrevoked, unexpired refresh token, most
+    /// recently used first.
+    async fn list_sessions(
+        &self,
+        user_id: Uuid,
+        now: NaiveDateTime,
+    ) -> Result<Vec<SessionRecord>, ApiErrors>;
+
+    /// Remembers the access token just issued on the session, replacing the previous one; the
+    /// caller denies that one first.
+    async fn record_access_token(
+        &self,
+        session_id: Uuid,
+        jti: Uuid,
+        expires_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors>;
+
+    /// The last access token of each session of `user_id` that has not expired by `now`.
+    async fn access_tokens(
+        &self,
+        user_id: Uuid,
+        now: NaiveDateTime,
+    ) -> Result<Vec<AccessTokenRecord>, ApiErrors>;
+
+    /// Revokes the refresh tokens of one session; false when `user_id` has no such session.
+    async fn revoke_session(&self, user_id: Uuid, session_id: Uuid) -> Result<bool, ApiErrors>;
+
+    /// Tokens are only ever handled here as their SHA-256, so the table holds nothing usable.
+    async fn store_refresh_token(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+        token_hash: &str,
+        expires_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors>;
 
     async fn find_refresh_token(
         &self,
-        token: &str,
+        token_hash: &str,
     ) -> Result<Option<RefreshTokenRecord>, ApiErrors>;
 
-    async fn revoke_refresh_token(&self, id: Uuid) -> Result<(), ApiErrors>;
+    /// False when the token was already revoked, e.g. by a concurrent rotation.
+    async fn revoke_refresh_token(&self, id: Uuid) -> Result<bool, ApiErrors>;
 
-    async fn revoke_refresh_token_by_value(&self, token: &str) -> Result<(), ApiErrors>;
+    async fn revoke_refresh_token_by_hash(&self, token_hash: &str) -> Result<(), ApiErrors>;
+
+    /// Revokes every token of `user_id` other than the one hashing to `except_hash`.
+    async fn revoke_user_refresh_tokens(
+        &self,
+        user_id: Uuid,
+        except_hash: Option<&str>,
+    ) -> Result<(), ApiErrors>;
+
+    /// Deletes up to `limit` tokens that expired before `now` or were revoked before
+    /// `revoked_before`, then sessions left without tokens and idle since then.
+    /// Returns how many tokens went; fewer than `limit` means the backlog is cleared.
+    async fn purge_expired(
+        &self,
+        now: NaiveDateTime,
+        revoked_before: NaiveDateTime,
+        limit: u32,
+    ) -> Result<u64, ApiErrors>;
 }
@@ -0,0 +1,294 @@
+use std::{cmp::Reverse, sync::Mutex};
+
+use async_trait::async_trait;
+use chrono::{NaiveDateTime, Utc};
+use uuid::Uuid;
+
+use crate::{
+    errors::api_errors::ApiErrors,
+    extractor::client_info_extractor::ClientInfo,
+    refresh_token::{
+        dto::{AccessTokenRecord, RefreshTokenRecord, SessionRecord},
+        repo::RefreshTokenRepository,
+    },
+};
+
+struct SessionRow {
+    user_id: Uuid,
+    session: SessionRecord,
+    access: Option<(Uuid, NaiveDateTime)>,
+}
+
+struct RefreshTokenRow {
+    id: Uuid,
+    user_id: Uuid,
+    session_id: Uuid,
+    token_hash: String,
+    created_at: NaiveDateTime,
+    expires_at: NaiveDateTime,
+    revoked_at: Option<NaiveDateTime>,
+}
+
+impl RefreshTokenRow {
+    fn record(&self) -> RefreshTokenRecord {
+        RefreshTokenRecord {
+            id: self.id,
+            user_id: self.user_id,
+            session_id: Some(self.session_id),
+            expires_at: self.expires_at,
+            revoked: Some(self.revoked_at.is_some()),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct RefreshTokenRepoMemory {
+    sessions: Mutex<Vec<SessionRow>>,
+    tokens: Mutex<Vec<RefreshTokenRow>>,
+}
+
+impl RefreshTokenRepoMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Moves every token's issue time `by` into the past, as if it had lived that long.
+    pub fn backdate_tokens(&self, by: chrono::Duration) {
+        for row in self.tokens.lock().unwrap().iter_mut() {
+            row.created_at -= by;
+        }
+    }
+}
+
+#[async_trait]
+impl RefreshTokenRepository for RefreshTokenRepoMemory {
+    async fn create_session(
+        &self,
+        session_id: Uuid,
+        user_id: Uuid,
+        client: &ClientInfo,
+    ) -> Result<(), ApiErrors> {
+        let now = Utc::now().naive_utc();
+
+        self.sessions.lock().unwrap().push(SessionRow {
+            user_id,
+            session: SessionRecord {
+                id: session_id,
+                user_agent: client.user_agent.clone(),
+                ip: client.ip.map(|ip| ip.to_string()),
+                created_at: now,
+                last_used_at: now,
+            },
+            access: None,
+        });
+
+        Ok(())
+    }
+
+    async fn touch_session(
+        &self,
+        session_id: Uuid,
+        client: &ClientInfo,
+        now: NaiveDateTime,
+    ) -> Result<(), ApiErrors> {
+        let mut sessions = self.sessions.lock().unwrap();
+
+        if let Some(row) = sessions.iter_mut().find(|row| row.session.id == session_id) {
+            row.session.last_used_at = now;
+
+            if let Some(ip) = client.ip {
+                row.session.ip = Some(ip.to_string());
+            }
+
+            if let Some(user_agent) = &client.user_agent {
+                row.session.user_agent = Some(user_agent.clone());
+            }
+        }
+
+        Ok(())
+    }
+
+    async fn list_sessions(
+        &self,
+        user_id: Uuid,
+        now: NaiveDateTime,
+    ) -> Result<Vec<SessionRecord>, ApiErrors> {
+        let sessions = self.sessions.lock().unwrap();
+        let tokens = self.tokens.lock().unwrap();
+
+        let mut list: Vec<SessionRecord> = sessions
+            .iter()
+            .filter(|row| row.user_id == user_id)
+            .filter(|row| {
+                tokens.iter().any(|token| {
+                    token.session_id == row.session.id
+                        && token.revoked_at.is_none()
+                        && token.expires_at > now
+                })
+            })
+            .map(|row| row.session.clone())
+            .collect();
+        list.sort_by_key(|session| Reverse(session.last_used_at));
+
+        Ok(list)
+    }
+
+    async fn record_access_token(
+        &self,
+        session_id: Uuid,
+        jti: Uuid,
+        expires_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors> {
+        let mut sessions = self.sessions.lock().unwrap();
+
+        if let Some(row) = sessions.iter_mut().find(|row| row.session.id == session_id) {
+            row.access = Some((jti, expires_at));
+        }
+
+        Ok(())
+    }
+
+    async fn access_tokens(
+        &self,
+        user_id: Uuid,
+        now: NaiveDateTime,
+    ) -> Result<Vec<AccessTokenRecord>, ApiErrors> {
+        let sessions = self.sessions.lock().unwrap();
+
+        Ok(sessions
+            .iter()
+            .filter(|row| row.user_id == user_id)
+            .filter_map(|row| {
+                let (jti, expires_at) = row.access?;
+
+                (expires_at > now).then_some(AccessTokenRecord {
+                    session_id: row.session.id,
+                    jti,
+                    expires_at,
+                })
+            })
+            .collect())
+    }
+
+    async fn revoke_session(&self, user_id: Uuid, session_id: Uuid) -> Result<bool, ApiErrors> {
+        let mut tokens = self.tokens.lock().unwrap();
+
+        let mut found = false;
+
+        for row in tokens.iter_mut().filter(|row| {
+            row.user_id == user_id && row.session_id == session_id && row.revoked_at.is_none()
+        }) {
+            row.revoked_at.get_or_insert(Utc::now().naive_utc());
+            found = true;
+        }
+
+        Ok(found)
+    }
+
+    async fn store_refresh_token(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+        token_hash: &str,
+        expires_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors> {
+        self.tokens.lock().unwrap().push(RefreshTokenRow {
+            id: Uuid::new_v4(),
+            user_id,
+            session_id,
+            token_hash: token_hash.to_string(),
+            created_at: Utc::now().naive_utc(),
+            expires_at,
+            revoked_at: None,
+        });
+
+        Ok(())
+    }
+
+    async fn find_refresh_token(
+        &self,
+        token_hash: &str,
+    ) -> Result<Option<RefreshTokenRecord>, ApiErrors> {
+        let tokens = self.tokens.lock().unwrap();
+
+        Ok(tokens
+            .iter()
+            .find(|row| row.token_hash == token_hash)
+            .map(RefreshTokenRow::record))
+    }
+
+    async fn revoke_refresh_token(&self, id: Uuid) -> Result<bool, ApiErrors> {
+        let mut tokens = self.tokens.lock().unwrap();
+
+        let Some(row) = tokens
+            .iter_mut()
+            .find(|row| row.id == id && row.revoked_at.is_none())
+        else {
+            return Ok(false);
+        };
+
+        row.revoked_at.get_or_insert(Utc::now().naive_utc());
+
+        Ok(true)
+    }
+
+    async fn revoke_refresh_token_by_hash(&self, token_hash: &str) -> Result<(), ApiErrors> {
+        let mut tokens = self.tokens.lock().unwrap();
+
+        if let Some(row) = tokens.iter_mut().find(|row| row.token_hash == token_hash) {
+            row.revoked_at.get_or_insert(Utc::now().naive_utc());
+        }
+
+        Ok(())
+    }
+
+    async fn revoke_user_refresh_tokens(
+        &self,
+        user_id: Uuid,
+        except_hash: Option<&str>,
+    ) -> Result<(), ApiErrors> {
+        let mut tokens = self.tokens.lock().unwrap();
+
+        for row in tokens
+            .iter_mut()
+            .filter(|row| row.user_id == user_id && Some(row.token_hash.as_str()) != except_hash)
+        {
+            row.revoked_at.get_or_insert(Utc::now().naive_utc());
+        }
+
+        Ok(())
+    }
+
+    async fn purge_expired(
+        &self,
+        now: NaiveDateTime,
+        revoked_before: NaiveDateTime,
+        limit: u32,
+    ) -> Result<u64, ApiErrors> {
+        let mut tokens = self.tokens.lock().unwrap();
+
+        let mut purged = 0;
+        tokens.retain(|row| {
+            let purgeable = row.expires_at < now
+                || row
+                    .revoked_at
+                    .is_some_and(|revoked_at| revoked_at < revoked_before);
+
+            if purgeable && purged < limit as u64 {
+                purged += 1;
+                return false;
+            }
+
+            true
+        });
+
+        self.sessions.lock().unwrap().retain(|row| {
+            row.session.last_used_at >= revoked_before
+                || tokens
+                    .iter()
+                    .any(|token| token.session_id == row.session.id)
+        });
+
+        Ok(purged)
+    }
+}
@@ -1,11 +1,15 @@
 use async_trait::async_trait;
-use chrono::{Duration, Utc};
+use chrono::NaiveDateTime;
 use sqlx::PgPool;
 use uuid::Uuid;
 
 use crate::{
     errors::api_errors::ApiErrors,
-    refresh_token::{dto::RefreshTokenRecord, repo::RefreshTokenRepository},
+    extractor::client_info_extractor::ClientInfo,
+    refresh_token::{
+        dto::{AccessTokenRecord, RefreshTokenRecord, SessionRecord},
+        repo::RefreshTokenRepository,
+    },
 };
 
 pub struct RefreshTokenRepoSqlx {
@@ -14,16 +18,144 @@ pub struct RefreshTokenRepoSqlx {
 
 #[async_trait]
 impl RefreshTokenRepository for RefreshTokenRepoSqlx {
-    async fn store_refresh_token(&self, user_id: Uuid, token: &str) -> Result<(), ApiErrors> {
+    async fn create_session(
+        &self,
+        session_id: Uuid,
+        user_id: Uuid,
+        client: &ClientInfo,
+    ) -> Result<(), ApiErrors> {
+        sqlx::query!(
+            "INSERT INTO sessions (id, user_id, user_agent, ip) VALUES ($1, $2, $3, $4)",
+            session_id,
+            user_id,
+            client.user_agent.as_deref(),
+            client.ip.map(|ip| ip.to_string()),
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed to store session".into()))?;
+
+        Ok(())
+    }
+
+    async fn touch_session(
+        &self,
+        session_id: Uuid,
+        client: &ClientInfo,
+        now: NaiveDateTime,
+    ) -> Result<(), ApiErrors> {
+        sqlx::query!(
+            r#"
+            UPDATE sessions
+            SET last_used_at = $2, ip = COALESCE($3, ip), user_agent = COALESCE($4, user_agent)
+            WHERE id = $1
+            "#,
+            session_id,
+            now,
+            client.ip.map(|ip| ip.to_string()),
+            client.user_agent.as_deref(),
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed to update session".into()))?;
+
+        Ok(())
+    }
+
+    async fn list_sessions(
+        &self,
+        user_id: Uuid,
+        now: NaiveDateTime,
+    ) -> Result<Vec<SessionRecord>, ApiErrors> {
+        sqlx::query_as!(
+            SessionRecord,
+            r#"
+            SELECT s.id, s.user_agent, s.ip, s.created_at, s.last_used_at
+            FROM sessions s
+            WHERE s.user_id = $1
+              AND EXISTS (
+                SELECT 1 FROM refresh_tokens t
+                WHERE t.session_id = s.id AND t.revoked IS NOT TRUE AND t.expires_at > $2
+              )
+            ORDER BY s.last_used_at DESC
+            "#,
+            user_id,
+            now,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Session lookup failed".into()))
+    }
+
+    async fn record_access_token(
+        &self,
+        session_id: Uuid,
+        jti: Uuid,
+        expires_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors> {
+        sqlx::query!(
+            "UPDATE sessions SET access_jti = $2, access_expires_at = $3 WHERE id = $1",
+            session_id,
+            jti,
+            expires_at,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed to update session".into()))?;
+
+        Ok(())
+    }
+
+    async fn access_tokens(
+        &self,
+        user_id: Uuid,
+        now: NaiveDateTime,
+    ) -> Result<Vec<AccessTokenRecord>, ApiErrors> {
+        sqlx::query_as!(
+            AccessTokenRecord,
+            r#"
+            SELECT id AS session_id, access_jti AS "jti!", access_expires_at AS "expires_at!"
+            FROM sessions
+            WHERE user_id = $1 AND access_jti IS NOT NULL AND access_expires_at > $2
+            "#,
+            user_id,
+            now,
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Session lookup failed".into()))
+    }
+
+    async fn revoke_session(&self, user_id: Uuid, session_id: Uuid) -> Result<bool, ApiErrors> {
+        let revoked = sqlx::query!(
+            r#"UPDATE refresh_tokens SET revoked = true, revoked_at = NOW() WHERE user_id = $1 AND session_id = $2 AND revoked IS NOT TRUE"#,
+            user_id,
+            session_id,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Token revoke failed".into()))?;
+
+        Ok(revoked.rows_affected() > 0)
+    }
+
+    async fn store_refresh_token(
+        &self,
+        user_id: Uuid,
+        session_id: Uuid,
+        token_hash: &str,
+        expires_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors> {
         sqlx::query!(
             r#"
-            INSERT INTO refresh_tokens (id, user_id, token, expires_at)
-            VALUES ($1, $2, $3, $4)
+            INSERT INTO refresh_tokens (id, user_id, session_id, token_hash, expires_at)
+            VALUES ($1, $2, $3, $4, $5)
             "#,
             Uuid::new_v4(),
             user_id,
-            token,
-            Utc::now().naive_utc() + Duration::days(7),
+            session_id,
+            token_hash,
+            expires_at,
         )
         .execute(&self.pool)
         .await
@@ -34,16 +166,16 @@ impl RefreshTokenRepository for RefreshTokenRepoSqlx {
 
     async fn find_refresh_token(
         &self,
-        token: &str,
+        token_hash: &str,
     ) -> Result<Option<RefreshTokenRecord>, ApiErrors> {
         let rec = sqlx::query_as!(
             RefreshTokenRecord,
             r#"
-            SELECT id, user_id, expires_at, revoked
+            SELECT id, user_id, session_id, expires_at, revoked
             FROM refresh_tokens
-            WHERE token = $1
+            WHERE token_hash = $1
             "#,
-            token
+            token_hash
         )
         .fetch_optional(&self.pool)
         .await
@@ -72,22 +204,39 @@ impl RefreshTokenRepository for RefreshTokenRepoSqlx {
         //     })
     }
 
-    async fn revoke_refresh_token(&self, id: Uuid) -> Result<(), ApiErrors> {
-        sqlx::query!(
-            r#"UPDATE refresh_tokens SET revoked = true WHERE id = $1"#,
+    async fn revoke_refresh_token(&self, id: Uuid) -> Result<bool, ApiErrors> {
+        let revoked = sqlx::query!(
+            r#"UPDATE refresh_tokens SET revoked = true, revoked_at = NOW() WHERE id = $1 AND revoked IS NOT TRUE"#,
             id
         )
         .execute(&self.pool)
         .await
         .map_err(|_| ApiErrors::InternalServerError("Token revoke failed".into()))?;
 
+        Ok(revoked.rows_affected() > 0)
+    }
+
+    async fn revoke_refresh_token_by_hash(&self, token_hash: &str) -> Result<(), ApiErrors> {
+        sqlx::query!(
+            r#"UPDATE refresh_tokens SET revoked = true, revoked_at = COALESCE(revoked_at, NOW()) WHERE token_hash = $1"#,
+            token_hash
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Token revoke failed".into()))?;
+
         Ok(())
     }
 
-    async fn revoke_refresh_token_by_value(&self, token: &str) -> Result<(), ApiErrors> {
+    async fn revoke_user_refresh_tokens(
+        &self,
+        user_id: Uuid,
+        except_hash: Option<&str>,
+    ) -> Result<(), ApiErrors> {
         sqlx::query!(
-            r#"UPDATE refresh_tokens SET revoked = true WHERE token = $1"#,
-            token
+            r#"UPDATE refresh_tokens SET revoked = true, revoked_at = NOW() WHERE user_id = $1 AND revoked IS NOT TRUE AND token_hash IS DISTINCT FROM $2"#,
+            user_id,
+            except_hash
         )
         .execute(&self.pool)
         .await
@@ -95,4 +244,43 @@ impl RefreshTokenRepository for RefreshTokenRepoSqlx {
 
         Ok(())
     }
+
+    async fn purge_expired(
+        &self,
+        now: NaiveDateTime,
+        revoked_before: NaiveDateTime,
+        limit: u32,
+    ) -> Result<u64, ApiErrors> {
+        let purged = sqlx::query!(
+            r#"
+            DELETE FROM refresh_tokens
+            WHERE id IN (
+                SELECT id FROM refresh_tokens
+                WHERE expires_at < $1 OR (revoked AND revoked_at < $2)
+                LIMIT $3
+            )
+            "#,
+            now,
+            revoked_before,
+            i64::from(limit),
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Token purge failed".into()))?;
+
+        // Idle sessions only, so one whose first token is being stored right now stays.
+        sqlx::query!(
+            r#"
+            DELETE FROM sessions s
+            WHERE s.last_used_at < $1
+              AND NOT EXISTS (SELECT 1 FROM refresh_tokens t WHERE t.session_id = s.id)
+            "#,
+            revoked_before,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Session purge failed".into()))?;
+
+        Ok(purged.rows_affected())
+    }
 }
@@ -1,24 +1,29 @@
-use sqlx::PgPool;
 use tokio::sync::mpsc;
 use uuid::Uuid;
 
 use crate::{
     errors::api_errors::ApiErrors,
-    fields::text::Text,
     stack::{
         dispatcher::stack_dispatcher,
         dto::{CreateStackData, UpdatedStackData},
         messages::{StackMessage, StackResponse},
+        repo::StackRepository,
     },
 };
 
-pub struct StackActor {
-    pool: PgPool,
+pub struct StackActor<R>
+where
+    R: StackRepository + Send + Sync + 'static,
+{
+    pub repo: R,
 }
 
-impl StackActor {
-    pub fn new(pool: PgPool) -> Self {
-        Self { pool }
+impl<R> StackActor<R>
+where
+    R: StackRepository + Send + Sync + 'static,
+{
+    pub fn new(repo: R) -> Self {
+        Self { repo }
     }
 
     pub async fn run(self, rx: mpsc::Receiver<StackMessage>) {
@@ -30,113 +35,45 @@ impl StackActor {
 
         let created_at = chrono::Utc::now().naive_utc();
 
-        sqlx::query!(
-            "INSERT INTO stack (id, title, slug, created_by, created_by_name, created_by_email, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
-            id,
-            stack.title.as_str(),
-            stack.slug.as_str(),
-            stack.created_by,
-            stack.created_by_name.as_str(),
-            stack.created_by_email.as_str(),
-            created_at,
-            created_at,
-        )
-        .execute(&self.pool)
-        .await
-        .map_err(|_| ApiErrors::Conflict("Stack already exists".to_string()))?;
+        self.repo.insert_stack(id, &stack, created_at).await?;
 
         Ok(id)
     }
 
     pub async fn get_single_stack(&self, stack_id: Uuid) -> Result<StackResponse, ApiErrors> {
-        let stack = sqlx::query!(
-            "SELECT id, title, slug, created_at, updated_at FROM stack WHERE id = $1",
-            stack_id
-        )
-        .fetch_one(&self.pool)
-        .await
-        .map_err(|_| ApiErrors::NotFound("Stack not found".to_string()))?;
-
-        Ok(StackResponse {
-            id: stack.id,
-            title: Text(stack.title),
-            slug: Text(stack.slug),
-            created_at: stack.created_at,
-            updated_at: stack.updated_at,
-        })
+        self.repo
+            .find_stack(stack_id)
+            .await?
+            .ok_or_else(|| ApiErrors::NotFound("Stack not found".to_string()))
     }
 
     pub async fn get_single_stack_by_title(
         &self,
         stack_title: String,
     ) -> Result<StackResponse, ApiErrors> {
-        let stack = sqlx::query!(
-            "SELECT title, id, slug, created_at, updated_at FROM stack WHERE title = $1",
-            stack_title
-        )
-        .fetch_one(&self.pool)
-        .await
-        .map_err(|_| ApiErrors::NotFound("Stack not found".to_string()))?;
-
-        Ok(StackResponse {
-            id: stack.id,
-            title: Text(stack.title),
-            slug: Text(stack.slug),
-            created_at: stack.created_at,
-            updated_at: stack.updated_at,
-        })
+        self.repo
+            .find_stack_by_title(&stack_title)
+            .await?
+            .ok_or_else(|| ApiErrors::NotFound("Stack not found".to_string()))
     }
 
     pub async fn get_all_stack(&self) -> Result<Vec<StackResponse>, ApiErrors> {
-        let stack = sqlx::query!(
-            "SELECT id, title, slug, created_at, updated_at FROM stack ORDER BY created_at DESC"
-        )
-        .fetch_all(&self.pool)
-        .await
-        .map_err(|_| ApiErrors::InternalServerError("Failed to fetch stack".to_string()))?;
-
-        Ok(stack
-            .into_iter()
-            .map(|sck| StackResponse {
-                id: sck.id,
-                title: Text(sck.title),
-                slug: Text(sck.slug),
-                created_at: sck.created_at,
-                updated_at: sck.updated_at,
-            })
-            .collect())
+        self.repo.list_stacks().await
     }
 
     pub async fn update_stack(&self, stack: UpdatedStackData) -> Result<bool, ApiErrors> {
-        let result = sqlx::query!(r#"UPDATE stack SET title = COALESCE($1, title), slug = COALESCE($2, slug), edited_by = $3, edited_by_name = $4, edited_by_email = $5 WHERE id = $6"#,
-                stack.title.as_ref().map(|s| s.as_str()),
-                stack.slug.as_ref().map(|s| s.as_str()),
-                stack.edited_by,
-                stack.edited_by_name.as_str(),
-                stack.edited_by_email.as_str(),
-                stack.stack_id,
-            )
-            .execute(&self.pool)
-            .await
-            .map_err(|_| ApiErrors::InternalServerError("Update failed".to_string()))?;
-
-        if result.rows_affected() == 0 {
+        if !self.repo.update_stack(&stack).await? {
             return Err(ApiErrors::NotFound("Stack not found".to_string()));
         }
 
-        Ok(result.rows_affected() > 0)
+        Ok(true)
     }
 
     pub async fn delete_stack(&self, stack_id: Uuid) -> Result<bool, ApiErrors> {
-        let result = sqlx::query!("DELETE FROM stack WHERE id = $1", stack_id)
-            .execute(&self.pool)
-            .await
-            .map_err(|_| ApiErrors::InternalServerError("Delete failed".to_string()))?;
-
-        if result.rows_affected() == 0 {
+        if !self.repo.delete_stack(stack_id).await? {
             return Err(ApiErrors::NotFound("Stack not found".to_string()));
         }
 
-        Ok(result.rows_affected() > 0)
+        Ok(true)
     }
 }
@@ -1,8 +1,11 @@
 use tokio::sync::mpsc;
 
-use crate::stack::{actor::StackActor, messages::StackMessage};
+use crate::stack::{actor::StackActor, messages::StackMessage, repo::StackRepository};
 
-pub async fn stack_dispatcher(actor: &StackActor, mut rx: mpsc::Receiver<StackMessage>) {
+pub async fn stack_dispatcher<R>(actor: &StackActor<R>, mut rx: mpsc::Receiver<StackMessage>)
+where
+    R: StackRepository + Send + Sync + 'static,
+{
     while let Some(msg) = rx.recv().await {
         match msg {
             StackMessage::Create { stack, respond_to } => {
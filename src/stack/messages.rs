@@ -8,7 +8,7 @@ use crate::{
     stack::dto::{CreateStackData, UpdatedStackData},
 };
 
-#[derive(serde::Serialize)]
+#[derive(serde::Serialize, Clone)]
 pub struct StackResponse {
     pub id: Uuid,
     pub title: Text,
@@ -0,0 +1,31 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use crate::{
+    errors::api_errors::ApiErrors,
+    stack::{
+        dto::{CreateStackData, UpdatedStackData},
+        messages::StackResponse,
+    },
+};
+
+#[async_trait]
+pub trait StackRepository: Send + Sync {
+    async fn insert_stack(
+        &self,
+        id: Uuid,
+        stack: &CreateStackData,
+        created_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors>;
+
+    async fn find_stack(&self, stack_id: Uuid) -> Result<Option<StackResponse>, ApiErrors>;
+
+    async fn find_stack_by_title(&self, title: &str) -> Result<Option<StackResponse>, ApiErrors>;
+
+    async fn list_stacks(&self) -> Result<Vec<StackResponse>, ApiErrors>;
+
+    async fn update_stack(&self, stack: &UpdatedStackData) -> Result<bool, ApiErrors>;
+
+    async fn delete_stack(&self, stack_id: Uuid) -> Result<bool, ApiErrors>;
+}
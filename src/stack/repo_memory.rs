@@ -0,0 +1,102 @@
+use std::{cmp::Reverse, sync::Mutex};
+
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use crate::{
+    errors::api_errors::ApiErrors,
+    stack::{
+        dto::{CreateStackData, UpdatedStackData},
+        messages::StackResponse,
+        repo::StackRepository,
+    },
+};
+
+#[derive(Default)]
+pub struct StackRepoMemory {
+    stacks: Mutex<Vec<StackResponse>>,
+}
+
+impl StackRepoMemory {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+#[async_trait]
+impl StackRepository for StackRepoMemory {
+    async fn insert_stack(
+        &self,
+        id: Uuid,
+        stack: &CreateStackData,
+        created_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors> {
+        let mut stacks = self.stacks.lock().unwrap();
+
+        if stacks
+            .iter()
+            .any(|s| s.title.as_str() == stack.title.as_str())
+        {
+            return Err(ApiErrors::Conflict("Stack already exists".to_string()));
+        }
+
+        stacks.push(StackResponse {
+            id,
+            title: stack.title.clone(),
+            slug: stack.slug.clone(),
+            created_at,
+            updated_at: created_at,
+        });
+
+        Ok(())
+    }
+
+    async fn find_stack(&self, stack_id: Uuid) -> Result<Option<StackResponse>, ApiErrors> {
+        let stacks = self.stacks.lock().unwrap();
+
+        Ok(stacks.iter().find(|s| s.id == stack_id).cloned())
+    }
+
+    async fn find_stack_by_title(&self, title: &str) -> Result<Option<StackResponse>, ApiErrors> {
+        let stacks = self.stacks.lock().unwrap();
+
+        Ok(stacks.iter().find(|s| s.title.as_str() == title).cloned())
+    }
+
+    async fn list_stacks(&self) -> Result<Vec<StackResponse>, ApiErrors> {
+        let stacks = self.stacks.lock().unwrap();
+
+        let mut list = stacks.clone();
+        list.sort_by_key(|item| Reverse(item.created_at));
+
+        Ok(list)
+    }
+
+    async fn update_stack(&self, stack: &UpdatedStackData) -> Result<bool, ApiErrors> {
+        let mut stacks = self.stacks.lock().unwrap();
+
+        let Some(row) = stacks.iter_mut().find(|s| s.id == stack.stack_id) else {
+            return Ok(false);
+        };
+
+        if let Some(title) = &stack.title {
+            row.title = title.clone();
+        }
+
+        if let Some(slug) = &stack.slug {
+            row.slug = slug.clone();
+        }
+
+        Ok(true)
+    }
+
+    async fn delete_stack(&self, stack_id: Uuid) -> Result<bool, ApiErrors> {
+        let mut stacks = self.stacks.lock().unwrap();
+
+        let before = stacks.len();
+        stacks.retain(|s| s.id != stack_id);
+
+        Ok(stacks.len() < before)
+    }
+}
@@ -0,0 +1,126 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    errors::api_errors::ApiErrors,
+    fields::text::Text,
+    stack::{
+        dto::{CreateStackData, UpdatedStackData},
+        messages::StackResponse,
+        repo::StackRepository,
+    },
+};
+
+pub struct StackRepoSqlx {
+    pub pool: PgPool,
+}
+
+#[async_trait]
+impl StackRepository for StackRepoSqlx {
+    async fn insert_stack(
+        &self,
+        id: Uuid,
+        stack: &CreateStackData,
+        created_at: NaiveDateTime,
+    ) -> Result<(), ApiErrors> {
+        sqlx::query!(
+            "INSERT INTO stack (id, title, slug, created_by, created_by_name, created_by_email, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+            id,
+            stack.title.as_str(),
+            stack.slug.as_str(),
+            stack.created_by,
+            stack.created_by_name.as_str(),
+            stack.created_by_email.as_str(),
+            created_at,
+            created_at,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::Conflict("Stack already exists".to_string()))?;
+
+        Ok(())
+    }
+
+    async fn find_stack(&self, stack_id: Uuid) -> Result<Option<StackResponse>, ApiErrors> {
+        let stack = sqlx::query!(
+            "SELECT id, title, slug, created_at, updated_at FROM stack WHERE id = $1",
+            stack_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Stack lookup failed".to_string()))?;
+
+        Ok(stack.map(|stack| StackResponse {
+            id: stack.id,
+            title: Text(stack.title),
+            slug: Text(stack.slug),
+            created_at: stack.created_at,
+            updated_at: stack.updated_at,
+        }))
+    }
+
+    async fn find_stack_by_title(&self, title: &str) -> Result<Option<StackResponse>, ApiErrors> {
+        let stack = sqlx::query!(
+            "SELECT title, id, slug, created_at, updated_at FROM stack WHERE title = $1",
+            title
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Stack lookup failed".to_string()))?;
+
+        Ok(stack.map(|stack| StackResponse {
+            id: stack.id,
+            title: Text(stack.title),
+            slug: Text(stack.slug),
+            created_at: stack.created_at,
+            updated_at: stack.updated_at,
+        }))
+    }
+
+    async fn list_stacks(&self) -> Result<Vec<StackResponse>, ApiErrors> {
+        let stack = sqlx::query!(
+            "SELECT id, title, slug, created_at, updated_at FROM stack ORDER BY created_at DESC"
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed to fetch stack".to_string()))?;
+
+        Ok(stack
+            .into_iter()
+            .map(|sck| StackResponse {
+                id: sck.id,
+                title: Text(sck.title),
+                slug: Text(sck.slug),
+                created_at: sck.created_at,
+                updated_at: sck.updated_at,
+            })
+            .collect())
+    }
+
+    async fn update_stack(&self, stack: &UpdatedStackData) -> Result<bool, ApiErrors> {
+        let result = sqlx::query!(r#"UPDATE stack SET title = COALESCE($1, title), slug = COALESCE($2, slug), edited_by = $3, edited_by_name = $4, edited_by_email = $5 WHERE id = $6"#,
+                stack.title.as_ref().map(|s| s.as_str()),
+                stack.slug.as_ref().map(|s| s.as_str()),
+                stack.edited_by,
+                stack.edited_by_name.as_str(),
+                stack.edited_by_email.as_str(),
+                stack.stack_id,
+            )
+            .execute(&self.pool)
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Update failed".to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn delete_stack(&self, stack_id: Uuid) -> Result<bool, ApiErrors> {
+        let result = sqlx::query!("DELETE FROM stack WHERE id = $1", stack_id)
+            .execute(&self.pool)
+            .await
+            .map_err(|_| ApiErrors::InternalServerError("Delete failed".to_string()))?;
+
+        Ok(result.rows_affected() > 0)
+    }
+}
@@ -1,9 +1,21 @@
+use std::sync::Arc;
+
 use tokio::sync::mpsc::Sender;
 
 use crate::{
-    auth::messages::AuthMessage, blog::messages::BlogMessage, image::messages::ImageMessage,
-    project::messages::ProjectMessage, refresh_token::messages::RefreshTokenMessage,
+    api_key::messages::ApiKeyMessage,
+    auth::messages::AuthMessage,
+    blog::messages::BlogMessage,
+    config::{ImageLimits, TokenConfig},
+    core::jwt_keys::JwtKeys,
+    image::{circuit_breaker::CircuitBreaker, messages::ImageMessage},
+    media::messages::MediaMessage,
+    oidc::messages::OidcMessage,
+    project::messages::ProjectMessage,
+    rate_limit::middleware::RateLimits,
+    refresh_token::messages::RefreshTokenMessage,
     stack::messages::StackMessage,
+    token_denylist::denylist::TokenDenylist,
 };
 
 #[derive(Clone)]
@@ -11,8 +23,18 @@ pub struct AppState {
     pub auth_tx: Sender<AuthMessage>,
     pub stack_tx: Sender<StackMessage>,
     pub image_tx: Sender<ImageMessage>,
+    pub media_tx: Sender<MediaMessage>,
     pub blog_tx: Sender<BlogMessage>,
     pub project_tx: Sender<ProjectMessage>,
     pub refresh_token_tx: Sender<RefreshTokenMessage>,
-    pub jwt_secret: String,
+    pub api_key_tx: Sender<ApiKeyMessage>,
+    /// `None` when single sign-on is not configured.
+    pub oidc_tx: Option<Sender<OidcMessage>>,
+    pub jwt_keys: Arc<JwtKeys>,
+    pub tokens: TokenConfig,
+    pub denylist: Arc<TokenDenylist>,
+    pub image_circuit: Option<Arc<CircuitBreaker>>,
+    pub image_limits: ImageLimits,
+    pub trust_forwarded_for: bool,
+    pub rate_limits: RateLimits,
 }
@@ -0,0 +1,14 @@
+mod api_key_tests;
+mod auth_tests;
+mod blog_tests;
+mod image_tests;
+mod jwt_tests;
+mod media_tests;
+mod oidc_tests;
+mod project_tests;
+mod rate_limit_tests;
+mod refresh_token_tests;
+mod router_tests;
+mod stack_tests;
+mod support;
+mod two_factor_tests;
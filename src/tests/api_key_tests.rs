@@ -0,0 +1,240 @@
+use axum::{
+    Router,
+    body::Body,
+    http::{Method, Request, StatusCode},
+};
+use serde_json::{Value, json};
+
+use crate::tests::support::{
+    PNG_DATA_URL, ROOT_EMAIL, login_token, memory_repos, request, seed_user, send, spawn_app,
+};
+
+fn with_api_key(mut request: Request<Body>, key: &str) -> Request<Body> {
+    request
+        .headers_mut()
+        .insert("x-api-key", key.parse().unwrap());
+    request
+}
+
+async fn create_key(app: &Router, token: &str, body: Value) -> crate::tests::support::TestResponse {
+    send(
+        app,
+        request(Method::POST, "/api/v1/api-keys", Some(token), Some(body)),
+    )
+    .await
+}
+
+fn create_stack(key: &str, title: &str) -> Request<Body> {
+    with_api_key(
+        request(
+            Method::POST,
+            "/api/v1/stack/create",
+            None,
+            Some(json!({ "title": title, "slug": title.to_lowercase() })),
+        ),
+        key,
+    )
+}
+
+#[tokio::test]
+async fn keys_act_as_their_creator_within_their_scopes() {
+    let app = spawn_app(memory_repos().await);
+    let root_token = login_token(&app, ROOT_EMAIL).await;
+
+    let created = create_key(
+        &app,
+        &root_token,
+        json!({ "name": "CI deploy", "scopes": ["stack:write"], "expires_in_days": 30 }),
+    )
+    .await;
+    assert_eq!(created.status, StatusCode::OK);
+    let key = created.body["data"]["key"].as_str().unwrap().to_string();
+    let prefix = created.body["data"]["api_key"]["prefix"].as_str().unwrap();
+    assert!(key.starts_with(&format!("pk_{prefix}_")));
+    assert!(created.body["data"]["api_key"]["expires_at"].is_string());
+
+    let stack = send(&app, create_stack(&key, "Rust")).await;
+    assert_eq!(stack.status, StatusCode::OK);
+
+    let out_of_scope = send(
+        &app,
+        with_api_key(
+            request(
+                Method::DELETE,
+                &format!("/api/v1/blog/detail/{}", uuid::Uuid::new_v4()),
+                None,
+                None,
+            ),
+            &key,
+        ),
+    )
+    .await;
+    assert_eq!(out_of_scope.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        out_of_scope.body["message"],
+        "API key lacks the blog:write scope"
+    );
+
+    let no_keys_here = send(
+        &app,
+        with_api_key(request(Method::GET, "/api/v1/auth/me", None, None), &key),
+    )
+    .await;
+    assert_eq!(no_keys_here.status, StatusCode::UNAUTHORIZED);
+
+    let listed = send(
+        &app,
+        request(Method::GET, "/api/v1/api-keys", Some(&root_token), None),
+    )
+    .await;
+    let listed_key = &listed.body["data"][0];
+    assert_eq!(listed_key["scopes"], json!(["stack:write"]));
+    assert!(listed_key["last_used_at"].is_string());
+    assert!(listed_key.get("secret_hash").is_none());
+
+    let revoked = send(
+        &app,
+        request(
+            Method::DELETE,
+            &format!("/api/v1/api-keys/{}", listed_key["id"].as_str().unwrap()),
+            Some(&root_token),
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(revoked.status, StatusCode::OK);
+
+    let refused = send(&app, create_stack(&key, "Go")).await;
+    assert_eq!(refused.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(refused.body["message"], "Invalid API key");
+}
+
+#[tokio::test]
+async fn a_key_and_a_bearer_token_together_are_refused() {
+    let repos = memory_repos().await;
+    seed_user(&repos.users, "normal@example.com", "normal").await;
+    let app = spawn_app(repos);
+
+    let root_token = login_token(&app, ROOT_EMAIL).await;
+    let normal_token = login_token(&app, "normal@example.com").await;
+
+    let created = create_key(
+        &app,
+        &root_token,
+        json!({ "name": "CI deploy", "scopes": ["stack:write"] }),
+    )
+    .await;
+    let key = created.body["data"]["key"].as_str().unwrap();
+
+    let both = send(
+        &app,
+        with_api_key(
+            request(
+                Method::POST,
+                "/api/v1/stack/create",
+                Some(&normal_token),
+                Some(json!({ "title": "Rust", "slug": "rust" })),
+            ),
+            key,
+        ),
+    )
+    .await;
+    assert_eq!(both.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn upload_keys_upload_but_leave_the_library_alone() {
+    let app = spawn_app(memory_repos().await);
+    let root_token = login_token(&app, ROOT_EMAIL).await;
+
+    let created = create_key(
+        &app,
+        &root_token,
+        json!({ "name": "Generator", "scopes": ["image:upload"] }),
+    )
+    .await;
+    let key = created.body["data"]["key"].as_str().unwrap().to_string();
+
+    let created = create_key(
+        &app,
+        &root_token,
+        json!({ "name": "CI deploy", "scopes": ["stack:write"] }),
+    )
+    .await;
+    let stack_key = created.body["data"]["key"].as_str().unwrap().to_string();
+
+    let upload = |key: &str| {
+        with_api_key(
+            request(
+                Method::POST,
+                "/api/v1/image/base64",
+                None,
+                Some(json!({ "image": PNG_DATA_URL })),
+            ),
+            key,
+        )
+    };
+
+    let refused = send(&app, upload(&stack_key)).await;
+    assert_eq!(refused.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(
+        refused.body["message"],
+        "API key lacks the image:upload scope"
+    );
+
+    let uploaded = send(&app, upload(&key)).await;
+    assert_eq!(uploaded.status, StatusCode::OK);
+    let detail = format!(
+        "/api/v1/media/detail/{}",
+        uploaded.body["media_id"].as_str().unwrap()
+    );
+
+    for method in [Method::PATCH, Method::DELETE] {
+        let response = send(
+            &app,
+            with_api_key(
+                request(method, &detail, None, Some(json!({ "alt": "Renamed" }))),
+                &key,
+            ),
+        )
+        .await;
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(
+            response.body["message"],
+            "API key lacks the media:write scope"
+        );
+    }
+}
+
+#[tokio::test]
+async fn only_admins_issue_keys_with_known_scopes() {
+    let repos = memory_repos().await;
+    seed_user(&repos.users, "normal@example.com", "normal").await;
+    let app = spawn_app(repos);
+
+    let normal_token = login_token(&app, "normal@example.com").await;
+    let by_normal = create_key(
+        &app,
+        &normal_token,
+        json!({ "name": "Mine", "scopes": ["blog:read"] }),
+    )
+    .await;
+    assert_eq!(by_normal.status, StatusCode::BAD_REQUEST);
+
+    let root_token = login_token(&app, ROOT_EMAIL).await;
+    let unknown = create_key(
+        &app,
+        &root_token,
+        json!({ "name": "Generator", "scopes": ["everything"] }),
+    )
+    .await;
+    assert_eq!(unknown.status, StatusCode::BAD_REQUEST);
+
+    let unscoped = create_key(
+        &app,
+        &root_token,
+        json!({ "name": "Generator", "scopes": [] }),
+    )
+    .await;
+    assert_eq!(unscoped.status, StatusCode::BAD_REQUEST);
+}
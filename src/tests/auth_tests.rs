@@ -0,0 +1,507 @@
+use std::{sync::Arc, time::Duration};
+
+use async_trait::async_trait;
+use axum::http::{Method, StatusCode, header};
+use serde_json::json;
+
+use crate::config::LoginThrottleConfig;
+use crate::errors::api_errors::ApiErrors;
+use crate::mail::mailer::{Mail, Mailer};
+use crate::tests::support::{
+    PASSWORD, ROOT_EMAIL, app_builder, login, login_token, mails, memory_repos, refresh_cookie,
+    request, seed_user, send, spawn_app, spawn_app_with_mailbox, with_cookie,
+};
+
+/// The `token` query parameter of the reset link in an email.
+fn reset_token(mail: &str) -> String {
+    mail.split("token=")
+        .nth(1)
+        .and_then(|rest| rest.split_whitespace().next())
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test]
+async fn login_returns_access_token_and_refresh_cookie() {
+    let app = spawn_app(memory_repos().await);
+
+    let response = login(&app, ROOT_EMAIL).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(response.body["token"].as_str().is_some());
+    assert!(refresh_cookie(&response).is_some());
+}
+
+#[tokio::test]
+async fn login_with_unknown_email_is_unauthorized() {
+    let app = spawn_app(memory_repos().await);
+
+    let response = login(&app, "nobody@example.com").await;
+
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn current_user_requires_a_token() {
+    let app = spawn_app(memory_repos().await);
+
+    let response = send(
+        &app,
+        request(Method::GET, "/api/v1/auth/current_users", None, None),
+    )
+    .await;
+
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn current_user_returns_the_logged_in_user() {
+    let app = spawn_app(memory_repos().await);
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    let response = send(
+        &app,
+        request(
+            Method::GET,
+            "/api/v1/auth/current_users",
+            Some(&token),
+            None,
+        ),
+    )
+    .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["data"]["email"], ROOT_EMAIL);
+    assert_eq!(response.body["data"]["roles"], "Root");
+}
+
+#[tokio::test]
+async fn registered_user_can_log_in() {
+    let app = spawn_app(memory_repos().await);
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    let response = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/auth/register",
+            Some(&token),
+            Some(json!({
+                "email": "mid@example.com",
+                "password": "Another#123",
+                "name": "Mid Admin",
+                "roles": "mid",
+            })),
+        ),
+    )
+    .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+
+    let login = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/auth/login",
+            None,
+            Some(json!({ "email": "mid@example.com", "password": "Another#123" })),
+        ),
+    )
+    .await;
+
+    assert_eq!(login.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn register_rejects_duplicate_email() {
+    let app = spawn_app(memory_repos().await);
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    let response = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/auth/register",
+            Some(&token),
+            Some(json!({
+                "email": ROOT_EMAIL,
+                "password": "Another#123",
+                "name": "Copy",
+                "roles": "mid",
+            })),
+        ),
+    )
+    .await;
+
+    assert_eq!(response.status, StatusCode::CONFLICT);
+}
+
+#[tokio::test]
+async fn normal_user_cannot_register_users() {
+    let repos = memory_repos().await;
+    seed_user(&repos.users, "normal@example.com", "normal").await;
+
+    let app = spawn_app(repos);
+    let token = login_token(&app, "normal@example.com").await;
+
+    let response = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/auth/register",
+            Some(&token),
+            Some(json!({
+                "email": "new@example.com",
+                "password": "Another#123",
+                "name": "New",
+                "roles": "mid",
+            })),
+        ),
+    )
+    .await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn deleted_user_is_no_longer_found() {
+    let repos = memory_repos().await;
+    let user_id = seed_user(&repos.users, "mid@example.com", "mid").await;
+
+    let app = spawn_app(repos);
+    let token = login_token(&app, ROOT_EMAIL).await;
+    let uri = format!("/api/v1/auth/users/{user_id}");
+
+    let delete = send(&app, request(Method::DELETE, &uri, Some(&token), None)).await;
+    assert_eq!(delete.status, StatusCode::OK);
+
+    let get = send(&app, request(Method::GET, &uri, Some(&token), None)).await;
+    assert_eq!(get.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn password_reset_replaces_the_password_and_ends_sessions() {
+    let (app, mailbox) = spawn_app_with_mailbox(memory_repos().await);
+
+    let cookie = refresh_cookie(&login(&app, ROOT_EMAIL).await).unwrap();
+
+    let forgot = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/auth/password/forgot",
+            None,
+            Some(json!({ "email": ROOT_EMAIL })),
+        ),
+    )
+    .await;
+    assert_eq!(forgot.status, StatusCode::OK);
+
+    let mails = mails(&mailbox, 1).await;
+    assert_eq!(mails.len(), 1);
+    assert!(mails[0].contains(&format!("To: {ROOT_EMAIL}")));
+
+    let token = reset_token(&mails[0]);
+    let new_password = "Changed#456";
+
+    let reset = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/auth/password/reset",
+            None,
+            Some(json!({ "token": token, "password": new_password })),
+        ),
+    )
+    .await;
+    assert_eq!(reset.status, StatusCode::OK);
+
+    let new_login = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/auth/login",
+            None,
+            Some(json!({ "email": ROOT_EMAIL, "password": new_password })),
+        ),
+    )
+    .await;
+    assert_eq!(new_login.status, StatusCode::OK);
+
+    let old_login = login(&app, ROOT_EMAIL).await;
+    assert_eq!(old_login.status, StatusCode::UNAUTHORIZED);
+
+    let refreshed = send(
+        &app,
+        with_cookie(
+            request(Method::POST, "/api/v1/token/refresh", None, None),
+            &cookie,
+        ),
+    )
+    .await;
+    assert_eq!(refreshed.status, StatusCode::UNAUTHORIZED);
+
+    let reused = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/auth/password/reset",
+            None,
+            Some(json!({ "token": token, "password": PASSWORD })),
+        ),
+    )
+    .await;
+    assert_eq!(reused.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn forgot_password_does_not_reveal_unknown_emails() {
+    let (app, mailbox) = spawn_app_with_mailbox(memory_repos().await);
+
+    let response = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/auth/password/forgot",
+            None,
+            Some(json!({ "email": "nobody@example.com" })),
+        ),
+    )
+    .await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(mails(&mailbox, 0).await.is_empty());
+}
+
+/// A relay that accepts the connection and never answers.
+struct StalledMailer;
+
+#[async_trait]
+impl Mailer for StalledMailer {
+    async fn send(&self, _mail: Mail) -> Result<(), ApiErrors> {
+        std::future::pending().await
+    }
+}
+
+#[tokio::test]
+async fn a_stalled_mail_relay_holds_up_nothing() {
+    let app = app_builder(memory_repos().await)
+        .mailer(Arc::new(StalledMailer))
+        .build();
+
+    let forgot = tokio::time::timeout(
+        Duration::from_secs(5),
+        send(
+            &app,
+            request(
+                Method::POST,
+                "/api/v1/auth/password/forgot",
+                None,
+                Some(json!({ "email": ROOT_EMAIL })),
+            ),
+        ),
+    )
+    .await
+    .expect("forgot password waited on the relay");
+    assert_eq!(forgot.status, StatusCode::OK);
+
+    let logged_in = tokio::time::timeout(Duration::from_secs(5), login(&app, ROOT_EMAIL))
+        .await
+        .expect("login waited behind the relay");
+    assert_eq!(logged_in.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn using_one_reset_link_voids_the_others() {
+    let (app, mailbox) = spawn_app_with_mailbox(memory_repos().await);
+
+    for _ in 0..2 {
+        send(
+            &app,
+            request(
+                Method::POST,
+                "/api/v1/auth/password/forgot",
+                None,
+                Some(json!({ "email": ROOT_EMAIL })),
+            ),
+        )
+        .await;
+    }
+
+    let tokens: Vec<_> = mails(&mailbox, 2)
+        .await
+        .iter()
+        .map(|mail| reset_token(mail))
+        .collect();
+    assert_eq!(tokens.len(), 2);
+
+    let reset = |token: String| {
+        send(
+            &app,
+            request(
+                Method::POST,
+                "/api/v1/auth/password/reset",
+                None,
+                Some(json!({ "token": token, "password": "Changed#456" })),
+            ),
+        )
+    };
+
+    assert_eq!(reset(tokens[1].clone()).await.status, StatusCode::OK);
+    assert_eq!(
+        reset(tokens[0].clone()).await.status,
+        StatusCode::BAD_REQUEST
+    );
+}
+
+#[tokio::test]
+async fn users_update_their_own_profile() {
+    let repos = memory_repos().await;
+    seed_user(&repos.users, "normal@example.com", "normal").await;
+    let app = spawn_app(repos);
+
+    let token = login_token(&app, "normal@example.com").await;
+
+    let updated = send(
+        &app,
+        request(
+            Method::PATCH,
+            "/api/v1/auth/me",
+            Some(&token),
+            Some(json!({ "name": "Renamed User" })),
+        ),
+    )
+    .await;
+    assert_eq!(updated.status, StatusCode::OK);
+
+    let me = send(
+        &app,
+        request(Method::GET, "/api/v1/auth/me", Some(&token), None),
+    )
+    .await;
+    assert_eq!(me.body["data"]["name"], "Renamed User");
+    assert_eq!(me.body["data"]["phone_number"], "+2348012345678");
+    assert_eq!(me.body["data"]["roles"], "Normal");
+}
+
+#[tokio::test]
+async fn changing_password_needs_the_current_one_and_ends_other_sessions() {
+    let app = spawn_app(memory_repos().await);
+
+    let other_session = refresh_cookie(&login(&app, ROOT_EMAIL).await).unwrap();
+
+    let current = login(&app, ROOT_EMAIL).await;
+    let token = current.body["token"].as_str().unwrap().to_string();
+    let current_session = refresh_cookie(&current).unwrap();
+
+    let change = |current_password: &str| {
+        with_cookie(
+            request(
+                Method::POST,
+                "/api/v1/auth/me/password",
+                Some(&token),
+                Some(json!({
+                    "current_password": current_password,
+                    "new_password": "Changed#456",
+                })),
+            ),
+            &current_session,
+        )
+    };
+
+    let wrong = send(&app, change("Wrong#123")).await;
+    assert_eq!(wrong.status, StatusCode::BAD_REQUEST);
+
+    let changed = send(&app, change(PASSWORD)).await;
+    assert_eq!(changed.status, StatusCode::OK);
+
+    let refresh = |cookie: &str| {
+        with_cookie(
+            request(Method::POST, "/api/v1/token/refresh", None, None),
+            cookie,
+        )
+    };
+
+    assert_eq!(
+        send(&app, refresh(&other_session)).await.status,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        send(&app, refresh(&current_session)).await.status,
+        StatusCode::OK
+    );
+
+    let new_login = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/auth/login",
+            None,
+            Some(json!({ "email": ROOT_EMAIL, "password": "Changed#456" })),
+        ),
+    )
+    .await;
+    assert_eq!(new_login.status, StatusCode::OK);
+}
+
+fn login_with(email: &str, password: &str) -> axum::http::Request<axum::body::Body> {
+    request(
+        Method::POST,
+        "/api/v1/auth/login",
+        None,
+        Some(json!({ "email": email, "password": password })),
+    )
+}
+
+#[tokio::test]
+async fn wrong_passwords_are_unauthorized_and_slow_down_the_account() {
+    let app = spawn_app(memory_repos().await);
+
+    let weak = send(&app, login_with(ROOT_EMAIL, "short")).await;
+    assert_eq!(weak.status, StatusCode::UNAUTHORIZED);
+
+    let throttled = send(&app, login_with(ROOT_EMAIL, PASSWORD)).await;
+    assert_eq!(throttled.status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(throttled.headers[header::RETRY_AFTER], "1");
+}
+
+#[tokio::test]
+async fn locked_accounts_are_unlocked_by_an_admin() {
+    let repos = memory_repos().await;
+    let user_id = seed_user(&repos.users, "mid@example.com", "mid").await;
+
+    let app = app_builder(repos)
+        .login_throttle(LoginThrottleConfig {
+            max_account_failures: 3,
+            base_delay: Duration::ZERO,
+            ..LoginThrottleConfig::default()
+        })
+        .build();
+
+    for _ in 0..3 {
+        let response = send(&app, login_with("mid@example.com", "Wrong#123")).await;
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+    }
+
+    let locked = send(&app, login_with("mid@example.com", PASSWORD)).await;
+    assert_eq!(locked.status, StatusCode::TOO_MANY_REQUESTS);
+    assert!(locked.headers.contains_key(header::RETRY_AFTER));
+
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    let unlock = send(
+        &app,
+        request(
+            Method::POST,
+            &format!("/api/v1/auth/users/{user_id}/unlock"),
+            Some(&token),
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(unlock.status, StatusCode::OK);
+    assert_eq!(unlock.body["data"]["had_failures"], true);
+
+    let unlocked = send(&app, login_with("mid@example.com", PASSWORD)).await;
+    assert_eq!(unlocked.status, StatusCode::OK);
+}
@@ -0,0 +1,183 @@
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+
+use crate::tests::support::{
+    PNG_DATA_URL, ROOT_EMAIL, TestResponse, login_token, memory_repos, png_data_url, request,
+    seed_blog, send, spawn_app,
+};
+
+#[tokio::test]
+async fn list_filters_by_title_and_paginates() {
+    let repos = memory_repos().await;
+    seed_blog(&repos.blogs, "Rust ownership").await;
+    seed_blog(&repos.blogs, "Async rust").await;
+    seed_blog(&repos.blogs, "Postgres tips").await;
+
+    let app = spawn_app(repos);
+
+    let filtered = send(
+        &app,
+        request(Method::GET, "/api/v1/blog/all?title=rust", None, None),
+    )
+    .await;
+    assert_eq!(filtered.status, StatusCode::OK);
+    assert_eq!(filtered.body["data"]["total"], 2);
+
+    let paged = send(
+        &app,
+        request(Method::GET, "/api/v1/blog/all?page=2&limit=2", None, None),
+    )
+    .await;
+    assert_eq!(paged.body["data"]["total"], 3);
+    assert_eq!(paged.body["data"]["blogs"].as_array().unwrap().len(), 1);
+
+    let total = send(&app, request(Method::GET, "/api/v1/blog/total", None, None)).await;
+    assert_eq!(total.body["data"]["total"], 3);
+}
+
+#[tokio::test]
+async fn update_and_delete_blog() {
+    let repos = memory_repos().await;
+    let blog_id = seed_blog(&repos.blogs, "Rust ownership").await;
+
+    let app = spawn_app(repos);
+    let token = login_token(&app, ROOT_EMAIL).await;
+    let uri = format!("/api/v1/blog/detail/{blog_id}");
+
+    let updated = send(
+        &app,
+        request(
+            Method::PATCH,
+            &uri,
+            Some(&token),
+            Some(json!({ "title": "Borrowing" })),
+        ),
+    )
+    .await;
+    assert_eq!(updated.status, StatusCode::OK);
+
+    let fetched = send(&app, request(Method::GET, &uri, None, None)).await;
+    assert_eq!(fetched.body["data"]["title"], "Borrowing");
+
+    let deleted = send(&app, request(Method::DELETE, &uri, Some(&token), None)).await;
+    assert_eq!(deleted.status, StatusCode::OK);
+
+    let again = send(&app, request(Method::DELETE, &uri, Some(&token), None)).await;
+    assert_eq!(again.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn invalid_blog_id_is_a_bad_request() {
+    let app = spawn_app(memory_repos().await);
+
+    let response = send(
+        &app,
+        request(Method::GET, "/api/v1/blog/detail/not-a-uuid", None, None),
+    )
+    .await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn create_blog_uploads_the_image() {
+    let app = spawn_app(memory_repos().await);
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    let created = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/blog/create",
+            Some(&token),
+            Some(json!({
+                "title": "Hello",
+                "description": "First post",
+                "content": "Hello world",
+                "word_count": 2,
+                "image": PNG_DATA_URL,
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(created.status, StatusCode::OK);
+
+    let blog_id = created.body["data"]["blog_id"].as_str().unwrap();
+
+    let fetched = send(
+        &app,
+        request(
+            Method::GET,
+            &format!("/api/v1/blog/detail/{blog_id}"),
+            None,
+            None,
+        ),
+    )
+    .await;
+    assert!(
+        fetched.body["data"]["image"]
+            .as_str()
+            .unwrap()
+            .starts_with("/media/")
+    );
+}
+
+#[tokio::test]
+async fn replacing_or_deleting_a_blog_removes_its_old_image() {
+    let app = spawn_app(memory_repos().await);
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    let created = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/blog/create",
+            Some(&token),
+            Some(json!({
+                "title": "Hello",
+                "description": "First post",
+                "content": "Hello world",
+                "word_count": 2,
+                "image": PNG_DATA_URL,
+            })),
+        ),
+    )
+    .await;
+    let uri = format!(
+        "/api/v1/blog/detail/{}",
+        created.body["data"]["blog_id"].as_str().unwrap()
+    );
+
+    let image_url =
+        |response: &TestResponse| response.body["data"]["image"].as_str().unwrap().to_string();
+
+    let first = send(&app, request(Method::GET, &uri, None, None)).await;
+    let first_image = image_url(&first);
+    let first_variant = first.body["data"]["image_variants"][0]["url"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let updated = send(
+        &app,
+        request(
+            Method::PATCH,
+            &uri,
+            Some(&token),
+            Some(json!({ "image": png_data_url(2, 2) })),
+        ),
+    )
+    .await;
+    assert_eq!(updated.status, StatusCode::OK);
+
+    let second_image = image_url(&send(&app, request(Method::GET, &uri, None, None)).await);
+    assert_ne!(first_image, second_image);
+
+    let fetch = |url: String| send(&app, request(Method::GET, &url, None, None));
+    assert_eq!(fetch(first_image).await.status, StatusCode::NOT_FOUND);
+    assert_eq!(fetch(first_variant).await.status, StatusCode::NOT_FOUND);
+    assert_eq!(fetch(second_image.clone()).await.status, StatusCode::OK);
+
+    send(&app, request(Method::DELETE, &uri, Some(&token), None)).await;
+    assert_eq!(fetch(second_image).await.status, StatusCode::NOT_FOUND);
+}
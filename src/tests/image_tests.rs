@@ -0,0 +1,620 @@
+use std::{
+    sync::{
+        Arc,
+        atomic::{AtomicUsize, Ordering},
+    },
+    time::Duration,
+};
+
+use axum::http::{Method, StatusCode};
+use base64::{Engine as _, engine::general_purpose};
+use serde_json::json;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::{
+    api::AppApisBuilder,
+    config::{CloudinaryClientConfig, CloudinaryFolders, ImageLimits},
+    core::{image_core::upload_image_core, jwt_keys::JwtKeys, media_core::upload_media_core},
+    image::{
+        dto::ImageUpload, reconcile::find_orphaned_images, store_cloudinary::CloudinaryStore,
+        store_local::LocalImageStore,
+    },
+    media::dto::{MediaSource, MediaUpload},
+    tests::support::{
+        JWT_SECRET, PNG_DATA_URL, ROOT_EMAIL, app_builder, login_token, memory_repos,
+        multipart_request, png_bytes, request, seed_user, send, spawn_app,
+    },
+};
+
+const MEMBER_EMAIL: &str = "member@example.com";
+
+#[tokio::test]
+async fn local_store_serves_uploaded_images_under_media() {
+    let app = spawn_app(memory_repos().await);
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    let uploaded = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/image/base64",
+            Some(&token),
+            Some(json!({ "image": PNG_DATA_URL })),
+        ),
+    )
+    .await;
+    assert_eq!(uploaded.status, StatusCode::OK);
+
+    let url = uploaded.body["url"].as_str().unwrap();
+    assert!(url.starts_with("/media/"));
+    assert!(url.ends_with(".png"));
+
+    let served = app
+        .clone()
+        .oneshot(
+            axum::http::Request::get(url)
+                .body(axum::body::Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(served.status(), StatusCode::OK);
+}
+
+#[tokio::test]
+async fn svgs_with_handlers_are_refused_and_the_rest_cannot_script() {
+    let app = app_builder(memory_repos().await)
+        .image_limits(ImageLimits {
+            allow_svg: true,
+            ..ImageLimits::default()
+        })
+        .build();
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    let upload = |svg: &str| {
+        request(
+            Method::POST,
+            "/api/v1/image/base64",
+            Some(&token),
+            Some(json!({
+                "image": format!(
+                    "data:image/svg+xml;base64,{}",
+                    general_purpose::STANDARD.encode(svg)
+                )
+            })),
+        )
+    };
+
+    let onload = send(
+        &app,
+        upload(r#"<svg xmlns="http://www.w3.org/2000/svg" onload="alert(document.cookie)"></svg>"#),
+    )
+    .await;
+    assert_eq!(onload.status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let drawing = send(
+        &app,
+        upload(r#"<svg xmlns="http://www.w3.org/2000/svg"><rect width="1" height="1"/></svg>"#),
+    )
+    .await;
+    assert_eq!(drawing.status, StatusCode::OK);
+
+    let served = app
+        .clone()
+        .oneshot(
+            axum::http::Request::get(drawing.body["url"].as_str().unwrap())
+                .body(axum::body::Body::empty())
+                .unwrap(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(served.status(), StatusCode::OK);
+    assert!(
+        served.headers()["content-security-policy"]
+            .to_str()
+            .unwrap()
+            .contains("script-src 'none'")
+    );
+    assert_eq!(served.headers()["content-disposition"], "attachment");
+}
+
+#[tokio::test]
+async fn invalid_base64_is_a_bad_request() {
+    let app = spawn_app(memory_repos().await);
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    let response = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/image/base64",
+            Some(&token),
+            Some(json!({ "image": "data:image/png;base64,@@@" })),
+        ),
+    )
+    .await;
+
+    assert_eq!(response.status, StatusCode::BAD_REQUEST);
+}
+
+#[tokio::test]
+async fn reconciliation_reports_only_unreferenced_assets() {
+    let repos = memory_repos().await;
+
+    let media_dir = std::env::temp_dir().join(format!("portfolio-media-{}", Uuid::new_v4()));
+    let image_store = LocalImageStore::new(media_dir, String::new());
+    let state = AppApisBuilder::new(
+        repos,
+        Box::new(image_store),
+        JwtKeys::from_secret(JWT_SECRET),
+    )
+    .build_state();
+
+    let upload = MediaUpload {
+        source: MediaSource::Base64(PNG_DATA_URL.to_string()),
+        entity: None,
+        alt: None,
+        caption: None,
+        retain: true,
+        uploaded_by: None,
+    };
+    upload_media_core(upload, &state.media_tx).await.unwrap();
+
+    // Stored directly, bypassing the media library.
+    let image = ImageUpload::from_base64(PNG_DATA_URL, &ImageLimits::default()).unwrap();
+    let orphan = upload_image_core(image, &state.image_tx).await.unwrap();
+
+    let orphans = find_orphaned_images(&state).await.unwrap();
+    assert_eq!(orphans, vec![orphan.public_id]);
+}
+
+#[tokio::test]
+async fn non_image_uploads_are_unsupported_media() {
+    let app = spawn_app(memory_repos().await);
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    let base64 = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/image/base64",
+            Some(&token),
+            Some(json!({ "image": "data:image/png;base64,aGVsbG8gd29ybGQ=" })),
+        ),
+    )
+    .await;
+    assert_eq!(base64.status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+
+    let boundary = "X-BOUNDARY";
+    let body = format!(
+        "--{boundary}\r\nContent-Disposition: form-data; name=\"file\"; filename=\"a.txt\"\r\nContent-Type: text/plain\r\n\r\nhello world\r\n--{boundary}--\r\n"
+    );
+    let form = send(
+        &app,
+        axum::http::Request::post("/api/v1/image/file")
+            .header(axum::http::header::AUTHORIZATION, format!("Bearer {token}"))
+            .header(
+                axum::http::header::CONTENT_TYPE,
+                format!("multipart/form-data; boundary={boundary}"),
+            )
+            .body(axum::body::Body::from(body))
+            .unwrap(),
+    )
+    .await;
+    assert_eq!(form.status, StatusCode::UNSUPPORTED_MEDIA_TYPE);
+}
+
+#[tokio::test]
+async fn oversized_dimensions_are_rejected() {
+    let app = spawn_app(memory_repos().await);
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    // A PNG header claiming 20000x1 pixels.
+    let mut png = b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR".to_vec();
+    png.extend_from_slice(&20000u32.to_be_bytes());
+    png.extend_from_slice(&1u32.to_be_bytes());
+    png.extend_from_slice(&[8, 6, 0, 0, 0]);
+
+    let response = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/image/base64",
+            Some(&token),
+            Some(json!({ "image": general_purpose::STANDARD.encode(png) })),
+        ),
+    )
+    .await;
+
+    assert_eq!(response.status, StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+#[tokio::test]
+async fn uploads_are_rendered_into_responsive_variants() {
+    let app = spawn_app(memory_repos().await);
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    let uploaded = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/image/base64",
+            Some(&token),
+            Some(json!({ "image": PNG_DATA_URL })),
+        ),
+    )
+    .await;
+    assert_eq!(uploaded.status, StatusCode::OK);
+
+    let variants = uploaded.body["variants"].as_array().unwrap();
+    assert_eq!(variants.len(), 6);
+
+    for variant in variants {
+        let url = variant["url"].as_str().unwrap();
+        let format = variant["format"].as_str().unwrap();
+        assert!(url.ends_with(&format!("_{}.{format}", variant["name"].as_str().unwrap())));
+        assert_eq!(variant["width"], 1, "a 1x1 image is never upscaled");
+
+        let served = send(&app, request(Method::GET, url, None, None)).await;
+        assert_eq!(served.status, StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn failing_cloudinary_trips_the_circuit_breaker() {
+    let hits = Arc::new(AtomicUsize::new(0));
+
+    // A stand-in Cloudinary that fails every request with a 502.
+    let upstream = {
+        let hits = hits.clone();
+        axum::Router::new().fallback(move || {
+            let hits = hits.clone();
+            async move {
+                hits.fetch_add(1, Ordering::SeqCst);
+                StatusCode::BAD_GATEWAY
+            }
+        })
+    };
+    let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    tokio::spawn(async move { axum::serve(listener, upstream).await.unwrap() });
+
+    let store = CloudinaryStore::new(
+        "demo".to_string(),
+        "key".to_string(),
+        "secret".to_string(),
+        CloudinaryFolders::default(),
+        CloudinaryClientConfig {
+            api_url: format!("http://{addr}"),
+            max_retries: 1,
+            retry_base_delay: Duration::from_millis(1),
+            breaker_threshold: 1,
+            breaker_open_for: Duration::from_secs(60),
+            ..CloudinaryClientConfig::default()
+        },
+    );
+    let app = AppApisBuilder::new(
+        memory_repos().await,
+        Box::new(store),
+        JwtKeys::from_secret(JWT_SECRET),
+    )
+    .build();
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    let upload = || {
+        request(
+            Method::POST,
+            "/api/v1/image/base64",
+            Some(&token),
+            Some(json!({ "image": PNG_DATA_URL })),
+        )
+    };
+
+    let failed = send(&app, upload()).await;
+    assert_eq!(failed.status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+    // The open circuit answers without calling Cloudinary again.
+    let rejected = send(&app, upload()).await;
+    assert_eq!(rejected.status, StatusCode::SERVICE_UNAVAILABLE);
+    assert_eq!(hits.load(Ordering::SeqCst), 2);
+
+    let health = send(&app, request(Method::GET, "/api/v1/health", None, None)).await;
+    assert_eq!(health.status, StatusCode::OK);
+    assert_eq!(health.body["status"], "degraded");
+    assert_eq!(health.body["image_store"]["circuit"]["state"], "open");
+}
+
+#[tokio::test]
+async fn anonymous_uploads_are_refused() {
+    let app = spawn_app(memory_repos().await);
+
+    let base64 = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/image/base64",
+            None,
+            Some(json!({ "image": PNG_DATA_URL })),
+        ),
+    )
+    .await;
+    assert_eq!(base64.status, StatusCode::UNAUTHORIZED);
+
+    for uri in ["/api/v1/image/file", "/api/v1/image/files"] {
+        let response = send(
+            &app,
+            multipart_request(uri, None, &[("file", "image/png", &png_bytes(1, 1))]),
+        )
+        .await;
+        assert_eq!(response.status, StatusCode::UNAUTHORIZED, "{uri}");
+    }
+}
+
+#[tokio::test]
+async fn several_files_upload_in_one_request() {
+    let app = spawn_app(memory_repos().await);
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    let response = send(
+        &app,
+        multipart_request(
+            "/api/v1/image/files",
+            Some(&token),
+            &[
+                ("first", "image/png", &png_bytes(2, 2)),
+                ("second", "application/octet-stream", &png_bytes(3, 3)),
+            ],
+        ),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::OK);
+
+    let uploads = response.body.as_array().unwrap();
+    assert_eq!(uploads.len(), 2);
+    assert_ne!(uploads[0]["media_id"], uploads[1]["media_id"]);
+
+    for upload in uploads {
+        let url = upload["url"].as_str().unwrap();
+        let served = send(&app, request(Method::GET, url, None, None)).await;
+        assert_eq!(served.status, StatusCode::OK);
+    }
+}
+
+#[tokio::test]
+async fn streamed_files_and_request_bodies_are_limited() {
+    let limits = ImageLimits {
+        max_bytes: 1024,
+        max_request_bytes: 64 * 1024,
+        ..ImageLimits::default()
+    };
+    let app = AppApisBuilder::new(
+        memory_repos().await,
+        Box::new(LocalImageStore::new(
+            std::env::temp_dir().join(format!("portfolio-media-{}", Uuid::new_v4())),
+            String::new(),
+        )),
+        JwtKeys::from_secret(JWT_SECRET),
+    )
+    .image_limits(limits)
+    .build();
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    // A valid PNG header followed by padding past the per-file limit.
+    let mut large = png_bytes(1, 1);
+    large.resize(4096, 0);
+
+    let file = send(
+        &app,
+        multipart_request(
+            "/api/v1/image/file",
+            Some(&token),
+            &[("file", "image/png", &large)],
+        ),
+    )
+    .await;
+    assert_eq!(file.status, StatusCode::PAYLOAD_TOO_LARGE);
+
+    let body = send(
+        &app,
+        multipart_request(
+            "/api/v1/image/files",
+            Some(&token),
+            &[("file", "image/png", &vec![0; 128 * 1024])],
+        ),
+    )
+    .await;
+    assert_eq!(body.status, StatusCode::PAYLOAD_TOO_LARGE);
+}
+
+fn direct_put(
+    url: &str,
+    content_type: &str,
+    bytes: Vec<u8>,
+) -> axum::http::Request<axum::body::Body> {
+    axum::http::Request::put(url)
+        .header(axum::http::header::CONTENT_TYPE, content_type)
+        .body(axum::body::Body::from(bytes))
+        .unwrap()
+}
+
+#[tokio::test]
+async fn ticketed_uploads_are_confirmed_into_the_library() {
+    let app = spawn_app(memory_repos().await);
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    let anonymous = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/image/ticket",
+            None,
+            Some(json!({ "content_type": "image/png" })),
+        ),
+    )
+    .await;
+    assert_eq!(anonymous.status, StatusCode::UNAUTHORIZED);
+
+    let ticket = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/image/ticket",
+            Some(&token),
+            Some(json!({ "content_type": "image/png" })),
+        ),
+    )
+    .await;
+    assert_eq!(ticket.status, StatusCode::OK);
+    assert_eq!(ticket.body["method"], "PUT");
+    assert_eq!(ticket.body["headers"]["content-type"], "image/png");
+
+    let public_id = ticket.body["public_id"].as_str().unwrap();
+    let url = ticket.body["url"].as_str().unwrap();
+    let confirmation = ticket.body["confirmation"].as_str().unwrap();
+
+    let forged = send(
+        &app,
+        direct_put(
+            &url.replace("signature=", "signature=00"),
+            "image/png",
+            png_bytes(4, 4),
+        ),
+    )
+    .await;
+    assert_eq!(forged.status, StatusCode::UNAUTHORIZED);
+
+    let uploaded = send(&app, direct_put(url, "image/png", png_bytes(4, 4))).await;
+    assert_eq!(uploaded.status, StatusCode::OK);
+
+    let replayed = send(&app, direct_put(url, "image/png", png_bytes(4, 4))).await;
+    assert_eq!(replayed.status, StatusCode::CONFLICT);
+
+    let confirm = || {
+        request(
+            Method::POST,
+            "/api/v1/image/confirm",
+            Some(&token),
+            Some(json!({
+                "public_id": public_id,
+                "confirmation": confirmation,
+                "alt": "Direct",
+            })),
+        )
+    };
+
+    let confirmed = send(&app, confirm()).await;
+    assert_eq!(confirmed.status, StatusCode::OK);
+    assert_eq!(confirmed.body["public_id"], public_id);
+    assert_eq!(confirmed.body["variants"].as_array().unwrap().len(), 6);
+
+    // A second confirmation adds nothing and keeps the asset.
+    let again = send(&app, confirm()).await;
+    assert_eq!(again.status, StatusCode::CONFLICT);
+
+    let served = send(
+        &app,
+        request(
+            Method::GET,
+            confirmed.body["url"].as_str().unwrap(),
+            None,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(served.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn confirming_an_unknown_upload_is_not_found() {
+    let app = spawn_app(memory_repos().await);
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    let ticket = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/image/ticket",
+            Some(&token),
+            Some(json!({ "content_type": "image/png" })),
+        ),
+    )
+    .await;
+
+    // Ticketed but never uploaded.
+    let response = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/image/confirm",
+            Some(&token),
+            Some(json!({
+                "public_id": ticket.body["public_id"],
+                "confirmation": ticket.body["confirmation"],
+            })),
+        ),
+    )
+    .await;
+
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn only_the_ticket_holder_can_confirm_and_only_their_upload() {
+    let repos = memory_repos().await;
+    seed_user(&repos.users, MEMBER_EMAIL, "normal").await;
+    let app = spawn_app(repos);
+    let root = login_token(&app, ROOT_EMAIL).await;
+    let member = login_token(&app, MEMBER_EMAIL).await;
+
+    // An image already in the library, standing in for one backfilled without a hash.
+    let existing = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/image/base64",
+            Some(&root),
+            Some(json!({ "image": PNG_DATA_URL })),
+        ),
+    )
+    .await;
+    let existing_id = existing.body["public_id"].as_str().unwrap();
+    let existing_url = existing.body["url"].as_str().unwrap();
+
+    let ticket = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/image/ticket",
+            Some(&member),
+            Some(json!({ "content_type": "image/png" })),
+        ),
+    )
+    .await;
+    let confirmation = ticket.body["confirmation"].as_str().unwrap();
+
+    let confirm = |token: &str, public_id: &str| {
+        request(
+            Method::POST,
+            "/api/v1/image/confirm",
+            Some(token),
+            Some(json!({ "public_id": public_id, "confirmation": confirmation })),
+        )
+    };
+
+    // The member's confirmation names their own ticket, not the library image.
+    let hijacked = send(&app, confirm(&member, existing_id)).await;
+    assert_eq!(hijacked.status, StatusCode::UNAUTHORIZED);
+
+    // Nor can anyone else use the member's ticket.
+    let borrowed = send(
+        &app,
+        confirm(&root, ticket.body["public_id"].as_str().unwrap()),
+    )
+    .await;
+    assert_eq!(borrowed.status, StatusCode::UNAUTHORIZED);
+
+    let served = send(&app, request(Method::GET, existing_url, None, None)).await;
+    assert_eq!(served.status, StatusCode::OK);
+}
@@ -0,0 +1,481 @@
+use std::path::PathBuf;
+
+use axum::{
+    Router,
+    http::{Method, StatusCode},
+};
+use ed25519_dalek::{
+    SigningKey,
+    pkcs8::{EncodePrivateKey, EncodePublicKey, spki::der::pem::LineEnding},
+};
+use jsonwebtoken::{Algorithm, decode_header};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    config::TokenConfig,
+    core::{jwt::decode_token, jwt_keys::JwtKeys},
+    tests::support::{
+        JWT_SECRET, MemoryRepositories, PASSWORD, ROOT_EMAIL, app_builder, app_builder_with_keys,
+        login, login_token, memory_repos, refresh_cookie, request, seed_user, send, spawn_app,
+        with_cookie,
+    },
+};
+
+const MID_EMAIL: &str = "mid@example.com";
+
+/// Writes a fresh Ed25519 key pair as PEM files and answers with their paths.
+fn ed25519_key_files() -> (String, String) {
+    let key = SigningKey::from_bytes(&rand::random::<[u8; 32]>());
+    let dir = std::env::temp_dir();
+    let name = Uuid::new_v4();
+
+    let private: PathBuf = dir.join(format!("jwt-{name}.pem"));
+    let public: PathBuf = dir.join(format!("jwt-{name}.pub.pem"));
+
+    std::fs::write(
+        &private,
+        key.to_pkcs8_pem(LineEnding::LF).unwrap().as_bytes(),
+    )
+    .unwrap();
+    std::fs::write(
+        &public,
+        key.verifying_key()
+            .to_public_key_pem(LineEnding::LF)
+            .unwrap(),
+    )
+    .unwrap();
+
+    (
+        private.to_string_lossy().into_owned(),
+        public.to_string_lossy().into_owned(),
+    )
+}
+
+async fn current_user(app: &Router, token: &str) -> crate::tests::support::TestResponse {
+    send(
+        app,
+        request(Method::GET, "/api/v1/auth/current_users", Some(token), None),
+    )
+    .await
+}
+
+async fn demote(app: &Router, root_token: &str, user_id: Uuid) {
+    let response = send(
+        app,
+        request(
+            Method::PATCH,
+            &format!("/api/v1/auth/users/{user_id}"),
+            Some(root_token),
+            Some(json!({ "roles": "normal" })),
+        ),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn access_tokens_carry_issuer_audience_and_roles() {
+    let app = spawn_app(memory_repos().await);
+
+    let token = login_token(&app, ROOT_EMAIL).await;
+    let claims = decode_token(
+        &token,
+        &JwtKeys::from_secret(JWT_SECRET),
+        &TokenConfig::default(),
+    )
+    .unwrap();
+
+    assert_eq!(claims.iss, "portfolio-api");
+    assert_eq!(claims.aud, "portfolio");
+    assert_eq!(claims.roles, ["root"]);
+    assert_eq!(claims.email, ROOT_EMAIL);
+    assert!(claims.nbf <= claims.iat + 1 && claims.iat < claims.exp);
+
+    let other = decode_token(
+        &login_token(&app, ROOT_EMAIL).await,
+        &JwtKeys::from_secret(JWT_SECRET),
+        &TokenConfig::default(),
+    )
+    .unwrap();
+    assert_ne!(claims.jti, other.jti);
+
+    let elsewhere = TokenConfig {
+        audience: "another-service".to_string(),
+        ..TokenConfig::default()
+    };
+    assert!(decode_token(&token, &JwtKeys::from_secret(JWT_SECRET), &elsewhere).is_err());
+
+    let foreign = app_builder(memory_repos().await)
+        .tokens(TokenConfig {
+            issuer: "someone-else".to_string(),
+            ..TokenConfig::default()
+        })
+        .build();
+    let foreign_token = login_token(&foreign, ROOT_EMAIL).await;
+    assert_eq!(
+        current_user(&app, &foreign_token).await.status,
+        StatusCode::UNAUTHORIZED
+    );
+}
+
+#[tokio::test]
+async fn role_changes_invalidate_outstanding_tokens() {
+    let repos = memory_repos().await;
+    let mid_id = seed_user(&repos.users, MID_EMAIL, "mid").await;
+    let app = app_builder(repos).build();
+
+    let root_token = login_token(&app, ROOT_EMAIL).await;
+    let mid_login = login(&app, MID_EMAIL).await;
+    let mid_token = mid_login.body["token"].as_str().unwrap().to_string();
+
+    demote(&app, &root_token, mid_id).await;
+
+    let stale = current_user(&app, &mid_token).await;
+    assert_eq!(stale.status, StatusCode::UNAUTHORIZED);
+
+    let refreshed = send(
+        &app,
+        with_cookie(
+            request(Method::POST, "/api/v1/token/refresh", None, None),
+            &refresh_cookie(&mid_login).unwrap(),
+        ),
+    )
+    .await;
+    let fresh_token = refreshed.body["access_token"].as_str().unwrap();
+
+    let fresh = current_user(&app, fresh_token).await;
+    assert_eq!(fresh.status, StatusCode::OK);
+    assert_eq!(fresh.body["data"]["roles"], "Normal");
+}
+
+#[tokio::test]
+async fn forced_logout_invalidates_access_tokens() {
+    let repos = memory_repos().await;
+    let mid_id = seed_user(&repos.users, MID_EMAIL, "mid").await;
+    let app = app_builder(repos).build();
+
+    let root_token = login_token(&app, ROOT_EMAIL).await;
+    let mid_token = login_token(&app, MID_EMAIL).await;
+
+    let logout = send(
+        &app,
+        request(
+            Method::POST,
+            &format!("/api/v1/auth/users/{mid_id}/logout"),
+            Some(&root_token),
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(logout.status, StatusCode::OK);
+
+    assert_eq!(
+        current_user(&app, &mid_token).await.status,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(current_user(&app, &root_token).await.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn trusted_claims_skip_the_user_lookup() {
+    let repos = memory_repos().await;
+    let mid_id = seed_user(&repos.users, MID_EMAIL, "mid").await;
+    let app = app_builder(repos)
+        .tokens(TokenConfig {
+            trust_claims: true,
+            ..TokenConfig::default()
+        })
+        .build();
+
+    let root_token = login_token(&app, ROOT_EMAIL).await;
+    let mid_token = login_token(&app, MID_EMAIL).await;
+
+    demote(&app, &root_token, mid_id).await;
+
+    // The token still says `mid` until it expires, so the demoted user can still register.
+    let stale = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/auth/register",
+            Some(&mid_token),
+            Some(json!({
+                "email": "new@example.com",
+                "password": "Secret#123",
+                "name": "New User",
+                "roles": "normal",
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(stale.status, StatusCode::OK);
+}
+
+/// Trusting claims skips the user lookup, so only the denylist can refuse these tokens.
+fn trusting_app(repos: MemoryRepositories) -> Router {
+    app_builder(repos)
+        .tokens(TokenConfig {
+            trust_claims: true,
+            ..TokenConfig::default()
+        })
+        .build()
+}
+
+#[tokio::test]
+async fn logging_out_denies_the_session_access_token() {
+    let app = trusting_app(memory_repos().await);
+
+    let leaving = login(&app, ROOT_EMAIL).await;
+    let staying = login_token(&app, ROOT_EMAIL).await;
+
+    let logout = send(
+        &app,
+        with_cookie(
+            request(Method::POST, "/api/v1/token/logout", None, None),
+            &refresh_cookie(&leaving).unwrap(),
+        ),
+    )
+    .await;
+    assert_eq!(logout.status, StatusCode::OK);
+
+    let denied = current_user(&app, leaving.body["token"].as_str().unwrap()).await;
+    assert_eq!(denied.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(denied.body["message"], "Token has been revoked");
+
+    assert_eq!(current_user(&app, &staying).await.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn logging_out_denies_access_tokens_from_before_a_refresh() {
+    let app = trusting_app(memory_repos().await);
+
+    let login = login(&app, ROOT_EMAIL).await;
+    let mut tokens = vec![login.body["token"].as_str().unwrap().to_string()];
+    let mut cookie = refresh_cookie(&login).unwrap();
+
+    for _ in 0..2 {
+        let refreshed = send(
+            &app,
+            with_cookie(
+                request(Method::POST, "/api/v1/token/refresh", None, None),
+                &cookie,
+            ),
+        )
+        .await;
+        assert_eq!(refreshed.status, StatusCode::OK);
+
+        tokens.push(refreshed.body["access_token"].as_str().unwrap().to_string());
+        cookie = refresh_cookie(&refreshed).unwrap();
+    }
+
+    let logout = send(
+        &app,
+        with_cookie(
+            request(Method::POST, "/api/v1/token/logout", None, None),
+            &cookie,
+        ),
+    )
+    .await;
+    assert_eq!(logout.status, StatusCode::OK);
+
+    for token in &tokens {
+        let denied = current_user(&app, token).await;
+        assert_eq!(denied.status, StatusCode::UNAUTHORIZED);
+        assert_eq!(denied.body["message"], "Token has been revoked");
+    }
+}
+
+#[tokio::test]
+async fn password_changes_deny_the_other_sessions_access_tokens() {
+    let app = trusting_app(memory_repos().await);
+
+    let current = login(&app, ROOT_EMAIL).await;
+    let current_token = current.body["token"].as_str().unwrap().to_string();
+    let other_token = login_token(&app, ROOT_EMAIL).await;
+
+    let changed = send(
+        &app,
+        with_cookie(
+            request(
+                Method::POST,
+                "/api/v1/auth/me/password",
+                Some(&current_token),
+                Some(json!({
+                    "current_password": PASSWORD,
+                    "new_password": "Changed#456",
+                })),
+            ),
+            &refresh_cookie(&current).unwrap(),
+        ),
+    )
+    .await;
+    assert_eq!(changed.status, StatusCode::OK);
+
+    assert_eq!(
+        current_user(&app, &other_token).await.status,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        current_user(&app, &current_token).await.status,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn deleting_a_user_denies_their_access_tokens() {
+    let repos = memory_repos().await;
+    let mid_id = seed_user(&repos.users, MID_EMAIL, "mid").await;
+    let app = trusting_app(repos);
+
+    let root_token = login_token(&app, ROOT_EMAIL).await;
+    let mid_token = login_token(&app, MID_EMAIL).await;
+
+    let deleted = send(
+        &app,
+        request(
+            Method::DELETE,
+            &format!("/api/v1/auth/users/{mid_id}"),
+            Some(&root_token),
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(deleted.status, StatusCode::OK);
+
+    assert_eq!(
+        current_user(&app, &mid_token).await.status,
+        StatusCode::UNAUTHORIZED
+    );
+}
+
+#[tokio::test]
+async fn failed_deletes_leave_sessions_alone() {
+    let repos = memory_repos().await;
+    let mid_id = seed_user(&repos.users, MID_EMAIL, "mid").await;
+    seed_user(&repos.users, "normal@example.com", "normal").await;
+    let app = trusting_app(repos);
+
+    let root_token = login_token(&app, ROOT_EMAIL).await;
+    let mid_token = login_token(&app, MID_EMAIL).await;
+    let normal_token = login_token(&app, "normal@example.com").await;
+
+    let not_admin = send(
+        &app,
+        request(
+            Method::DELETE,
+            &format!("/api/v1/auth/users/{mid_id}"),
+            Some(&normal_token),
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(not_admin.status, StatusCode::BAD_REQUEST);
+
+    let unknown = send(
+        &app,
+        request(
+            Method::DELETE,
+            &format!("/api/v1/auth/users/{}", Uuid::new_v4()),
+            Some(&root_token),
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(unknown.status, StatusCode::NOT_FOUND);
+
+    assert_eq!(current_user(&app, &mid_token).await.status, StatusCode::OK);
+    assert_eq!(current_user(&app, &root_token).await.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn rotated_keys_keep_verifying_and_are_published() {
+    let (old_private, old_public) = ed25519_key_files();
+    let (new_private, new_public) = ed25519_key_files();
+
+    let old_keys = JwtKeys::from_files(
+        Algorithm::EdDSA,
+        "old",
+        &old_private,
+        &[("old".to_string(), old_public.clone())],
+    )
+    .unwrap();
+    let old_app = app_builder_with_keys(memory_repos().await, old_keys).build();
+    let old_token = login_token(&old_app, ROOT_EMAIL).await;
+    assert_eq!(
+        decode_header(&old_token).unwrap().kid.as_deref(),
+        Some("old")
+    );
+
+    let rotated = JwtKeys::from_files(
+        Algorithm::EdDSA,
+        "new",
+        &new_private,
+        &[
+            ("new".to_string(), new_public.clone()),
+            ("old".to_string(), old_public),
+        ],
+    )
+    .unwrap();
+    assert!(decode_token(&old_token, &rotated, &TokenConfig::default()).is_ok());
+
+    let retired = JwtKeys::from_files(
+        Algorithm::EdDSA,
+        "new",
+        &new_private,
+        &[("new".to_string(), new_public)],
+    )
+    .unwrap();
+    assert!(decode_token(&old_token, &retired, &TokenConfig::default()).is_err());
+
+    let app = app_builder_with_keys(memory_repos().await, rotated).build();
+    let jwks = send(
+        &app,
+        request(Method::GET, "/.well-known/jwks.json", None, None),
+    )
+    .await;
+    assert_eq!(jwks.status, StatusCode::OK);
+
+    let keys = jwks.body["keys"].as_array().unwrap();
+    assert_eq!(keys.len(), 2);
+    assert_eq!(keys[0]["kid"], "new");
+    assert_eq!(keys[0]["kty"], "OKP");
+    assert_eq!(keys[0]["crv"], "Ed25519");
+    assert_eq!(keys[0]["alg"], "EdDSA");
+    assert!(keys[0].get("d").is_none());
+
+    let new_token = login_token(&app, ROOT_EMAIL).await;
+    assert_eq!(
+        decode_header(&new_token).unwrap().kid.as_deref(),
+        Some("new")
+    );
+}
+
+#[tokio::test]
+async fn shared_secrets_are_never_published() {
+    let app = spawn_app(memory_repos().await);
+
+    let jwks = send(
+        &app,
+        request(Method::GET, "/.well-known/jwks.json", None, None),
+    )
+    .await;
+
+    assert_eq!(jwks.status, StatusCode::OK);
+    assert_eq!(jwks.body["keys"], json!([]));
+}
+
+#[test]
+fn the_signing_key_must_be_verifiable() {
+    let (private, _) = ed25519_key_files();
+    let (_, other_public) = ed25519_key_files();
+
+    let keys = JwtKeys::from_files(
+        Algorithm::EdDSA,
+        "current",
+        &private,
+        &[("previous".to_string(), other_public)],
+    );
+
+    assert!(keys.is_err());
+}
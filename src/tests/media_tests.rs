@@ -0,0 +1,184 @@
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+
+use crate::tests::support::{
+    PNG_DATA_URL, ROOT_EMAIL, login_token, memory_repos, request, send, spawn_app,
+};
+
+#[tokio::test]
+async fn identical_uploads_share_one_media_item() {
+    let app = spawn_app(memory_repos().await);
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    let first = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/media/base64",
+            Some(&token),
+            Some(json!({ "image": PNG_DATA_URL, "alt": "A pixel", "caption": "Tiny" })),
+        ),
+    )
+    .await;
+    assert_eq!(first.status, StatusCode::OK);
+    assert_eq!(first.body["data"]["alt"], "A pixel");
+    assert_eq!(first.body["data"]["width"], 1);
+    assert_eq!(first.body["data"]["height"], 1);
+    assert_eq!(first.body["data"]["content_type"], "image/png");
+
+    let second = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/blog/create",
+            Some(&token),
+            Some(json!({
+                "title": "Hello",
+                "description": "First post",
+                "content": "Hello world",
+                "word_count": 2,
+                "image": PNG_DATA_URL,
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(second.status, StatusCode::OK);
+
+    let all = send(&app, request(Method::GET, "/api/v1/media/all", None, None)).await;
+    assert_eq!(all.body["data"]["total"], 1);
+}
+
+#[tokio::test]
+async fn blogs_reference_media_and_block_its_deletion() {
+    let app = spawn_app(memory_repos().await);
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    let media = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/media/base64",
+            Some(&token),
+            Some(json!({ "image": PNG_DATA_URL })),
+        ),
+    )
+    .await;
+    let media_id = media.body["data"]["id"].as_str().unwrap().to_string();
+    let media_url = media.body["data"]["url"].as_str().unwrap().to_string();
+    let media_uri = format!("/api/v1/media/detail/{media_id}");
+
+    let created = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/blog/create",
+            Some(&token),
+            Some(json!({
+                "title": "Hello",
+                "description": "First post",
+                "content": "Hello world",
+                "word_count": 2,
+                "media_id": media_id,
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(created.status, StatusCode::OK);
+    let blog_id = created.body["data"]["blog_id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+    let blog_uri = format!("/api/v1/blog/detail/{blog_id}");
+
+    let blog = send(&app, request(Method::GET, &blog_uri, None, None)).await;
+    assert_eq!(blog.body["data"]["media_id"], media_id.as_str());
+    assert_eq!(blog.body["data"]["image"], media_url.as_str());
+
+    let detail = send(&app, request(Method::GET, &media_uri, None, None)).await;
+    assert_eq!(
+        detail.body["data"]["usages"],
+        json!([{ "entity": "blog", "entity_id": blog_id }])
+    );
+
+    let blocked = send(
+        &app,
+        request(Method::DELETE, &media_uri, Some(&token), None),
+    )
+    .await;
+    assert_eq!(blocked.status, StatusCode::CONFLICT);
+
+    send(&app, request(Method::DELETE, &blog_uri, Some(&token), None)).await;
+
+    // Library uploads outlive the content that used them.
+    let kept = send(&app, request(Method::GET, &media_uri, None, None)).await;
+    assert_eq!(kept.status, StatusCode::OK);
+    assert_eq!(kept.body["data"]["usages"], json!([]));
+
+    let deleted = send(
+        &app,
+        request(Method::DELETE, &media_uri, Some(&token), None),
+    )
+    .await;
+    assert_eq!(deleted.status, StatusCode::OK);
+
+    let served = send(&app, request(Method::GET, &media_url, None, None)).await;
+    assert_eq!(served.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn blog_payload_needs_exactly_one_image_source() {
+    let app = spawn_app(memory_repos().await);
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    let payload = |extra: serde_json::Value| {
+        let mut body = json!({
+            "title": "Hello",
+            "description": "First post",
+            "content": "Hello world",
+            "word_count": 2,
+        });
+        body.as_object_mut()
+            .unwrap()
+            .extend(extra.as_object().unwrap().clone());
+        body
+    };
+
+    let missing = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/blog/create",
+            Some(&token),
+            Some(payload(json!({}))),
+        ),
+    )
+    .await;
+    assert_eq!(missing.status, StatusCode::BAD_REQUEST);
+
+    let both = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/blog/create",
+            Some(&token),
+            Some(payload(json!({
+                "image": PNG_DATA_URL,
+                "media_id": uuid::Uuid::new_v4(),
+            }))),
+        ),
+    )
+    .await;
+    assert_eq!(both.status, StatusCode::BAD_REQUEST);
+
+    let unknown = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/blog/create",
+            Some(&token),
+            Some(payload(json!({ "media_id": uuid::Uuid::new_v4() }))),
+        ),
+    )
+    .await;
+    assert_eq!(unknown.status, StatusCode::NOT_FOUND);
+}
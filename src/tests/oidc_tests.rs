@@ -0,0 +1,448 @@
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use axum::{
+    Form, Json, Router,
+    extract::State,
+    http::{Method, StatusCode, header},
+    routing::{get, post},
+};
+use base64::{Engine, engine::general_purpose::URL_SAFE_NO_PAD};
+use ed25519_dalek::{
+    SigningKey,
+    pkcs8::{EncodePrivateKey, spki::der::pem::LineEnding},
+};
+use jsonwebtoken::{Algorithm, EncodingKey, Header, encode};
+use serde_json::{Value, json};
+use sha2::{Digest, Sha256};
+use url::Url;
+
+use crate::{
+    config::{OidcConfig, TwoFactorConfig},
+    core::totp_core::{TOTP_STEP, current_totp},
+    tests::support::{
+        ROOT_EMAIL, TestResponse, app_builder, login_token, memory_repos, refresh_cookie, request,
+        seed_user, send, spawn_app, with_cookie,
+    },
+};
+
+const CLIENT_ID: &str = "portfolio-web";
+
+const REDIRECT_URI: &str = "http://localhost:5173/sso/callback";
+
+/// What the mock provider hands out for one authorization code.
+struct Grant {
+    email: String,
+    email_verified: bool,
+    nonce: String,
+    code_challenge: String,
+    /// Signs the ID token with HS256 keyed by the published public key, as a forger would.
+    forged: bool,
+}
+
+/// A local OpenID provider: discovery, keys and a token endpoint that honours PKCE.
+#[derive(Clone)]
+struct MockIssuer {
+    url: String,
+    key: Arc<EncodingKey>,
+    jwk: Value,
+    grants: Arc<Mutex<HashMap<String, Grant>>>,
+}
+
+impl MockIssuer {
+    async fn spawn() -> Self {
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+
+        let signing_key = SigningKey::from_bytes(&rand::random::<[u8; 32]>());
+        let pem = signing_key.to_pkcs8_pem(LineEnding::LF).unwrap();
+
+        let issuer = Self {
+            url: format!("http://{}", listener.local_addr().unwrap()),
+            key: Arc::new(EncodingKey::from_ed_pem(pem.as_bytes()).unwrap()),
+            jwk: json!({
+                "kty": "OKP",
+                "crv": "Ed25519",
+                "x": URL_SAFE_NO_PAD.encode(signing_key.verifying_key().as_bytes()),
+                "kid": "mock",
+                "alg": "EdDSA",
+                "use": "sig",
+            }),
+            grants: Arc::new(Mutex::new(HashMap::new())),
+        };
+
+        let router = Router::new()
+            .route("/.well-known/openid-configuration", get(discovery))
+            .route("/jwks", get(jwks))
+            .route("/token", post(token))
+            .with_state(issuer.clone());
+
+        tokio::spawn(async move { axum::serve(listener, router).await.unwrap() });
+
+        issuer
+    }
+
+    fn config(&self) -> OidcConfig {
+        OidcConfig::new(
+            self.url.clone(),
+            CLIENT_ID.to_string(),
+            REDIRECT_URI.to_string(),
+        )
+    }
+
+    /// The user signing in at the provider as `email`, for the login `params` describe.
+    fn grant(
+        &self,
+        code: &str,
+        email: &str,
+        email_verified: bool,
+        params: &HashMap<String, String>,
+    ) {
+        self.insert(code, email, email_verified, params, false);
+    }
+
+    /// Like [`MockIssuer::grant`], with an ID token the provider did not sign.
+    fn forge(&self, code: &str, email: &str, params: &HashMap<String, String>) {
+        self.insert(code, email, true, params, true);
+    }
+
+    fn insert(
+        &self,
+        code: &str,
+        email: &str,
+        email_verified: bool,
+        params: &HashMap<String, String>,
+        forged: bool,
+    ) {
+        self.grants.lock().unwrap().insert(
+            code.to_string(),
+            Grant {
+                email: email.to_string(),
+                email_verified,
+                nonce: params["nonce"].clone(),
+                code_challenge: params["code_challenge"].clone(),
+                forged,
+            },
+        );
+    }
+}
+
+async fn discovery(State(issuer): State<MockIssuer>) -> Json<Value> {
+    Json(json!({
+        "issuer": issuer.url,
+        "authorization_endpoint": format!("{}/authorize", issuer.url),
+        "token_endpoint": format!("{}/token", issuer.url),
+        "jwks_uri": format!("{}/jwks", issuer.url),
+        "id_token_signing_alg_values_supported": ["EdDSA"],
+    }))
+}
+
+async fn jwks(State(issuer): State<MockIssuer>) -> Json<Value> {
+    Json(json!({ "keys": [issuer.jwk] }))
+}
+
+async fn token(
+    State(issuer): State<MockIssuer>,
+    Form(form): Form<HashMap<String, String>>,
+) -> Result<Json<Value>, (StatusCode, Json<Value>)> {
+    let invalid = || {
+        (
+            StatusCode::BAD_REQUEST,
+            Json(json!({ "error": "invalid_grant" })),
+        )
+    };
+
+    let grant = issuer
+        .grants
+        .lock()
+        .unwrap()
+        .remove(&form["code"])
+        .ok_or_else(invalid)?;
+
+    let challenge = URL_SAFE_NO_PAD.encode(Sha256::digest(form["code_verifier"].as_bytes()));
+    if challenge != grant.code_challenge
+        || form["client_id"] != CLIENT_ID
+        || form["redirect_uri"] != REDIRECT_URI
+    {
+        return Err(invalid());
+    }
+
+    let now = chrono::Utc::now().timestamp();
+    let forged_key = EncodingKey::from_secret(issuer.jwk["x"].as_str().unwrap().as_bytes());
+    let (algorithm, key) = if grant.forged {
+        (Algorithm::HS256, &forged_key)
+    } else {
+        (Algorithm::EdDSA, issuer.key.as_ref())
+    };
+
+    let mut header = Header::new(algorithm);
+    header.kid = Some("mock".to_string());
+
+    let id_token = encode(
+        &header,
+        &json!({
+            "iss": issuer.url,
+            "aud": CLIENT_ID,
+            "sub": format!("mock|{}", grant.email),
+            "iat": now,
+            "exp": now + 300,
+            "email": grant.email,
+            "email_verified": grant.email_verified,
+            "nonce": grant.nonce,
+        }),
+        key,
+    )
+    .unwrap();
+
+    Ok(Json(json!({
+        "access_token": "unused",
+        "token_type": "Bearer",
+        "id_token": id_token,
+    })))
+}
+
+/// A login begun in one browser: the query of the provider URL it was sent to and the
+/// `oidc_state` cookie it was given.
+struct Started {
+    params: HashMap<String, String>,
+    cookie: String,
+}
+
+async fn start_login(app: &Router) -> Started {
+    let response = send(
+        app,
+        request(Method::GET, "/api/v1/auth/oidc/login", None, None),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::SEE_OTHER);
+
+    let location = Url::parse(response.headers[header::LOCATION].to_str().unwrap()).unwrap();
+    assert!(location.path().ends_with("/authorize"));
+
+    let cookie = response
+        .headers
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .find(|value| value.starts_with("oidc_state="))
+        .and_then(|value| value.split(';').next())
+        .unwrap()
+        .to_string();
+
+    Started {
+        params: location.query_pairs().into_owned().collect(),
+        cookie,
+    }
+}
+
+async fn callback_from(
+    app: &Router,
+    code: &str,
+    state: &str,
+    cookie: Option<&str>,
+) -> TestResponse {
+    let request = request(
+        Method::POST,
+        "/api/v1/auth/oidc/callback",
+        None,
+        Some(json!({ "code": code, "state": state })),
+    );
+
+    send(
+        app,
+        match cookie {
+            Some(cookie) => with_cookie(request, cookie),
+            None => request,
+        },
+    )
+    .await
+}
+
+/// The provider sending the browser that started `login` back with `code`.
+async fn callback(app: &Router, code: &str, login: &Started) -> TestResponse {
+    callback_from(app, code, &login.params["state"], Some(&login.cookie)).await
+}
+
+#[tokio::test]
+async fn verified_emails_sign_in_as_their_user() {
+    let issuer = MockIssuer::spawn().await;
+    let app = app_builder(memory_repos().await)
+        .oidc(issuer.config())
+        .build();
+
+    let login = start_login(&app).await;
+    assert_eq!(login.params["client_id"], CLIENT_ID);
+    assert_eq!(login.params["redirect_uri"], REDIRECT_URI);
+    assert_eq!(login.params["code_challenge_method"], "S256");
+    assert_eq!(login.params["scope"], "openid email profile");
+
+    issuer.grant("code-1", ROOT_EMAIL, true, &login.params);
+    let signed_in = callback(&app, "code-1", &login).await;
+    assert_eq!(signed_in.status, StatusCode::OK);
+    assert!(refresh_cookie(&signed_in).is_some());
+
+    let token = signed_in.body["token"].as_str().unwrap();
+    let me = send(
+        &app,
+        request(Method::GET, "/api/v1/auth/me", Some(token), None),
+    )
+    .await;
+    assert_eq!(me.body["data"]["email"], ROOT_EMAIL);
+
+    issuer.grant("code-2", ROOT_EMAIL, true, &login.params);
+    let replayed = callback(&app, "code-2", &login).await;
+    assert_eq!(replayed.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn unknown_or_unverified_identities_are_refused() {
+    let issuer = MockIssuer::spawn().await;
+    let app = app_builder(memory_repos().await)
+        .oidc(issuer.config())
+        .build();
+
+    let login = start_login(&app).await;
+    issuer.grant("stranger", "stranger@example.com", true, &login.params);
+    let stranger = callback(&app, "stranger", &login).await;
+    assert_eq!(stranger.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(stranger.body["message"], "No account matches this identity");
+
+    let login = start_login(&app).await;
+    issuer.grant("unverified", ROOT_EMAIL, false, &login.params);
+    let unverified = callback(&app, "unverified", &login).await;
+    assert_eq!(unverified.status, StatusCode::UNAUTHORIZED);
+    assert!(refresh_cookie(&unverified).is_none());
+
+    // A code issued for a different login carries the wrong PKCE challenge and nonce.
+    let first = start_login(&app).await;
+    let second = start_login(&app).await;
+    issuer.grant("crossed", ROOT_EMAIL, true, &first.params);
+    let crossed = callback(&app, "crossed", &second).await;
+    assert_eq!(crossed.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(crossed.body["message"], "Single sign-on failed");
+
+    let login = start_login(&app).await;
+    issuer.forge("forged", ROOT_EMAIL, &login.params);
+    let forged = callback(&app, "forged", &login).await;
+    assert_eq!(forged.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(forged.body["message"], "Single sign-on failed");
+}
+
+#[tokio::test]
+async fn enrolled_users_still_answer_a_two_factor_challenge() {
+    let issuer = MockIssuer::spawn().await;
+    let app = app_builder(memory_repos().await)
+        .oidc(issuer.config())
+        .build();
+
+    let token = login_token(&app, ROOT_EMAIL).await;
+    let setup = send(
+        &app,
+        request(Method::POST, "/api/v1/auth/2fa/setup", Some(&token), None),
+    )
+    .await;
+    let secret = setup.body["data"]["secret"].as_str().unwrap().to_string();
+    let now = chrono::Utc::now().timestamp();
+    let confirmed = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/auth/2fa/confirm",
+            Some(&token),
+            Some(json!({ "code": current_totp(&secret, now) })),
+        ),
+    )
+    .await;
+    assert_eq!(confirmed.status, StatusCode::OK);
+
+    let login = start_login(&app).await;
+    issuer.grant("enrolled", ROOT_EMAIL, true, &login.params);
+    let challenged = callback(&app, "enrolled", &login).await;
+    assert_eq!(challenged.status, StatusCode::OK);
+    assert_eq!(challenged.body["message"], "two_factor_required");
+    assert!(challenged.body["token"].is_null());
+    assert!(refresh_cookie(&challenged).is_none());
+
+    let answered = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/auth/login/2fa",
+            None,
+            Some(json!({
+                "challenge_token": challenged.body["data"]["challenge_token"],
+                "code": current_totp(&secret, now + TOTP_STEP),
+            })),
+        ),
+    )
+    .await;
+    assert_eq!(answered.status, StatusCode::OK);
+    assert!(answered.body["token"].is_string());
+}
+
+#[tokio::test]
+async fn admins_enrol_first_when_two_factor_is_required() {
+    let issuer = MockIssuer::spawn().await;
+    let repos = memory_repos().await;
+    seed_user(&repos.users, "normal@example.com", "normal").await;
+    let app = app_builder(repos)
+        .oidc(issuer.config())
+        .two_factor(TwoFactorConfig {
+            required_for_admins: true,
+            ..TwoFactorConfig::default()
+        })
+        .build();
+
+    let login = start_login(&app).await;
+    issuer.grant("admin", ROOT_EMAIL, true, &login.params);
+    let admin = callback(&app, "admin", &login).await;
+    assert_eq!(admin.body["message"], "two_factor_required");
+    assert_eq!(admin.body["data"]["setup_required"], true);
+    assert!(refresh_cookie(&admin).is_none());
+
+    let login = start_login(&app).await;
+    issuer.grant("normal", "normal@example.com", true, &login.params);
+    let normal = callback(&app, "normal", &login).await;
+    assert_eq!(normal.status, StatusCode::OK);
+    assert!(normal.body["token"].is_string());
+}
+
+#[tokio::test]
+async fn only_the_browser_that_started_a_login_can_finish_it() {
+    let issuer = MockIssuer::spawn().await;
+    let app = app_builder(memory_repos().await)
+        .oidc(issuer.config())
+        .build();
+
+    // Someone starts a login of their own and passes its code and state to someone else.
+    let planted = start_login(&app).await;
+    issuer.grant("planted", ROOT_EMAIL, true, &planted.params);
+
+    let without_cookie = callback_from(&app, "planted", &planted.params["state"], None).await;
+    assert_eq!(without_cookie.status, StatusCode::UNAUTHORIZED);
+    assert!(refresh_cookie(&without_cookie).is_none());
+
+    let victim = start_login(&app).await;
+    let other_browser = callback_from(
+        &app,
+        "planted",
+        &planted.params["state"],
+        Some(&victim.cookie),
+    )
+    .await;
+    assert_eq!(other_browser.status, StatusCode::UNAUTHORIZED);
+    assert!(refresh_cookie(&other_browser).is_none());
+}
+
+#[tokio::test]
+async fn single_sign_on_is_off_unless_configured() {
+    let app = spawn_app(memory_repos().await);
+
+    let response = send(
+        &app,
+        request(Method::GET, "/api/v1/auth/oidc/login", None, None),
+    )
+    .await;
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
@@ -0,0 +1,83 @@
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::tests::support::{
+    ROOT_EMAIL, login_token, memory_repos, request, seed_project, send, spawn_app,
+};
+
+#[tokio::test]
+async fn get_and_list_projects() {
+    let repos = memory_repos().await;
+    let project_id = seed_project(&repos.projects, "Portfolio API").await;
+    seed_project(&repos.projects, "Static site").await;
+
+    let app = spawn_app(repos);
+
+    let single = send(
+        &app,
+        request(
+            Method::GET,
+            &format!("/api/v1/project/detail/{project_id}"),
+            None,
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(single.status, StatusCode::OK);
+    assert_eq!(single.body["data"]["title"], "Portfolio API");
+
+    let filtered = send(
+        &app,
+        request(Method::GET, "/api/v1/project/all?title=api", None, None),
+    )
+    .await;
+    assert_eq!(filtered.body["data"][1], 1);
+
+    let total = send(
+        &app,
+        request(Method::GET, "/api/v1/project/total", None, None),
+    )
+    .await;
+    assert_eq!(total.body["data"]["total"], 2);
+}
+
+#[tokio::test]
+async fn missing_project_is_not_found() {
+    let app = spawn_app(memory_repos().await);
+
+    let response = send(
+        &app,
+        request(
+            Method::GET,
+            &format!("/api/v1/project/detail/{}", Uuid::new_v4()),
+            None,
+            None,
+        ),
+    )
+    .await;
+
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn update_project_checks_the_stack_exists() {
+    let repos = memory_repos().await;
+    let project_id = seed_project(&repos.projects, "Portfolio API").await;
+
+    let app = spawn_app(repos);
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    let response = send(
+        &app,
+        request(
+            Method::PATCH,
+            &format!("/api/v1/project/detail/{project_id}"),
+            Some(&token),
+            Some(json!({ "stack": "Haskell" })),
+        ),
+    )
+    .await;
+
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+}
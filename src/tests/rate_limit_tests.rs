@@ -0,0 +1,155 @@
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    time::Duration,
+};
+
+use axum::{
+    extract::ConnectInfo,
+    http::{Method, Request, StatusCode, header},
+};
+use serde_json::json;
+
+use crate::config::{RateLimitConfig, RateLimitKey, RateLimitRule};
+use crate::tests::support::{
+    ROOT_EMAIL, app_builder, login_token, memory_repos, request, send, spawn_app,
+};
+
+fn rule(burst: u32, key: RateLimitKey) -> RateLimitRule {
+    RateLimitRule {
+        burst,
+        per: Duration::from_secs(60),
+        key,
+    }
+}
+
+fn from_ip<B>(mut request: Request<B>, last_octet: u8) -> Request<B> {
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((
+            Ipv4Addr::new(10, 0, 0, last_octet),
+            4000,
+        ))));
+    request
+}
+
+fn forgot_password(last_octet: u8) -> Request<axum::body::Body> {
+    from_ip(
+        request(
+            Method::POST,
+            "/api/v1/auth/password/forgot",
+            None,
+            Some(json!({ "email": ROOT_EMAIL })),
+        ),
+        last_octet,
+    )
+}
+
+#[tokio::test]
+async fn credential_routes_are_limited_per_client_address() {
+    let app = app_builder(memory_repos().await)
+        .rate_limits(RateLimitConfig {
+            login: rule(2, RateLimitKey::Ip),
+            ..RateLimitConfig::default()
+        })
+        .build();
+
+    let first = send(&app, forgot_password(1)).await;
+    assert_eq!(first.status, StatusCode::OK);
+    assert_eq!(first.headers["ratelimit-limit"], "2");
+    assert_eq!(first.headers["ratelimit-remaining"], "1");
+    assert_eq!(first.headers["ratelimit-policy"], "2;w=60");
+
+    send(&app, forgot_password(1)).await;
+
+    let limited = send(&app, forgot_password(1)).await;
+    assert_eq!(limited.status, StatusCode::TOO_MANY_REQUESTS);
+    assert_eq!(limited.headers[header::RETRY_AFTER], "30");
+    assert_eq!(limited.headers["ratelimit-remaining"], "0");
+
+    let elsewhere = send(&app, forgot_password(2)).await;
+    assert_eq!(elsewhere.status, StatusCode::OK);
+}
+
+#[tokio::test]
+async fn spoofed_forwarded_hops_do_not_change_the_client_address() {
+    let app = app_builder(memory_repos().await)
+        .trust_forwarded_for(true)
+        .rate_limits(RateLimitConfig {
+            login: rule(2, RateLimitKey::Ip),
+            ..RateLimitConfig::default()
+        })
+        .build();
+
+    // The client makes up the leftmost hop; the proxy appends the address it saw.
+    let forwarded = |spoofed: u8| {
+        let mut request = forgot_password(1);
+        request.headers_mut().insert(
+            "x-forwarded-for",
+            format!("192.0.2.{spoofed}, 203.0.113.7").parse().unwrap(),
+        );
+        request
+    };
+
+    for spoofed in 1..=2 {
+        let allowed = send(&app, forwarded(spoofed)).await;
+        assert_eq!(allowed.status, StatusCode::OK);
+    }
+
+    let limited = send(&app, forwarded(3)).await;
+    assert_eq!(limited.status, StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn public_reads_do_not_count_against_writes() {
+    let app = app_builder(memory_repos().await)
+        .rate_limits(RateLimitConfig {
+            public_read: rule(1, RateLimitKey::Ip),
+            ..RateLimitConfig::default()
+        })
+        .build();
+
+    let read = || from_ip(request(Method::GET, "/api/v1/blog/all", None, None), 1);
+
+    assert_eq!(send(&app, read()).await.status, StatusCode::OK);
+    assert_eq!(
+        send(&app, read()).await.status,
+        StatusCode::TOO_MANY_REQUESTS
+    );
+
+    let token = login_token(&app, ROOT_EMAIL).await;
+    let write = send(
+        &app,
+        from_ip(
+            request(
+                Method::POST,
+                "/api/v1/stack/create",
+                Some(&token),
+                Some(json!({ "name": "Rust" })),
+            ),
+            1,
+        ),
+    )
+    .await;
+    assert_ne!(write.status, StatusCode::TOO_MANY_REQUESTS);
+}
+
+#[tokio::test]
+async fn disabled_limits_send_no_headers() {
+    let app = app_builder(memory_repos().await)
+        .rate_limits(RateLimitConfig {
+            enabled: false,
+            ..RateLimitConfig::default()
+        })
+        .build();
+
+    let response = send(&app, request(Method::GET, "/api/v1/blog/all", None, None)).await;
+    assert_eq!(response.status, StatusCode::OK);
+    assert!(!response.headers.contains_key("ratelimit-limit"));
+
+    let limited = send(
+        &spawn_app(memory_repos().await),
+        request(Method::GET, "/api/v1/blog/all", None, None),
+    )
+    .await;
+    assert!(limited.headers.contains_key("ratelimit-limit"));
+}
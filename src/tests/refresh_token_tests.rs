@@ -0,0 +1,356 @@
+use std::{
+    net::{Ipv4Addr, SocketAddr},
+    time::Duration,
+};
+
+use axum::{
+    body::Body,
+    extract::ConnectInfo,
+    http::{Method, Request, StatusCode, header},
+};
+use serde_json::json;
+use tower_cookies::cookie::SameSite;
+
+use crate::{
+    config::{RefreshCookieConfig, TokenConfig},
+    tests::support::{
+        PASSWORD, ROOT_EMAIL, TestResponse, app_builder, login, login_token, memory_repos,
+        refresh_cookie, request, seed_user, send, spawn_app, with_cookie,
+    },
+};
+
+fn set_cookie_header(response: &TestResponse) -> String {
+    response.headers[header::SET_COOKIE]
+        .to_str()
+        .unwrap()
+        .to_string()
+}
+
+#[tokio::test]
+async fn refresh_rotates_the_refresh_token() {
+    let app = spawn_app(memory_repos().await);
+
+    let cookie = refresh_cookie(&login(&app, ROOT_EMAIL).await).unwrap();
+
+    let refreshed = send(
+        &app,
+        with_cookie(
+            request(Method::POST, "/api/v1/token/refresh", None, None),
+            &cookie,
+        ),
+    )
+    .await;
+    assert_eq!(refreshed.status, StatusCode::OK);
+    assert!(refreshed.body["access_token"].as_str().is_some());
+
+    let rotated = refresh_cookie(&refreshed).unwrap();
+    assert_ne!(rotated, cookie);
+
+    let reused = send(
+        &app,
+        with_cookie(
+            request(Method::POST, "/api/v1/token/refresh", None, None),
+            &cookie,
+        ),
+    )
+    .await;
+    assert_eq!(reused.status, StatusCode::UNAUTHORIZED);
+
+    // The reuse gave the rotated token away as compromised too.
+    let successor = send(
+        &app,
+        with_cookie(
+            request(Method::POST, "/api/v1/token/refresh", None, None),
+            &rotated,
+        ),
+    )
+    .await;
+    assert_eq!(successor.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn logout_revokes_the_refresh_token() {
+    let app = spawn_app(memory_repos().await);
+
+    let cookie = refresh_cookie(&login(&app, ROOT_EMAIL).await).unwrap();
+
+    let logout = send(
+        &app,
+        with_cookie(
+            request(Method::POST, "/api/v1/token/logout", None, None),
+            &cookie,
+        ),
+    )
+    .await;
+    assert_eq!(logout.status, StatusCode::OK);
+
+    let refreshed = send(
+        &app,
+        with_cookie(
+            request(Method::POST, "/api/v1/token/refresh", None, None),
+            &cookie,
+        ),
+    )
+    .await;
+    assert_eq!(refreshed.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn refresh_cookie_follows_the_token_config() {
+    let app = app_builder(memory_repos().await)
+        .tokens(TokenConfig {
+            refresh_ttl: Duration::from_secs(2 * 24 * 60 * 60),
+            cookie: RefreshCookieConfig {
+                domain: Some("example.com".to_string()),
+                secure: true,
+                same_site: SameSite::Strict,
+                path: "/api".to_string(),
+            },
+            ..TokenConfig::default()
+        })
+        .build();
+
+    let login = login(&app, ROOT_EMAIL).await;
+    let set = set_cookie_header(&login);
+    for attribute in [
+        "HttpOnly",
+        "Secure",
+        "SameSite=Strict",
+        "Path=/api",
+        "Domain=example.com",
+        "Max-Age=172800",
+    ] {
+        assert!(set.contains(attribute), "{attribute} missing from {set}");
+    }
+
+    let logout = send(
+        &app,
+        with_cookie(
+            request(Method::POST, "/api/v1/token/logout", None, None),
+            &refresh_cookie(&login).unwrap(),
+        ),
+    )
+    .await;
+    let cleared = set_cookie_header(&logout);
+    assert!(cleared.starts_with("refresh_token=;"));
+    for attribute in ["Max-Age=0", "Path=/api", "Domain=example.com", "1970"] {
+        assert!(
+            cleared.contains(attribute),
+            "{attribute} missing from {cleared}"
+        );
+    }
+}
+
+#[test]
+fn token_config_rejects_unusable_settings() {
+    assert!(TokenConfig::default().validate().is_ok());
+
+    let cross_site_over_http = TokenConfig {
+        cookie: RefreshCookieConfig {
+            same_site: SameSite::None,
+            ..RefreshCookieConfig::default()
+        },
+        ..TokenConfig::default()
+    };
+    assert!(cross_site_over_http.validate().is_err());
+
+    let outlived_by_access = TokenConfig {
+        refresh_ttl: Duration::from_secs(60),
+        ..TokenConfig::default()
+    };
+    assert!(outlived_by_access.validate().is_err());
+}
+
+#[tokio::test]
+async fn refresh_without_cookie_is_unauthorized() {
+    let app = spawn_app(memory_repos().await);
+
+    let response = send(
+        &app,
+        request(Method::POST, "/api/v1/token/refresh", None, None),
+    )
+    .await;
+
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+}
+
+fn login_from(email: &str, user_agent: &str, last_octet: u8) -> Request<Body> {
+    let mut request = request(
+        Method::POST,
+        "/api/v1/auth/login",
+        None,
+        Some(json!({ "email": email, "password": PASSWORD })),
+    );
+
+    request
+        .headers_mut()
+        .insert(header::USER_AGENT, user_agent.parse().unwrap());
+    request
+        .extensions_mut()
+        .insert(ConnectInfo(SocketAddr::from((
+            Ipv4Addr::new(10, 0, 0, last_octet),
+            4000,
+        ))));
+
+    request
+}
+
+fn refresh_with(cookie: &str) -> Request<Body> {
+    with_cookie(
+        request(Method::POST, "/api/v1/token/refresh", None, None),
+        cookie,
+    )
+}
+
+#[tokio::test]
+async fn sessions_record_the_device_and_survive_rotation() {
+    let app = spawn_app(memory_repos().await);
+
+    let laptop = send(&app, login_from(ROOT_EMAIL, "Laptop/1.0", 1)).await;
+    let laptop_token = laptop.body["token"].as_str().unwrap().to_string();
+    let laptop_cookie = refresh_cookie(&laptop).unwrap();
+
+    let phone = send(&app, login_from(ROOT_EMAIL, "Phone/2.0", 2)).await;
+    let phone_cookie = refresh_cookie(&phone).unwrap();
+
+    let rotated = send(&app, refresh_with(&phone_cookie)).await;
+    assert_eq!(rotated.status, StatusCode::OK);
+
+    let sessions = send(
+        &app,
+        with_cookie(
+            request(
+                Method::GET,
+                "/api/v1/auth/sessions",
+                Some(&laptop_token),
+                None,
+            ),
+            &laptop_cookie,
+        ),
+    )
+    .await;
+    assert_eq!(sessions.status, StatusCode::OK);
+
+    let list = sessions.body["data"].as_array().unwrap();
+    assert_eq!(list.len(), 2);
+
+    let current: Vec<_> = list.iter().filter(|s| s["current"] == true).collect();
+    assert_eq!(current.len(), 1);
+    assert_eq!(current[0]["user_agent"], "Laptop/1.0");
+    assert_eq!(current[0]["ip"], "10.0.0.1");
+
+    let other = list.iter().find(|s| s["current"] == false).unwrap();
+    assert_eq!(other["user_agent"], "Phone/2.0");
+}
+
+#[tokio::test]
+async fn users_revoke_one_session_or_all_others() {
+    let app = spawn_app(memory_repos().await);
+
+    let first = send(&app, login_from(ROOT_EMAIL, "First", 1)).await;
+    let token = first.body["token"].as_str().unwrap().to_string();
+    let first_cookie = refresh_cookie(&first).unwrap();
+
+    let second_cookie =
+        refresh_cookie(&send(&app, login_from(ROOT_EMAIL, "Second", 2)).await).unwrap();
+    let third_cookie =
+        refresh_cookie(&send(&app, login_from(ROOT_EMAIL, "Third", 3)).await).unwrap();
+
+    let sessions = send(
+        &app,
+        request(Method::GET, "/api/v1/auth/sessions", Some(&token), None),
+    )
+    .await;
+    let second_id = sessions.body["data"]
+        .as_array()
+        .unwrap()
+        .iter()
+        .find(|s| s["user_agent"] == "Second")
+        .unwrap()["id"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let revoked = send(
+        &app,
+        request(
+            Method::DELETE,
+            &format!("/api/v1/auth/sessions/{second_id}"),
+            Some(&token),
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(revoked.status, StatusCode::OK);
+    assert_eq!(
+        send(&app, refresh_with(&second_cookie)).await.status,
+        StatusCode::UNAUTHORIZED
+    );
+
+    let again = send(
+        &app,
+        request(
+            Method::DELETE,
+            &format!("/api/v1/auth/sessions/{second_id}"),
+            Some(&token),
+            None,
+        ),
+    )
+    .await;
+    assert_eq!(again.status, StatusCode::NOT_FOUND);
+
+    let others = send(
+        &app,
+        with_cookie(
+            request(Method::DELETE, "/api/v1/auth/sessions", Some(&token), None),
+            &first_cookie,
+        ),
+    )
+    .await;
+    assert_eq!(others.status, StatusCode::OK);
+    assert_eq!(
+        send(&app, refresh_with(&third_cookie)).await.status,
+        StatusCode::UNAUTHORIZED
+    );
+    assert_eq!(
+        send(&app, refresh_with(&first_cookie)).await.status,
+        StatusCode::OK
+    );
+}
+
+#[tokio::test]
+async fn admins_force_users_to_log_out() {
+    let repos = memory_repos().await;
+    let mid_id = seed_user(&repos.users, "mid@example.com", "mid").await;
+    seed_user(&repos.users, "normal@example.com", "normal").await;
+
+    let app = spawn_app(repos);
+
+    let mid_cookie = refresh_cookie(&login(&app, "mid@example.com").await).unwrap();
+    let normal_token = login_token(&app, "normal@example.com").await;
+    let root_token = login_token(&app, ROOT_EMAIL).await;
+
+    let logout_uri = format!("/api/v1/auth/users/{mid_id}/logout");
+
+    let refused = send(
+        &app,
+        request(Method::POST, &logout_uri, Some(&normal_token), None),
+    )
+    .await;
+    assert_eq!(refused.status, StatusCode::BAD_REQUEST);
+
+    let still_in = send(&app, refresh_with(&mid_cookie)).await;
+    assert_eq!(still_in.status, StatusCode::OK);
+    let rotated_cookie = refresh_cookie(&still_in).unwrap();
+
+    let forced = send(
+        &app,
+        request(Method::POST, &logout_uri, Some(&root_token), None),
+    )
+    .await;
+    assert_eq!(forced.status, StatusCode::OK);
+    assert_eq!(
+        send(&app, refresh_with(&rotated_cookie)).await.status,
+        StatusCode::UNAUTHORIZED
+    );
+}
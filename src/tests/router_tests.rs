@@ -0,0 +1,37 @@
+use axum::http::{Method, StatusCode};
+
+use crate::tests::support::{memory_repos, request, send, spawn_app};
+
+#[tokio::test]
+async fn unknown_route_returns_json_404() {
+    let app = spawn_app(memory_repos().await);
+
+    let response = send(&app, request(Method::GET, "/api/v1/nope", None, None)).await;
+
+    assert_eq!(response.status, StatusCode::NOT_FOUND);
+    assert_eq!(
+        response.body["message"],
+        "Route GET /api/v1/nope does not exist"
+    );
+}
+
+#[tokio::test]
+async fn wrong_method_returns_json_405() {
+    let app = spawn_app(memory_repos().await);
+
+    let response = send(&app, request(Method::PUT, "/api/v1/blog/all", None, None)).await;
+
+    assert_eq!(response.status, StatusCode::METHOD_NOT_ALLOWED);
+    assert!(response.body["message"].is_string());
+}
+
+#[tokio::test]
+async fn health_reports_ok_without_remote_dependencies() {
+    let app = spawn_app(memory_repos().await);
+
+    let response = send(&app, request(Method::GET, "/api/v1/health", None, None)).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["status"], "ok");
+    assert!(response.body["image_store"]["circuit"].is_null());
+}
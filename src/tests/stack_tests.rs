@@ -0,0 +1,96 @@
+use axum::http::{Method, StatusCode};
+use serde_json::json;
+
+use crate::tests::support::{ROOT_EMAIL, login_token, memory_repos, request, send, spawn_app};
+
+#[tokio::test]
+async fn create_stack_requires_a_token() {
+    let app = spawn_app(memory_repos().await);
+
+    let response = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/stack/create",
+            None,
+            Some(json!({ "title": "Rust", "slug": "rust" })),
+        ),
+    )
+    .await;
+
+    assert_eq!(response.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn stack_crud_round_trip() {
+    let app = spawn_app(memory_repos().await);
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    let created = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/stack/create",
+            Some(&token),
+            Some(json!({ "title": "Rust", "slug": "rust" })),
+        ),
+    )
+    .await;
+    assert_eq!(created.status, StatusCode::OK);
+
+    let stack_id = created.body["data"].as_str().unwrap().to_string();
+    let uri = format!("/api/v1/stack/detail/{stack_id}");
+
+    let by_title = send(
+        &app,
+        request(Method::GET, "/api/v1/stack/by/Rust", None, None),
+    )
+    .await;
+    assert_eq!(by_title.status, StatusCode::OK);
+    assert_eq!(by_title.body["data"]["id"], stack_id.as_str());
+
+    let updated = send(
+        &app,
+        request(
+            Method::PATCH,
+            &uri,
+            Some(&token),
+            Some(json!({ "slug": "rust-lang" })),
+        ),
+    )
+    .await;
+    assert_eq!(updated.status, StatusCode::OK);
+
+    let fetched = send(&app, request(Method::GET, &uri, None, None)).await;
+    assert_eq!(fetched.body["data"]["slug"], "rust-lang");
+
+    let all = send(&app, request(Method::GET, "/api/v1/stack/all", None, None)).await;
+    assert_eq!(all.body["data"].as_array().unwrap().len(), 1);
+
+    let deleted = send(&app, request(Method::DELETE, &uri, Some(&token), None)).await;
+    assert_eq!(deleted.status, StatusCode::OK);
+
+    let missing = send(&app, request(Method::GET, &uri, None, None)).await;
+    assert_eq!(missing.status, StatusCode::NOT_FOUND);
+}
+
+#[tokio::test]
+async fn duplicate_stack_title_conflicts() {
+    let app = spawn_app(memory_repos().await);
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    for expected in [StatusCode::OK, StatusCode::CONFLICT] {
+        let response = send(
+            &app,
+            request(
+                Method::POST,
+                "/api/v1/stack/create",
+                Some(&token),
+                Some(json!({ "title": "Rust", "slug": "rust" })),
+            ),
+        )
+        .await;
+
+        assert_eq!(response.status, expected);
+    }
+}
@@ -0,0 +1,368 @@
+use std::{
+    path::{Path, PathBuf},
+    sync::Arc,
+    time::Duration,
+};
+
+use axum::{
+    Router,
+    body::{Body, to_bytes},
+    http::{HeaderMap, Method, Request, StatusCode, header},
+};
+use base64::{Engine as _, engine::general_purpose};
+use chrono::{NaiveDate, Utc};
+use serde_json::Value;
+use tower::ServiceExt;
+use uuid::Uuid;
+
+use crate::{
+    api::{AppApisBuilder, AppRepositories},
+    api_key::repo_memory::ApiKeyRepoMemory,
+    auth::{dto::RegisteredData, repo::UserRepository, repo_memory::UserRepoMemory},
+    blog::{dto::CreateBlogData, repo::BlogRepository, repo_memory::BlogRepoMemory},
+    core::{jwt_keys::JwtKeys, password_core::hash_password},
+    fields::{
+        email::Email, password::Password, phone_number::PhoneNumber, roles::Roles, text::Text,
+    },
+    image::store_local::LocalImageStore,
+    mail::mailer_file::FileMailer,
+    media::repo_memory::MediaRepoMemory,
+    project::{dto::CreateProjectData, repo::ProjectRepository, repo_memory::ProjectRepoMemory},
+    refresh_token::repo_memory::RefreshTokenRepoMemory,
+    stack::repo_memory::StackRepoMemory,
+};
+
+pub const JWT_SECRET: &str = "test-secret";
+
+pub const ROOT_EMAIL: &str = "root@example.com";
+
+pub const PASSWORD: &str = "Secret#123";
+
+pub type MemoryRepositories = AppRepositories<
+    UserRepoMemory,
+    StackRepoMemory,
+    BlogRepoMemory,
+    ProjectRepoMemory,
+    RefreshTokenRepoMemory,
+    MediaRepoMemory,
+    ApiKeyRepoMemory,
+>;
+
+pub struct TestResponse {
+    pub status: StatusCode,
+    pub headers: HeaderMap,
+    pub body: Value,
+}
+
+/// Empty in-memory repositories with a single `root` admin able to log in with [`PASSWORD`].
+pub async fn memory_repos() -> MemoryRepositories {
+    let repos = AppRepositories {
+        users: UserRepoMemory::new(),
+        stacks: StackRepoMemory::new(),
+        blogs: BlogRepoMemory::new(),
+        projects: ProjectRepoMemory::new(),
+        refresh_tokens: RefreshTokenRepoMemory::new(),
+        media: MediaRepoMemory::new(),
+        api_keys: ApiKeyRepoMemory::new(),
+    };
+
+    seed_user(&repos.users, ROOT_EMAIL, "root").await;
+
+    repos
+}
+
+pub async fn seed_user(users: &UserRepoMemory, email: &str, roles: &str) -> Uuid {
+    let id = Uuid::new_v4();
+
+    let user = RegisteredData {
+        email: Email::new(email).unwrap(),
+        password: Password::new(PASSWORD).unwrap(),
+        name: Text::new("Test Admin").unwrap(),
+        phone_number: Some(PhoneNumber::new("+2348012345678").unwrap()),
+        roles: Roles::new(roles).unwrap(),
+        created_by: id,
+        created_by_name: Text::new("Seed").unwrap(),
+        created_by_email: Email::new(email).unwrap(),
+    };
+
+    let hash = hash_password(PASSWORD).unwrap();
+
+    users
+        .insert_user(id, &user, &hash, Utc::now().naive_utc())
+        .await
+        .unwrap();
+
+    id
+}
+
+pub async fn seed_blog(blogs: &BlogRepoMemory, title: &str) -> Uuid {
+    let id = Uuid::new_v4();
+
+    let blog = CreateBlogData {
+        title: title.to_string(),
+        description: "A description".to_string(),
+        content: "Some content".to_string(),
+        word_count: 2,
+        image: "https://images.example.com/blog.png".to_string(),
+        image_id: format!("blog-{id}"),
+        image_variants: Vec::new(),
+        media_id: Uuid::new_v4(),
+        created_by: id,
+        created_by_name: Text::new("Seed").unwrap(),
+        created_by_email: Email::new(ROOT_EMAIL).unwrap(),
+    };
+
+    blogs
+        .insert_blog(id, &blog, Utc::now().naive_utc())
+        .await
+        .unwrap();
+
+    id
+}
+
+pub async fn seed_project(projects: &ProjectRepoMemory, title: &str) -> Uuid {
+    let id = Uuid::new_v4();
+
+    let project = CreateProjectData {
+        title: title.to_string(),
+        description: "A description".to_string(),
+        company: "Acme".to_string(),
+        role: "Engineer".to_string(),
+        start_date: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+        end_date: None,
+        tag: "backend".to_string(),
+        link: "https://example.com".to_string(),
+        stack: "Rust".to_string(),
+        content: "Some content".to_string(),
+        word_count: 2,
+        image: "https://images.example.com/project.png".to_string(),
+        image_id: format!("project-{id}"),
+        image_variants: Vec::new(),
+        media_id: Uuid::new_v4(),
+        created_by: id,
+        created_by_name: Text::new("Seed").unwrap(),
+        created_by_email: Email::new(ROOT_EMAIL).unwrap(),
+    };
+
+    projects
+        .insert_project(id, &project, Utc::now().naive_utc())
+        .await
+        .unwrap();
+
+    id
+}
+
+/// A 1x1 transparent PNG as a data URL.
+pub const PNG_DATA_URL: &str = "data:image/png;base64,iVBORw0KGgoAAAANSUhEUgAAAAEAAAABCAQAAAC1HAwCAAAAC0lEQVR42mNkYAAAAAYAAjCB0C8AAAAASUVORK5CYII=";
+
+/// A transparent PNG of the given size; different sizes hash differently.
+pub fn png_bytes(width: u32, height: u32) -> Vec<u8> {
+    let mut bytes = std::io::Cursor::new(Vec::new());
+
+    image::RgbaImage::new(width, height)
+        .write_to(&mut bytes, image::ImageFormat::Png)
+        .unwrap();
+
+    bytes.into_inner()
+}
+
+/// [`png_bytes`] as a data URL.
+pub fn png_data_url(width: u32, height: u32) -> String {
+    format!(
+        "data:image/png;base64,{}",
+        general_purpose::STANDARD.encode(png_bytes(width, height))
+    )
+}
+
+pub fn spawn_app(repos: MemoryRepositories) -> Router {
+    app_builder(repos).build()
+}
+
+/// [`spawn_app`] with outgoing mail written to the returned directory.
+pub fn spawn_app_with_mailbox(repos: MemoryRepositories) -> (Router, PathBuf) {
+    let mailbox = std::env::temp_dir().join(format!("portfolio-mail-{}", Uuid::new_v4()));
+
+    let app = app_builder(repos)
+        .mailer(Arc::new(FileMailer::new(Some(mailbox.clone()))))
+        .build();
+
+    (app, mailbox)
+}
+
+pub fn app_builder(
+    repos: MemoryRepositories,
+) -> AppApisBuilder<
+    UserRepoMemory,
+    StackRepoMemory,
+    BlogRepoMemory,
+    ProjectRepoMemory,
+    RefreshTokenRepoMemory,
+    MediaRepoMemory,
+    ApiKeyRepoMemory,
+> {
+    app_builder_with_keys(repos, JwtKeys::from_secret(JWT_SECRET))
+}
+
+/// [`app_builder`] signing tokens with `keys` instead of [`JWT_SECRET`].
+pub fn app_builder_with_keys(
+    repos: MemoryRepositories,
+    keys: JwtKeys,
+) -> AppApisBuilder<
+    UserRepoMemory,
+    StackRepoMemory,
+    BlogRepoMemory,
+    ProjectRepoMemory,
+    RefreshTokenRepoMemory,
+    MediaRepoMemory,
+    ApiKeyRepoMemory,
+> {
+    let media_dir = std::env::temp_dir().join(format!("portfolio-media-{}", Uuid::new_v4()));
+
+    let image_store = LocalImageStore::new(media_dir, String::new());
+
+    AppApisBuilder::new(repos, Box::new(image_store), keys)
+}
+
+/// Every email delivered to `mailbox`, oldest first, once at least `expected` have arrived.
+/// Mail goes out in the background, so it may land after the response that triggered it.
+pub async fn mails(mailbox: &Path, expected: usize) -> Vec<String> {
+    let mut paths = Vec::new();
+
+    for _ in 0..100 {
+        paths = std::fs::read_dir(mailbox)
+            .map(|entries| {
+                entries
+                    .map(|entry| entry.unwrap().path())
+                    .filter(|path| path.extension().is_some_and(|ext| ext == "eml"))
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        if paths.len() >= expected {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(20)).await;
+    }
+    paths.sort();
+
+    paths
+        .iter()
+        .map(|path| std::fs::read_to_string(path).unwrap())
+        .collect()
+}
+
+pub async fn send(app: &Router, request: Request<Body>) -> TestResponse {
+    let response = app.clone().oneshot(request).await.unwrap();
+
+    let status = response.status();
+    let headers = response.headers().clone();
+
+    let bytes = to_bytes(response.into_body(), usize::MAX).await.unwrap();
+
+    let body = if bytes.is_empty() {
+        Value::Null
+    } else {
+        serde_json::from_slice(&bytes).unwrap_or(Value::Null)
+    };
+
+    TestResponse {
+        status,
+        headers,
+        body,
+    }
+}
+
+pub fn request(
+    method: Method,
+    uri: &str,
+    token: Option<&str>,
+    body: Option<Value>,
+) -> Request<Body> {
+    let mut builder = Request::builder().method(method).uri(uri);
+
+    if let Some(token) = token {
+        builder = builder.header(header::AUTHORIZATION, format!("Bearer {token}"));
+    }
+
+    match body {
+        Some(body) => builder
+            .header(header::CONTENT_TYPE, "application/json")
+            .body(Body::from(body.to_string()))
+            .unwrap(),
+        None => builder.body(Body::empty()).unwrap(),
+    }
+}
+
+/// A `multipart/form-data` request with one file field per `(name, content_type, bytes)`.
+pub fn multipart_request(
+    uri: &str,
+    token: Option<&str>,
+    files: &[(&str, &str, &[u8])],
+) -> Request<Body> {
+    let boundary = "X-BOUNDARY";
+    let mut body = Vec::new();
+
+    for (name, content_type, bytes) in files {
+        body.extend_from_slice(
+            format!(
+                "--{boundary}\r\nContent-Disposition: form-data; name=\"{name}\"; filename=\"{name}\"\r\nContent-Type: {content_type}\r\n\r\n"
+            )
+            .as_bytes(),
+        );
+        body.extend_from_slice(bytes);
+        body.extend_from_slice(b"\r\n");
+    }
+
+    body.extend_from_slice(format!("--{boundary}--\r\n").as_bytes());
+
+    let mut builder = Request::builder().method(Method::POST).uri(uri).header(
+        header::CONTENT_TYPE,
+        format!("multipart/form-data; boundary={boundary}"),
+    );
+
+    if let Some(token) = token {
+        builder = builder.header(header::AUTHORIZATION, format!("Bearer {token}"));
+    }
+
+    builder.body(Body::from(body)).unwrap()
+}
+
+pub fn with_cookie(mut request: Request<Body>, cookie: &str) -> Request<Body> {
+    request
+        .headers_mut()
+        .insert(header::COOKIE, cookie.parse().unwrap());
+    request
+}
+
+/// Returns the `name=value` pair of the refresh cookie set on a response, if any.
+pub fn refresh_cookie(response: &TestResponse) -> Option<String> {
+    response
+        .headers
+        .get_all(header::SET_COOKIE)
+        .iter()
+        .filter_map(|value| value.to_str().ok())
+        .find(|value| value.starts_with("refresh_token="))
+        .and_then(|value| value.split(';').next())
+        .map(str::to_string)
+}
+
+pub async fn login(app: &Router, email: &str) -> TestResponse {
+    send(
+        app,
+        request(
+            Method::POST,
+            "/api/v1/auth/login",
+            None,
+            Some(serde_json::json!({ "email": email, "password": PASSWORD })),
+        ),
+    )
+    .await
+}
+
+pub async fn login_token(app: &Router, email: &str) -> String {
+    let response = login(app, email).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+
+    response.body["token"].as_str().unwrap().to_string()
+}
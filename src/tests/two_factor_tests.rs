@@ -0,0 +1,215 @@
+use std::time::Duration;
+
+use axum::{
+    Router,
+    http::{Method, StatusCode},
+};
+use chrono::Utc;
+use serde_json::json;
+
+use crate::{
+    config::{LoginThrottleConfig, RateLimitConfig, TwoFactorConfig},
+    core::totp_core::{TOTP_STEP, current_totp},
+    tests::support::{
+        PASSWORD, ROOT_EMAIL, TestResponse, app_builder, login, login_token, memory_repos,
+        refresh_cookie, request, seed_user, send,
+    },
+};
+
+/// Wrong codes are part of these tests, so the login throttle must not add waits.
+fn no_delay() -> LoginThrottleConfig {
+    LoginThrottleConfig {
+        base_delay: Duration::ZERO,
+        ..LoginThrottleConfig::default()
+    }
+}
+
+fn code_at(secret: &str, steps_ahead: i64) -> String {
+    current_totp(secret, Utc::now().timestamp() + steps_ahead * TOTP_STEP)
+}
+
+async fn challenge(app: &Router, email: &str) -> String {
+    let response = login(app, email).await;
+
+    assert_eq!(response.status, StatusCode::OK);
+    assert_eq!(response.body["message"], "two_factor_required");
+    assert!(response.body["token"].is_null());
+    assert!(refresh_cookie(&response).is_none());
+
+    response.body["data"]["challenge_token"]
+        .as_str()
+        .unwrap()
+        .to_string()
+}
+
+async fn answer(app: &Router, challenge_token: &str, code: &str) -> TestResponse {
+    send(
+        app,
+        request(
+            Method::POST,
+            "/api/v1/auth/login/2fa",
+            None,
+            Some(json!({ "challenge_token": challenge_token, "code": code })),
+        ),
+    )
+    .await
+}
+
+#[tokio::test]
+async fn enrolled_users_need_a_code_to_log_in() {
+    let app = app_builder(memory_repos().await)
+        .login_throttle(no_delay())
+        .rate_limits(RateLimitConfig {
+            enabled: false,
+            ..RateLimitConfig::default()
+        })
+        .build();
+
+    let token = login_token(&app, ROOT_EMAIL).await;
+
+    let setup = send(
+        &app,
+        request(Method::POST, "/api/v1/auth/2fa/setup", Some(&token), None),
+    )
+    .await;
+    assert_eq!(setup.status, StatusCode::OK);
+    let secret = setup.body["data"]["secret"].as_str().unwrap().to_string();
+    assert!(
+        setup.body["data"]["otpauth_uri"]
+            .as_str()
+            .unwrap()
+            .starts_with("otpauth://totp/Portfolio:root@example.com?secret=")
+    );
+
+    let confirm = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/auth/2fa/confirm",
+            Some(&token),
+            Some(json!({ "code": code_at(&secret, 0) })),
+        ),
+    )
+    .await;
+    assert_eq!(confirm.status, StatusCode::OK);
+    let recovery_codes = confirm.body["data"]["recovery_codes"].as_array().unwrap();
+    assert_eq!(recovery_codes.len(), 10);
+    let recovery_code = recovery_codes[0].as_str().unwrap().to_string();
+
+    let challenge_token = challenge(&app, ROOT_EMAIL).await;
+
+    let wrong = answer(&app, &challenge_token, "not-a-code").await;
+    assert_eq!(wrong.status, StatusCode::UNAUTHORIZED);
+
+    let next_code = code_at(&secret, 1);
+    let passed = answer(&app, &challenge_token, &next_code).await;
+    assert_eq!(passed.status, StatusCode::OK);
+    assert!(passed.body["token"].is_string());
+    assert!(refresh_cookie(&passed).is_some());
+
+    let used_up = answer(&app, &challenge_token, &next_code).await;
+    assert_eq!(used_up.status, StatusCode::UNAUTHORIZED);
+
+    let replayed = answer(&app, &challenge(&app, ROOT_EMAIL).await, &next_code).await;
+    assert_eq!(replayed.status, StatusCode::UNAUTHORIZED);
+
+    let recovered = answer(&app, &challenge(&app, ROOT_EMAIL).await, &recovery_code).await;
+    assert_eq!(recovered.status, StatusCode::OK);
+
+    let recovered_again = answer(&app, &challenge(&app, ROOT_EMAIL).await, &recovery_code).await;
+    assert_eq!(recovered_again.status, StatusCode::UNAUTHORIZED);
+}
+
+#[tokio::test]
+async fn challenges_give_out_after_too_many_wrong_codes() {
+    let app = app_builder(memory_repos().await)
+        .login_throttle(no_delay())
+        .two_factor(TwoFactorConfig {
+            required_for_admins: true,
+            max_attempts: 2,
+            ..TwoFactorConfig::default()
+        })
+        .build();
+
+    let challenge_token = challenge(&app, ROOT_EMAIL).await;
+
+    let setup = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/auth/login/2fa/setup",
+            None,
+            Some(json!({ "challenge_token": challenge_token })),
+        ),
+    )
+    .await;
+    let secret = setup.body["data"]["secret"].as_str().unwrap().to_string();
+
+    for _ in 0..2 {
+        let wrong = answer(&app, &challenge_token, "not-a-code").await;
+        assert_eq!(wrong.status, StatusCode::UNAUTHORIZED);
+    }
+
+    let too_late = answer(&app, &challenge_token, &code_at(&secret, 0)).await;
+    assert_eq!(too_late.status, StatusCode::UNAUTHORIZED);
+    assert_eq!(too_late.body["message"], "Invalid or expired challenge");
+}
+
+#[tokio::test]
+async fn admins_enrol_on_login_when_two_factor_is_required() {
+    let repos = memory_repos().await;
+    seed_user(&repos.users, "normal@example.com", "normal").await;
+
+    let app = app_builder(repos)
+        .two_factor(TwoFactorConfig {
+            required_for_admins: true,
+            ..TwoFactorConfig::default()
+        })
+        .build();
+
+    let normal = login(&app, "normal@example.com").await;
+    assert!(normal.body["token"].is_string());
+
+    let response = login(&app, ROOT_EMAIL).await;
+    assert_eq!(response.body["data"]["setup_required"], true);
+    let challenge_token = response.body["data"]["challenge_token"]
+        .as_str()
+        .unwrap()
+        .to_string();
+
+    let not_set_up = answer(&app, &challenge_token, "123456").await;
+    assert_eq!(not_set_up.status, StatusCode::BAD_REQUEST);
+
+    let setup = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/auth/login/2fa/setup",
+            None,
+            Some(json!({ "challenge_token": challenge_token })),
+        ),
+    )
+    .await;
+    assert_eq!(setup.status, StatusCode::OK);
+    let secret = setup.body["data"]["secret"].as_str().unwrap().to_string();
+
+    let enrolled = answer(&app, &challenge_token, &code_at(&secret, 0)).await;
+    assert_eq!(enrolled.status, StatusCode::OK);
+    assert_eq!(
+        enrolled.body["recovery_codes"].as_array().unwrap().len(),
+        10
+    );
+    let token = enrolled.body["token"].as_str().unwrap().to_string();
+
+    let disable = send(
+        &app,
+        request(
+            Method::POST,
+            "/api/v1/auth/2fa/disable",
+            Some(&token),
+            Some(json!({ "password": PASSWORD, "code": code_at(&secret, 1) })),
+        ),
+    )
+    .await;
+    assert_eq!(disable.status, StatusCode::BAD_REQUEST);
+}
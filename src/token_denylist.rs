@@ -0,0 +1,3 @@
+pub mod denylist;
+pub mod store;
+pub mod store_postgres;
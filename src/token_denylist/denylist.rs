@@ -0,0 +1,99 @@
+use std::{collections::HashMap, sync::Arc, sync::Mutex};
+
+use chrono::NaiveDateTime;
+use uuid::Uuid;
+
+use crate::{errors::api_errors::ApiErrors, token_denylist::store::DenylistStore};
+
+/// Access tokens revoked before their `exp`, by `jti`. Lookups only touch memory; with a
+/// store, denials are also written there and [`TokenDenylist::sync`] picks up those made by
+/// other instances.
+pub struct TokenDenylist {
+    entries: Mutex<HashMap<Uuid, NaiveDateTime>>,
+    store: Option<Arc<dyn DenylistStore>>,
+}
+
+impl TokenDenylist {
+    pub fn new(store: Option<Arc<dyn DenylistStore>>) -> Self {
+        Self {
+            entries: Mutex::new(HashMap::new()),
+            store,
+        }
+    }
+
+    /// Keeps `jti` denied until `expires_at`, after which the token is refused anyway.
+    pub async fn deny(&self, jti: Uuid, expires_at: NaiveDateTime) -> Result<(), ApiErrors> {
+        self.entries.lock().unwrap().insert(jti, expires_at);
+
+        if let Some(store) = &self.store {
+            store.insert(jti, expires_at).await?;
+        }
+
+        Ok(())
+    }
+
+    pub fn is_denied(&self, jti: Uuid) -> bool {
+        self.entries.lock().unwrap().contains_key(&jti)
+    }
+
+    /// Drops entries past their expiry and merges in what the store holds.
+    pub async fn sync(&self, now: NaiveDateTime) -> Result<(), ApiErrors> {
+        let stored = match &self.store {
+            Some(store) => {
+                store.prune(now).await?;
+                store.load_active(now).await?
+            }
+            None => Vec::new(),
+        };
+
+        let mut entries = self.entries.lock().unwrap();
+        entries.retain(|_, expires_at| *expires_at > now);
+        entries.extend(stored);
+
+        Ok(())
+    }
+}
+
+/// Keeps the denylist in step with its store every `every`, starting right away so denials
+/// made before a restart hold.
+pub fn spawn_denylist_sync(denylist: Arc<TokenDenylist>, every: std::time::Duration) {
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(every);
+
+        loop {
+            interval.tick().await;
+
+            if let Err(e) = denylist.sync(chrono::Utc::now().naive_utc()).await {
+                println!("token denylist sync failed: {e}");
+            }
+        }
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn entries_are_forgotten_once_expired() {
+        let denylist = TokenDenylist::new(None);
+        let now = chrono::Utc::now().naive_utc();
+
+        let live = Uuid::new_v4();
+        let lapsed = Uuid::new_v4();
+        denylist
+            .deny(live, now + chrono::Duration::minutes(5))
+            .await
+            .unwrap();
+        denylist
+            .deny(lapsed, now - chrono::Duration::minutes(5))
+            .await
+            .unwrap();
+
+        denylist.sync(now).await.unwrap();
+
+        assert!(denylist.is_denied(live));
+        assert!(!denylist.is_denied(lapsed));
+        assert!(!denylist.is_denied(Uuid::new_v4()));
+    }
+}
@@ -0,0 +1,40 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{
+    config::DenylistStoreConfig, errors::api_errors::ApiErrors,
+    token_denylist::store_postgres::PostgresDenylistStore,
+};
+
+/// Where denied `jti`s outlive a restart and are shared between instances.
+#[async_trait]
+pub trait DenylistStore: Send + Sync {
+    async fn insert(&self, jti: Uuid, expires_at: NaiveDateTime) -> Result<(), ApiErrors>;
+
+    /// Every entry that has not expired by `now`.
+    async fn load_active(
+        &self,
+        now: NaiveDateTime,
+    ) -> Result<Vec<(Uuid, NaiveDateTime)>, ApiErrors>;
+
+    /// Deletes entries expired by `now`, returning how many went.
+    async fn prune(&self, now: NaiveDateTime) -> Result<u64, ApiErrors>;
+}
+
+/// `None` keeps denials in memory only, so they are lost on restart and each instance has
+/// its own.
+pub fn denylist_store_from_config(
+    config: DenylistStoreConfig,
+    pool: &PgPool,
+) -> Option<Arc<dyn DenylistStore>> {
+    match config {
+        DenylistStoreConfig::Memory => None,
+        DenylistStoreConfig::Postgres => {
+            Some(Arc::new(PostgresDenylistStore { pool: pool.clone() }))
+        }
+    }
+}
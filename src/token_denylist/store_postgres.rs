@@ -0,0 +1,56 @@
+use async_trait::async_trait;
+use chrono::NaiveDateTime;
+use sqlx::PgPool;
+use uuid::Uuid;
+
+use crate::{errors::api_errors::ApiErrors, token_denylist::store::DenylistStore};
+
+pub struct PostgresDenylistStore {
+    pub pool: PgPool,
+}
+
+#[async_trait]
+impl DenylistStore for PostgresDenylistStore {
+    async fn insert(&self, jti: Uuid, expires_at: NaiveDateTime) -> Result<(), ApiErrors> {
+        sqlx::query!(
+            "INSERT INTO access_token_denylist (jti, expires_at) VALUES ($1, $2) ON CONFLICT (jti) DO NOTHING",
+            jti,
+            expires_at,
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Failed to deny token".to_string()))?;
+
+        Ok(())
+    }
+
+    async fn load_active(
+        &self,
+        now: NaiveDateTime,
+    ) -> Result<Vec<(Uuid, NaiveDateTime)>, ApiErrors> {
+        let rows = sqlx::query!(
+            "SELECT jti, expires_at FROM access_token_denylist WHERE expires_at > $1",
+            now
+        )
+        .fetch_all(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Denylist lookup failed".to_string()))?;
+
+        Ok(rows
+            .into_iter()
+            .map(|row| (row.jti, row.expires_at))
+            .collect())
+    }
+
+    async fn prune(&self, now: NaiveDateTime) -> Result<u64, ApiErrors> {
+        let result = sqlx::query!(
+            "DELETE FROM access_token_denylist WHERE expires_at <= $1",
+            now
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|_| ApiErrors::InternalServerError("Denylist pruning failed".to_string()))?;
+
+        Ok(result.rows_affected())
+    }
+}
@@ -1 +1,2 @@
 pub mod cookies;
+pub mod tokens;
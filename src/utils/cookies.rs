@@ -1,23 +1,67 @@
-use tower_cookies::cookie::{Cookie, SameSite, time::Duration};
+use std::time::Duration;
 
-pub fn set_refresh_cookie(token: String) -> Cookie<'static> {
-    Cookie::build(("refresh_token", token))
-        .secure(false) // true in production (HTTPS)
-        .http_only(true)
-        .same_site(SameSite::Lax)
-        // .domain("http://0.0.0.0:7400")
-        .path("/")
-        .max_age(Duration::days(1))
+use tower_cookies::cookie::{Cookie, CookieBuilder, time};
+
+use crate::config::RefreshCookieConfig;
+
+/// Holds the `state` of the single sign-on the browser started, so only that browser can
+/// finish it.
+pub const OIDC_STATE_COOKIE: &str = "oidc_state";
+
+pub fn set_refresh_cookie(
+    token: String,
+    config: &RefreshCookieConfig,
+    ttl: Duration,
+) -> Cookie<'static> {
+    refresh_cookie(token, config)
+        .max_age(time::Duration::try_from(ttl).unwrap_or(time::Duration::MAX))
         .build()
 }
 
-pub fn clear_refresh_cookies() -> Cookie<'static> {
-    Cookie::build(("refresh_token", ""))
-        .secure(false) // true in production (HTTPS)
-        .http_only(true)
-        .same_site(SameSite::Lax)
-        .path("/")
-        .max_age(Duration::days(30))
+/// Overwrites the refresh cookie with one that has already expired. It has to carry the same
+/// domain and path as the original, or the browser treats it as a different cookie.
+pub fn clear_refresh_cookie(config: &RefreshCookieConfig) -> Cookie<'static> {
+    refresh_cookie(String::new(), config)
+        .max_age(time::Duration::ZERO)
+        .expires(time::OffsetDateTime::UNIX_EPOCH)
         .build()
 }
-// .domain("www.rust-lang.org")
+
+/// Shares the refresh cookie's attributes, which already reach the auth routes.
+pub fn set_oidc_state_cookie(
+    state: String,
+    config: &RefreshCookieConfig,
+    ttl: Duration,
+) -> Cookie<'static> {
+    auth_cookie(OIDC_STATE_COOKIE, state, config)
+        .max_age(time::Duration::try_from(ttl).unwrap_or(time::Duration::MAX))
+        .build()
+}
+
+pub fn clear_oidc_state_cookie(config: &RefreshCookieConfig) -> Cookie<'static> {
+    auth_cookie(OIDC_STATE_COOKIE, String::new(), config)
+        .max_age(time::Duration::ZERO)
+        .expires(time::OffsetDateTime::UNIX_EPOCH)
+        .build()
+}
+
+fn refresh_cookie(value: String, config: &RefreshCookieConfig) -> CookieBuilder<'static> {
+    auth_cookie("refresh_token", value, config)
+}
+
+fn auth_cookie(
+    name: &'static str,
+    value: String,
+    config: &RefreshCookieConfig,
+) -> CookieBuilder<'static> {
+    let cookie = Cookie::build((name, value))
+        .secure(config.secure)
+        .http_only(true)
+        .same_site(config.same_site)
+        .path(config.path.clone());
+
+    match &config.domain {
+        Some(domain) => cookie.domain(domain.clone()),
+        None => cookie,
+    }
+}
@@ -0,0 +1,18 @@
+use sha2::{Digest, Sha256};
+use subtle::ConstantTimeEq;
+
+/// A random 256-bit token, hex encoded, for handing out by email or cookie.
+pub fn generate_opaque_token() -> String {
+    hex::encode(rand::random::<[u8; 32]>())
+}
+
+/// What gets stored in place of an opaque token, so a database leak does not leak usable tokens.
+pub fn hash_opaque_token(token: &str) -> String {
+    hex::encode(Sha256::digest(token.as_bytes()))
+}
+
+/// Compares two secrets, or hashes of them, in time that does not depend on where they first
+/// differ.
+pub fn tokens_match(a: &str, b: &str) -> bool {
+    a.as_bytes().ct_eq(b.as_bytes()).into()
+}